@@ -3,8 +3,10 @@
 //! Allows users to define and execute multi-step operations automatically.
 //! Example: Erase → Write → Verify → Write Protect
 
+use crate::application::use_cases::detect_chip::format_unique_id;
+use crate::application::use_cases::write_flash::format_relocations;
 use crate::application::use_cases::*;
-use crate::domain::{BadBlockStrategy, ChipSpec, FlashType, OobMode, Progress};
+use crate::domain::{BadBlockStrategy, ChipSpec, FlashType, OobMode, Progress, WriteMode};
 use crate::error::{Error, Result};
 use crate::infrastructure::chip_database::ChipRegistry;
 use crate::infrastructure::flash_protocol::eeprom::{I2cEeprom, MicrowireEeprom, SpiEeprom};
@@ -38,6 +40,12 @@ pub enum BatchOperation {
         start: u32,
         #[serde(default = "default_true")]
         verify: bool,
+        /// Read-modify-write the affected erase units (NOR only)
+        #[serde(default)]
+        patch: bool,
+        /// Skip erase units and pages that already hold the data
+        #[serde(default)]
+        delta: bool,
     },
 
     /// Verify flash against file
@@ -64,6 +72,29 @@ fn default_true() -> bool {
     true
 }
 
+/// Summary of a batch run, suitable for saving alongside production logs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub chip: String,
+    pub manufacturer: String,
+    pub jedec_id: String,
+    /// Factory unique ID (hex), when the chip provides one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unique_id: Option<String>,
+    pub operations_completed: usize,
+}
+
+impl BatchReport {
+    /// Save report to JSON file
+    pub fn to_json_file(&self, path: &PathBuf) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Other(format!("Failed to serialize JSON: {}", e)))?;
+        fs::write(path, content)
+            .map_err(|e| Error::Other(format!("Failed to write report file: {}", e)))?;
+        Ok(())
+    }
+}
+
 /// A batch script containing multiple operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchScript {
@@ -126,12 +157,32 @@ impl BatchScript {
         Ok(())
     }
 
+    /// Check the operations for conflicting options before anything runs
+    pub fn validate(&self) -> Result<()> {
+        for (i, op) in self.operations.iter().enumerate() {
+            if let BatchOperation::Write {
+                patch: true,
+                delta: true,
+                ..
+            } = op
+            {
+                return Err(Error::InvalidParameter(format!(
+                    "Step {}: a write cannot be both patch and delta",
+                    i + 1
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Execute all operations in sequence
     pub fn execute(
         &self,
         programmer: &mut dyn Programmer,
         registry: &ChipRegistry,
-    ) -> Result<ChipSpec> {
+    ) -> Result<BatchReport> {
+        self.validate()?;
+
         if let Some(desc) = &self.description {
             info!("📋 Batch: {}", desc);
         }
@@ -144,6 +195,18 @@ impl BatchScript {
         let chip = detect_use_case.identify_chip(programmer)?;
         info!("✓ Detected: {} ({})", chip.name, chip.manufacturer);
 
+        let unique_id = match detect_use_case.read_unique_id(programmer, &chip) {
+            Ok(id) => {
+                let id = format_unique_id(&id);
+                info!("✓ Unique ID: {}", id);
+                Some(id)
+            }
+            Err(e) => {
+                log::debug!("Unique ID not available: {}", e);
+                None
+            }
+        };
+
         for (i, op) in self.operations.iter().enumerate() {
             info!("\n📝 Step {}/{}:", i + 1, self.operations.len());
             self.execute_operation(op, programmer, &chip)?;
@@ -151,7 +214,13 @@ impl BatchScript {
 
         info!("\n─────────────────────────────────────");
         info!("✅ Batch execution completed successfully!");
-        Ok(chip)
+        Ok(BatchReport {
+            chip: chip.name,
+            manufacturer: chip.manufacturer,
+            jedec_id: chip.jedec_id.to_string(),
+            unique_id,
+            operations_completed: self.operations.len(),
+        })
     }
 
     fn execute_operation(
@@ -179,6 +248,8 @@ impl BatchScript {
                     length: erase_len,
                     bad_block_strategy: BadBlockStrategy::Skip,
                     bbt: None,
+                    mark_bad: false,
+                    partitions: Vec::new(),
                 };
 
                 let on_progress = |p: Progress| {
@@ -220,6 +291,8 @@ impl BatchScript {
                 file,
                 start,
                 verify,
+                patch,
+                delta,
             } => {
                 info!("   📝 Writing {:?} to 0x{:08X}...", file, start);
 
@@ -232,10 +305,19 @@ impl BatchScript {
                     use_ecc: true,
                     verify: *verify,
                     ignore_ecc_errors: false,
+                    soft_ecc: None,
                     oob_mode: OobMode::None,
                     bad_block_strategy: BadBlockStrategy::Skip,
                     bbt: None,
                     retry_count: 3,
+                    mode: if *patch {
+                        WriteMode::Patch
+                    } else if *delta {
+                        WriteMode::Delta
+                    } else {
+                        WriteMode::Direct
+                    },
+                    partitions: Vec::new(),
                 };
 
                 let on_progress = |p: Progress| {
@@ -244,7 +326,7 @@ impl BatchScript {
                     }
                 };
 
-                match chip.flash_type {
+                let report = match chip.flash_type {
                     FlashType::Nand => {
                         WriteFlashUseCase::new(SpiNand::new(programmer, chip.clone()))
                             .execute(params, on_progress)
@@ -269,6 +351,15 @@ impl BatchScript {
                     }
                 }?;
 
+                if *patch || *delta {
+                    info!(
+                        "   ⏭  Skipped {}/{} unchanged erase units",
+                        report.units_skipped, report.units_total
+                    );
+                }
+                for line in format_relocations(&report.relocated_blocks) {
+                    warn!("   {}", line);
+                }
                 info!("   ✓ Write complete");
                 Ok(())
             }
@@ -283,10 +374,12 @@ impl BatchScript {
                     data: &data,
                     use_ecc: true,
                     ignore_ecc_errors: false,
+                    soft_ecc: None,
                     oob_mode: OobMode::None,
                     bad_block_strategy: BadBlockStrategy::Skip,
                     bbt: None,
                     retry_count: 3,
+                    partitions: Vec::new(),
                 };
 
                 let on_progress = |p: Progress| {
@@ -377,6 +470,8 @@ pub mod templates {
                 file: firmware_file.clone(),
                 start: 0,
                 verify: false,
+                patch: false,
+                delta: false,
             })
             .add_operation(BatchOperation::Verify {
                 file: firmware_file,
@@ -391,3 +486,29 @@ pub mod templates {
             .add_operation(BatchOperation::Protect { enable: true })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rejects_patch_and_delta() {
+        let write = |patch, delta| BatchOperation::Write {
+            file: PathBuf::from("firmware.bin"),
+            start: 0,
+            verify: true,
+            patch,
+            delta,
+        };
+        let script = BatchScript::new()
+            .add_operation(BatchOperation::DetectChip)
+            .add_operation(write(false, true));
+        assert!(script.validate().is_ok());
+
+        let err = script
+            .add_operation(write(true, true))
+            .validate()
+            .expect_err("Patch and delta write accepted");
+        assert!(err.to_string().contains("Step 3"));
+    }
+}
@@ -8,7 +8,7 @@ pub mod services;
 pub mod use_cases;
 
 // Re-export commonly used types
-pub use batch::{BatchOperation, BatchScript};
+pub use batch::{BatchOperation, BatchReport, BatchScript};
 pub use diagnostics::DiagnosticTool;
 pub use use_cases::{
     DetectChipUseCase, EraseFlashUseCase, EraseParams, ReadFlashUseCase, ReadParams,
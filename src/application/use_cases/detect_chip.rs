@@ -2,9 +2,11 @@
 //!
 //! Orchestrates the process of identifying a connected flash chip.
 
-use crate::domain::{ChipSpec, JedecId};
-use crate::error::Result;
+use crate::domain::{ChipSpec, FlashType, JedecId};
+use crate::error::{Error, Result};
 use crate::infrastructure::chip_database::ChipRegistry;
+use crate::infrastructure::flash_protocol::nand::SpiNand;
+use crate::infrastructure::flash_protocol::nor::{sfdp, SpiNor};
 use crate::infrastructure::programmer::{self, Programmer};
 
 pub struct DetectChipUseCase {
@@ -47,11 +49,62 @@ impl DetectChipUseCase {
         let jedec = JedecId::new([id_bytes[0], id_bytes[1], id_bytes[2]]);
 
         // Lookup in registry
-        let spec = self.registry.find_by_id(jedec).ok_or_else(|| {
-            crate::error::Error::UnsupportedChip(id_bytes[0], id_bytes[1], id_bytes[2])
-        })?;
+        if let Some(spec) = self.registry.find_by_id(jedec) {
+            return Ok(spec);
+        }
+
+        // Unknown part: let it describe itself via SFDP (NOR only)
+        match self.identify_by_sfdp(programmer, jedec) {
+            Ok(spec) => {
+                log::info!(
+                    "Chip {} not in database, configured from SFDP ({})",
+                    jedec,
+                    spec.capacity
+                );
+                Ok(spec)
+            }
+            Err(e) => {
+                log::debug!("SFDP probe failed: {}", e);
+                Err(Error::UnsupportedChip(
+                    id_bytes[0],
+                    id_bytes[1],
+                    id_bytes[2],
+                ))
+            }
+        }
+    }
+
+    /// Build a chip specification from the chip's own SFDP table
+    fn identify_by_sfdp(
+        &self,
+        programmer: &mut dyn Programmer,
+        jedec: JedecId,
+    ) -> Result<ChipSpec> {
+        let params = sfdp::read_basic_params(programmer)?;
+        let manufacturer = self
+            .registry
+            .manufacturer_name(jedec.manufacturer)
+            .unwrap_or_else(|| format!("Unknown (0x{:02X})", jedec.manufacturer));
+        params.to_chip_spec(jedec, &manufacturer)
+    }
 
-        Ok(spec)
+    /// Read the factory unique ID of a detected chip
+    ///
+    /// The command and ID length depend on the flash type and manufacturer;
+    /// chips without a known unique ID return `NotSupported`.
+    pub fn read_unique_id(
+        &self,
+        programmer: &mut dyn Programmer,
+        spec: &ChipSpec,
+    ) -> Result<Vec<u8>> {
+        match spec.flash_type {
+            FlashType::Nor => SpiNor::new(programmer, spec.clone()).read_unique_id(),
+            FlashType::Nand => SpiNand::new(programmer, spec.clone()).read_unique_id(),
+            other => Err(Error::NotSupported(format!(
+                "{} has no factory unique ID",
+                other
+            ))),
+        }
     }
 
     pub fn list_supported_chips(&self) -> Vec<ChipSpec> {
@@ -59,6 +112,11 @@ impl DetectChipUseCase {
     }
 }
 
+/// Format a unique ID as an uppercase hex string
+pub fn format_unique_id(id: &[u8]) -> String {
+    id.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,6 +138,8 @@ mod tests {
                 block_size: 4096,
                 oob_size: None,
                 is_dataflash: false,
+                die_count: 1,
+                oob_layout: None,
             },
             capabilities: ChipCapabilities::default(),
             otp: None,
@@ -114,7 +174,7 @@ mod tests {
 
         assert!(result.is_err());
         match result {
-            Err(crate::error::Error::UnsupportedChip(m, d, de)) => {
+            Err(Error::UnsupportedChip(m, d, de)) => {
                 assert_eq!(m, 0x00);
                 assert_eq!(d, 0x01);
                 assert_eq!(de, 0x02);
@@ -122,4 +182,79 @@ mod tests {
             _ => panic!("Expected UnsupportedChip error"),
         }
     }
+
+    #[test]
+    fn test_detect_unknown_chip_via_sfdp() {
+        let registry = ChipRegistry::from_specs(vec![]);
+        let use_case = DetectChipUseCase::new(registry);
+
+        // SFDP header: signature, v1.6, 1 parameter header
+        let header = vec![0x53, 0x46, 0x44, 0x50, 0x06, 0x01, 0x00, 0xFF];
+        // BFPT parameter header: ID 0xFF00, v1.6, 9 DWORDs at 0x000030
+        let param_header = vec![0x00, 0x06, 0x01, 0x09, 0x30, 0x00, 0x00, 0xFF];
+        let bfpt: Vec<u8> = [
+            0xFFF1_20E5u32, // 4K erase 0x20, 3-byte addressing
+            0x00FF_FFFF,    // 16 Mbit
+            0,
+            0,
+            0,
+            0,
+            0,
+            0x520F_200C, // 4K/0x20, 32K/0x52
+            0xFF00_D810, // 64K/0xD8
+        ]
+        .iter()
+        .flat_map(|d| d.to_le_bytes())
+        .collect();
+
+        let mock = MockProgrammer::new();
+        mock.expect_reads(vec![vec![0xFF], vec![0x85, 0x60, 0x15]]);
+        // Each SFDP read is one transaction: command echo (ignored) + data
+        mock.expect_reads(vec![vec![0xFF; 5], header]);
+        mock.expect_reads(vec![vec![0xFF; 5], param_header]);
+        mock.expect_reads(vec![vec![0xFF; 5], bfpt]);
+        let mut mock = mock;
+
+        let spec = use_case.identify_chip(&mut mock).unwrap();
+        assert_eq!(spec.flash_type, FlashType::Nor);
+        assert_eq!(spec.capacity.as_megabytes(), 2);
+        assert_eq!(spec.layout.block_size, 64 * 1024);
+        assert_eq!(spec.manufacturer, "Unknown (0x85)");
+    }
+
+    #[test]
+    fn test_read_nor_unique_id() {
+        let use_case = DetectChipUseCase::new(ChipRegistry::from_specs(vec![]));
+        let spec = ChipSpec {
+            name: "W25Q64".to_string(),
+            manufacturer: "Winbond".to_string(),
+            jedec_id: JedecId::new([0xEF, 0x40, 0x17]),
+            flash_type: FlashType::Nor,
+            capacity: Capacity::megabytes(8),
+            layout: ChipLayout {
+                page_size: 256,
+                block_size: 64 * 1024,
+                oob_size: None,
+                is_dataflash: false,
+                die_count: 1,
+                oob_layout: None,
+            },
+            capabilities: ChipCapabilities::default(),
+            otp: None,
+        };
+
+        let mut mock = MockProgrammer::new();
+        mock.expect_reads(vec![
+            vec![0xFF; 5],
+            vec![0xD1, 0x62, 0x38, 0x46, 0x1B, 0x2A, 0x19, 0x2D],
+        ]);
+
+        let id = use_case.read_unique_id(&mut mock, &spec).unwrap();
+        assert_eq!(format_unique_id(&id), "D16238461B2A192D");
+        assert_eq!(mock.get_writes()[0], vec![0x4B, 0x00, 0x00, 0x00, 0x00]);
+
+        let mut other = spec.clone();
+        other.jedec_id = JedecId::new([0xC2, 0x20, 0x17]);
+        assert!(use_case.read_unique_id(&mut mock, &other).is_err());
+    }
 }
@@ -3,7 +3,8 @@
 //! Orchestrates erasing flash memory blocks.
 
 use crate::domain::{
-    bad_block::BadBlockTable, Address, BadBlockStrategy, EraseRequest, FlashOperation, Progress,
+    bad_block::BadBlockTable, Address, BadBlockStrategy, EraseReport, EraseRequest, FlashOperation,
+    Partition, Progress,
 };
 use crate::error::Result;
 
@@ -13,6 +14,9 @@ pub struct EraseParams {
     pub length: u32,
     pub bad_block_strategy: BadBlockStrategy,
     pub bbt: Option<BadBlockTable>,
+    /// Mark blocks bad when their erase fails (NAND only)
+    pub mark_bad: bool,
+    pub partitions: Vec<Partition>,
 }
 
 /// Use case for erasing data from flash
@@ -31,21 +35,39 @@ impl<F: FlashOperation> EraseFlashUseCase<F> {
     where
         P: Fn(Progress),
     {
-        let request = EraseRequest {
+        self.flash.erase(Self::request(params), &on_progress)
+    }
+
+    /// Execute the erase operation, also returning the blocks it retired and
+    /// the bad block table with them recorded
+    pub fn execute_with_report<P>(
+        &mut self,
+        params: EraseParams,
+        on_progress: P,
+    ) -> Result<EraseReport>
+    where
+        P: Fn(Progress),
+    {
+        self.flash
+            .erase_with_report(Self::request(params), &on_progress)
+    }
+
+    fn request(params: EraseParams) -> EraseRequest {
+        EraseRequest {
             address: Address::new(params.address),
             length: params.length,
             bad_block_strategy: params.bad_block_strategy,
             bbt: params.bbt,
-        };
-
-        self.flash.erase(request, &on_progress)
+            mark_bad: params.mark_bad,
+            partitions: params.partitions,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{FlashOperation, Progress, ReadRequest, WriteRequest};
+    use crate::domain::{FlashOperation, Progress, ReadRequest, WriteReport, WriteRequest};
     use crate::error::Result;
     use std::cell::RefCell;
 
@@ -70,8 +92,12 @@ mod tests {
             Ok(vec![])
         }
 
-        fn write(&mut self, _request: WriteRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
-            Ok(())
+        fn write(
+            &mut self,
+            _request: WriteRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<WriteReport> {
+            Ok(WriteReport::default())
         }
 
         fn erase(&mut self, request: EraseRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
@@ -90,6 +116,8 @@ mod tests {
             length: 128 * 1024,
             bad_block_strategy: BadBlockStrategy::Skip,
             bbt: None,
+            mark_bad: false,
+            partitions: Vec::new(),
         };
 
         let result = use_case.execute(params, |_| {});
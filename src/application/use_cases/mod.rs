@@ -4,6 +4,7 @@
 
 pub mod detect_chip;
 pub mod erase_flash;
+pub mod otp_flash;
 pub mod read_flash;
 pub mod status_flash;
 pub mod verify_flash;
@@ -12,6 +13,7 @@ pub mod write_flash;
 // Re-export use cases
 pub use detect_chip::DetectChipUseCase;
 pub use erase_flash::{EraseFlashUseCase, EraseParams};
+pub use otp_flash::OtpUseCase;
 pub use read_flash::{ReadFlashUseCase, ReadParams};
 pub use status_flash::StatusUseCase;
 pub use verify_flash::{VerifyFlashUseCase, VerifyParams};
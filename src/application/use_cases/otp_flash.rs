@@ -0,0 +1,114 @@
+//! Use Case - OTP / Security Registers
+//!
+//! Orchestrates reading, programming, erasing and locking one-time
+//! programmable regions.
+
+use crate::domain::FlashOperation;
+use crate::error::{Error, Result};
+
+pub struct OtpUseCase<T: FlashOperation> {
+    flash: T,
+}
+
+impl<T: FlashOperation> OtpUseCase<T> {
+    pub fn new(flash: T) -> Self {
+        Self { flash }
+    }
+
+    /// Read `length` bytes at `offset` within `region`
+    pub fn read(&mut self, region: u32, offset: u32, length: u32) -> Result<Vec<u8>> {
+        self.flash.otp_read(region, offset, length)
+    }
+
+    /// Program `data` at `offset` within `region` and read it back
+    pub fn program(&mut self, region: u32, offset: u32, data: &[u8]) -> Result<()> {
+        self.flash.otp_program(region, offset, data)?;
+
+        let read_back = self.flash.otp_read(region, offset, data.len() as u32)?;
+        if let Some(i) = read_back.iter().zip(data).position(|(a, e)| a != e) {
+            return Err(Error::VerificationFailed {
+                address: offset + i as u32,
+                expected: data[i],
+                actual: read_back[i],
+            });
+        }
+        Ok(())
+    }
+
+    /// Erase `region`
+    pub fn erase(&mut self, region: u32) -> Result<()> {
+        self.flash.otp_erase(region)
+    }
+
+    /// Permanently lock `region`
+    pub fn lock(&mut self, region: u32) -> Result<()> {
+        self.flash.otp_lock(region)
+    }
+
+    /// Whether `region` is locked
+    pub fn is_locked(&mut self, region: u32) -> Result<bool> {
+        self.flash.otp_is_locked(region)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{EraseRequest, Progress, ReadRequest, WriteReport, WriteRequest};
+
+    /// Flash with a single 16-byte OTP region whose bits can only be cleared
+    struct MockOtpFlash {
+        otp: Vec<u8>,
+    }
+
+    impl FlashOperation for MockOtpFlash {
+        fn read(
+            &mut self,
+            _request: ReadRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<Vec<u8>> {
+            Ok(vec![])
+        }
+        fn write(
+            &mut self,
+            _request: WriteRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<WriteReport> {
+            Ok(WriteReport::default())
+        }
+        fn erase(&mut self, _request: EraseRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
+            Ok(())
+        }
+
+        fn otp_read(&mut self, _region: u32, offset: u32, length: u32) -> Result<Vec<u8>> {
+            Ok(self.otp[offset as usize..(offset + length) as usize].to_vec())
+        }
+
+        fn otp_program(&mut self, _region: u32, offset: u32, data: &[u8]) -> Result<()> {
+            for (i, b) in data.iter().enumerate() {
+                self.otp[offset as usize + i] &= b;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_otp_program_verifies() {
+        let mut use_case = OtpUseCase::new(MockOtpFlash {
+            otp: vec![0xFF; 16],
+        });
+
+        use_case.program(0, 2, &[0x00, 0x11, 0x22]).unwrap();
+        assert_eq!(use_case.read(0, 2, 3).unwrap(), vec![0x00, 0x11, 0x22]);
+
+        // 0x00 can't be programmed back to 0x33 without an erase
+        match use_case.program(0, 2, &[0x33]) {
+            Err(Error::VerificationFailed {
+                address,
+                expected,
+                actual,
+            }) => assert_eq!((address, expected, actual), (2, 0x33, 0x00)),
+            other => panic!("Expected VerificationFailed, got {:?}", other),
+        }
+    }
+}
@@ -2,11 +2,18 @@
 //!
 //! Orchestrates reading data from flash memory.
 
+use crate::domain::consensus::{majority_vote, Disagreement};
 use crate::domain::{
-    bad_block::BadBlockTable, Address, BadBlockStrategy, FlashOperation, OobMode, Progress,
-    ReadRequest,
+    bad_block::BadBlockTable, Address, BadBlockStrategy, EccReport, EccStatus, FlashOperation,
+    OobMode, Partition, Progress, ReadReport, ReadRequest, ReadSegment, SoftEccConfig,
 };
-use crate::error::Result;
+use crate::error::{Error, Result};
+
+/// Bytes read per pass of a consensus read before voting
+const CONSENSUS_CHUNK: u32 = 64 * 1024;
+
+/// Disagreeing addresses listed before the rest are only counted
+const MAX_LISTED_DISAGREEMENTS: usize = 16;
 
 /// Parameters for read operation
 pub struct ReadParams {
@@ -15,10 +22,14 @@ pub struct ReadParams {
     pub use_ecc: bool,
     /// Ignore ECC errors and continue reading (for data recovery)
     pub ignore_ecc_errors: bool,
+    pub soft_ecc: Option<SoftEccConfig>,
     pub oob_mode: OobMode,
     pub bad_block_strategy: BadBlockStrategy,
     pub bbt: Option<BadBlockTable>,
     pub retry_count: u32,
+    pub partitions: Vec<Partition>,
+    /// Times each chunk is read for a bytewise majority vote; 1 reads once
+    pub passes: u32,
 }
 
 /// Use case for reading data from flash
@@ -37,25 +48,206 @@ impl<F: FlashOperation> ReadFlashUseCase<F> {
     where
         P: Fn(Progress),
     {
-        let request = ReadRequest {
+        if params.passes != 1 {
+            return Ok(self.execute_with_report(params, on_progress)?.0);
+        }
+        self.flash.read(Self::request(params), &on_progress)
+    }
+
+    /// Execute the read operation, also returning per-page ECC outcomes
+    pub fn execute_with_report<P>(
+        &mut self,
+        params: ReadParams,
+        on_progress: P,
+    ) -> Result<(Vec<u8>, ReadReport)>
+    where
+        P: Fn(Progress),
+    {
+        match params.passes {
+            0 => Err(Error::InvalidParameter(
+                "A read needs at least one pass".to_string(),
+            )),
+            1 => self
+                .flash
+                .read_with_report(Self::request(params), &on_progress),
+            _ => self.consensus_read(params, &on_progress),
+        }
+    }
+
+    /// Read every chunk `params.passes` times and keep the bytewise
+    /// majority. The ECC report is that of the first pass.
+    fn consensus_read(
+        &mut self,
+        params: ReadParams,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<(Vec<u8>, ReadReport)> {
+        // Skipping bad blocks or adding OOB bytes breaks the mapping from
+        // offsets in the data to flash addresses, so such NAND reads are
+        // voted on per erase block of the physical ranges they map to
+        let mapped = params.bad_block_strategy == BadBlockStrategy::Skip
+            || !params.partitions.is_empty()
+            || params.oob_mode != OobMode::None;
+        let segments = if mapped {
+            let request = Self::chunk_request(&params, params.address, params.length);
+            self.flash.read_segments(&request)?
+        } else {
+            (0..params.length)
+                .step_by(CONSENSUS_CHUNK as usize)
+                .map(|offset| ReadSegment::Read {
+                    address: params.address + offset,
+                    length: CONSENSUS_CHUNK.min(params.length - offset),
+                })
+                .collect()
+        };
+        let total = params.length as u64 * params.passes as u64;
+
+        let mut data = Vec::with_capacity(params.length as usize);
+        let mut report = ReadReport::default();
+        for segment in segments {
+            let offset = data.len() as u32;
+            let (address, length) = match segment {
+                ReadSegment::Read { address, length } => (address, length),
+                ReadSegment::Erased { length } => {
+                    data.resize(data.len() + length as usize, 0xFF);
+                    continue;
+                }
+            };
+            let mut passes = Vec::with_capacity(params.passes as usize);
+            for pass in 0..params.passes {
+                let done = offset as u64 * params.passes as u64 + pass as u64 * length as u64;
+                let mut request = Self::chunk_request(&params, address, length);
+                if mapped {
+                    // The segment already lies past any bad blocks
+                    request.bad_block_strategy = BadBlockStrategy::Include;
+                    request.partitions.clear();
+                }
+                let (chunk, chunk_report) = self.flash.read_with_report(request, &|progress| {
+                    on_progress(Progress::new(done + progress.current, total))
+                })?;
+                if pass == 0 {
+                    report.ecc.strength = chunk_report.ecc.strength;
+                    report.ecc.pages.extend(chunk_report.ecc.pages);
+                    report
+                        .ecc
+                        .read_retries
+                        .extend(chunk_report.ecc.read_retries);
+                }
+                passes.push(chunk);
+            }
+            // Disagreements are reported at their offset in the data read,
+            // not at the physical address of a mapped segment
+            let vote = majority_vote(params.address + offset, &passes, &mut report.disagreements)?;
+            data.extend(vote);
+        }
+        Ok((data, report))
+    }
+
+    fn request(params: ReadParams) -> ReadRequest {
+        ReadRequest {
             address: Address::new(params.address),
             length: params.length,
             use_ecc: params.use_ecc,
             ignore_ecc_errors: params.ignore_ecc_errors,
+            soft_ecc: params.soft_ecc,
             oob_mode: params.oob_mode,
             bad_block_strategy: params.bad_block_strategy,
             bbt: params.bbt,
             retry_count: params.retry_count,
-        };
+            partitions: params.partitions,
+        }
+    }
 
-        self.flash.read(request, &on_progress)
+    fn chunk_request(params: &ReadParams, address: u32, length: u32) -> ReadRequest {
+        ReadRequest {
+            address: Address::new(address),
+            length,
+            use_ecc: params.use_ecc,
+            ignore_ecc_errors: params.ignore_ecc_errors,
+            soft_ecc: params.soft_ecc,
+            oob_mode: params.oob_mode,
+            bad_block_strategy: params.bad_block_strategy,
+            bbt: params.bbt.clone(),
+            retry_count: params.retry_count,
+            partitions: params.partitions.clone(),
+        }
     }
 }
 
+/// Human-readable summary of a consensus read over `passes` passes: how
+/// many bytes they disagreed on and the first of their addresses, given as
+/// the start address plus the offset in the data read
+pub fn format_consensus_report(passes: u32, disagreements: &[Disagreement]) -> Vec<String> {
+    if disagreements.is_empty() {
+        return vec![format!("All {} passes agreed", passes)];
+    }
+
+    let mut lines = vec![format!(
+        "Passes disagreed at {} byte(s), settled by majority (start address + offset in the data):",
+        disagreements.len()
+    )];
+    for disagreement in disagreements.iter().take(MAX_LISTED_DISAGREEMENTS) {
+        lines.push(format!(
+            "  0x{:08X}: {} of {} passes",
+            disagreement.address, disagreement.votes, passes
+        ));
+    }
+    if disagreements.len() > MAX_LISTED_DISAGREEMENTS {
+        lines.push(format!(
+            "  ... and {} more",
+            disagreements.len() - MAX_LISTED_DISAGREEMENTS
+        ));
+    }
+    lines
+}
+
+/// Human-readable summary of an ECC report: the bit-flip histogram, then the
+/// pages at or above the refresh threshold and the uncorrectable ones
+pub fn format_ecc_report(report: &EccReport) -> Vec<String> {
+    let mut lines = vec![format!(
+        "ECC: {} pages checked (strength {} bits, threshold {})",
+        report.pages.len(),
+        report.strength,
+        report.threshold()
+    )];
+    for (flips, count) in report.histogram() {
+        lines.push(format!("  {:>2} bit flips: {} pages", flips, count));
+    }
+
+    let near = report.near_threshold();
+    if !near.is_empty() {
+        lines.push(format!("Pages near the ECC threshold: {}", near.len()));
+        for page in near {
+            if let EccStatus::Corrected { bit_flips } = page.status {
+                lines.push(format!("  page {}: {} bit flips", page.page, bit_flips));
+            }
+        }
+    }
+
+    if !report.read_retries.is_empty() {
+        lines.push(format!(
+            "Pages recovered with read retry: {}",
+            report.read_retries.len()
+        ));
+        for recovery in &report.read_retries {
+            lines.push(format!(
+                "  page {}: level {}",
+                recovery.page, recovery.level
+            ));
+        }
+    }
+
+    let uncorrectable = report.uncorrectable();
+    if !uncorrectable.is_empty() {
+        let pages: Vec<_> = uncorrectable.iter().map(u32::to_string).collect();
+        lines.push(format!("Uncorrectable pages: {}", pages.join(", ")));
+    }
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{EraseRequest, FlashOperation, Progress, WriteRequest};
+    use crate::domain::{EraseRequest, FlashOperation, Progress, WriteReport, WriteRequest};
     use crate::error::Result;
     use std::cell::RefCell;
 
@@ -81,8 +273,12 @@ mod tests {
             Ok(vec![0xAA; 10])
         }
 
-        fn write(&mut self, _request: WriteRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
-            Ok(())
+        fn write(
+            &mut self,
+            _request: WriteRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<WriteReport> {
+            Ok(WriteReport::default())
         }
         fn erase(&mut self, _request: EraseRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
             Ok(())
@@ -99,10 +295,13 @@ mod tests {
             length: 2048,
             use_ecc: true,
             ignore_ecc_errors: false,
+            soft_ecc: None,
             oob_mode: OobMode::Included,
             bad_block_strategy: BadBlockStrategy::Skip,
             bbt: None,
             retry_count: 3,
+            partitions: Vec::new(),
+            passes: 1,
         };
 
         let result = use_case.execute(params, |_| {});
@@ -119,4 +318,222 @@ mod tests {
         assert_eq!(req.bad_block_strategy, BadBlockStrategy::Skip);
         assert_eq!(req.retry_count, 3);
     }
+
+    /// Flash that reads `address as u8` everywhere, except that the
+    /// second read covering `noisy` returns it inverted
+    struct NoisyFlash {
+        reads: u32,
+        noisy: u32,
+        noisy_reads: u32,
+    }
+
+    impl FlashOperation for NoisyFlash {
+        fn read(
+            &mut self,
+            request: ReadRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<Vec<u8>> {
+            let start = request.address.as_u32();
+            let range = start..start + request.length;
+            self.reads += 1;
+            if range.contains(&self.noisy) {
+                self.noisy_reads += 1;
+            }
+            Ok(range
+                .map(
+                    |address| match address == self.noisy && self.noisy_reads == 2 {
+                        true => !(address as u8),
+                        false => address as u8,
+                    },
+                )
+                .collect())
+        }
+
+        fn write(
+            &mut self,
+            _request: WriteRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<WriteReport> {
+            Ok(WriteReport::default())
+        }
+        fn erase(&mut self, _request: EraseRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_consensus_read() {
+        let params = |passes| ReadParams {
+            address: 0x100,
+            length: 0x10000 + 0x80,
+            use_ecc: false,
+            ignore_ecc_errors: false,
+            soft_ecc: None,
+            oob_mode: OobMode::None,
+            bad_block_strategy: BadBlockStrategy::Fail,
+            bbt: None,
+            retry_count: 0,
+            partitions: Vec::new(),
+            passes,
+        };
+        let expected: Vec<u8> = (0x100..0x10180u32).map(|address| address as u8).collect();
+
+        let noisy_flash = || NoisyFlash {
+            reads: 0,
+            noisy: 0x10110,
+            noisy_reads: 0,
+        };
+
+        // Two chunks of three passes; one pass misreads a byte
+        let mut use_case = ReadFlashUseCase::new(noisy_flash());
+        let (data, report) = use_case.execute_with_report(params(3), |_| {}).unwrap();
+        assert!(data == expected);
+        assert_eq!(use_case.flash.reads, 6);
+        assert_eq!(
+            report.disagreements,
+            [Disagreement {
+                address: 0x10110,
+                votes: 2
+            }]
+        );
+        assert_eq!(
+            format_consensus_report(3, &report.disagreements),
+            vec![
+                "Passes disagreed at 1 byte(s), settled by majority (start address + offset in the data):",
+                "  0x00010110: 2 of 3 passes",
+            ]
+        );
+
+        // Two passes that disagree leave no majority
+        let mut use_case = ReadFlashUseCase::new(noisy_flash());
+        let err = use_case
+            .execute(params(2), |_| {})
+            .expect_err("Read without consensus succeeded");
+        assert!(err.to_string().contains("0x00010110"));
+        assert!(use_case.execute(params(0), |_| {}).is_err());
+    }
+
+    /// Flash that maps every read onto two blocks with erased padding
+    /// between them, recording the requests it reads
+    struct MappedFlash {
+        requests: Vec<(u32, u32, BadBlockStrategy)>,
+    }
+
+    impl FlashOperation for MappedFlash {
+        fn read(
+            &mut self,
+            request: ReadRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<Vec<u8>> {
+            let start = request.address.as_u32();
+            self.requests
+                .push((start, request.length, request.bad_block_strategy));
+            Ok((start..start + request.length)
+                .map(|address| address as u8)
+                .collect())
+        }
+
+        fn read_segments(&mut self, _request: &ReadRequest) -> Result<Vec<ReadSegment>> {
+            Ok(vec![
+                ReadSegment::Read {
+                    address: 0x20000,
+                    length: 0x800,
+                },
+                ReadSegment::Erased { length: 0x10 },
+                ReadSegment::Read {
+                    address: 0x60000,
+                    length: 0x400,
+                },
+            ])
+        }
+
+        fn write(
+            &mut self,
+            _request: WriteRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<WriteReport> {
+            Ok(WriteReport::default())
+        }
+        fn erase(&mut self, _request: EraseRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_consensus_read_per_segment() {
+        let params = ReadParams {
+            address: 0,
+            length: 0xC10,
+            use_ecc: true,
+            ignore_ecc_errors: false,
+            soft_ecc: None,
+            oob_mode: OobMode::None,
+            bad_block_strategy: BadBlockStrategy::Skip,
+            bbt: None,
+            retry_count: 0,
+            partitions: Vec::new(),
+            passes: 2,
+        };
+        let mut use_case = ReadFlashUseCase::new(MappedFlash {
+            requests: Vec::new(),
+        });
+        let data = use_case.execute(params, |_| {}).unwrap();
+
+        // Each segment is read on its own, past the bad blocks already
+        let include = BadBlockStrategy::Include;
+        assert_eq!(
+            use_case.flash.requests,
+            [
+                (0x20000, 0x800, include),
+                (0x20000, 0x800, include),
+                (0x60000, 0x400, include),
+                (0x60000, 0x400, include),
+            ]
+        );
+        let expected: Vec<u8> = (0x20000..0x20800u32)
+            .map(|address| address as u8)
+            .chain([0xFF; 0x10])
+            .chain((0x60000..0x60400u32).map(|address| address as u8))
+            .collect();
+        assert!(data == expected);
+    }
+
+    #[test]
+    fn test_format_ecc_report() {
+        use crate::domain::{PageEcc, ReadRetryRecovery};
+
+        let status = [
+            EccStatus::NoError,
+            EccStatus::Corrected { bit_flips: 2 },
+            EccStatus::Corrected { bit_flips: 7 },
+            EccStatus::Uncorrectable,
+        ];
+        let report = EccReport {
+            pages: status
+                .into_iter()
+                .enumerate()
+                .map(|(page, status)| PageEcc {
+                    page: page as u32,
+                    status,
+                })
+                .collect(),
+            strength: 8,
+            read_retries: vec![ReadRetryRecovery { page: 1, level: 3 }],
+        };
+
+        assert_eq!(
+            format_ecc_report(&report),
+            vec![
+                "ECC: 4 pages checked (strength 8 bits, threshold 6)",
+                "   0 bit flips: 1 pages",
+                "   2 bit flips: 1 pages",
+                "   7 bit flips: 1 pages",
+                "Pages near the ECC threshold: 1",
+                "  page 2: 7 bit flips",
+                "Pages recovered with read retry: 1",
+                "  page 1: level 3",
+                "Uncorrectable pages: 3",
+            ]
+        );
+    }
 }
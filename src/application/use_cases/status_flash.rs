@@ -28,7 +28,7 @@ impl<T: FlashOperation> StatusUseCase<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{EraseRequest, Progress, ReadRequest, WriteRequest};
+    use crate::domain::{EraseRequest, Progress, ReadRequest, WriteReport, WriteRequest};
     use std::cell::RefCell;
 
     struct MockFlashSpy {
@@ -53,8 +53,12 @@ mod tests {
         ) -> Result<Vec<u8>> {
             Ok(vec![])
         }
-        fn write(&mut self, _request: WriteRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
-            Ok(())
+        fn write(
+            &mut self,
+            _request: WriteRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<WriteReport> {
+            Ok(WriteReport::default())
         }
         fn erase(&mut self, _request: EraseRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
             Ok(())
@@ -3,8 +3,8 @@
 //! Orchestrates the verification of data written to flash memory.
 
 use crate::domain::{
-    bad_block::BadBlockTable, Address, BadBlockStrategy, FlashOperation, OobMode, Progress,
-    ReadRequest,
+    bad_block::BadBlockTable, Address, BadBlockStrategy, FlashOperation, OobMode, Partition,
+    Progress, ReadRequest, SoftEccConfig,
 };
 use crate::error::{Error, Result};
 
@@ -15,10 +15,12 @@ pub struct VerifyParams<'a> {
     pub use_ecc: bool,
     /// Ignore ECC errors and continue verifying (for data recovery)
     pub ignore_ecc_errors: bool,
+    pub soft_ecc: Option<SoftEccConfig>,
     pub oob_mode: OobMode,
     pub bad_block_strategy: BadBlockStrategy,
     pub bbt: Option<BadBlockTable>,
     pub retry_count: u32,
+    pub partitions: Vec<Partition>,
 }
 
 /// Use case for verifying flash contents
@@ -42,10 +44,12 @@ impl<F: FlashOperation> VerifyFlashUseCase<F> {
             length: params.data.len() as u32,
             use_ecc: params.use_ecc,
             ignore_ecc_errors: params.ignore_ecc_errors,
+            soft_ecc: params.soft_ecc,
             oob_mode: params.oob_mode,
             bad_block_strategy: params.bad_block_strategy,
             bbt: params.bbt,
             retry_count: params.retry_count,
+            partitions: params.partitions,
         };
 
         // Read back the data from flash
@@ -74,7 +78,7 @@ impl<F: FlashOperation> VerifyFlashUseCase<F> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{EraseRequest, FlashOperation, WriteRequest};
+    use crate::domain::{EraseRequest, FlashOperation, WriteReport, WriteRequest};
 
     struct MockFlashReader {
         mock_data: Vec<u8>,
@@ -98,8 +102,12 @@ mod tests {
             Ok(self.mock_data.clone())
         }
 
-        fn write(&mut self, _request: WriteRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
-            Ok(())
+        fn write(
+            &mut self,
+            _request: WriteRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<WriteReport> {
+            Ok(WriteReport::default())
         }
         fn erase(&mut self, _request: EraseRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
             Ok(())
@@ -117,10 +125,12 @@ mod tests {
             data: &data,
             use_ecc: true,
             ignore_ecc_errors: false,
+            soft_ecc: None,
             oob_mode: OobMode::None,
             bad_block_strategy: BadBlockStrategy::Fail,
             bbt: None,
             retry_count: 0,
+            partitions: Vec::new(),
         };
 
         let result = use_case.execute(params, |_| {});
@@ -140,10 +150,12 @@ mod tests {
             data: &expected_data,
             use_ecc: true,
             ignore_ecc_errors: false,
+            soft_ecc: None,
             oob_mode: OobMode::None,
             bad_block_strategy: BadBlockStrategy::Fail,
             bbt: None,
             retry_count: 0,
+            partitions: Vec::new(),
         };
 
         let result = use_case.execute(params, |_| {});
@@ -3,8 +3,8 @@
 //! Orchestrates writing data to flash memory.
 
 use crate::domain::{
-    bad_block::BadBlockTable, Address, BadBlockStrategy, FlashOperation, OobMode, Progress,
-    WriteRequest,
+    bad_block::BadBlockTable, Address, BadBlockStrategy, BlockRelocation, FlashOperation, OobMode,
+    Partition, Progress, SoftEccConfig, WriteMode, WriteReport, WriteRequest,
 };
 use crate::error::Result;
 
@@ -16,10 +16,13 @@ pub struct WriteParams<'a> {
     pub verify: bool,
     /// Ignore ECC errors during verify (for data recovery)
     pub ignore_ecc_errors: bool,
+    pub soft_ecc: Option<SoftEccConfig>,
     pub oob_mode: OobMode,
     pub bad_block_strategy: BadBlockStrategy,
     pub bbt: Option<BadBlockTable>,
     pub retry_count: u32,
+    pub mode: WriteMode,
+    pub partitions: Vec<Partition>,
 }
 
 /// Use case for writing data to flash
@@ -34,7 +37,7 @@ impl<F: FlashOperation> WriteFlashUseCase<F> {
     }
 
     /// Execute the write operation
-    pub fn execute<P>(&mut self, params: WriteParams, on_progress: P) -> Result<()>
+    pub fn execute<P>(&mut self, params: WriteParams, on_progress: P) -> Result<WriteReport>
     where
         P: Fn(Progress),
     {
@@ -44,16 +47,38 @@ impl<F: FlashOperation> WriteFlashUseCase<F> {
             use_ecc: params.use_ecc,
             verify: params.verify,
             ignore_ecc_errors: params.ignore_ecc_errors,
+            soft_ecc: params.soft_ecc,
             oob_mode: params.oob_mode,
             bad_block_strategy: params.bad_block_strategy,
             bbt: params.bbt,
             retry_count: params.retry_count,
+            mode: params.mode,
+            partitions: params.partitions,
         };
 
         self.flash.write(request, &on_progress)
     }
 }
 
+/// Human-readable list of the blocks a write relocated after program
+/// failures; empty when there were none
+pub fn format_relocations(relocations: &[BlockRelocation]) -> Vec<String> {
+    if relocations.is_empty() {
+        return Vec::new();
+    }
+    let mut lines = vec![format!(
+        "Relocated {} block(s) after program failures:",
+        relocations.len()
+    )];
+    for relocation in relocations {
+        lines.push(format!(
+            "  logical block {}: block {} -> block {}",
+            relocation.logical_block, relocation.failed_block, relocation.new_block
+        ));
+    }
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,10 +109,14 @@ mod tests {
             Ok(vec![])
         }
 
-        fn write(&mut self, request: WriteRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
+        fn write(
+            &mut self,
+            request: WriteRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<WriteReport> {
             *self.last_write_request.borrow_mut() = Some(request.data.to_vec());
             *self.last_addr.borrow_mut() = request.address.as_u32();
-            Ok(())
+            Ok(WriteReport::default())
         }
         fn erase(&mut self, _request: EraseRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
             Ok(())
@@ -106,10 +135,13 @@ mod tests {
             use_ecc: true,
             verify: true,
             ignore_ecc_errors: true,
+            soft_ecc: None,
             oob_mode: OobMode::None,
             bad_block_strategy: BadBlockStrategy::Fail,
             bbt: None,
             retry_count: 0,
+            mode: WriteMode::Direct,
+            partitions: Vec::new(),
         };
 
         let result = use_case.execute(params, |_| {});
@@ -119,4 +151,22 @@ mod tests {
         let written_data = use_case.flash.last_write_request.borrow();
         assert_eq!(written_data.as_ref().unwrap(), &data);
     }
+
+    #[test]
+    fn test_format_relocations() {
+        assert!(format_relocations(&[]).is_empty());
+
+        let relocations = [BlockRelocation {
+            logical_block: 1,
+            failed_block: 5,
+            new_block: 6,
+        }];
+        assert_eq!(
+            format_relocations(&relocations),
+            vec![
+                "Relocated 1 block(s) after program failures:".to_string(),
+                "  logical block 1: block 5 -> block 6".to_string(),
+            ]
+        );
+    }
 }
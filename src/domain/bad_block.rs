@@ -4,6 +4,7 @@
 //! independent of implementation details.
 
 use super::types::Address;
+use crate::error::{Error, Result};
 
 /// Information about a bad block
 #[derive(Debug, Clone)]
@@ -63,6 +64,30 @@ pub enum BlockStatus {
     BadFactory,
     /// Bad block detected during runtime
     BadRuntime,
+    /// Good block holding an on-flash bad block table, kept out of data use
+    Reserved,
+}
+
+/// A block whose status differs between two tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockChange {
+    pub block: usize,
+    pub before: BlockStatus,
+    pub after: BlockStatus,
+}
+
+impl BlockChange {
+    /// The block was usable before and is bad now
+    pub fn went_bad(&self) -> bool {
+        !is_bad_status(self.before) && is_bad_status(self.after)
+    }
+}
+
+fn is_bad_status(status: BlockStatus) -> bool {
+    matches!(
+        status,
+        BlockStatus::BadFactory | BlockStatus::BadRuntime | BlockStatus::Reserved
+    )
 }
 
 /// In-memory Bad Block Table
@@ -100,11 +125,36 @@ impl BadBlockTable {
         }
     }
 
+    /// Record `block` as bad by hand; it is kept as a runtime bad block
+    pub fn mark_bad(&mut self, block: usize) -> Result<()> {
+        self.check_block(block)?;
+        if !self.is_bad(block) {
+            self.status[block] = BlockStatus::BadRuntime;
+        }
+        Ok(())
+    }
+
+    /// Clear the bad entry of `block`, factory marks included
+    pub fn mark_good(&mut self, block: usize) -> Result<()> {
+        self.check_block(block)?;
+        self.status[block] = BlockStatus::Good;
+        Ok(())
+    }
+
+    fn check_block(&self, block: usize) -> Result<()> {
+        if block >= self.status.len() {
+            return Err(Error::InvalidParameter(format!(
+                "Block {} is outside the table ({} blocks)",
+                block,
+                self.status.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// True for blocks data must not go to, including reserved ones
     pub fn is_bad(&self, block: usize) -> bool {
-        matches!(
-            self.get_status(block),
-            BlockStatus::BadFactory | BlockStatus::BadRuntime
-        )
+        is_bad_status(self.get_status(block))
     }
 
     pub fn bad_block_count(&self) -> usize {
@@ -113,6 +163,27 @@ impl BadBlockTable {
             .filter(|&&s| s == BlockStatus::BadFactory || s == BlockStatus::BadRuntime)
             .count()
     }
+
+    /// Blocks whose status changed from this table to `newer`. Unknown and
+    /// good blocks are treated alike; a size mismatch shows up as changes
+    /// of the blocks only one table has.
+    pub fn diff(&self, newer: &BadBlockTable) -> Vec<BlockChange> {
+        let normalize = |status| match status {
+            BlockStatus::Unknown => BlockStatus::Good,
+            status => status,
+        };
+        (0..self.len().max(newer.len()))
+            .filter_map(|block| {
+                let before = normalize(self.get_status(block));
+                let after = normalize(newer.get_status(block));
+                (before != after).then_some(BlockChange {
+                    block,
+                    before,
+                    after,
+                })
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -168,4 +239,44 @@ mod tests {
         // Count check
         assert_eq!(bbt.bad_block_count(), 2);
     }
+
+    #[test]
+    fn test_bad_block_table_manual_marks() {
+        let mut bbt = BadBlockTable::new(4);
+        bbt.set_status(0, BlockStatus::BadFactory);
+
+        bbt.mark_bad(2).unwrap();
+        assert_eq!(bbt.get_status(2), BlockStatus::BadRuntime);
+        // Factory marks are kept
+        bbt.mark_bad(0).unwrap();
+        assert_eq!(bbt.get_status(0), BlockStatus::BadFactory);
+
+        bbt.mark_good(0).unwrap();
+        bbt.mark_good(2).unwrap();
+        assert_eq!(bbt.bad_block_count(), 0);
+
+        assert!(bbt.mark_bad(4).is_err());
+        assert!(bbt.mark_good(4).is_err());
+    }
+
+    #[test]
+    fn test_bad_block_table_diff() {
+        let mut factory = BadBlockTable::new(6);
+        factory.set_status(1, BlockStatus::BadFactory);
+        factory.set_status(2, BlockStatus::Good);
+        factory.set_status(4, BlockStatus::BadRuntime);
+
+        let mut today = factory.clone();
+        today.set_status(2, BlockStatus::Unknown);
+        today.set_status(3, BlockStatus::BadRuntime);
+        today.set_status(4, BlockStatus::Good);
+
+        let changes = factory.diff(&today);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].block, 3);
+        assert!(changes[0].went_bad());
+        assert_eq!(changes[1].block, 4);
+        assert!(!changes[1].went_bad());
+        assert!(factory.diff(&factory).is_empty());
+    }
 }
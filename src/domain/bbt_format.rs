@@ -0,0 +1,316 @@
+//! Domain Model - Bad Block Table File Formats
+//!
+//! Besides the native JSON, tables are imported and exported as
+//! - a plain-text list, one `<block> [factory|runtime|reserved]` per line
+//!   after a `# blocks: <count>` header;
+//! - the binary 2-bit-per-block format of vendor tools, the same encoding
+//!   as the Linux on-flash table ([`super::nand_bbt`]);
+//! - the output of U-Boot `nand bad`, one hex byte offset per bad block.
+//!
+//! Plain-text lists without a header and U-Boot output do not say how many
+//! blocks the chip has, and U-Boot output needs the block size to turn
+//! offsets into blocks; [`BbtFileOptions`] supplies both.
+
+use std::path::Path;
+
+use super::bad_block::{BadBlockTable, BlockStatus};
+use super::nand_bbt;
+use crate::error::{Error, Result};
+
+/// Format of a bad block table file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BbtFormat {
+    Json,
+    Text,
+    Binary,
+    UBoot,
+}
+
+impl BbtFormat {
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|ext| ext.to_str())? {
+            "json" => Some(Self::Json),
+            "txt" => Some(Self::Text),
+            "bin" | "bbt" => Some(Self::Binary),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from a file extension, then from the contents:
+    /// JSON objects, U-Boot output, other text and otherwise binary
+    pub fn detect(path: &Path, data: &[u8]) -> Self {
+        if let Some(format) = Self::from_extension(path) {
+            return format;
+        }
+        match std::str::from_utf8(data) {
+            Ok(text) if text.trim_start().starts_with('{') => Self::Json,
+            Ok(text) if text.contains("bad blocks:") => Self::UBoot,
+            Ok(_) => Self::Text,
+            Err(_) => Self::Binary,
+        }
+    }
+}
+
+/// How a table file is read or written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BbtFileOptions {
+    /// File format; detected from the file when unset
+    pub format: Option<BbtFormat>,
+    /// Number of blocks of the chip, for files that do not record it
+    pub total_blocks: Option<usize>,
+    /// Block size in bytes, for U-Boot offsets
+    pub block_size: Option<u32>,
+}
+
+impl BbtFileOptions {
+    /// Format of the existing file `path` holding `data`
+    pub fn load_format(&self, path: &Path, data: &[u8]) -> BbtFormat {
+        self.format.unwrap_or_else(|| BbtFormat::detect(path, data))
+    }
+
+    /// Format to save `path` in; JSON unless the extension says otherwise
+    pub fn save_format(&self, path: &Path) -> BbtFormat {
+        self.format
+            .or_else(|| BbtFormat::from_extension(path))
+            .unwrap_or(BbtFormat::Json)
+    }
+
+    pub fn encode(&self, format: BbtFormat, table: &BadBlockTable) -> Result<Vec<u8>> {
+        match format {
+            BbtFormat::Json => serde_json::to_vec_pretty(table)
+                .map_err(|e| Error::Other(format!("Failed to encode BBT: {}", e))),
+            BbtFormat::Text => Ok(encode_text(table).into_bytes()),
+            BbtFormat::Binary => Ok(nand_bbt::encode(table)),
+            BbtFormat::UBoot => Ok(encode_uboot(table, self.require_block_size()?).into_bytes()),
+        }
+    }
+
+    pub fn decode(&self, format: BbtFormat, data: &[u8]) -> Result<BadBlockTable> {
+        let table = match format {
+            BbtFormat::Json => serde_json::from_slice(data)
+                .map_err(|e| Error::Other(format!("Failed to parse BBT file: {}", e)))?,
+            BbtFormat::Text => self.decode_text(as_text(data)?)?,
+            BbtFormat::Binary => {
+                let capacity = data.len() * 4;
+                let total_blocks = self.total_blocks.unwrap_or(capacity);
+                if total_blocks > capacity {
+                    return Err(Error::InvalidParameter(format!(
+                        "Binary BBT holds {} blocks, not {}",
+                        capacity, total_blocks
+                    )));
+                }
+                nand_bbt::decode(data, total_blocks)
+            }
+            BbtFormat::UBoot => self.decode_uboot(as_text(data)?)?,
+        };
+        Ok(table)
+    }
+
+    fn require_block_size(&self) -> Result<u32> {
+        self.block_size.ok_or_else(|| {
+            Error::InvalidParameter("U-Boot BBT files need the block size".to_string())
+        })
+    }
+
+    fn require_total_blocks(&self, header: Option<usize>) -> Result<usize> {
+        self.total_blocks.or(header).ok_or_else(|| {
+            Error::InvalidParameter("The BBT file does not record the block count".to_string())
+        })
+    }
+
+    fn decode_text(&self, text: &str) -> Result<BadBlockTable> {
+        let header = text.lines().find_map(|line| {
+            let count = line
+                .trim()
+                .strip_prefix('#')?
+                .trim()
+                .strip_prefix("blocks:")?;
+            count.trim().parse().ok()
+        });
+        let mut table = good_table(self.require_total_blocks(header)?);
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let block = parse_number(fields.next().unwrap_or_default(), 10)
+                .ok_or_else(|| Error::InvalidParameter(format!("Bad BBT line: {}", line)))?;
+            let status = match fields.next() {
+                None | Some("factory") | Some("bad") => BlockStatus::BadFactory,
+                Some("runtime") => BlockStatus::BadRuntime,
+                Some("reserved") => BlockStatus::Reserved,
+                Some(other) => {
+                    return Err(Error::InvalidParameter(format!(
+                        "Unknown block status '{}'",
+                        other
+                    )))
+                }
+            };
+            set_bad(&mut table, block, status)?;
+        }
+        Ok(table)
+    }
+
+    fn decode_uboot(&self, text: &str) -> Result<BadBlockTable> {
+        let block_size = self.require_block_size()? as u64;
+        let mut table = good_table(self.require_total_blocks(None)?);
+
+        // Anything that is not a bare offset (headers, prompts) is skipped
+        for word in text.lines().filter_map(|line| {
+            let mut words = line.split_whitespace();
+            words.next().filter(|_| words.next().is_none())
+        }) {
+            let Some(offset) = parse_number(word, 16) else {
+                continue;
+            };
+            if !(offset as u64).is_multiple_of(block_size) {
+                return Err(Error::InvalidParameter(format!(
+                    "Offset {:#x} is not block aligned",
+                    offset
+                )));
+            }
+            set_bad(
+                &mut table,
+                (offset as u64 / block_size) as usize,
+                BlockStatus::BadFactory,
+            )?;
+        }
+        Ok(table)
+    }
+}
+
+fn as_text(data: &[u8]) -> Result<&str> {
+    std::str::from_utf8(data)
+        .map_err(|_| Error::InvalidParameter("BBT file is not text".to_string()))
+}
+
+/// `value` in `radix`, or in hex with a `0x` prefix
+fn parse_number(value: &str, radix: u32) -> Option<usize> {
+    match value.strip_prefix("0x").or(value.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => usize::from_str_radix(value, radix).ok(),
+    }
+}
+
+/// Listing formats only name the bad blocks; all others are good
+fn good_table(total_blocks: usize) -> BadBlockTable {
+    let mut table = BadBlockTable::new(total_blocks);
+    for block in 0..total_blocks {
+        table.set_status(block, BlockStatus::Good);
+    }
+    table
+}
+
+fn set_bad(table: &mut BadBlockTable, block: usize, status: BlockStatus) -> Result<()> {
+    if block >= table.len() {
+        return Err(Error::InvalidParameter(format!(
+            "Block {} is outside the table ({} blocks)",
+            block,
+            table.len()
+        )));
+    }
+    table.set_status(block, status);
+    Ok(())
+}
+
+fn encode_text(table: &BadBlockTable) -> String {
+    let mut text = format!("# blocks: {}\n", table.len());
+    for block in 0..table.len() {
+        let status = match table.get_status(block) {
+            BlockStatus::BadFactory => "factory",
+            BlockStatus::BadRuntime => "runtime",
+            BlockStatus::Reserved => "reserved",
+            BlockStatus::Unknown | BlockStatus::Good => continue,
+        };
+        text.push_str(&format!("{} {}\n", block, status));
+    }
+    text
+}
+
+fn encode_uboot(table: &BadBlockTable, block_size: u32) -> String {
+    let mut text = String::from("Device 0 bad blocks:\n");
+    for block in (0..table.len()).filter(|&block| table.is_bad(block)) {
+        text.push_str(&format!("  {:08x}\n", block as u64 * block_size as u64));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> BadBlockTable {
+        let mut table = good_table(16);
+        table.set_status(2, BlockStatus::BadFactory);
+        table.set_status(9, BlockStatus::BadRuntime);
+        table
+    }
+
+    #[test]
+    fn test_bbt_text_round_trip() {
+        let options = BbtFileOptions::default();
+        let text = options.encode(BbtFormat::Text, &sample()).unwrap();
+        assert_eq!(text, b"# blocks: 16\n2 factory\n9 runtime\n");
+
+        let table = options.decode(BbtFormat::Text, &text).unwrap();
+        assert_eq!(table.len(), 16);
+        assert_eq!(table.get_status(2), BlockStatus::BadFactory);
+        assert_eq!(table.get_status(9), BlockStatus::BadRuntime);
+        assert_eq!(table.get_status(3), BlockStatus::Good);
+
+        // A bare list needs the block count from elsewhere
+        assert!(options.decode(BbtFormat::Text, b"0x10\n").is_err());
+        let options = BbtFileOptions {
+            total_blocks: Some(32),
+            ..options
+        };
+        let table = options.decode(BbtFormat::Text, b"0x10\n").unwrap();
+        assert_eq!(table.get_status(16), BlockStatus::BadFactory);
+        assert!(options.decode(BbtFormat::Text, b"40\n").is_err());
+    }
+
+    #[test]
+    fn test_bbt_uboot_round_trip() {
+        let options = BbtFileOptions {
+            format: None,
+            total_blocks: Some(16),
+            block_size: Some(0x20000),
+        };
+        let text = options.encode(BbtFormat::UBoot, &sample()).unwrap();
+        assert_eq!(
+            text,
+            b"Device 0 bad blocks:\n  00040000\n  00120000\n".to_vec()
+        );
+
+        let table = options
+            .decode(
+                BbtFormat::UBoot,
+                b"=> nand bad\n\nDevice 0 bad blocks:\n  00040000\n  00120000\n=>\n",
+            )
+            .unwrap();
+        assert_eq!(table.bad_block_count(), 2);
+        assert!(table.is_bad(2));
+        assert!(table.is_bad(9));
+        assert!(options.decode(BbtFormat::UBoot, b"  00040010\n").is_err());
+    }
+
+    #[test]
+    fn test_bbt_binary_and_format_guess() {
+        let options = BbtFileOptions::default();
+        let data = options.encode(BbtFormat::Binary, &sample()).unwrap();
+        assert_eq!(data.len(), 4);
+        let table = options.decode(BbtFormat::Binary, &data).unwrap();
+        assert_eq!(table.len(), 16);
+        assert_eq!(table.get_status(9), BlockStatus::BadRuntime);
+
+        let detect = |name: &str, data: &[u8]| BbtFormat::detect(Path::new(name), data);
+        assert_eq!(detect("a.txt", b"{"), BbtFormat::Text);
+        assert_eq!(detect("a.bbt", b"2\n"), BbtFormat::Binary);
+        assert_eq!(detect("a", b"  {\"status\": []}"), BbtFormat::Json);
+        assert_eq!(detect("a.log", b"Device 0 bad blocks:\n"), BbtFormat::UBoot);
+        assert_eq!(detect("a", b"2 factory\n"), BbtFormat::Text);
+        assert_eq!(detect("a", &[0xF3, 0xFF]), BbtFormat::Binary);
+    }
+}
@@ -3,6 +3,8 @@
 //! This module defines what a Flash chip "is" and what it can do,
 //! independent of how it's programmed.
 
+use super::oob::OobLayout;
+use super::protection::{ProtectBit, ProtectionScheme};
 use super::types::{Capacity, FlashType, JedecId};
 
 /// Core specification of a flash chip
@@ -26,9 +28,22 @@ pub struct ChipLayout {
     pub oob_size: Option<u32>,
     /// Whether this is a DataFlash (AT45DB) with non-power-of-two pages
     pub is_dataflash: bool,
+    /// Number of stacked dies sharing the package, switched with Software
+    /// Die Select (0xC2). Each die holds an equal share of the capacity.
+    pub die_count: u32,
+    /// How the OOB area is divided (NAND only); `None` when unknown
+    pub oob_layout: Option<OobLayout>,
 }
 
 /// OTP (One-Time Programmable) region layout
+///
+/// `enter_opcode` selects the access scheme: 0x48 for security registers
+/// (Winbond and the Spansion FL-K series) or 0x4B for a directly addressed
+/// OTP array (Spansion). `exit_opcode` is unused by both.
+///
+/// For SPI NAND each region is one user OTP page (the pages after the unique
+/// ID and parameter pages), reached through the OTP_EN configuration bit; the
+/// opcodes are unused.
 #[derive(Debug, Clone, Copy)]
 pub struct OtpLayout {
     pub region_count: u32,
@@ -45,6 +60,10 @@ impl ChipLayout {
     pub fn total_pages(&self, capacity: Capacity) -> u32 {
         capacity.as_bytes() / self.page_size
     }
+
+    pub fn die_size(&self, capacity: Capacity) -> u32 {
+        capacity.as_bytes() / self.die_count.max(1)
+    }
 }
 
 /// What the chip supports
@@ -52,8 +71,174 @@ impl ChipLayout {
 pub struct ChipCapabilities {
     pub supports_ecc_control: bool,
     pub supports_4byte_addr: bool,
+    /// How addresses above 16 MiB are reached when `supports_4byte_addr` is set
+    pub four_byte_mode: FourByteMode,
     pub supports_quad_spi: bool,
     pub supports_dual_spi: bool,
+    /// Pipelined multi-page read the chip offers (NAND)
+    pub cache_read: NandCacheRead,
+    /// Erase granularities supported by the chip (NOR), smallest first.
+    /// Empty slots mean the chip only advertises its `block_size` erase.
+    pub erase_types: [Option<EraseType>; 4],
+    /// How the Quad Enable bit is set (NOR)
+    pub quad_enable: QuadEnableMethod,
+    /// Quad page program form the chip offers (NOR)
+    pub quad_program: QuadProgram,
+    /// Status/configuration registers, SR1 first (NOR). Empty means only
+    /// the generic SR1 is known.
+    pub status_registers: &'static [StatusRegister],
+    /// Block protection bits and semantics; `None` uses a generic scheme
+    pub protection: Option<ProtectionScheme>,
+    /// Status register bit that switches to individual block locks (NOR);
+    /// `None` when the chip has no individual block protection
+    pub wps: Option<ProtectBit>,
+    /// Bits the on-die ECC corrects per ECC step (NAND); 0 when unknown
+    pub ecc_strength: u8,
+    /// Where the corrected bit-flip count is reported (NAND)
+    pub ecc_status: EccStatusMethod,
+    /// Vendor read-retry levels for recovering worn pages (NAND)
+    pub read_retry: Option<ReadRetry>,
+}
+
+/// One NOR status or configuration register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusRegister {
+    pub name: &'static str,
+    pub read_opcode: u8,
+    pub write: StatusWrite,
+    /// Bit names from bit 7 down to bit 0; empty for reserved bits
+    pub bits: [&'static str; 8],
+}
+
+/// How a status register is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusWrite {
+    /// Written on its own with this opcode (e.g. 0x01, 0x31, 0x11)
+    Opcode(u8),
+    /// Appended after SR1 in a multi-byte 0x01 write
+    WithSr1,
+    /// Read-only (e.g. flag status registers)
+    ReadOnly,
+}
+
+/// Status register 1 as found on virtually every SPI NOR chip
+pub const GENERIC_SR1: StatusRegister = StatusRegister {
+    name: "SR1",
+    read_opcode: 0x05,
+    write: StatusWrite::Opcode(0x01),
+    bits: ["SRWD", "", "BP3", "BP2", "BP1", "BP0", "WEL", "WIP"],
+};
+
+impl StatusRegister {
+    /// Names of the bits set in `value`, most significant first
+    pub fn set_bits(&self, value: u8) -> Vec<&'static str> {
+        self.bits
+            .iter()
+            .enumerate()
+            .filter(|(i, name)| !name.is_empty() && value & (0x80 >> i) != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+/// A single erase granularity and the opcode that performs it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EraseType {
+    pub size: u32,
+    pub opcode: u8,
+}
+
+/// Pipelined multi-page read mode of an SPI NAND chip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NandCacheRead {
+    /// One Page Read (0x13) per page
+    #[default]
+    None,
+    /// Cache Read Sequential (0x31) loads the next page into the data
+    /// register while the current one is clocked out of the cache; Cache
+    /// Read End (0x3F) fetches the last page of a run
+    Sequential,
+    /// Winbond continuous read: with BUF=0 a single Read (0x03) streams
+    /// consecutive pages (main area only) until CS is released
+    Continuous,
+}
+
+/// Addressing strategy for NOR parts larger than 16 MiB (JESD216 DWORD 16)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FourByteMode {
+    /// Dedicated 4-byte opcodes (0x13/0x0C/0x12/0xDC...); the chip stays in
+    /// 3-byte mode
+    #[default]
+    Opcodes,
+    /// Enter 4-byte mode with 0xB7 and leave it with 0xE9, optionally
+    /// preceded by a write enable
+    EnterExit { write_enable: bool },
+    /// Stay in 3-byte mode and select the 16 MiB bank through the Extended
+    /// Address Register (0xC5/0xC8)
+    ExtendedAddress,
+}
+
+/// Quad Enable bit location and write method (JESD216 QER field)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuadEnableMethod {
+    /// No QE bit, or quad mode does not need enabling
+    #[default]
+    None,
+    /// QE is bit 1 of SR2, written with a 2-byte 0x01; SR2 has no read opcode
+    Sr2Bit1WriteSr1Sr2,
+    /// QE is bit 6 of SR1, written with a 1-byte 0x01
+    Sr1Bit6,
+    /// QE is bit 7 of SR2, read with 0x3F and written with 0x3E
+    Sr2Bit7,
+    /// QE is bit 1 of SR2, written with a 2-byte 0x01 (read SR2 with 0x35)
+    Sr2Bit1ReadSr2,
+    /// QE is bit 1 of SR2, read with 0x35 and written with 0x31
+    Sr2Bit1WriteSr2,
+    /// SPI NAND: QE is bit 0 of the configuration feature (0xB0)
+    ConfigBit0,
+}
+
+/// Quad page program command of a NOR chip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuadProgram {
+    /// No quad program; pages are programmed on one line
+    #[default]
+    None,
+    /// 1-1-4 quad input page program (0x32)
+    Input,
+    /// 1-4-4 quad I/O page program (0x38, Macronix 4PP)
+    Io,
+}
+
+/// How an SPI NAND reports the number of corrected bit flips
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EccStatusMethod {
+    /// Only the ECCS bits of the status feature (0xC0); a corrected page
+    /// counts as `ecc_strength` flips since the exact number is unknown
+    #[default]
+    Standard,
+    /// Macronix: ECCS 01 corrected, 10 uncorrectable; the flip count of the
+    /// worst sector is read with Get ECC Status (0x7C)
+    MacronixEccsr,
+    /// GigaDevice: ECCS 01 is refined by the ECCSE bits of status feature
+    /// 0xF0, ECCS 11 means 8 flips and 10 uncorrectable
+    GigaDeviceEccse,
+    /// Micron: ECCS bits 6:4 give a range (001 1-3, 011 4-6, 101 7-8 flips),
+    /// counted at its upper end like Linux does; 010 is uncorrectable
+    MicronEccs,
+    /// Toshiba/Kioxia: ECCS 01 or 11 corrected, 10 uncorrectable; bits 7:4
+    /// of feature 0x30 hold the most flips corrected in any sector
+    KioxiaMaxBitFlips,
+}
+
+/// Read-retry levels of a NAND chip: writing level `n` to the vendor
+/// feature register `feature` shifts the read reference voltages, writing
+/// 0 restores the default
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadRetry {
+    pub feature: u8,
+    /// Number of levels besides the default
+    pub levels: u8,
 }
 
 /// Bad block management status
@@ -76,6 +261,8 @@ mod tests {
             block_size: 128 * 1024, // 128KB
             oob_size: Some(64),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         };
 
         // Pages per block: 128KB / 2KB = 64
@@ -87,4 +274,11 @@ mod tests {
         let capacity = Capacity::megabytes(128);
         assert_eq!(layout.total_pages(capacity), 65536);
     }
+
+    #[test]
+    fn test_status_register_set_bits() {
+        assert_eq!(GENERIC_SR1.set_bits(0x1C), vec!["BP2", "BP1", "BP0"]);
+        // Reserved bits are never reported
+        assert_eq!(GENERIC_SR1.set_bits(0x40), Vec::<&str>::new());
+    }
 }
@@ -0,0 +1,113 @@
+//! Domain Model - Consensus Read
+//!
+//! A marginal connection, such as a clip-on test clip, flips random bits on
+//! the bus, so two dumps of the same chip rarely match. Reading a range
+//! several times and taking a bytewise majority vote recovers the data as
+//! long as most passes read each byte right. A byte without a strict
+//! majority has no consensus and fails the read.
+
+use crate::error::{Error, Result};
+
+/// A byte the read passes did not all agree on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Disagreement {
+    /// Start address of the read plus the byte's offset in the data read.
+    /// This is not the physical address when a NAND read skipped bad blocks
+    /// or included OOB bytes.
+    pub address: u32,
+    /// Passes that read the value the vote settled on
+    pub votes: u32,
+}
+
+/// Vote on the passes of one chunk read at `address`, recording the bytes
+/// they disagreed on in `disagreements`
+pub fn majority_vote(
+    address: u32,
+    passes: &[Vec<u8>],
+    disagreements: &mut Vec<Disagreement>,
+) -> Result<Vec<u8>> {
+    let Some((first, others)) = passes.split_first() else {
+        return Ok(Vec::new());
+    };
+    if others.iter().any(|pass| pass.len() != first.len()) {
+        return Err(Error::Other(format!(
+            "Read passes at address 0x{:08X} returned different lengths",
+            address
+        )));
+    }
+
+    let mut result = first.clone();
+    for (offset, byte) in result.iter_mut().enumerate() {
+        if others.iter().all(|pass| pass[offset] == *byte) {
+            continue;
+        }
+
+        let address = address + offset as u32;
+        let (value, votes) = passes
+            .iter()
+            .map(|pass| {
+                let value = pass[offset];
+                let votes = passes.iter().filter(|other| other[offset] == value).count();
+                (value, votes)
+            })
+            .max_by_key(|&(_, votes)| votes)
+            .unwrap_or_default();
+        if votes * 2 <= passes.len() {
+            return Err(Error::Other(format!(
+                "No consensus at address 0x{:08X}: the most common value was read by {} of {} passes",
+                address,
+                votes,
+                passes.len()
+            )));
+        }
+        *byte = value;
+        disagreements.push(Disagreement {
+            address,
+            votes: votes as u32,
+        });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_majority_vote() {
+        let passes = vec![
+            vec![0x11, 0x22, 0x33, 0x44],
+            vec![0x11, 0x2A, 0x33, 0x40],
+            vec![0x11, 0x22, 0x33, 0x44],
+        ];
+        let mut disagreements = Vec::new();
+        let data = majority_vote(0x100, &passes, &mut disagreements).unwrap();
+        assert_eq!(data, [0x11, 0x22, 0x33, 0x44]);
+        assert_eq!(
+            disagreements,
+            [
+                Disagreement {
+                    address: 0x101,
+                    votes: 2
+                },
+                Disagreement {
+                    address: 0x103,
+                    votes: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_majority_vote_without_consensus() {
+        let mut disagreements = Vec::new();
+        // Two passes that disagree have no majority
+        let err = majority_vote(0x10, &[vec![0, 1], vec![0, 2]], &mut disagreements)
+            .expect_err("A tie produced a consensus");
+        assert!(err.to_string().contains("0x00000011"));
+
+        let passes = [vec![1], vec![2], vec![3], vec![1]];
+        assert!(majority_vote(0, &passes, &mut disagreements).is_err());
+        assert!(majority_vote(0, &[vec![1, 2], vec![1]], &mut disagreements).is_err());
+    }
+}
@@ -0,0 +1,323 @@
+//! BCH Codes
+//!
+//! Binary BCH codes over GF(2^m), bit-compatible with the Linux `lib/bch.c`
+//! encoder used by `nand_bch`: data bits are taken MSB first and the parity
+//! bits are stored MSB first, zero-padded to whole bytes. Decoding computes
+//! the syndromes from the parity difference, finds the error locator with
+//! Berlekamp-Massey and its roots with a Chien search.
+
+use crate::error::{Error, Result};
+
+/// Primitive polynomials of GF(2^5)..GF(2^15), the ones Linux uses
+const PRIMITIVE_POLYNOMIALS: [u32; 11] = [
+    0x25, 0x43, 0x83, 0x11D, 0x211, 0x409, 0x805, 0x1053, 0x201B, 0x402B, 0x8003,
+];
+
+/// A BCH code correcting `t` bit errors per codeword
+#[derive(Debug, Clone)]
+pub struct Bch {
+    t: u32,
+    /// Multiplicative order of the field, 2^m - 1
+    n: u32,
+    /// `exp[i]` = alpha^i
+    exp: Vec<u16>,
+    /// `log[x]` = i such that alpha^i = x (x != 0)
+    log: Vec<u16>,
+    /// Degree of the generator polynomial, i.e. the number of parity bits
+    ecc_bits: u32,
+    /// Parity bytes per codeword, `ceil(m * t / 8)` as in Linux
+    ecc_bytes: usize,
+    /// 32-bit words holding the parity register
+    words: usize,
+    /// Parity of each byte value (`v(x) * x^ecc_bits mod g(x)`), left-aligned
+    table: Vec<u32>,
+}
+
+impl Bch {
+    /// Code over GF(2^m) correcting `t` bits
+    pub fn new(m: u32, t: u32) -> Result<Self> {
+        if !(5..=15).contains(&m) || t == 0 || m * t >= (1 << m) - 1 {
+            return Err(Error::InvalidParameter(format!(
+                "Unsupported BCH code: GF(2^{}) correcting {} bits",
+                m, t
+            )));
+        }
+
+        let n = (1u32 << m) - 1;
+        let polynomial = PRIMITIVE_POLYNOMIALS[(m - 5) as usize];
+        let mut exp = vec![0u16; n as usize];
+        let mut log = vec![0u16; n as usize + 1];
+        let mut x = 1u32;
+        for i in 0..n {
+            exp[i as usize] = x as u16;
+            log[x as usize] = i as u16;
+            x <<= 1;
+            if x & (1 << m) != 0 {
+                x ^= polynomial;
+            }
+        }
+
+        let mut bch = Self {
+            t,
+            n,
+            exp,
+            log,
+            ecc_bits: 0,
+            ecc_bytes: (m * t).div_ceil(8) as usize,
+            words: 0,
+            table: Vec::new(),
+        };
+
+        // g(x) is the product of (x + alpha^r) over alpha^1..alpha^2t and
+        // their conjugates; its coefficients end up in GF(2)
+        let mut roots = vec![false; n as usize];
+        for i in 0..t {
+            let mut r = 2 * i + 1;
+            for _ in 0..m {
+                roots[r as usize] = true;
+                r = 2 * r % n;
+            }
+        }
+        let mut generator = vec![1u16];
+        for r in (0..n).filter(|&r| roots[r as usize]) {
+            let root = bch.exp[r as usize];
+            let mut product = vec![0u16; generator.len() + 1];
+            for (i, &c) in generator.iter().enumerate() {
+                product[i + 1] ^= c;
+                product[i] ^= bch.mul(c, root);
+            }
+            generator = product;
+        }
+
+        bch.ecc_bits = generator.len() as u32 - 1;
+        bch.words = bch.ecc_bits.div_ceil(32) as usize;
+
+        // The generator without its leading term, highest degree first
+        let mut low = vec![0u32; bch.words];
+        for k in 0..bch.ecc_bits as usize {
+            if generator[bch.ecc_bits as usize - 1 - k] != 0 {
+                low[k / 32] |= 0x8000_0000 >> (k % 32);
+            }
+        }
+
+        bch.table = vec![0u32; 256 * bch.words];
+        for value in 0..256usize {
+            let mut register = vec![0u32; bch.words];
+            for bit in (0..8).rev() {
+                let feedback = (value >> bit) & 1 != 0;
+                let msb = register[0] & 0x8000_0000 != 0;
+                shift_left(&mut register, 1);
+                if feedback != msb {
+                    register.iter_mut().zip(&low).for_each(|(r, g)| *r ^= g);
+                }
+            }
+            bch.table[value * bch.words..(value + 1) * bch.words].copy_from_slice(&register);
+        }
+
+        Ok(bch)
+    }
+
+    /// Bits corrected per codeword
+    pub fn strength(&self) -> u32 {
+        self.t
+    }
+
+    /// Parity bytes per codeword
+    pub fn ecc_bytes(&self) -> usize {
+        self.ecc_bytes
+    }
+
+    /// Parity bytes of `data`
+    pub fn encode(&self, data: &[u8]) -> Vec<u8> {
+        let mut register = vec![0u32; self.words];
+        for &byte in data {
+            let index = ((register[0] >> 24) as u8 ^ byte) as usize;
+            shift_left(&mut register, 8);
+            let row = &self.table[index * self.words..(index + 1) * self.words];
+            register.iter_mut().zip(row).for_each(|(r, p)| *r ^= p);
+        }
+
+        let mut ecc: Vec<u8> = register.iter().flat_map(|w| w.to_be_bytes()).collect();
+        ecc.resize(self.ecc_bytes, 0);
+        ecc
+    }
+
+    /// Correct `data` given the parity read from flash and the parity
+    /// computed over `data`. Returns the number of bit errors found (in data
+    /// and parity), or `None` when there are more than the code corrects.
+    pub fn correct(&self, data: &mut [u8], read_ecc: &[u8], calc_ecc: &[u8]) -> Option<u32> {
+        let diff: Vec<u8> = read_ecc.iter().zip(calc_ecc).map(|(r, c)| r ^ c).collect();
+        let w = self.ecc_bits;
+        let errors: Vec<u32> = (0..w)
+            .filter(|&k| diff[k as usize / 8] & (0x80 >> (k % 8)) != 0)
+            .map(|k| w - 1 - k)
+            .collect();
+        if errors.is_empty() {
+            return Some(0);
+        }
+
+        // S_j = r(alpha^j) of the parity difference polynomial, j = 1..2t
+        let mut syndromes = vec![0u16; 2 * self.t as usize];
+        for &degree in &errors {
+            for (j, s) in syndromes.iter_mut().enumerate() {
+                *s ^= self.exp[((j as u64 + 1) * degree as u64 % self.n as u64) as usize];
+            }
+        }
+
+        let locator = self.berlekamp_massey(&syndromes)?;
+        let degree = locator.len() - 1;
+
+        // Chien search over the positions of the shortened codeword
+        let data_bits = data.len() as u32 * 8;
+        let mut positions = Vec::with_capacity(degree);
+        for p in 0..data_bits + w {
+            let value = locator.iter().enumerate().fold(0u16, |acc, (i, &c)| {
+                if c == 0 {
+                    return acc;
+                }
+                let e = (self.log[c as usize] as u64 + self.n as u64
+                    - (p as u64 * i as u64) % self.n as u64)
+                    % self.n as u64;
+                acc ^ self.exp[e as usize]
+            });
+            if value == 0 {
+                positions.push(p);
+            }
+        }
+        if positions.len() != degree {
+            return None;
+        }
+
+        for p in positions {
+            if p >= w {
+                let bit = data_bits - 1 - (p - w);
+                data[bit as usize / 8] ^= 0x80 >> (bit % 8);
+            }
+        }
+        Some(degree as u32)
+    }
+
+    /// Error locator polynomial (lowest degree first), or `None` when its
+    /// degree exceeds `t`
+    fn berlekamp_massey(&self, syndromes: &[u16]) -> Option<Vec<u16>> {
+        let len = syndromes.len() + 1;
+        let mut c = vec![0u16; len];
+        let mut b = vec![0u16; len];
+        c[0] = 1;
+        b[0] = 1;
+        let mut l = 0usize;
+        let mut shift = 1usize;
+        let mut last = 1u16;
+
+        for k in 0..syndromes.len() {
+            let d = (1..=l).fold(syndromes[k], |d, i| d ^ self.mul(c[i], syndromes[k - i]));
+            if d == 0 {
+                shift += 1;
+                continue;
+            }
+
+            let coefficient = self.div(d, last);
+            let previous = c.clone();
+            for i in 0..len - shift {
+                c[i + shift] ^= self.mul(coefficient, b[i]);
+            }
+            if 2 * l <= k {
+                l = k + 1 - l;
+                b = previous;
+                last = d;
+                shift = 1;
+            } else {
+                shift += 1;
+            }
+        }
+
+        if l > self.t as usize || c[l] == 0 {
+            return None;
+        }
+        c.truncate(l + 1);
+        Some(c)
+    }
+
+    fn mul(&self, a: u16, b: u16) -> u16 {
+        if a == 0 || b == 0 {
+            return 0;
+        }
+        let e = (self.log[a as usize] as u32 + self.log[b as usize] as u32) % self.n;
+        self.exp[e as usize]
+    }
+
+    fn div(&self, a: u16, b: u16) -> u16 {
+        if a == 0 {
+            return 0;
+        }
+        let e = (self.log[a as usize] as u32 + self.n - self.log[b as usize] as u32) % self.n;
+        self.exp[e as usize]
+    }
+}
+
+/// Shift a left-aligned multi-word register towards its MSB
+fn shift_left(register: &mut [u32], bits: u32) {
+    for i in 0..register.len() {
+        let carry = register.get(i + 1).map_or(0, |next| next >> (32 - bits));
+        register[i] = (register[i] << bits) | carry;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 + i / 3) as u8).collect()
+    }
+
+    #[test]
+    fn test_bch_parameters() {
+        let bch = Bch::new(13, 4).unwrap();
+        assert_eq!(bch.ecc_bits, 52);
+        assert_eq!(bch.ecc_bytes(), 7);
+
+        let bch = Bch::new(13, 8).unwrap();
+        assert_eq!(bch.ecc_bits, 104);
+        assert_eq!(bch.ecc_bytes(), 13);
+
+        assert!(Bch::new(4, 1).is_err());
+        assert!(Bch::new(13, 0).is_err());
+    }
+
+    #[test]
+    fn test_bch_corrects_up_to_strength() {
+        let bch = Bch::new(13, 8).unwrap();
+        let data = test_data(512);
+        let ecc = bch.encode(&data);
+
+        let mut clean = data.clone();
+        assert_eq!(bch.correct(&mut clean, &ecc, &ecc), Some(0));
+
+        // Seven flips in the data and one in the parity
+        let mut corrupted = data.clone();
+        for bit in [0usize, 9, 700, 1234, 2048, 3000, 4095] {
+            corrupted[bit / 8] ^= 0x80 >> (bit % 8);
+        }
+        let mut read_ecc = ecc.clone();
+        read_ecc[3] ^= 0x10;
+
+        let calc = bch.encode(&corrupted);
+        assert_eq!(bch.correct(&mut corrupted, &read_ecc, &calc), Some(8));
+        assert_eq!(corrupted, data);
+    }
+
+    #[test]
+    fn test_bch_detects_too_many_errors() {
+        let bch = Bch::new(13, 4).unwrap();
+        let data = test_data(512);
+        let ecc = bch.encode(&data);
+
+        let mut corrupted = data.clone();
+        for byte in [1usize, 50, 100, 200, 300, 400] {
+            corrupted[byte] ^= 0x01;
+        }
+        let calc = bch.encode(&corrupted);
+        assert_eq!(bch.correct(&mut corrupted, &ecc, &calc), None);
+    }
+}
@@ -0,0 +1,131 @@
+//! Hamming Codes
+//!
+//! The 1-bit correcting Hamming code of Linux `nand_ecc` (the SmartMedia
+//! scheme): 3 ECC bytes per 256 or 512 data bytes, made of inverted row
+//! parities over the byte addresses and column parities over the bit
+//! positions. Bytes are stored in Linux's default (non-SmartMedia) order.
+
+/// ECC bytes per step
+pub const ECC_BYTES: usize = 3;
+
+/// Inverted parity: 1 when `x` has an even number of set bits
+fn inv_parity(x: u8) -> u8 {
+    x.count_ones().is_multiple_of(2) as u8
+}
+
+/// Row address bits 1, 3, 5 and 7 of a parity difference byte
+fn address_bits(x: u8) -> u8 {
+    (0..4).fold(0, |acc, i| acc | (((x >> (2 * i + 1)) & 1) << i))
+}
+
+/// ECC bytes of a 256 or 512 byte step
+pub fn calculate(data: &[u8]) -> [u8; ECC_BYTES] {
+    let address_width = if data.len() > 256 { 9 } else { 8 };
+
+    // rp[2k] covers the bytes whose address bit k is 0, rp[2k + 1] the rest
+    let mut rp = [0u8; 18];
+    let mut par = 0u8;
+    for (i, &byte) in data.iter().enumerate() {
+        par ^= byte;
+        for k in 0..address_width {
+            rp[2 * k + ((i >> k) & 1)] ^= byte;
+        }
+    }
+
+    let row = |first: usize| (0..8).fold(0, |acc, j| acc | (inv_parity(rp[first + j]) << j));
+    let tail = if address_width == 9 {
+        (inv_parity(rp[17]) << 1) | inv_parity(rp[16])
+    } else {
+        0x03
+    };
+    [
+        row(8),
+        row(0),
+        (inv_parity(par & 0xF0) << 7)
+            | (inv_parity(par & 0x0F) << 6)
+            | (inv_parity(par & 0xCC) << 5)
+            | (inv_parity(par & 0x33) << 4)
+            | (inv_parity(par & 0xAA) << 3)
+            | (inv_parity(par & 0x55) << 2)
+            | tail,
+    ]
+}
+
+/// Correct `data` given the ECC read from flash and the ECC computed over
+/// `data`. Returns the number of bit errors found, or `None` when the error
+/// cannot be corrected.
+pub fn correct(data: &mut [u8], read_ecc: &[u8], calc_ecc: &[u8]) -> Option<u32> {
+    let b0 = read_ecc[1] ^ calc_ecc[1];
+    let b1 = read_ecc[0] ^ calc_ecc[0];
+    let b2 = read_ecc[2] ^ calc_ecc[2];
+    if b0 | b1 | b2 == 0 {
+        return Some(0);
+    }
+
+    // A single data bit error flips exactly one parity of every pair
+    let large = data.len() > 256;
+    let pairs = |b: u8, mask: u8| (b ^ (b >> 1)) & mask == mask;
+    if pairs(b0, 0x55) && pairs(b1, 0x55) && pairs(b2, if large { 0x55 } else { 0x54 }) {
+        let mut byte = ((address_bits(b1) as usize) << 4) | address_bits(b0) as usize;
+        if large {
+            byte |= (address_bits(b2 & 0x03) as usize) << 8;
+        }
+        data[byte] ^= 1 << address_bits(b2 >> 2);
+        return Some(1);
+    }
+
+    // A single flipped bit in the ECC bytes themselves
+    if b0.count_ones() + b1.count_ones() + b2.count_ones() == 1 {
+        return Some(1);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hamming_erased_step() {
+        assert_eq!(calculate(&[0xFF; 256]), [0xFF; 3]);
+        assert_eq!(calculate(&[0xFF; 512]), [0xFF; 3]);
+    }
+
+    #[test]
+    fn test_hamming_known_answers() {
+        // Reference values of the Linux nand_ecc calculate (default byte
+        // order); the data is the C library LCG used by the BCH vectors
+        let data = crate::domain::ecc::tests::lcg_data();
+        assert_eq!(calculate(&data[..256]), [0xC3, 0xFF, 0x03]);
+        assert_eq!(calculate(&data[256..]), [0xFC, 0xCC, 0x3F]);
+        assert_eq!(calculate(&data), [0xC0, 0xCC, 0xC3]);
+
+        // A single set bit: byte 100, bit 4
+        let mut one_bit = [0u8; 256];
+        one_bit[100] = 0x10;
+        assert_eq!(calculate(&one_bit), [0x96, 0x9A, 0x6B]);
+    }
+
+    #[test]
+    fn test_hamming_corrects_single_bit() {
+        for len in [256usize, 512] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 13) as u8).collect();
+            let ecc = calculate(&data);
+
+            for (byte, bit) in [(0usize, 0u8), (77, 5), (len - 1, 7)] {
+                let mut corrupted = data.clone();
+                corrupted[byte] ^= 1 << bit;
+                let calc = calculate(&corrupted);
+                assert_eq!(correct(&mut corrupted, &ecc, &calc), Some(1));
+                assert_eq!(corrupted, data);
+            }
+
+            // Two flips are detected, not miscorrected
+            let mut corrupted = data.clone();
+            corrupted[3] ^= 0x01;
+            corrupted[9] ^= 0x04;
+            let calc = calculate(&corrupted);
+            assert_eq!(correct(&mut corrupted, &ecc, &calc), None);
+        }
+    }
+}
@@ -0,0 +1,532 @@
+//! Domain Model - ECC (Error Correction Code) Management
+//!
+//! This module defines ECC policies and status types,
+//! independent of implementation details, and the host-side (software)
+//! ECC used for NAND without usable on-die ECC.
+
+pub mod bch;
+pub mod hamming;
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::error::{Error, Result};
+use bch::Bch;
+
+/// Policy for ECC handling during operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EccPolicy {
+    /// Use hardware ECC (chip's internal ECC)
+    #[default]
+    Hardware,
+    /// Disable hardware ECC for raw access
+    Disabled,
+    /// Use software ECC (for chips without hardware ECC)
+    Software,
+}
+
+impl EccPolicy {
+    /// Policy of a request: software ECC, when configured, replaces the
+    /// on-die ECC
+    pub fn select(use_ecc: bool, soft_ecc: Option<&SoftEccConfig>) -> Self {
+        match (soft_ecc, use_ecc) {
+            (Some(_), _) => Self::Software,
+            (None, true) => Self::Hardware,
+            (None, false) => Self::Disabled,
+        }
+    }
+
+    /// Returns true if ECC should be enabled
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self, Self::Disabled)
+    }
+
+    /// Returns true if using hardware ECC
+    pub fn is_hardware(&self) -> bool {
+        matches!(self, Self::Hardware)
+    }
+}
+
+/// Status of ECC operation after a read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EccStatus {
+    /// No errors detected
+    NoError,
+    /// Errors detected and corrected
+    Corrected {
+        /// Number of bits corrected
+        bit_flips: u8,
+    },
+    /// Errors detected but uncorrectable
+    Uncorrectable,
+    /// ECC not available or disabled
+    NotAvailable,
+}
+
+impl EccStatus {
+    /// Returns true if data is valid (no error or corrected)
+    pub fn is_valid(&self) -> bool {
+        matches!(
+            self,
+            Self::NoError | Self::Corrected { .. } | Self::NotAvailable
+        )
+    }
+
+    /// Returns true if there were bit flips that got corrected
+    pub fn had_corrections(&self) -> bool {
+        matches!(self, Self::Corrected { .. })
+    }
+}
+
+/// Flip count from which a page should be refreshed: three quarters of
+/// the ECC `strength`, like the Linux MTD default `bitflip_threshold`
+pub fn bitflip_threshold(strength: u8) -> u8 {
+    ((strength as u32 * 3).div_ceil(4) as u8).max(1)
+}
+
+/// ECC outcome of one page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageEcc {
+    pub page: u32,
+    pub status: EccStatus,
+}
+
+/// An uncorrectable page that decoded at a read-retry level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadRetryRecovery {
+    pub page: u32,
+    pub level: u8,
+}
+
+/// Per-page ECC outcomes collected during a read
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EccReport {
+    /// Pages read with ECC enabled, in read order
+    pub pages: Vec<PageEcc>,
+    /// Bits the on-die ECC corrects per step; 0 when unknown
+    pub strength: u8,
+    /// Pages recovered by shifting the read voltages (SPI NAND)
+    pub read_retries: Vec<ReadRetryRecovery>,
+}
+
+impl EccReport {
+    /// Number of pages per corrected bit-flip count (0 = clean).
+    /// Uncorrectable pages are not included.
+    pub fn histogram(&self) -> BTreeMap<u8, u32> {
+        let mut histogram = BTreeMap::new();
+        for page in &self.pages {
+            let flips = match page.status {
+                EccStatus::NoError => 0,
+                EccStatus::Corrected { bit_flips } => bit_flips,
+                EccStatus::Uncorrectable | EccStatus::NotAvailable => continue,
+            };
+            *histogram.entry(flips).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Flip count from which a page should be refreshed, see
+    /// [`bitflip_threshold`]
+    pub fn threshold(&self) -> u8 {
+        bitflip_threshold(self.strength)
+    }
+
+    /// Corrected pages at or above [`threshold`](Self::threshold)
+    pub fn near_threshold(&self) -> Vec<PageEcc> {
+        let threshold = self.threshold();
+        self.pages
+            .iter()
+            .filter(|page| {
+                matches!(page.status, EccStatus::Corrected { bit_flips } if bit_flips >= threshold)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Pages the ECC could not correct
+    pub fn uncorrectable(&self) -> Vec<u32> {
+        self.pages
+            .iter()
+            .filter(|page| page.status == EccStatus::Uncorrectable)
+            .map(|page| page.page)
+            .collect()
+    }
+}
+
+/// Host-side ECC algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoftEccAlgorithm {
+    /// 1-bit Hamming code, 3 bytes per step (Linux `nand_ecc`)
+    Hamming,
+    /// BCH code correcting `strength` bits per step (Linux `nand_bch`)
+    Bch { strength: u8 },
+}
+
+/// Software ECC configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoftEccConfig {
+    pub algorithm: SoftEccAlgorithm,
+    /// Data bytes covered by one ECC step
+    pub step_size: u32,
+    /// Offset of the ECC bytes within the OOB area; `None` uses the Linux
+    /// large page layout (see [`SoftEcc::ecc_range`])
+    pub ecc_offset: Option<u32>,
+}
+
+impl SoftEccConfig {
+    /// Hamming over 256-byte steps, the Linux default
+    pub fn hamming() -> Self {
+        Self {
+            algorithm: SoftEccAlgorithm::Hamming,
+            step_size: 256,
+            ecc_offset: None,
+        }
+    }
+
+    /// BCH correcting `strength` bits per 512-byte step
+    pub fn bch(strength: u8) -> Self {
+        Self {
+            algorithm: SoftEccAlgorithm::Bch { strength },
+            step_size: 512,
+            ecc_offset: None,
+        }
+    }
+}
+
+/// Computes and checks software ECC over raw NAND pages (data followed by
+/// the OOB area), with the ECC bytes of all steps stored contiguously in OOB
+#[derive(Debug, Clone)]
+pub struct SoftEcc {
+    config: SoftEccConfig,
+    bch: Option<Bch>,
+    /// ECC of an erased step, inverted; XORed into every BCH ECC so that
+    /// erased pages carry valid all-0xFF ECC (as `nand_bch` does)
+    erased_mask: Vec<u8>,
+}
+
+impl SoftEcc {
+    pub fn new(config: SoftEccConfig) -> Result<Self> {
+        let step_size = config.step_size as usize;
+        let bch = match config.algorithm {
+            SoftEccAlgorithm::Hamming => {
+                if step_size != 256 && step_size != 512 {
+                    return Err(Error::InvalidParameter(format!(
+                        "Hamming ECC needs 256 or 512 byte steps, not {}",
+                        step_size
+                    )));
+                }
+                None
+            }
+            SoftEccAlgorithm::Bch { strength } => {
+                if step_size == 0 {
+                    return Err(Error::InvalidParameter(
+                        "ECC step size must not be zero".to_string(),
+                    ));
+                }
+                // Smallest field whose codewords hold a step, as in nand_bch
+                let m = u32::BITS - (1 + 8 * config.step_size).leading_zeros();
+                Some(Bch::new(m, strength as u32)?)
+            }
+        };
+
+        let mut ecc = Self {
+            config,
+            bch,
+            erased_mask: Vec::new(),
+        };
+        if let Some(bch) = &ecc.bch {
+            ecc.erased_mask = bch
+                .encode(&vec![0xFF; step_size])
+                .iter()
+                .map(|b| !b)
+                .collect();
+        }
+        Ok(ecc)
+    }
+
+    pub fn config(&self) -> &SoftEccConfig {
+        &self.config
+    }
+
+    /// Bits corrected per step
+    pub fn strength(&self) -> u8 {
+        match self.config.algorithm {
+            SoftEccAlgorithm::Hamming => 1,
+            SoftEccAlgorithm::Bch { strength } => strength,
+        }
+    }
+
+    /// ECC bytes per step
+    pub fn bytes_per_step(&self) -> usize {
+        match &self.bch {
+            Some(bch) => bch.ecc_bytes(),
+            None => hamming::ECC_BYTES,
+        }
+    }
+
+    /// Where the ECC bytes of a `page_size` page live within its
+    /// `oob_size` byte OOB area. Without an explicit offset this follows
+    /// Linux: `nand_ooblayout_lp_hamming` puts Hamming ECC at offset 40 of a
+    /// 64-byte OOB or 80 of a 128-byte one, and `nand_ooblayout_lp` puts BCH
+    /// (and Hamming on other OOB sizes) at the end of the OOB.
+    pub fn ecc_range(&self, page_size: usize, oob_size: usize) -> Result<Range<usize>> {
+        let step_size = self.config.step_size as usize;
+        if !page_size.is_multiple_of(step_size) {
+            return Err(Error::InvalidParameter(format!(
+                "Page size {} is not a multiple of the {} byte ECC step",
+                page_size, step_size
+            )));
+        }
+        let total = page_size / step_size * self.bytes_per_step();
+        // The first two OOB bytes hold the bad block marker
+        let start = match (self.config.ecc_offset, self.config.algorithm, oob_size) {
+            (Some(offset), _, _) => offset as usize,
+            (None, SoftEccAlgorithm::Hamming, 64) => 40,
+            (None, SoftEccAlgorithm::Hamming, 128) => 80,
+            (None, _, _) => oob_size.saturating_sub(total).max(2),
+        };
+        if start + total > oob_size {
+            return Err(Error::InvalidParameter(format!(
+                "{} ECC bytes at OOB offset {} do not fit in {} bytes of OOB",
+                total, start, oob_size
+            )));
+        }
+        Ok(start..start + total)
+    }
+
+    /// ECC bytes of one step of data
+    pub fn calculate(&self, data: &[u8]) -> Vec<u8> {
+        match &self.bch {
+            Some(bch) => bch
+                .encode(data)
+                .iter()
+                .zip(&self.erased_mask)
+                .map(|(e, m)| e ^ m)
+                .collect(),
+            None => hamming::calculate(data).to_vec(),
+        }
+    }
+
+    /// Correct one step of data in place against the ECC read with it
+    pub fn correct(&self, data: &mut [u8], read_ecc: &[u8]) -> EccStatus {
+        let calc_ecc = self.calculate(data);
+        let corrected = match &self.bch {
+            Some(bch) => bch.correct(data, read_ecc, &calc_ecc),
+            None => hamming::correct(data, read_ecc, &calc_ecc),
+        };
+        match corrected {
+            Some(0) => EccStatus::NoError,
+            Some(bit_flips) => EccStatus::Corrected {
+                bit_flips: bit_flips as u8,
+            },
+            None => EccStatus::Uncorrectable,
+        }
+    }
+
+    /// Fill in the ECC bytes of a raw page from its data
+    pub fn encode_page(&self, raw: &mut [u8], page_size: usize) -> Result<()> {
+        let range = self.ecc_range(page_size, raw.len() - page_size)?;
+        let (data, oob) = raw.split_at_mut(page_size);
+        let ecc = &mut oob[range];
+        for (step, ecc) in data
+            .chunks(self.config.step_size as usize)
+            .zip(ecc.chunks_mut(self.bytes_per_step()))
+        {
+            ecc.copy_from_slice(&self.calculate(step));
+        }
+        Ok(())
+    }
+
+    /// Correct the data of a raw page in place. The result is the worst
+    /// step: uncorrectable if any step is, else the most bits corrected.
+    pub fn decode_page(&self, raw: &mut [u8], page_size: usize) -> Result<EccStatus> {
+        let range = self.ecc_range(page_size, raw.len() - page_size)?;
+        let (data, oob) = raw.split_at_mut(page_size);
+        let mut worst = EccStatus::NoError;
+        for (step, ecc) in data
+            .chunks_mut(self.config.step_size as usize)
+            .zip(oob[range].chunks(self.bytes_per_step()))
+        {
+            worst = match (worst, self.correct(step, ecc)) {
+                (EccStatus::Uncorrectable, _) | (_, EccStatus::Uncorrectable) => {
+                    EccStatus::Uncorrectable
+                }
+                (EccStatus::Corrected { bit_flips: a }, EccStatus::Corrected { bit_flips: b }) => {
+                    EccStatus::Corrected {
+                        bit_flips: a.max(b),
+                    }
+                }
+                (EccStatus::NoError, status) | (status, _) => status,
+            };
+        }
+        Ok(worst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ecc_policy_helpers() {
+        let hw = EccPolicy::Hardware;
+        assert!(hw.is_enabled());
+        assert!(hw.is_hardware());
+
+        let sw = EccPolicy::Software;
+        assert!(sw.is_enabled());
+        assert!(!sw.is_hardware());
+
+        let disabled = EccPolicy::Disabled;
+        assert!(!disabled.is_enabled());
+        assert!(!disabled.is_hardware());
+    }
+
+    #[test]
+    fn test_ecc_policy_select() {
+        let bch = SoftEccConfig::bch(8);
+        assert_eq!(EccPolicy::select(true, None), EccPolicy::Hardware);
+        assert_eq!(EccPolicy::select(false, None), EccPolicy::Disabled);
+        assert_eq!(EccPolicy::select(true, Some(&bch)), EccPolicy::Software);
+        assert_eq!(EccPolicy::select(false, Some(&bch)), EccPolicy::Software);
+    }
+
+    #[test]
+    fn test_ecc_status_helpers() {
+        let no_error = EccStatus::NoError;
+        assert!(no_error.is_valid());
+        assert!(!no_error.had_corrections());
+
+        let corrected = EccStatus::Corrected { bit_flips: 2 };
+        assert!(corrected.is_valid());
+        assert!(corrected.had_corrections());
+
+        let uncorrectable = EccStatus::Uncorrectable;
+        assert!(!uncorrectable.is_valid());
+        assert!(!uncorrectable.had_corrections());
+
+        let not_available = EccStatus::NotAvailable;
+        assert!(not_available.is_valid()); // Considered valid as we can't tell
+        assert!(!not_available.had_corrections());
+    }
+
+    #[test]
+    fn test_ecc_report_histogram_and_threshold() {
+        let status = [
+            EccStatus::NoError,
+            EccStatus::Corrected { bit_flips: 2 },
+            EccStatus::Corrected { bit_flips: 6 },
+            EccStatus::NoError,
+            EccStatus::Uncorrectable,
+        ];
+        let report = EccReport {
+            pages: status
+                .iter()
+                .enumerate()
+                .map(|(page, &status)| PageEcc {
+                    page: page as u32,
+                    status,
+                })
+                .collect(),
+            strength: 8,
+            ..Default::default()
+        };
+
+        let histogram = report.histogram();
+        assert_eq!(histogram.get(&0), Some(&2));
+        assert_eq!(histogram.get(&2), Some(&1));
+        assert_eq!(histogram.get(&6), Some(&1));
+        assert_eq!(report.threshold(), 6);
+        assert_eq!(report.near_threshold().len(), 1);
+        assert_eq!(report.near_threshold()[0].page, 2);
+        assert_eq!(report.uncorrectable(), vec![4]);
+    }
+
+    #[test]
+    fn test_soft_ecc_layout() {
+        let bch = SoftEcc::new(SoftEccConfig::bch(8)).unwrap();
+        assert_eq!(bch.bytes_per_step(), 13);
+        assert_eq!(bch.ecc_range(2048, 64).unwrap(), 12..64);
+        assert!(bch.ecc_range(4096, 64).is_err());
+
+        let hamming = SoftEcc::new(SoftEccConfig::hamming()).unwrap();
+        assert_eq!(hamming.ecc_range(2048, 64).unwrap(), 40..64);
+        // nand_ooblayout_lp_hamming: offset 80 on 128-byte OOB, not the end
+        assert_eq!(hamming.ecc_range(2048, 128).unwrap(), 80..104);
+        assert_eq!(hamming.ecc_range(4096, 256).unwrap(), 208..256);
+        let hamming_512 = SoftEcc::new(SoftEccConfig {
+            step_size: 512,
+            ..SoftEccConfig::hamming()
+        })
+        .unwrap();
+        assert_eq!(hamming_512.ecc_range(2048, 64).unwrap(), 40..52);
+        assert_eq!(bch.ecc_range(2048, 128).unwrap(), 76..128);
+
+        let config = SoftEccConfig {
+            ecc_offset: Some(8),
+            ..SoftEccConfig::bch(4)
+        };
+        let offset = SoftEcc::new(config).unwrap();
+        assert_eq!(offset.ecc_range(2048, 64).unwrap(), 8..36);
+    }
+
+    /// 512 bytes from the C library LCG (`rand()` with seed 1, bits 16-23)
+    pub(super) fn lcg_data() -> Vec<u8> {
+        let mut x = 1u32;
+        (0..512)
+            .map(|_| {
+                x = x.wrapping_mul(1_103_515_245).wrapping_add(12345) & 0x7FFF_FFFF;
+                (x >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_soft_ecc_known_answers() {
+        // Reference values of the Linux nand_bch calculate over 512-byte
+        // steps (GF(2^13), ECC inverted against an erased step)
+        let bch4 = SoftEcc::new(SoftEccConfig::bch(4)).unwrap();
+        assert_eq!(
+            bch4.calculate(&[0; 512]),
+            [0x28, 0x13, 0xCC, 0x39, 0x96, 0xAC, 0x7F]
+        );
+        assert_eq!(
+            bch4.calculate(&lcg_data()),
+            [0x70, 0xCF, 0x0B, 0xA9, 0xA1, 0x18, 0xCF]
+        );
+        let bch8 = SoftEcc::new(SoftEccConfig::bch(8)).unwrap();
+        assert_eq!(
+            bch8.calculate(&lcg_data()),
+            [0xE6, 0xEC, 0x8C, 0x77, 0x77, 0xDC, 0x31, 0x61, 0xB9, 0xEF, 0xA0, 0xA3, 0xEC]
+        );
+    }
+
+    #[test]
+    fn test_soft_ecc_page_round_trip() {
+        for config in [SoftEccConfig::hamming(), SoftEccConfig::bch(4)] {
+            let ecc = SoftEcc::new(config).unwrap();
+
+            // Erased pages carry valid ECC
+            let mut erased = vec![0xFF; 2048 + 64];
+            assert_eq!(
+                ecc.decode_page(&mut erased, 2048).unwrap(),
+                EccStatus::NoError
+            );
+            let mut encoded = erased.clone();
+            ecc.encode_page(&mut encoded, 2048).unwrap();
+            assert_eq!(encoded, erased);
+
+            let mut raw: Vec<u8> = (0..2048 + 64).map(|i| (i * 31) as u8).collect();
+            ecc.encode_page(&mut raw, 2048).unwrap();
+            let original = raw.clone();
+
+            raw[100] ^= 0x08;
+            raw[1500] ^= 0x40;
+            assert_eq!(
+                ecc.decode_page(&mut raw, 2048).unwrap(),
+                EccStatus::Corrected { bit_flips: 1 }
+            );
+            assert_eq!(raw, original);
+        }
+    }
+}
@@ -3,6 +3,9 @@
 //! Definintions of core flash operations as seen by the application.
 
 use super::bad_block::{BadBlockStrategy, BadBlockTable};
+use super::consensus::Disagreement;
+use super::ecc::{EccPolicy, EccReport, SoftEccConfig};
+use super::partition::Partition;
 use super::types::{Address, Progress};
 use crate::error::Result;
 
@@ -13,12 +16,25 @@ pub struct ReadRequest {
     pub use_ecc: bool,
     /// Ignore ECC errors and continue reading (for data recovery)
     pub ignore_ecc_errors: bool,
+    /// Correct with host-side ECC over the OOB area instead of on-die ECC
+    /// (SPI NAND only)
+    pub soft_ecc: Option<SoftEccConfig>,
     pub oob_mode: OobMode,
     pub bad_block_strategy: BadBlockStrategy,
     /// Pre-scanned Bad Block Table (optional)
     pub bbt: Option<BadBlockTable>,
     /// Number of retries for read operations
     pub retry_count: u32,
+    /// Partitions that keep bad block skipping within their bounds
+    /// (NAND only); empty to skip across the whole chip
+    pub partitions: Vec<Partition>,
+}
+
+impl ReadRequest {
+    /// ECC the read uses
+    pub fn ecc_policy(&self) -> EccPolicy {
+        EccPolicy::select(self.use_ecc, self.soft_ecc.as_ref())
+    }
 }
 
 /// How to handle Out Of Band data
@@ -30,6 +46,75 @@ pub enum OobMode {
     Included,
     /// Read ONLY OOB data
     Only,
+    /// Read only the free OOB bytes of the chip's OOB layout, packed
+    /// together without the bad block marker and ECC bytes
+    Free,
+}
+
+/// How a write treats the existing contents of the target range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteMode {
+    /// Program the data as-is; the range must already be erased
+    #[default]
+    Direct,
+    /// Read-modify-write each affected erase unit, preserving neighbouring data
+    Patch,
+    /// Compare against the current contents first and only erase/program
+    /// the erase units and pages that actually differ
+    Delta,
+}
+
+/// Summary of the work a write actually performed
+///
+/// The unit and page counts are only filled in by the comparing modes
+/// ([`WriteMode::Patch`] and [`WriteMode::Delta`]); a direct write only
+/// reports the NAND blocks it had to relocate.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WriteReport {
+    /// Erase units (NOR sectors / NAND blocks) touched by the write
+    pub units_total: u32,
+    /// Erase units that already held the requested data
+    pub units_skipped: u32,
+    /// Erase units that had to be erased before programming
+    pub units_erased: u32,
+    /// Pages programmed
+    pub pages_programmed: u32,
+    /// Pages skipped because they were unchanged or blank after erase
+    pub pages_skipped: u32,
+    /// NAND blocks whose data was moved after a program failure
+    pub relocated_blocks: Vec<BlockRelocation>,
+}
+
+/// A NAND block rewritten elsewhere after it failed to program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRelocation {
+    /// Block of the written data, counted from the start of the write
+    pub logical_block: u32,
+    /// Physical block that failed and was marked bad
+    pub failed_block: u32,
+    /// Physical block now holding the data
+    pub new_block: u32,
+}
+
+/// What a read observed besides the data
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadReport {
+    /// Per-page ECC outcomes; empty unless on-die ECC was used (SPI NAND)
+    pub ecc: EccReport,
+    /// Bytes the passes of a consensus read disagreed on
+    pub disagreements: Vec<Disagreement>,
+}
+
+/// Where one piece of a read comes from, once bad block skipping and
+/// partitions have mapped it onto the chip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadSegment {
+    /// `length` bytes of the result read from physical `address` within a
+    /// single erase block
+    Read { address: u32, length: u32 },
+    /// `length` bytes of erased padding for blocks a partition lost to bad
+    /// ones
+    Erased { length: u32 },
 }
 
 /// Request for a write operation
@@ -40,12 +125,35 @@ pub struct WriteRequest<'a> {
     pub verify: bool,
     /// Ignore ECC errors during verification read back
     pub ignore_ecc_errors: bool,
+    /// Compute host-side ECC into the OOB area instead of using on-die ECC
+    /// (SPI NAND only)
+    pub soft_ecc: Option<SoftEccConfig>,
     pub oob_mode: OobMode,
     pub bad_block_strategy: BadBlockStrategy,
     /// Pre-scanned Bad Block Table (optional)
     pub bbt: Option<BadBlockTable>,
     /// Number of retries for verify read operations
     pub retry_count: u32,
+    pub mode: WriteMode,
+    /// Partitions that keep bad block skipping within their bounds
+    /// (NAND only); empty to skip across the whole chip
+    pub partitions: Vec<Partition>,
+}
+
+impl WriteRequest<'_> {
+    /// ECC the write uses
+    pub fn ecc_policy(&self) -> EccPolicy {
+        EccPolicy::select(self.use_ecc, self.soft_ecc.as_ref())
+    }
+}
+
+/// What an erase changed besides the data
+#[derive(Debug, Clone, Default)]
+pub struct EraseReport {
+    /// NAND blocks whose erase failed and that were marked bad
+    pub retired_blocks: Vec<u32>,
+    /// The request's bad block table with the retired blocks recorded
+    pub bbt: Option<BadBlockTable>,
 }
 
 /// Request for an erase operation
@@ -55,15 +163,61 @@ pub struct EraseRequest {
     pub bad_block_strategy: BadBlockStrategy,
     /// Pre-scanned Bad Block Table (optional)
     pub bbt: Option<BadBlockTable>,
+    /// Write a bad block marker to blocks whose erase fails (NAND only)
+    pub mark_bad: bool,
+    /// Partitions that keep bad block skipping within their bounds
+    /// (NAND only); empty to skip across the whole chip
+    pub partitions: Vec<Partition>,
 }
 
 /// Service Trait for Flash Operations
 /// This will be implemented by the Infrastructure layer (Protocols)
 pub trait FlashOperation {
     fn read(&mut self, request: ReadRequest, on_progress: &dyn Fn(Progress)) -> Result<Vec<u8>>;
-    fn write(&mut self, request: WriteRequest, on_progress: &dyn Fn(Progress)) -> Result<()>;
+
+    /// Read like [`read`](Self::read), also returning what was observed
+    /// along the way (per-page ECC outcomes)
+    fn read_with_report(
+        &mut self,
+        request: ReadRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<(Vec<u8>, ReadReport)> {
+        Ok((self.read(request, on_progress)?, ReadReport::default()))
+    }
+
+    /// Split the range of `request` into the physical ranges a read of it
+    /// covers, one per erase block (NAND). Each of them reads back on its
+    /// own with [`BadBlockStrategy::Include`] and no partitions. Chips
+    /// without bad blocks read the range as it is.
+    fn read_segments(&mut self, request: &ReadRequest) -> Result<Vec<ReadSegment>> {
+        Ok(vec![ReadSegment::Read {
+            address: request.address.as_u32(),
+            length: request.length,
+        }])
+    }
+
+    fn write(
+        &mut self,
+        request: WriteRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<WriteReport>;
     fn erase(&mut self, request: EraseRequest, on_progress: &dyn Fn(Progress)) -> Result<()>;
 
+    /// Erase like [`erase`](Self::erase), also returning the blocks it
+    /// retired (NAND)
+    fn erase_with_report(
+        &mut self,
+        request: EraseRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<EraseReport> {
+        let bbt = request.bbt.clone();
+        self.erase(request, on_progress)?;
+        Ok(EraseReport {
+            bbt,
+            ..Default::default()
+        })
+    }
+
     /// Read status register(s)
     fn get_status(&mut self) -> Result<Vec<u8>> {
         Err(crate::error::Error::NotSupported(
@@ -78,6 +232,41 @@ pub trait FlashOperation {
         ))
     }
 
+    /// Read `length` bytes at `offset` within OTP region `region` (0-based)
+    fn otp_read(&mut self, _region: u32, _offset: u32, _length: u32) -> Result<Vec<u8>> {
+        Err(crate::error::Error::NotSupported(
+            "otp_read not implemented".to_string(),
+        ))
+    }
+
+    /// Program bytes at `offset` within OTP region `region`
+    fn otp_program(&mut self, _region: u32, _offset: u32, _data: &[u8]) -> Result<()> {
+        Err(crate::error::Error::NotSupported(
+            "otp_program not implemented".to_string(),
+        ))
+    }
+
+    /// Erase OTP region `region` (only possible while it is unlocked)
+    fn otp_erase(&mut self, _region: u32) -> Result<()> {
+        Err(crate::error::Error::NotSupported(
+            "otp_erase not implemented".to_string(),
+        ))
+    }
+
+    /// Permanently lock OTP region `region`. This cannot be undone.
+    fn otp_lock(&mut self, _region: u32) -> Result<()> {
+        Err(crate::error::Error::NotSupported(
+            "otp_lock not implemented".to_string(),
+        ))
+    }
+
+    /// Whether OTP region `region` has been locked
+    fn otp_is_locked(&mut self, _region: u32) -> Result<bool> {
+        Err(crate::error::Error::NotSupported(
+            "otp_is_locked not implemented".to_string(),
+        ))
+    }
+
     /// Scan for bad blocks and return a BadBlockTable
     fn scan_bbt(&mut self, _on_progress: &dyn Fn(Progress)) -> Result<BadBlockTable> {
         // Default implementation returns an empty table (or should error?)
@@ -87,6 +276,20 @@ pub trait FlashOperation {
             "BBT scan not implemented".to_string(),
         ))
     }
+
+    /// Write a bad block marker to `block`
+    fn mark_bad_block(&mut self, _block: u32) -> Result<()> {
+        Err(crate::error::Error::NotSupported(
+            "Bad block marking not implemented".to_string(),
+        ))
+    }
+
+    /// Clear the bad block marker of `block`, erasing it
+    fn unmark_bad_block(&mut self, _block: u32) -> Result<()> {
+        Err(crate::error::Error::NotSupported(
+            "Bad block marking not implemented".to_string(),
+        ))
+    }
 }
 
 impl FlashOperation for Box<dyn FlashOperation> {
@@ -94,7 +297,23 @@ impl FlashOperation for Box<dyn FlashOperation> {
         self.as_mut().read(request, on_progress)
     }
 
-    fn write(&mut self, request: WriteRequest, on_progress: &dyn Fn(Progress)) -> Result<()> {
+    fn read_with_report(
+        &mut self,
+        request: ReadRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<(Vec<u8>, ReadReport)> {
+        self.as_mut().read_with_report(request, on_progress)
+    }
+
+    fn read_segments(&mut self, request: &ReadRequest) -> Result<Vec<ReadSegment>> {
+        self.as_mut().read_segments(request)
+    }
+
+    fn write(
+        &mut self,
+        request: WriteRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<WriteReport> {
         self.as_mut().write(request, on_progress)
     }
 
@@ -102,6 +321,14 @@ impl FlashOperation for Box<dyn FlashOperation> {
         self.as_mut().erase(request, on_progress)
     }
 
+    fn erase_with_report(
+        &mut self,
+        request: EraseRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<EraseReport> {
+        self.as_mut().erase_with_report(request, on_progress)
+    }
+
     fn get_status(&mut self) -> Result<Vec<u8>> {
         self.as_mut().get_status()
     }
@@ -110,9 +337,37 @@ impl FlashOperation for Box<dyn FlashOperation> {
         self.as_mut().set_status(status)
     }
 
+    fn otp_read(&mut self, region: u32, offset: u32, length: u32) -> Result<Vec<u8>> {
+        self.as_mut().otp_read(region, offset, length)
+    }
+
+    fn otp_program(&mut self, region: u32, offset: u32, data: &[u8]) -> Result<()> {
+        self.as_mut().otp_program(region, offset, data)
+    }
+
+    fn otp_erase(&mut self, region: u32) -> Result<()> {
+        self.as_mut().otp_erase(region)
+    }
+
+    fn otp_lock(&mut self, region: u32) -> Result<()> {
+        self.as_mut().otp_lock(region)
+    }
+
+    fn otp_is_locked(&mut self, region: u32) -> Result<bool> {
+        self.as_mut().otp_is_locked(region)
+    }
+
     fn scan_bbt(&mut self, on_progress: &dyn Fn(Progress)) -> Result<BadBlockTable> {
         self.as_mut().scan_bbt(on_progress)
     }
+
+    fn mark_bad_block(&mut self, block: u32) -> Result<()> {
+        self.as_mut().mark_bad_block(block)
+    }
+
+    fn unmark_bad_block(&mut self, block: u32) -> Result<()> {
+        self.as_mut().unmark_bad_block(block)
+    }
 }
 
 impl<T: FlashOperation + ?Sized> FlashOperation for &mut T {
@@ -120,7 +375,23 @@ impl<T: FlashOperation + ?Sized> FlashOperation for &mut T {
         (**self).read(request, on_progress)
     }
 
-    fn write(&mut self, request: WriteRequest, on_progress: &dyn Fn(Progress)) -> Result<()> {
+    fn read_with_report(
+        &mut self,
+        request: ReadRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<(Vec<u8>, ReadReport)> {
+        (**self).read_with_report(request, on_progress)
+    }
+
+    fn read_segments(&mut self, request: &ReadRequest) -> Result<Vec<ReadSegment>> {
+        (**self).read_segments(request)
+    }
+
+    fn write(
+        &mut self,
+        request: WriteRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<WriteReport> {
         (**self).write(request, on_progress)
     }
 
@@ -128,6 +399,14 @@ impl<T: FlashOperation + ?Sized> FlashOperation for &mut T {
         (**self).erase(request, on_progress)
     }
 
+    fn erase_with_report(
+        &mut self,
+        request: EraseRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<EraseReport> {
+        (**self).erase_with_report(request, on_progress)
+    }
+
     fn get_status(&mut self) -> Result<Vec<u8>> {
         (**self).get_status()
     }
@@ -136,9 +415,37 @@ impl<T: FlashOperation + ?Sized> FlashOperation for &mut T {
         (**self).set_status(status)
     }
 
+    fn otp_read(&mut self, region: u32, offset: u32, length: u32) -> Result<Vec<u8>> {
+        (**self).otp_read(region, offset, length)
+    }
+
+    fn otp_program(&mut self, region: u32, offset: u32, data: &[u8]) -> Result<()> {
+        (**self).otp_program(region, offset, data)
+    }
+
+    fn otp_erase(&mut self, region: u32) -> Result<()> {
+        (**self).otp_erase(region)
+    }
+
+    fn otp_lock(&mut self, region: u32) -> Result<()> {
+        (**self).otp_lock(region)
+    }
+
+    fn otp_is_locked(&mut self, region: u32) -> Result<bool> {
+        (**self).otp_is_locked(region)
+    }
+
     fn scan_bbt(&mut self, on_progress: &dyn Fn(Progress)) -> Result<BadBlockTable> {
         (**self).scan_bbt(on_progress)
     }
+
+    fn mark_bad_block(&mut self, block: u32) -> Result<()> {
+        (**self).mark_bad_block(block)
+    }
+
+    fn unmark_bad_block(&mut self, block: u32) -> Result<()> {
+        (**self).unmark_bad_block(block)
+    }
 }
 
 #[cfg(test)]
@@ -157,8 +464,12 @@ mod tests {
         ) -> Result<Vec<u8>> {
             Ok(vec![])
         }
-        fn write(&mut self, _request: WriteRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
-            Ok(())
+        fn write(
+            &mut self,
+            _request: WriteRequest,
+            _on_progress: &dyn Fn(Progress),
+        ) -> Result<WriteReport> {
+            Ok(WriteReport::default())
         }
         fn erase(&mut self, _request: EraseRequest, _on_progress: &dyn Fn(Progress)) -> Result<()> {
             Ok(())
@@ -181,6 +492,12 @@ mod tests {
             _ => panic!("Expected NotSupported error"),
         }
 
+        // Test otp_read default impl
+        match op.otp_read(0, 0, 16) {
+            Err(Error::NotSupported(msg)) => assert_eq!(msg, "otp_read not implemented"),
+            _ => panic!("Expected NotSupported error"),
+        }
+
         // Test scan_bbt default impl
         let dummy_progress = |_: Progress| {};
         match op.scan_bbt(&dummy_progress) {
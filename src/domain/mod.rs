@@ -4,15 +4,35 @@
 //! It has NO dependencies on infrastructure or presentation layers.
 
 pub mod bad_block;
+pub mod bbt_format;
 pub mod chip;
+pub mod consensus;
 pub mod ecc;
 pub mod flash_operation;
+pub mod nand_bbt;
+pub mod nand_health;
+pub mod oob;
+pub mod partition;
+pub mod protection;
 pub mod serial_analysis;
 pub mod types;
 
 // Re-exports
 pub use bad_block::{BadBlockInfo, BadBlockReason, BadBlockStrategy};
-pub use chip::{BlockStatus, ChipCapabilities, ChipLayout, ChipSpec};
-pub use ecc::{EccPolicy, EccStatus};
-pub use flash_operation::{EraseRequest, FlashOperation, OobMode, ReadRequest, WriteRequest};
+pub use chip::{
+    BlockStatus, ChipCapabilities, ChipLayout, ChipSpec, EccStatusMethod, EraseType, FourByteMode,
+    NandCacheRead, QuadEnableMethod, ReadRetry, StatusRegister, StatusWrite,
+};
+pub use consensus::Disagreement;
+pub use ecc::{
+    EccPolicy, EccReport, EccStatus, PageEcc, ReadRetryRecovery, SoftEcc, SoftEccAlgorithm,
+    SoftEccConfig,
+};
+pub use flash_operation::{
+    BlockRelocation, EraseReport, EraseRequest, FlashOperation, OobMode, ReadReport, ReadRequest,
+    ReadSegment, WriteMode, WriteReport, WriteRequest,
+};
+pub use oob::{OobLayout, OobRegion};
+pub use partition::Partition;
+pub use protection::{ProtectBit, ProtectUnit, ProtectionScheme};
 pub use types::*;
@@ -0,0 +1,154 @@
+//! Domain Model - Linux On-Flash Bad Block Table
+//!
+//! The format of Linux `nand_bbt` with its default flash-based descriptors:
+//! a main table ("Bbt0") and a mirror ("1tbB"), each in one of the last
+//! blocks of the chip. The pattern and a version byte sit in the OOB area of
+//! the block's first page; the table itself fills the data area from the
+//! first page on, two bits per block, block 0 in the low bits of byte 0.
+//!
+//! On flash `11` is a good block, `00` a factory bad one and `01`/`10` a
+//! block worn out at runtime. The blocks holding the tables are written as
+//! `00` and recognised as reserved when the table is read back.
+
+use super::bad_block::{BadBlockTable, BlockStatus};
+use super::oob::OobRegion;
+
+/// Where one of the two tables is looked for and how it is tagged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BbtDescriptor {
+    pub pattern: [u8; 4],
+    /// Offset of the pattern within the OOB area
+    pub pattern_offset: u32,
+    /// Offset of the version byte within the OOB area
+    pub version_offset: u32,
+    /// Number of blocks searched from the end of the chip
+    pub max_blocks: u32,
+}
+
+/// Linux `bbt_main_descr`
+pub const BBT_MAIN: BbtDescriptor = BbtDescriptor {
+    pattern: *b"Bbt0",
+    pattern_offset: 8,
+    version_offset: 12,
+    max_blocks: 4,
+};
+
+/// Linux `bbt_mirror_descr`
+pub const BBT_MIRROR: BbtDescriptor = BbtDescriptor {
+    pattern: *b"1tbB",
+    pattern_offset: 8,
+    version_offset: 12,
+    max_blocks: 4,
+};
+
+impl BbtDescriptor {
+    /// OOB bytes taken by the pattern and the version byte
+    pub fn tag_region(&self) -> OobRegion {
+        let start = self.pattern_offset.min(self.version_offset);
+        let end = (self.pattern_offset + self.pattern.len() as u32).max(self.version_offset + 1);
+        OobRegion::new(start, end - start)
+    }
+
+    /// Version of the table tagged in `oob`, or `None` when the pattern is
+    /// missing
+    pub fn version(&self, oob: &[u8]) -> Option<u8> {
+        let start = self.pattern_offset as usize;
+        let pattern = oob.get(start..start + self.pattern.len())?;
+        if pattern != self.pattern {
+            return None;
+        }
+        oob.get(self.version_offset as usize).copied()
+    }
+
+    /// OOB area of a table's first page: erased apart from the tag
+    pub fn oob(&self, version: u8, oob_size: usize) -> Vec<u8> {
+        let mut oob = vec![0xFF; oob_size];
+        let start = self.pattern_offset as usize;
+        oob[start..start + self.pattern.len()].copy_from_slice(&self.pattern);
+        oob[self.version_offset as usize] = version;
+        oob
+    }
+}
+
+/// Block and version of one table copy found on flash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BbtLocation {
+    pub block: u32,
+    pub version: u8,
+}
+
+/// A table read from flash together with where its copies live
+#[derive(Debug, Clone)]
+pub struct FlashBbt {
+    pub table: BadBlockTable,
+    pub main: Option<BbtLocation>,
+    pub mirror: Option<BbtLocation>,
+}
+
+/// Bytes the table of `total_blocks` blocks occupies
+pub fn table_len(total_blocks: usize) -> usize {
+    total_blocks.div_ceil(4)
+}
+
+/// Encode `table` in the on-flash format; Unknown blocks count as good
+pub fn encode(table: &BadBlockTable) -> Vec<u8> {
+    let mut data = vec![0xFF; table_len(table.len())];
+    for block in 0..table.len() {
+        let code = match table.get_status(block) {
+            BlockStatus::Unknown | BlockStatus::Good => 0b11,
+            BlockStatus::BadRuntime => 0b10,
+            BlockStatus::BadFactory | BlockStatus::Reserved => 0b00,
+        };
+        let shift = (block % 4) * 2;
+        data[block / 4] &= !(0b11 << shift) | (code << shift);
+    }
+    data
+}
+
+/// Decode an on-flash table of `total_blocks` blocks. The blocks holding
+/// the tables come back as factory bad; mark them reserved afterwards.
+pub fn decode(data: &[u8], total_blocks: usize) -> BadBlockTable {
+    let mut table = BadBlockTable::new(total_blocks);
+    for block in 0..total_blocks {
+        let byte = data.get(block / 4).copied().unwrap_or(0xFF);
+        let status = match (byte >> ((block % 4) * 2)) & 0b11 {
+            0b11 => BlockStatus::Good,
+            0b00 => BlockStatus::BadFactory,
+            _ => BlockStatus::BadRuntime,
+        };
+        table.set_status(block, status);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nand_bbt_round_trip() {
+        let mut table = BadBlockTable::new(10);
+        table.set_status(1, BlockStatus::BadFactory);
+        table.set_status(6, BlockStatus::BadRuntime);
+        table.set_status(9, BlockStatus::Reserved);
+
+        let data = encode(&table);
+        // Block 1 in bits 2-3 of byte 0, block 6 in bits 4-5 of byte 1
+        assert_eq!(data, vec![0xF3, 0xEF, 0xF3]);
+
+        let decoded = decode(&data, 10);
+        assert_eq!(decoded.get_status(0), BlockStatus::Good);
+        assert_eq!(decoded.get_status(1), BlockStatus::BadFactory);
+        assert_eq!(decoded.get_status(6), BlockStatus::BadRuntime);
+        assert_eq!(decoded.get_status(9), BlockStatus::BadFactory);
+    }
+
+    #[test]
+    fn test_nand_bbt_descriptor_tag() {
+        let oob = BBT_MAIN.oob(7, 64);
+        assert_eq!(&oob[8..12], b"Bbt0");
+        assert_eq!(BBT_MAIN.version(&oob), Some(7));
+        assert_eq!(BBT_MIRROR.version(&oob), None);
+        assert_eq!(BBT_MIRROR.version(&BBT_MIRROR.oob(2, 64)), Some(2));
+    }
+}
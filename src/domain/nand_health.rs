@@ -0,0 +1,295 @@
+//! Domain Model - NAND Health Survey
+//!
+//! A survey reads every good block with on-die ECC and keeps the corrected
+//! bit flips of each page. A block whose worst page reaches the refresh
+//! threshold ([`bitflip_threshold`]) is wearing out; a page the ECC cannot
+//! correct has lost data. An optional stress test erases, programs and
+//! reads back a range of blocks several times. [`HealthReport::verdict`]
+//! sums it all up into whether the flash should be replaced.
+
+use serde::Serialize;
+
+use super::ecc::{bitflip_threshold, EccStatus};
+use crate::error::{Error, Result};
+
+/// Share of bad blocks above which the flash is worn out. Datasheets
+/// guarantee at least 98% good blocks over the life of the part.
+pub const MAX_BAD_BLOCK_PERCENT: usize = 2;
+
+/// Condition of one block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockCondition {
+    /// Every page reads back below the threshold
+    Good,
+    /// Some page reaches the bit-flip threshold
+    Worn,
+    /// Some page is uncorrectable or failed to read
+    Failing,
+    /// Marked bad; not read
+    Bad,
+    /// Holds the on-flash BBT; not read
+    Reserved,
+}
+
+/// Survey result of one block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockHealth {
+    pub block: u32,
+    pub condition: BlockCondition,
+    /// Corrected bit flips of each page, `None` where uncorrectable or
+    /// unreadable; empty for bad blocks
+    pub bit_flips: Vec<Option<u8>>,
+}
+
+impl BlockHealth {
+    pub fn bad(block: u32) -> Self {
+        Self {
+            block,
+            condition: BlockCondition::Bad,
+            bit_flips: Vec::new(),
+        }
+    }
+
+    pub fn reserved(block: u32) -> Self {
+        Self {
+            block,
+            condition: BlockCondition::Reserved,
+            bit_flips: Vec::new(),
+        }
+    }
+
+    /// Classify a block from the ECC outcome of each of its pages
+    pub fn from_pages(block: u32, pages: &[EccStatus], threshold: u8) -> Self {
+        let bit_flips: Vec<Option<u8>> = pages
+            .iter()
+            .map(|status| match status {
+                EccStatus::NoError | EccStatus::NotAvailable => Some(0),
+                EccStatus::Corrected { bit_flips } => Some(*bit_flips),
+                EccStatus::Uncorrectable => None,
+            })
+            .collect();
+
+        let condition = if bit_flips.contains(&None) {
+            BlockCondition::Failing
+        } else if bit_flips.iter().flatten().any(|&flips| flips >= threshold) {
+            BlockCondition::Worn
+        } else {
+            BlockCondition::Good
+        };
+        Self {
+            block,
+            condition,
+            bit_flips,
+        }
+    }
+
+    /// Highest corrected bit-flip count of any page
+    pub fn max_bit_flips(&self) -> u8 {
+        self.bit_flips.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    pub fn uncorrectable_pages(&self) -> usize {
+        self.bit_flips
+            .iter()
+            .filter(|flips| flips.is_none())
+            .count()
+    }
+}
+
+/// Stress test result of one block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StressResult {
+    pub block: u32,
+    /// Erase/program/read cycles passed
+    pub cycles: u32,
+    /// Highest corrected bit-flip count seen in any cycle
+    pub max_bit_flips: u8,
+    /// Why the block failed, if it did
+    pub failure: Option<String>,
+}
+
+/// What the survey says about replacing the flash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthVerdict {
+    /// No block near the ECC limit
+    Healthy,
+    /// Some blocks are wearing out, but no data was lost
+    Degraded,
+    /// Data was lost, a stressed block failed or too many blocks are bad
+    Replace,
+}
+
+/// Result of a health survey
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthReport {
+    pub chip: String,
+    pub pages_per_block: u32,
+    /// Bits the on-die ECC corrects per step
+    pub ecc_strength: u8,
+    /// Bit flips from which a block counts as worn
+    pub threshold: u8,
+    pub blocks: Vec<BlockHealth>,
+    pub stress: Vec<StressResult>,
+}
+
+/// Block counts and verdict, saved at the top of the report file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HealthSummary {
+    pub good: usize,
+    pub worn: usize,
+    pub failing: usize,
+    pub bad: usize,
+    pub stress_failures: usize,
+    pub verdict: HealthVerdict,
+}
+
+impl HealthReport {
+    /// An empty report; `threshold` defaults to [`bitflip_threshold`]
+    pub fn new(
+        chip: impl Into<String>,
+        pages_per_block: u32,
+        ecc_strength: u8,
+        threshold: Option<u8>,
+    ) -> Self {
+        Self {
+            chip: chip.into(),
+            pages_per_block,
+            ecc_strength,
+            threshold: threshold.unwrap_or_else(|| bitflip_threshold(ecc_strength)),
+            blocks: Vec::new(),
+            stress: Vec::new(),
+        }
+    }
+
+    pub fn count(&self, condition: BlockCondition) -> usize {
+        self.blocks
+            .iter()
+            .filter(|block| block.condition == condition)
+            .count()
+    }
+
+    pub fn verdict(&self) -> HealthVerdict {
+        let too_many_bad =
+            self.count(BlockCondition::Bad) * 100 > self.blocks.len() * MAX_BAD_BLOCK_PERCENT;
+        if self.count(BlockCondition::Failing) > 0
+            || self.stress.iter().any(|result| result.failure.is_some())
+            || too_many_bad
+        {
+            HealthVerdict::Replace
+        } else if self.count(BlockCondition::Worn) > 0 {
+            HealthVerdict::Degraded
+        } else {
+            HealthVerdict::Healthy
+        }
+    }
+
+    pub fn summary(&self) -> HealthSummary {
+        HealthSummary {
+            good: self.count(BlockCondition::Good),
+            worn: self.count(BlockCondition::Worn),
+            failing: self.count(BlockCondition::Failing),
+            bad: self.count(BlockCondition::Bad),
+            stress_failures: self
+                .stress
+                .iter()
+                .filter(|result| result.failure.is_some())
+                .count(),
+            verdict: self.verdict(),
+        }
+    }
+
+    /// The report as JSON, its summary first
+    pub fn to_json(&self) -> Result<Vec<u8>> {
+        #[derive(Serialize)]
+        struct HealthFile<'a> {
+            summary: HealthSummary,
+            #[serde(flatten)]
+            report: &'a HealthReport,
+        }
+
+        serde_json::to_vec_pretty(&HealthFile {
+            summary: self.summary(),
+            report: self,
+        })
+        .map_err(|e| Error::Other(format!("Failed to encode health report: {}", e)))
+    }
+}
+
+/// Data programmed to `page` of `block` in stress `cycle`: pseudo-random,
+/// and inverted every other cycle so every cell is programmed both ways
+pub fn stress_pattern(block: u32, cycle: u32, page: u32, len: usize) -> Vec<u8> {
+    let mut state = (block.wrapping_mul(0x9E37_79B9) ^ page.wrapping_mul(0x85EB_CA6B)) | 1;
+    let invert = if cycle % 2 == 1 { 0xFF } else { 0x00 };
+    (0..len)
+        .map(|_| {
+            // xorshift32
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8 ^ invert
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_health_classification() {
+        let corrected = |bit_flips| EccStatus::Corrected { bit_flips };
+
+        let good = BlockHealth::from_pages(0, &[EccStatus::NoError, corrected(2)], 6);
+        assert_eq!(good.condition, BlockCondition::Good);
+        assert_eq!(good.max_bit_flips(), 2);
+
+        let worn = BlockHealth::from_pages(1, &[corrected(6), EccStatus::NoError], 6);
+        assert_eq!(worn.condition, BlockCondition::Worn);
+
+        let failing = BlockHealth::from_pages(2, &[corrected(7), EccStatus::Uncorrectable], 6);
+        assert_eq!(failing.condition, BlockCondition::Failing);
+        assert_eq!(failing.bit_flips, vec![Some(7), None]);
+        assert_eq!(failing.uncorrectable_pages(), 1);
+    }
+
+    #[test]
+    fn test_health_verdict() {
+        let mut report = HealthReport::new("test", 2, 8, None);
+        assert_eq!(report.threshold, 6);
+        report.blocks = (0..100)
+            .map(|block| BlockHealth::from_pages(block, &[EccStatus::NoError], 6))
+            .collect();
+        assert_eq!(report.verdict(), HealthVerdict::Healthy);
+
+        report.blocks[3] = BlockHealth::from_pages(3, &[EccStatus::Corrected { bit_flips: 6 }], 6);
+        report.blocks[4] = BlockHealth::bad(4);
+        report.blocks[5] = BlockHealth::bad(5);
+        assert_eq!(report.verdict(), HealthVerdict::Degraded);
+
+        // A third bad block in a hundred is over the limit
+        report.blocks[6] = BlockHealth::bad(6);
+        assert_eq!(report.verdict(), HealthVerdict::Replace);
+        report.blocks[6] = BlockHealth::from_pages(6, &[EccStatus::NoError], 6);
+
+        report.stress.push(StressResult {
+            block: 9,
+            cycles: 3,
+            max_bit_flips: 1,
+            failure: Some("erase failed".to_string()),
+        });
+        let summary = report.summary();
+        assert_eq!((summary.worn, summary.bad), (1, 2));
+        assert_eq!(summary.verdict, HealthVerdict::Replace);
+    }
+
+    #[test]
+    fn test_stress_pattern() {
+        let even = stress_pattern(3, 0, 1, 64);
+        let odd = stress_pattern(3, 1, 1, 64);
+        assert!(even.iter().zip(&odd).all(|(a, b)| a ^ b == 0xFF));
+        assert_ne!(even, stress_pattern(3, 0, 2, 64));
+        assert_ne!(even, stress_pattern(4, 0, 1, 64));
+    }
+}
@@ -0,0 +1,165 @@
+//! Domain Model - OOB Layout
+//!
+//! Describes how the spare (OOB) area of a NAND page is divided, the way
+//! Linux `mtd_ooblayout` does: the bad block marker, the bytes the on-die
+//! ECC keeps its parity in, and the free bytes left to filesystems (JFFS2
+//! cleanmarkers, YAFFS tags, ...). Offsets are relative to the start of the
+//! OOB area of a page.
+
+use std::ops::Range;
+
+use crate::error::{Error, Result};
+
+/// A contiguous run of OOB bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OobRegion {
+    pub offset: u32,
+    pub length: u32,
+}
+
+impl OobRegion {
+    pub const fn new(offset: u32, length: u32) -> Self {
+        Self { offset, length }
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.offset as usize..(self.offset + self.length) as usize
+    }
+}
+
+/// Division of a page's OOB area into marker, ECC and free regions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OobLayout {
+    /// Factory bad block marker, checked in the first page of a block
+    pub bad_block_marker: OobRegion,
+    /// Bytes the user may program, in the order they are packed
+    pub free: &'static [OobRegion],
+    /// Bytes holding the on-die ECC parity
+    pub ecc: &'static [OobRegion],
+}
+
+impl OobLayout {
+    /// Number of free bytes per page
+    pub fn free_bytes(&self) -> usize {
+        self.free.iter().map(|region| region.length as usize).sum()
+    }
+
+    /// Number of ECC bytes per page
+    pub fn ecc_bytes(&self) -> usize {
+        self.ecc.iter().map(|region| region.length as usize).sum()
+    }
+
+    /// Check that every region lies within an OOB area of `oob_size` bytes
+    /// and that free bytes never overlap the marker or the ECC bytes
+    pub fn validate(&self, oob_size: u32) -> Result<()> {
+        let mut regions = std::iter::once(&self.bad_block_marker)
+            .chain(self.free)
+            .chain(self.ecc);
+        if let Some(region) = regions.find(|r| r.offset + r.length > oob_size) {
+            return Err(Error::InvalidParameter(format!(
+                "OOB region {}..{} exceeds the {}-byte OOB area",
+                region.offset,
+                region.offset + region.length,
+                oob_size
+            )));
+        }
+
+        let reserved = std::iter::once(&self.bad_block_marker).chain(self.ecc);
+        for free in self.free {
+            if let Some(region) = reserved.clone().find(|r| overlaps(free, r)) {
+                return Err(Error::InvalidParameter(format!(
+                    "Free OOB bytes {}..{} overlap reserved bytes {}..{}",
+                    free.offset,
+                    free.offset + free.length,
+                    region.offset,
+                    region.offset + region.length
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The ECC region `region` overlaps, if any
+    pub fn ecc_overlap(&self, region: &OobRegion) -> Option<OobRegion> {
+        self.ecc.iter().find(|ecc| overlaps(region, ecc)).copied()
+    }
+
+    /// The free bytes of one page's OOB area, packed together
+    pub fn extract_free(&self, oob: &[u8]) -> Vec<u8> {
+        let mut free = Vec::with_capacity(self.free_bytes());
+        for region in self.free {
+            free.extend_from_slice(&oob[region.range()]);
+        }
+        free
+    }
+
+    /// Scatter packed free bytes into one page's OOB area; a short `free`
+    /// leaves the remaining regions untouched
+    pub fn insert_free(&self, oob: &mut [u8], free: &[u8]) {
+        let mut rest = free;
+        for region in self.free {
+            let len = rest.len().min(region.length as usize);
+            let start = region.offset as usize;
+            oob[start..start + len].copy_from_slice(&rest[..len]);
+            rest = &rest[len..];
+        }
+    }
+}
+
+fn overlaps(a: &OobRegion, b: &OobRegion) -> bool {
+    a.offset < b.offset + b.length && b.offset < a.offset + a.length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT: OobLayout = OobLayout {
+        bad_block_marker: OobRegion::new(0, 2),
+        free: &[
+            OobRegion::new(2, 6),
+            OobRegion::new(18, 6),
+            OobRegion::new(34, 6),
+            OobRegion::new(50, 6),
+        ],
+        ecc: &[
+            OobRegion::new(8, 8),
+            OobRegion::new(24, 8),
+            OobRegion::new(40, 8),
+            OobRegion::new(56, 8),
+        ],
+    };
+
+    #[test]
+    fn test_oob_layout_free_round_trip() {
+        assert_eq!(LAYOUT.free_bytes(), 24);
+        assert_eq!(LAYOUT.ecc_bytes(), 32);
+        assert!(LAYOUT.validate(64).is_ok());
+        assert!(LAYOUT.validate(32).is_err());
+
+        let free: Vec<u8> = (0..24).collect();
+        let mut oob = vec![0xFF; 64];
+        LAYOUT.insert_free(&mut oob, &free);
+        assert_eq!(&oob[..2], &[0xFF, 0xFF]);
+        assert_eq!(&oob[2..8], &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(&oob[8..18], &[0xFF; 10]);
+        assert_eq!(&oob[50..56], &[18, 19, 20, 21, 22, 23]);
+        assert_eq!(LAYOUT.extract_free(&oob), free);
+
+        // A short buffer only fills the first regions
+        let mut oob = vec![0xFF; 64];
+        LAYOUT.insert_free(&mut oob, &[0xAA; 8]);
+        assert_eq!(&oob[2..8], &[0xAA; 6]);
+        assert_eq!(&oob[18..24], &[0xAA, 0xAA, 0xFF, 0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_oob_layout_rejects_overlap() {
+        const OVERLAPPING: OobLayout = OobLayout {
+            bad_block_marker: OobRegion::new(0, 2),
+            free: &[OobRegion::new(1, 8)],
+            ecc: &[],
+        };
+        assert!(OVERLAPPING.validate(64).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+//! Domain Model - Flash Partitions
+//!
+//! Partitions as Linux `mtdparts` describes them, e.g.
+//! `512k(u-boot),4m(kernel),-(rootfs)`. When skipping NAND bad blocks, each
+//! partition absorbs its own: data shifted past a bad block never spills
+//! into the next partition, which starts where it always does. This is how
+//! U-Boot `nand write` and MTD partitions lay out images, so a bad block in
+//! the bootloader does not move the kernel.
+
+use std::ops::Range;
+
+use crate::error::{Error, Result};
+
+/// A named region of the flash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partition {
+    pub name: String,
+    pub offset: u32,
+    /// Size in bytes; `None` extends the partition to the end of the chip
+    pub size: Option<u32>,
+}
+
+impl Partition {
+    /// Byte range of the partition on a chip of `capacity` bytes
+    pub fn range(&self, capacity: u32) -> Range<u32> {
+        let end = match self.size {
+            Some(size) => self.offset.saturating_add(size),
+            None => capacity,
+        };
+        self.offset..end
+    }
+}
+
+/// Parse an `mtdparts` definition. A leading `mtdparts=` and `<mtd-id>:`
+/// are accepted; only the first device of several is used. Partitions
+/// without `@offset` follow the previous one; `ro`/`lk` flags are ignored.
+pub fn parse_mtdparts(spec: &str) -> Result<Vec<Partition>> {
+    let spec = spec.trim();
+    let spec = spec.strip_prefix("mtdparts=").unwrap_or(spec);
+    let spec = spec.split(';').next().unwrap_or_default();
+    let spec = spec.split_once(':').map_or(spec, |(_, parts)| parts);
+
+    let mut partitions: Vec<Partition> = Vec::new();
+    let mut next_offset = Some(0u32);
+    for definition in spec.split(',') {
+        let invalid =
+            || Error::InvalidParameter(format!("Invalid partition definition '{}'", definition));
+
+        let (layout, rest) = definition.split_once('(').ok_or_else(invalid)?;
+        let (name, _flags) = rest.split_once(')').ok_or_else(invalid)?;
+        let (size, offset) = match layout.split_once('@') {
+            Some((size, offset)) => (size, Some(parse_size(offset).ok_or_else(invalid)?)),
+            None => (layout, None),
+        };
+        let size = match size.trim() {
+            "-" => None,
+            size => Some(parse_size(size).ok_or_else(invalid)?),
+        };
+        let offset = offset.or(next_offset).ok_or_else(|| {
+            Error::InvalidParameter(format!("Partition '{}' follows one filling the chip", name))
+        })?;
+
+        next_offset = size.map(|size| offset.saturating_add(size));
+        partitions.push(Partition {
+            name: name.to_string(),
+            offset,
+            size,
+        });
+    }
+    Ok(partitions)
+}
+
+/// Decimal or `0x` hex, optionally followed by k, m or g
+fn parse_size(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let (digits, multiplier) = match value.chars().last()? {
+        'k' | 'K' => (&value[..value.len() - 1], 1024),
+        'm' | 'M' => (&value[..value.len() - 1], 1024 * 1024),
+        'g' | 'G' => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+    let number = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<u32>().ok()?,
+    };
+    number.checked_mul(multiplier)
+}
+
+/// Keeps bad block skipping within partition bounds. Operations walk
+/// logical blocks (where the data belongs) and physical blocks (where it
+/// goes, past any bad ones); entering a new partition drops the shift the
+/// previous one built up. The last blocks of a partition are its spares:
+/// once skipping has used them up, the logical blocks left over must be
+/// erased padding.
+#[derive(Debug)]
+pub struct PartitionBounds<'a> {
+    partitions: &'a [Partition],
+    block_size: u32,
+    capacity: u32,
+    current: Option<usize>,
+}
+
+impl<'a> PartitionBounds<'a> {
+    /// Check that `partitions` are block aligned, lie within the chip and
+    /// do not overlap
+    pub fn new(partitions: &'a [Partition], block_size: u32, capacity: u32) -> Result<Self> {
+        for (i, partition) in partitions.iter().enumerate() {
+            let range = partition.range(capacity);
+            if !range.start.is_multiple_of(block_size) || !range.end.is_multiple_of(block_size) {
+                return Err(Error::InvalidParameter(format!(
+                    "Partition '{}' is not aligned to the {}-byte block size",
+                    partition.name, block_size
+                )));
+            }
+            if range.is_empty() || range.end > capacity {
+                return Err(Error::InvalidParameter(format!(
+                    "Partition '{}' does not fit the {}-byte chip",
+                    partition.name, capacity
+                )));
+            }
+            let overlap = partitions[..i].iter().find(|other| {
+                let other = other.range(capacity);
+                range.start < other.end && other.start < range.end
+            });
+            if let Some(other) = overlap {
+                return Err(Error::InvalidParameter(format!(
+                    "Partitions '{}' and '{}' overlap",
+                    other.name, partition.name
+                )));
+            }
+        }
+        Ok(Self {
+            partitions,
+            block_size,
+            capacity,
+            current: None,
+        })
+    }
+
+    /// The physical block to continue at for `logical`, given that the walk
+    /// past bad blocks reached `physical`, or `None` when the partition of
+    /// `logical` has no blocks left. Blocks outside every partition keep the
+    /// plain shift.
+    pub fn place(&mut self, logical: u32, physical: u32) -> Option<u32> {
+        let address = logical as u64 * self.block_size as u64;
+        let index = self.partitions.iter().position(|partition| {
+            let range = partition.range(self.capacity);
+            (range.start as u64..range.end as u64).contains(&address)
+        });
+        let entered = index != self.current;
+        self.current = index;
+        if index.is_none() {
+            return Some(physical);
+        }
+
+        let physical = if entered { logical } else { physical };
+        (physical < self.partition_end()).then_some(physical)
+    }
+
+    fn partition(&self) -> Option<&Partition> {
+        self.current.map(|index| &self.partitions[index])
+    }
+
+    /// First block after the partition of the last placed block
+    pub fn partition_end(&self) -> u32 {
+        self.partition().map_or(self.capacity, |partition| {
+            partition.range(self.capacity).end
+        }) / self.block_size
+    }
+
+    /// Error for data that does not fit the good blocks of its partition
+    pub fn out_of_blocks(&self) -> Error {
+        Error::Other(format!(
+            "Partition '{}' ran out of good blocks",
+            self.partition()
+                .map_or("", |partition| partition.name.as_str())
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mtdparts() {
+        let parts =
+            parse_mtdparts("mtdparts=spi0.0:256k(u-boot)ro,1m@0x80000(kernel),-(rootfs)").unwrap();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].range(0x400000), 0..0x40000);
+        assert_eq!(parts[1].name, "kernel");
+        assert_eq!(parts[1].range(0x400000), 0x80000..0x180000);
+        assert_eq!(parts[2].range(0x400000), 0x180000..0x400000);
+
+        assert!(parse_mtdparts("-(all),1m(more)").is_err());
+        assert!(parse_mtdparts("1m").is_err());
+        assert!(parse_mtdparts("1x(bad)").is_err());
+    }
+
+    #[test]
+    fn test_partition_bounds() {
+        let parts = parse_mtdparts("2k(boot),2k(kernel),-(rootfs)").unwrap();
+        let mut bounds = PartitionBounds::new(&parts, 1024, 8 * 1024).unwrap();
+
+        // boot absorbs one bad block and runs out for its second block
+        assert_eq!(bounds.place(0, 0), Some(0));
+        assert_eq!(bounds.place(0, 1), Some(1));
+        assert_eq!(bounds.place(1, 2), None);
+        assert_eq!(bounds.partition_end(), 2);
+        // kernel starts at block 2 regardless
+        assert_eq!(bounds.place(2, 3), Some(2));
+        assert_eq!(bounds.place(3, 4), None);
+        assert!(bounds.out_of_blocks().to_string().contains("kernel"));
+        assert_eq!(bounds.place(4, 4), Some(4));
+        assert_eq!(bounds.partition_end(), 8);
+
+        assert!(PartitionBounds::new(&parts, 4096, 8 * 1024).is_err());
+        let overlapping = parse_mtdparts("2k(a),2k@1k(b)").unwrap();
+        assert!(PartitionBounds::new(&overlapping, 1024, 8 * 1024).is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! Domain Model - Block Protection
+//!
+//! Describes how a chip's block-protect bits (BP0..BPn, TB, SEC, CMP) map to
+//! protected address ranges, so protection can be decoded into an exact
+//! range and a requested range can be turned back into register bits.
+//!
+//! Register indices refer to the values returned by
+//! [`FlashOperation::get_status`](super::FlashOperation::get_status):
+//! SR1, SR2, ... for NOR, the 0xA0 protection feature for SPI NAND and the
+//! status register for SPI EEPROM.
+
+use std::ops::Range;
+
+use super::chip::ChipSpec;
+use super::types::FlashType;
+use crate::error::{Error, Result};
+
+/// Size of the smallest area protected with SEC set
+const SECTOR_PROTECT_UNIT: u64 = 4 * 1024;
+
+/// Largest area SEC-mode protection covers
+const SECTOR_PROTECT_MAX: u64 = 32 * 1024;
+
+/// Location of one protection bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtectBit {
+    /// Index into the register values
+    pub register: usize,
+    pub bit: u8,
+}
+
+impl ProtectBit {
+    pub const fn new(register: usize, bit: u8) -> Self {
+        Self { register, bit }
+    }
+
+    fn is_set(&self, registers: &[u8]) -> bool {
+        registers
+            .get(self.register)
+            .is_some_and(|value| value & (1 << self.bit) != 0)
+    }
+
+    fn apply(&self, registers: &mut [u8], set: bool) {
+        if let Some(value) = registers.get_mut(self.register) {
+            if set {
+                *value |= 1 << self.bit;
+            } else {
+                *value &= !(1 << self.bit);
+            }
+        }
+    }
+}
+
+/// Area protected by the lowest non-zero BP level; each level above doubles it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtectUnit {
+    /// `capacity >> n` (e.g. 6 for Winbond W25Q: 1/64 of the array)
+    Fraction(u8),
+    /// A fixed size in bytes (e.g. one 64 KiB block)
+    Bytes(u32),
+    /// Semantics unknown: any non-zero level is treated as the whole chip
+    Whole,
+}
+
+/// Block protection bits and their semantics for one chip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtectionScheme {
+    /// BP0, BP1, ... (least significant first)
+    pub bp: &'static [ProtectBit],
+    /// Top/Bottom: protect from the start of the array instead of the end
+    pub tb: Option<ProtectBit>,
+    /// Sector mode: protect 4 KiB..32 KiB instead of `unit` multiples
+    pub sec: Option<ProtectBit>,
+    /// Complement: protect everything except the decoded range
+    pub cmp: Option<ProtectBit>,
+    pub unit: ProtectUnit,
+}
+
+/// SR1 BP0-BP3, meaning unknown
+const GENERIC_NOR: ProtectionScheme = ProtectionScheme {
+    bp: &[
+        ProtectBit::new(0, 2),
+        ProtectBit::new(0, 3),
+        ProtectBit::new(0, 4),
+        ProtectBit::new(0, 5),
+    ],
+    tb: None,
+    sec: None,
+    cmp: None,
+    unit: ProtectUnit::Whole,
+};
+
+/// Protection feature (0xA0) bits 2-6, meaning unknown
+const GENERIC_NAND: ProtectionScheme = ProtectionScheme {
+    bp: &[
+        ProtectBit::new(0, 2),
+        ProtectBit::new(0, 3),
+        ProtectBit::new(0, 4),
+        ProtectBit::new(0, 5),
+        ProtectBit::new(0, 6),
+    ],
+    tb: None,
+    sec: None,
+    cmp: None,
+    unit: ProtectUnit::Whole,
+};
+
+/// 25xxx EEPROM BP0-BP1: upper quarter, upper half, all
+const GENERIC_EEPROM: ProtectionScheme = ProtectionScheme {
+    bp: &[ProtectBit::new(0, 2), ProtectBit::new(0, 3)],
+    tb: None,
+    sec: None,
+    cmp: None,
+    unit: ProtectUnit::Fraction(2),
+};
+
+impl ChipSpec {
+    /// The chip's protection scheme, or a generic one for its flash type
+    pub fn protection_scheme(&self) -> Option<ProtectionScheme> {
+        self.capabilities.protection.or(match self.flash_type {
+            FlashType::Nor => Some(GENERIC_NOR),
+            FlashType::Nand => Some(GENERIC_NAND),
+            FlashType::SpiEeprom => Some(GENERIC_EEPROM),
+            _ => None,
+        })
+    }
+}
+
+impl ProtectionScheme {
+    /// Whether the range semantics are known (not [`ProtectUnit::Whole`])
+    pub fn is_exact(&self) -> bool {
+        self.unit != ProtectUnit::Whole
+    }
+
+    /// Number of leading registers that hold protection bits
+    pub fn registers_used(&self) -> usize {
+        self.bp
+            .iter()
+            .chain(self.tb.iter())
+            .chain(self.sec.iter())
+            .chain(self.cmp.iter())
+            .map(|bit| bit.register + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn level(&self, registers: &[u8]) -> u32 {
+        self.bp
+            .iter()
+            .enumerate()
+            .filter(|(_, bit)| bit.is_set(registers))
+            .fold(0, |level, (i, _)| level | (1 << i))
+    }
+
+    fn max_level(&self) -> u32 {
+        (1 << self.bp.len()) - 1
+    }
+
+    fn flag(bit: Option<ProtectBit>, registers: &[u8]) -> bool {
+        bit.is_some_and(|bit| bit.is_set(registers))
+    }
+
+    /// Decode the protected address range from register values
+    pub fn decode(&self, registers: &[u8], capacity: u32) -> Range<u32> {
+        let capacity = capacity as u64;
+        let level = self.level(registers);
+        let top_down = !Self::flag(self.tb, registers);
+        let sec = Self::flag(self.sec, registers);
+
+        let size = if level == 0 {
+            0
+        } else if sec && level != self.max_level() {
+            (SECTOR_PROTECT_UNIT << (level - 1)).min(SECTOR_PROTECT_MAX)
+        } else {
+            let unit = match self.unit {
+                ProtectUnit::Fraction(shift) => capacity >> shift,
+                ProtectUnit::Bytes(bytes) => bytes as u64,
+                ProtectUnit::Whole => capacity,
+            };
+            unit.checked_shl(level - 1)
+                .unwrap_or(capacity)
+                .min(capacity)
+        };
+
+        let (start, end) = if top_down {
+            (capacity - size, capacity)
+        } else {
+            (0, size)
+        };
+
+        let (start, end) = if !Self::flag(self.cmp, registers) {
+            (start, end)
+        } else if size == 0 {
+            (0, capacity)
+        } else if size == capacity {
+            (0, 0)
+        } else if top_down {
+            (0, start)
+        } else {
+            (end, capacity)
+        };
+
+        if start == end {
+            0..0
+        } else {
+            start as u32..end as u32
+        }
+    }
+
+    /// Compute register values that protect exactly `range`, keeping every
+    /// unrelated bit of `registers`. An empty range clears protection.
+    pub fn encode(&self, registers: &[u8], range: Range<u32>, capacity: u32) -> Result<Vec<u8>> {
+        let range = if range.is_empty() { 0..0 } else { range };
+        let options = |bit: Option<ProtectBit>| if bit.is_some() { 2 } else { 1 };
+
+        // Prefer plain BP settings, then TB, then SEC, then CMP. Levels are
+        // tried highest first so "everything" sets every BP bit.
+        for cmp in 0..options(self.cmp) {
+            for sec in 0..options(self.sec) {
+                for tb in 0..options(self.tb) {
+                    for level in (0..=self.max_level()).rev() {
+                        let mut candidate = registers.to_vec();
+                        for (i, bit) in self.bp.iter().enumerate() {
+                            bit.apply(&mut candidate, level & (1 << i) != 0);
+                        }
+                        for (bit, set) in [(self.tb, tb), (self.sec, sec), (self.cmp, cmp)] {
+                            if let Some(bit) = bit {
+                                bit.apply(&mut candidate, set == 1);
+                            }
+                        }
+                        if self.decode(&candidate, capacity) == range {
+                            return Ok(candidate);
+                        }
+                    }
+                }
+            }
+        }
+
+        Err(Error::InvalidParameter(format!(
+            "No protection setting covers exactly 0x{:08X}-0x{:08X}",
+            range.start,
+            range.end.saturating_sub(1)
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Winbond W25Q: BP0-2 in SR1 bits 2-4, TB bit 5, SEC bit 6, CMP in SR2 bit 6
+    const W25Q: ProtectionScheme = ProtectionScheme {
+        bp: &[
+            ProtectBit::new(0, 2),
+            ProtectBit::new(0, 3),
+            ProtectBit::new(0, 4),
+        ],
+        tb: Some(ProtectBit::new(0, 5)),
+        sec: Some(ProtectBit::new(0, 6)),
+        cmp: Some(ProtectBit::new(1, 6)),
+        unit: ProtectUnit::Fraction(6),
+    };
+
+    const MB8: u32 = 8 * 1024 * 1024;
+
+    #[test]
+    fn test_decode_w25q64() {
+        assert_eq!(W25Q.decode(&[0x00, 0x00], MB8), 0..0);
+        // BP=001: upper 128 KiB
+        assert_eq!(W25Q.decode(&[0x04, 0x00], MB8), 0x7E0000..MB8);
+        // BP=011, TB: lower 512 KiB
+        assert_eq!(W25Q.decode(&[0x2C, 0x00], MB8), 0..0x80000);
+        // BP=111: everything
+        assert_eq!(W25Q.decode(&[0x1C, 0x00], MB8), 0..MB8);
+        // SEC, BP=010: upper 8 KiB
+        assert_eq!(W25Q.decode(&[0x48, 0x00], MB8), MB8 - 0x2000..MB8);
+        // CMP with BP=001: all but the upper 128 KiB
+        assert_eq!(W25Q.decode(&[0x04, 0x40], MB8), 0..0x7E0000);
+        // CMP with BP=000: everything
+        assert_eq!(W25Q.decode(&[0x00, 0x40], MB8), 0..MB8);
+    }
+
+    #[test]
+    fn test_encode_round_trips() {
+        // First 256 KiB: BP=010 with TB
+        let regs = W25Q.encode(&[0x02, 0x02], 0..0x40000, MB8).unwrap();
+        assert_eq!(regs, vec![0x2A, 0x02]); // WEL and QE preserved
+        assert_eq!(W25Q.decode(&regs, MB8), 0..0x40000);
+
+        // Needs CMP: everything but the lower 4 KiB
+        let regs = W25Q.encode(&[0x00, 0x00], 0x1000..MB8, MB8).unwrap();
+        assert_eq!(W25Q.decode(&regs, MB8), 0x1000..MB8);
+
+        let regs = W25Q.encode(&[0x1C, 0x40], 0..0, MB8).unwrap();
+        assert_eq!(W25Q.decode(&regs, MB8), 0..0);
+
+        assert!(W25Q.encode(&[0x00, 0x00], 0x1000..0x3000, MB8).is_err());
+        assert_eq!(W25Q.registers_used(), 2);
+    }
+
+    #[test]
+    fn test_encode_whole_chip_sets_every_bp_bit() {
+        let regs = GENERIC_NOR.encode(&[0x02], 0..MB8, MB8).unwrap();
+        assert_eq!(regs, vec![0x3E]);
+        assert!(!GENERIC_NOR.is_exact());
+    }
+
+    #[test]
+    fn test_generic_eeprom_quarters() {
+        assert_eq!(GENERIC_EEPROM.decode(&[0x04], 1024), 768..1024);
+        assert_eq!(GENERIC_EEPROM.decode(&[0x08], 1024), 512..1024);
+        assert_eq!(GENERIC_EEPROM.decode(&[0x0C], 1024), 0..1024);
+    }
+}
@@ -93,6 +93,8 @@ pub struct FlashOptions {
     pub use_ecc: bool,
     /// Whether to ignore ECC errors (useful for recovery)
     pub ignore_ecc_errors: bool,
+    /// Host-side ECC to use instead of on-die ECC (NAND only)
+    pub soft_ecc: Option<super::SoftEccConfig>,
     /// Strategy for handling NAND bad blocks
     pub bad_block_strategy: super::bad_block::BadBlockStrategy,
     /// How to handle NAND OOB data
@@ -101,12 +103,22 @@ pub struct FlashOptions {
     pub speed: Option<u8>,
     /// Whether to verify after write
     pub verify: bool,
+    /// How writes treat existing flash contents
+    pub write_mode: super::WriteMode,
     /// Number of retries for read operations
     pub retry_count: u32,
+    /// Times each chunk is read for a bytewise majority vote
+    pub passes: u32,
     /// Optional bad block table file path
     pub bbt_file: Option<std::path::PathBuf>,
     /// Optional explicit driver selection
     pub driver: Option<String>,
+    /// Globally unlock individually locked NOR blocks without asking
+    pub unlock: bool,
+    /// Mark NAND blocks bad when their erase fails
+    pub mark_bad: bool,
+    /// NAND partitions bad block skipping stays within
+    pub partitions: Vec<super::Partition>,
 }
 
 impl Default for FlashOptions {
@@ -116,13 +128,19 @@ impl Default for FlashOptions {
             length: None,
             use_ecc: true,
             ignore_ecc_errors: false,
+            soft_ecc: None,
             bad_block_strategy: super::bad_block::BadBlockStrategy::Fail,
             oob_mode: super::OobMode::None,
             speed: None,
             verify: false,
+            write_mode: super::WriteMode::Direct,
             retry_count: 0,
+            passes: 1,
             bbt_file: None,
             driver: None,
+            unlock: false,
+            mark_bad: false,
+            partitions: Vec::new(),
         }
     }
 }
@@ -52,6 +52,8 @@ fn i2c_eeprom(name: &str, capacity_bytes: u32, page_size: u32) -> ChipSpec {
             block_size: page_size,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities::default(),
         otp: None,
@@ -42,6 +42,8 @@ fn mw_eeprom(name: &str, capacity_bytes: u32) -> ChipSpec {
             block_size: 1,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities::default(),
         otp: None,
@@ -143,6 +143,8 @@ fn spi_eeprom(name: &str, manufacturer: &str, capacity_bytes: u32, page_size: u3
             block_size: page_size, // EEPROMs are byte-writable, use page as block
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: capacity_bytes > 65536,
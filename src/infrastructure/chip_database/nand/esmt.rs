@@ -3,12 +3,31 @@
 //! ESMT/Zentel - Manufacturer ID: 0xC8 (shares with GigaDevice)
 
 use crate::domain::chip::*;
+use crate::domain::oob::*;
 use crate::domain::types::*;
 
 /// ESMT uses same ID as GigaDevice in some cases
 pub const MANUFACTURER_ID: u8 = 0xC8;
 pub const MANUFACTURER_NAME: &str = "ESMT";
 
+/// F50 OOB: per 16-byte sector the marker (sector 0), 6 free bytes and
+/// 8 ECC bytes
+const OOB_LAYOUT: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 2),
+    free: &[
+        OobRegion::new(2, 6),
+        OobRegion::new(18, 6),
+        OobRegion::new(34, 6),
+        OobRegion::new(50, 6),
+    ],
+    ecc: &[
+        OobRegion::new(8, 8),
+        OobRegion::new(24, 8),
+        OobRegion::new(40, 8),
+        OobRegion::new(56, 8),
+    ],
+};
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -53,6 +72,8 @@ fn nand_chip(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: Some(OOB_LAYOUT),
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
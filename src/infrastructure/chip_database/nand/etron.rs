@@ -58,6 +58,8 @@ fn nand_chip(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
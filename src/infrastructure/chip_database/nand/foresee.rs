@@ -3,12 +3,25 @@
 //! FORESEE/Longsys - Manufacturer ID: 0xCD
 
 use crate::domain::chip::*;
+use crate::domain::oob::*;
 use crate::domain::types::*;
 
 /// FORESEE Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0xCD;
 pub const MANUFACTURER_NAME: &str = "FORESEE";
 
+/// F35SQA OOB: ECC parity is kept outside the OOB area
+const OOB_LAYOUT_SQA: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 2),
+    free: &[OobRegion::new(2, 62)],
+    ecc: &[],
+};
+
+/// Only the F35SQA parts have a documented layout
+fn oob_layout(name: &str) -> Option<OobLayout> {
+    name.starts_with("F35SQA").then_some(OOB_LAYOUT_SQA)
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -45,6 +58,8 @@ fn nand_chip(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: oob_layout(name),
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
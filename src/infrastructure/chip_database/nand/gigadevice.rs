@@ -3,12 +3,71 @@
 //! GigaDevice Corporation - Manufacturer ID: 0xC8
 
 use crate::domain::chip::*;
+use crate::domain::oob::*;
 use crate::domain::types::*;
 
 /// GigaDevice Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0xC8;
 pub const MANUFACTURER_NAME: &str = "GigaDevice";
 
+/// The GD5FxGQ4xB/C/E parts correct 8 bits and refine ECCS with the ECCSE
+/// bits; the GD5FxGQ5 and GD5FxGM7 generations correct 4 bits
+fn ecc_strength(name: &str) -> u8 {
+    if name.contains("GQ4") {
+        8
+    } else {
+        4
+    }
+}
+
+fn ecc_status(name: &str) -> EccStatusMethod {
+    if name.contains("GQ4") && !name.ends_with("UA") {
+        EccStatusMethod::GigaDeviceEccse
+    } else {
+        EccStatusMethod::Standard
+    }
+}
+
+/// GD5FxGQ4xA OOB: 8 ECC bytes at the end of each 16-byte sector, the rest
+/// free except the 1-byte marker
+const OOB_LAYOUT_Q4XA: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 1),
+    free: &[
+        OobRegion::new(1, 7),
+        OobRegion::new(16, 8),
+        OobRegion::new(32, 8),
+        OobRegion::new(48, 8),
+    ],
+    ecc: &[
+        OobRegion::new(8, 8),
+        OobRegion::new(24, 8),
+        OobRegion::new(40, 8),
+        OobRegion::new(56, 8),
+    ],
+};
+
+/// Later generations keep the ECC bytes after the first 64 OOB bytes
+const OOB_LAYOUT_128: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 1),
+    free: &[OobRegion::new(1, 63)],
+    ecc: &[OobRegion::new(64, 64)],
+};
+
+const OOB_LAYOUT_256: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 1),
+    free: &[OobRegion::new(1, 63)],
+    ecc: &[OobRegion::new(64, 192)],
+};
+
+fn oob_layout(name: &str, oob_size: u32) -> Option<OobLayout> {
+    match oob_size {
+        _ if name.ends_with("Q4UA") => Some(OOB_LAYOUT_Q4XA),
+        128 => Some(OOB_LAYOUT_128),
+        256 => Some(OOB_LAYOUT_256),
+        _ => None,
+    }
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -55,12 +114,23 @@ fn nand_chip(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: oob_layout(name, oob_size),
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
             supports_dual_spi: true,
+            supports_quad_spi: true,
+            quad_enable: QuadEnableMethod::ConfigBit0,
+            ecc_strength: ecc_strength(name),
+            ecc_status: ecc_status(name),
             ..Default::default()
         },
-        otp: None,
+        otp: Some(OtpLayout {
+            region_count: 4,
+            region_size: page_size,
+            enter_opcode: 0x00,
+            exit_opcode: 0x00,
+        }),
     }
 }
@@ -3,12 +3,41 @@
 //! Macronix International Co., Ltd. - Manufacturer ID: 0xC2
 
 use crate::domain::chip::*;
+use crate::domain::oob::*;
 use crate::domain::types::*;
 
 /// Macronix Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0xC2;
 pub const MANUFACTURER_NAME: &str = "Macronix";
 
+/// MX35LF OOB: ECC parity is kept outside the OOB area, so everything but
+/// the marker is free
+const OOB_LAYOUT_64: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 2),
+    free: &[OobRegion::new(2, 62)],
+    ecc: &[],
+};
+
+const OOB_LAYOUT_128: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 2),
+    free: &[OobRegion::new(2, 126)],
+    ecc: &[],
+};
+
+/// MX35LF-AD read retry: five levels in feature register 0x70
+const READ_RETRY: ReadRetry = ReadRetry {
+    feature: 0x70,
+    levels: 5,
+};
+
+fn oob_layout(oob_size: u32) -> Option<OobLayout> {
+    match oob_size {
+        64 => Some(OOB_LAYOUT_64),
+        128 => Some(OOB_LAYOUT_128),
+        _ => None,
+    }
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -45,10 +74,16 @@ fn nand_chip(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: oob_layout(oob_size),
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
             supports_dual_spi: true,
+            ecc_strength: 4,
+            ecc_status: EccStatusMethod::MacronixEccsr,
+            // The AD generation added read retry
+            read_retry: name.ends_with("AD").then_some(READ_RETRY),
             ..Default::default()
         },
         otp: None,
@@ -3,12 +3,20 @@
 //! Micron Technology, Inc. - Manufacturer ID: 0x2C
 
 use crate::domain::chip::*;
+use crate::domain::oob::*;
 use crate::domain::types::*;
 
 /// Micron Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0x2C;
 pub const MANUFACTURER_NAME: &str = "Micron";
 
+/// MT29F OOB: free bytes in the first half, ECC parity in the second
+const OOB_LAYOUT: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 2),
+    free: &[OobRegion::new(2, 62)],
+    ecc: &[OobRegion::new(64, 64)],
+};
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -43,12 +51,22 @@ fn nand_chip(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: Some(OOB_LAYOUT),
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
             supports_dual_spi: true,
+            cache_read: NandCacheRead::Sequential,
+            ecc_strength: 8,
+            ecc_status: EccStatusMethod::MicronEccs,
             ..Default::default()
         },
-        otp: None,
+        otp: Some(OtpLayout {
+            region_count: 10,
+            region_size: page_size,
+            enter_opcode: 0x00,
+            exit_opcode: 0x00,
+        }),
     }
 }
@@ -203,6 +203,8 @@ fn nand_chip(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
@@ -234,6 +236,8 @@ fn nand_chip_custom(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
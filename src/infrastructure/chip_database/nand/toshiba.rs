@@ -3,12 +3,34 @@
 //! Toshiba Corporation (now Kioxia) - Manufacturer ID: 0x98
 
 use crate::domain::chip::*;
+use crate::domain::oob::*;
 use crate::domain::types::*;
 
 /// Toshiba/Kioxia Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0x98;
 pub const MANUFACTURER_NAME: &str = "Toshiba/Kioxia";
 
+/// TC58CVG OOB: free bytes in the first half, ECC parity in the second
+const OOB_LAYOUT_64: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 2),
+    free: &[OobRegion::new(2, 30)],
+    ecc: &[OobRegion::new(32, 32)],
+};
+
+const OOB_LAYOUT_128: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 2),
+    free: &[OobRegion::new(2, 62)],
+    ecc: &[OobRegion::new(64, 64)],
+};
+
+fn oob_layout(oob_size: u32) -> Option<OobLayout> {
+    match oob_size {
+        64 => Some(OOB_LAYOUT_64),
+        128 => Some(OOB_LAYOUT_128),
+        _ => None,
+    }
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -45,10 +67,15 @@ fn nand_chip(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: oob_layout(oob_size),
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
             supports_dual_spi: true,
+            cache_read: NandCacheRead::Sequential,
+            ecc_strength: 8,
+            ecc_status: EccStatusMethod::KioxiaMaxBitFlips,
             ..Default::default()
         },
         otp: None,
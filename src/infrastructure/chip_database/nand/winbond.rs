@@ -3,12 +3,101 @@
 //! Winbond Electronics Corporation - Manufacturer ID: 0xEF
 
 use crate::domain::chip::*;
+use crate::domain::oob::*;
+use crate::domain::protection::*;
 use crate::domain::types::*;
 
 /// Winbond Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0xEF;
 pub const MANUFACTURER_NAME: &str = "Winbond";
 
+/// W25N block protection: BP0-3 and TB in the protection feature (0xA0).
+/// BP=0001 protects 1/256 of the array.
+pub const PROTECTION: ProtectionScheme = ProtectionScheme {
+    bp: &[
+        ProtectBit::new(0, 3),
+        ProtectBit::new(0, 4),
+        ProtectBit::new(0, 5),
+        ProtectBit::new(0, 6),
+    ],
+    tb: Some(ProtectBit::new(0, 2)),
+    sec: None,
+    cmp: None,
+    unit: ProtectUnit::Fraction(8),
+};
+
+/// W25M parts stack two W25N dies behind Software Die Select
+fn die_count(name: &str) -> u32 {
+    if name.starts_with("W25M") {
+        2
+    } else {
+        1
+    }
+}
+
+/// W25N01GV and W25M02GV correct 1 bit per sector, the KV parts 8
+fn ecc_strength(name: &str) -> u8 {
+    if name.contains("KV") {
+        8
+    } else {
+        1
+    }
+}
+
+/// W25N01GV and W25M02GV have the BUF bit for continuous read. The KV parts
+/// have no BUF bit and read page by page.
+fn cache_read(name: &str) -> NandCacheRead {
+    if name.contains("KV") {
+        NandCacheRead::None
+    } else {
+        NandCacheRead::Continuous
+    }
+}
+
+/// W25N01GV/W25M02GV OOB: per 16-byte sector the marker (sector 0), 6 free
+/// bytes and 8 ECC bytes
+const OOB_LAYOUT_GV: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 2),
+    free: &[
+        OobRegion::new(2, 6),
+        OobRegion::new(18, 6),
+        OobRegion::new(34, 6),
+        OobRegion::new(50, 6),
+    ],
+    ecc: &[
+        OobRegion::new(8, 8),
+        OobRegion::new(24, 8),
+        OobRegion::new(40, 8),
+        OobRegion::new(56, 8),
+    ],
+};
+
+/// W25NxxKV OOB: 14 free bytes per sector in the first half, the 13-byte
+/// BCH parity of each sector in the second
+const OOB_LAYOUT_KV: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 2),
+    free: &[
+        OobRegion::new(2, 14),
+        OobRegion::new(18, 14),
+        OobRegion::new(34, 14),
+        OobRegion::new(50, 14),
+    ],
+    ecc: &[
+        OobRegion::new(64, 13),
+        OobRegion::new(80, 13),
+        OobRegion::new(96, 13),
+        OobRegion::new(112, 13),
+    ],
+};
+
+fn oob_layout(name: &str) -> OobLayout {
+    if name.contains("KV") {
+        OOB_LAYOUT_KV
+    } else {
+        OOB_LAYOUT_GV
+    }
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -48,12 +137,23 @@ fn nand_chip_2id(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: die_count(name),
+            oob_layout: Some(oob_layout(name)),
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
             supports_dual_spi: true,
+            supports_quad_spi: true,
+            cache_read: cache_read(name),
+            ecc_strength: ecc_strength(name),
+            protection: Some(PROTECTION),
             ..Default::default()
         },
-        otp: None,
+        otp: Some(OtpLayout {
+            region_count: 8,
+            region_size: page_size,
+            enter_opcode: 0x00,
+            exit_opcode: 0x00,
+        }),
     }
 }
@@ -3,12 +3,26 @@
 //! XTX Technology Limited - Manufacturer ID: 0x0B
 
 use crate::domain::chip::*;
+use crate::domain::oob::*;
 use crate::domain::types::*;
 
 /// XTX Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0x0B;
 pub const MANUFACTURER_NAME: &str = "XTX";
 
+/// XT26G0xA OOB: one free run after the 1-byte marker, ECC in the last
+/// 16 bytes
+const OOB_LAYOUT_A: OobLayout = OobLayout {
+    bad_block_marker: OobRegion::new(0, 1),
+    free: &[OobRegion::new(1, 47)],
+    ecc: &[OobRegion::new(48, 16)],
+};
+
+/// Only the A parts have a documented layout
+fn oob_layout(name: &str) -> Option<OobLayout> {
+    name.ends_with('A').then_some(OOB_LAYOUT_A)
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -43,6 +57,8 @@ fn nand_chip(
             block_size: block_size_kb * 1024,
             oob_size: Some(oob_size),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: oob_layout(name),
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
@@ -46,6 +46,8 @@ fn nor_chip(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -> C
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: false,
@@ -70,9 +72,14 @@ fn nor_chip_4b(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: true,
+            four_byte_mode: FourByteMode::EnterExit {
+                write_enable: false,
+            },
             ..Default::default()
         },
         otp: None,
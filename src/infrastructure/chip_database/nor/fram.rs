@@ -32,6 +32,8 @@ fn fram_chip(name: &str, jedec_id: u16, size_kb: u32) -> ChipSpec {
             block_size: 256,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities::default(),
         otp: None,
@@ -3,12 +3,40 @@
 //! GigaDevice Corporation - Manufacturer ID: 0xC8
 
 use crate::domain::chip::*;
+use crate::domain::protection::*;
 use crate::domain::types::*;
 
 /// GigaDevice Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0xC8;
 pub const MANUFACTURER_NAME: &str = "GigaDevice";
 
+/// GD25Q status registers (SR2 written through the 2-byte 0x01 form)
+pub const STATUS_REGISTERS: &[StatusRegister] = &[
+    StatusRegister {
+        name: "SR1",
+        read_opcode: 0x05,
+        write: StatusWrite::Opcode(0x01),
+        bits: ["SRP0", "BP4", "BP3", "BP2", "BP1", "BP0", "WEL", "WIP"],
+    },
+    StatusRegister {
+        name: "SR2",
+        read_opcode: 0x35,
+        write: StatusWrite::WithSr1,
+        bits: ["SUS1", "CMP", "LB3", "LB2", "LB1", "SUS2", "QE", "SRP1"],
+    },
+    StatusRegister {
+        name: "SR3",
+        read_opcode: 0x15,
+        write: StatusWrite::Opcode(0x11),
+        bits: ["", "DRV1", "DRV0", "HPF", "", "WPS", "", ""],
+    },
+];
+
+/// GD25Q block protection, laid out like W25Q (BP3 acts as TB, BP4 as SEC)
+fn protection(capacity: u32) -> ProtectionScheme {
+    super::winbond::sized_protection(capacity)
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -37,9 +65,14 @@ fn nor_chip(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -> C
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: false,
+            status_registers: STATUS_REGISTERS,
+            protection: Some(protection(capacity_bytes)),
+            wps: Some(super::winbond::WPS),
             ..Default::default()
         },
         otp: None,
@@ -61,9 +94,17 @@ fn nor_chip_4b(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: true,
+            four_byte_mode: FourByteMode::EnterExit {
+                write_enable: false,
+            },
+            status_registers: STATUS_REGISTERS,
+            protection: Some(super::winbond::PROTECTION_4B),
+            wps: Some(super::winbond::WPS),
             ..Default::default()
         },
         otp: None,
@@ -3,12 +3,56 @@
 //! Macronix International Co., Ltd. - Manufacturer ID: 0xC2
 
 use crate::domain::chip::*;
+use crate::domain::protection::*;
 use crate::domain::types::*;
 
 /// Macronix Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0xC2;
 pub const MANUFACTURER_NAME: &str = "Macronix";
 
+/// MX25L status and configuration registers. The configuration register
+/// (0x15) is written as the second byte of 0x01.
+pub const STATUS_REGISTERS: &[StatusRegister] = &[
+    StatusRegister {
+        name: "SR",
+        read_opcode: 0x05,
+        write: StatusWrite::Opcode(0x01),
+        bits: ["SRWD", "QE", "BP3", "BP2", "BP1", "BP0", "WEL", "WIP"],
+    },
+    StatusRegister {
+        name: "CR",
+        read_opcode: 0x15,
+        write: StatusWrite::WithSr1,
+        bits: ["DC1", "DC0", "4BYTE", "", "TB", "ODS2", "ODS1", "ODS0"],
+    },
+];
+
+/// MX25L block protection: BP0-3 in SR. BP=0001 protects the top 64 KiB
+/// block; TB lives in the one-time configuration register and is not used.
+pub const PROTECTION: ProtectionScheme = ProtectionScheme {
+    bp: &[
+        ProtectBit::new(0, 2),
+        ProtectBit::new(0, 3),
+        ProtectBit::new(0, 4),
+        ProtectBit::new(0, 5),
+    ],
+    tb: None,
+    sec: None,
+    cmp: None,
+    unit: ProtectUnit::Bytes(64 * 1024),
+};
+
+/// The older MX25L25635E only supports 4-byte mode entry (0xB7)
+fn four_byte_mode(name: &str) -> FourByteMode {
+    if name == "MX25L25635E" {
+        FourByteMode::EnterExit {
+            write_enable: false,
+        }
+    } else {
+        FourByteMode::Opcodes
+    }
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -40,10 +84,18 @@ fn nor_chip(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -> C
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: false,
             supports_quad_spi: true,
+            supports_dual_spi: true,
+            quad_enable: QuadEnableMethod::Sr1Bit6,
+            // Macronix only offers the 1-4-4 form
+            quad_program: QuadProgram::Io,
+            status_registers: STATUS_REGISTERS,
+            protection: Some(PROTECTION),
             ..Default::default()
         },
         otp: None,
@@ -65,10 +117,19 @@ fn nor_chip_4b(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: true,
+            four_byte_mode: four_byte_mode(name),
             supports_quad_spi: true,
+            supports_dual_spi: true,
+            quad_enable: QuadEnableMethod::Sr1Bit6,
+            // Macronix only offers the 1-4-4 form
+            quad_program: QuadProgram::Io,
+            status_registers: STATUS_REGISTERS,
+            protection: Some(PROTECTION),
             ..Default::default()
         },
         otp: None,
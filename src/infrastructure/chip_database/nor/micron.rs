@@ -3,12 +3,78 @@
 //! Micron/STMicroelectronics - Manufacturer ID: 0x20
 
 use crate::domain::chip::*;
+use crate::domain::protection::*;
 use crate::domain::types::*;
 
 /// Micron/ST Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0x20;
 pub const MANUFACTURER_NAME: &str = "Micron";
 
+/// N25Q/MT25Q status register plus the read-only flag status register
+pub const STATUS_REGISTERS: &[StatusRegister] = &[
+    StatusRegister {
+        name: "SR",
+        read_opcode: 0x05,
+        write: StatusWrite::Opcode(0x01),
+        bits: ["SRWD", "BP3", "TB", "BP2", "BP1", "BP0", "WEL", "WIP"],
+    },
+    StatusRegister {
+        name: "FSR",
+        read_opcode: 0x70,
+        write: StatusWrite::ReadOnly,
+        bits: [
+            "READY", "ERS_SUS", "ERS_ERR", "PGM_ERR", "", "PGM_SUS", "PROT_ERR", "ADDR4",
+        ],
+    },
+];
+
+/// Status registers for a part in this module: legacy M25P parts only have
+/// the status register, XMC parts follow Winbond
+fn status_registers(name: &str) -> &'static [StatusRegister] {
+    if name.starts_with("M25P") {
+        &STATUS_REGISTERS[..1]
+    } else if name.starts_with("XM25") {
+        super::winbond::STATUS_REGISTERS
+    } else {
+        STATUS_REGISTERS
+    }
+}
+
+/// N25Q/MT25Q block protection: BP0-2 in SR bits 2-4, BP3 in bit 6, TB in
+/// bit 5. BP=0001 protects one 64 KiB sector.
+pub const PROTECTION: ProtectionScheme = ProtectionScheme {
+    bp: &[
+        ProtectBit::new(0, 2),
+        ProtectBit::new(0, 3),
+        ProtectBit::new(0, 4),
+        ProtectBit::new(0, 6),
+    ],
+    tb: Some(ProtectBit::new(0, 5)),
+    sec: None,
+    cmp: None,
+    unit: ProtectUnit::Bytes(64 * 1024),
+};
+
+/// Protection scheme for a part in this module (legacy M25P parts vary)
+fn protection(name: &str, capacity: u32) -> Option<ProtectionScheme> {
+    if name.starts_with("M25P") {
+        None
+    } else if name.starts_with("XM25") {
+        Some(super::winbond::sized_protection(capacity))
+    } else {
+        Some(PROTECTION)
+    }
+}
+
+/// N25Q256A has no 4-byte opcodes and needs a write enable around 0xB7/0xE9
+fn four_byte_mode(name: &str) -> FourByteMode {
+    if name.starts_with("N25Q") {
+        FourByteMode::EnterExit { write_enable: true }
+    } else {
+        FourByteMode::Opcodes
+    }
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -48,9 +114,13 @@ fn nor_chip(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -> C
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: false,
+            status_registers: status_registers(name),
+            protection: protection(name, capacity_bytes),
             ..Default::default()
         },
         otp: None,
@@ -72,9 +142,14 @@ fn nor_chip_4b(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: true,
+            four_byte_mode: four_byte_mode(name),
+            status_registers: status_registers(name),
+            protection: protection(name, capacity_bytes),
             ..Default::default()
         },
         otp: None,
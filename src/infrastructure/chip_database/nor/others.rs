@@ -65,6 +65,8 @@ fn dataflash_chip(name: &str, jedec_id: u16, n_pages: u32, page_size: u32) -> Ch
             block_size: page_size * 8, // DataFlash blocks are usually 8 pages
             oob_size: None,
             is_dataflash: true,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities::default(),
         otp: None,
@@ -209,6 +211,8 @@ fn nor_chip_with_id(
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities::default(),
         otp: Some(OtpLayout {
@@ -3,12 +3,82 @@
 //! Spansion (now Infineon) - Manufacturer ID: 0x01
 
 use crate::domain::chip::*;
+use crate::domain::protection::*;
 use crate::domain::types::*;
 
 /// Spansion Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0x01;
 pub const MANUFACTURER_NAME: &str = "Spansion";
 
+/// S25FL-P/S status and configuration registers. CR1 (0x35) is written as
+/// the second byte of 0x01.
+pub const STATUS_REGISTERS: &[StatusRegister] = &[
+    StatusRegister {
+        name: "SR1",
+        read_opcode: 0x05,
+        write: StatusWrite::Opcode(0x01),
+        bits: ["SRWD", "P_ERR", "E_ERR", "BP2", "BP1", "BP0", "WEL", "WIP"],
+    },
+    StatusRegister {
+        name: "CR1",
+        read_opcode: 0x35,
+        write: StatusWrite::WithSr1,
+        bits: [
+            "LC1", "LC0", "TBPROT", "", "BPNV", "TBPARM", "QUAD", "FREEZE",
+        ],
+    },
+];
+
+/// 1 KiB OTP array in 32-byte regions, each locked by its own bit of the
+/// lock word in region 0
+const OTP_LAYOUT: OtpLayout = OtpLayout {
+    region_count: 32,
+    region_size: 32,
+    enter_opcode: 0x4B,
+    exit_opcode: 0x00,
+};
+
+/// OTP layout of a Spansion part: the FL-K series has Winbond security
+/// registers and reads its unique ID with 0x4B
+fn otp_layout(name: &str) -> OtpLayout {
+    if name.starts_with("S25FL1") {
+        super::winbond::OTP_LAYOUT
+    } else {
+        OTP_LAYOUT
+    }
+}
+
+/// Status registers for a Spansion part (the FL-K series is Winbond compatible)
+fn status_registers(name: &str) -> &'static [StatusRegister] {
+    if name.starts_with("S25FL1") {
+        super::winbond::STATUS_REGISTERS
+    } else {
+        STATUS_REGISTERS
+    }
+}
+
+/// S25FL-P/S block protection: BP0-2 in SR1, TBPROT in CR1
+pub const PROTECTION: ProtectionScheme = ProtectionScheme {
+    bp: &[
+        ProtectBit::new(0, 2),
+        ProtectBit::new(0, 3),
+        ProtectBit::new(0, 4),
+    ],
+    tb: Some(ProtectBit::new(1, 5)),
+    sec: None,
+    cmp: None,
+    unit: ProtectUnit::Fraction(6),
+};
+
+/// Protection scheme for a Spansion part (the FL-K series is Winbond compatible)
+fn protection(name: &str, capacity: u32) -> ProtectionScheme {
+    if name.starts_with("S25FL1") {
+        super::winbond::sized_protection(capacity)
+    } else {
+        PROTECTION
+    }
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -49,17 +119,16 @@ fn nor_chip(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -> C
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: false,
+            status_registers: status_registers(name),
+            protection: Some(protection(name, capacity_bytes)),
             ..Default::default()
         },
-        otp: Some(OtpLayout {
-            region_count: 1,
-            region_size: 1024,
-            enter_opcode: 0x4B,
-            exit_opcode: 0x00,
-        }),
+        otp: Some(otp_layout(name)),
     }
 }
 
@@ -78,16 +147,16 @@ fn nor_chip_4b(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: true,
+            four_byte_mode: FourByteMode::Opcodes,
+            status_registers: status_registers(name),
+            protection: Some(protection(name, capacity_bytes)),
             ..Default::default()
         },
-        otp: Some(OtpLayout {
-            region_count: 1,
-            region_size: 1024,
-            enter_opcode: 0x4B,
-            exit_opcode: 0x00,
-        }),
+        otp: Some(otp_layout(name)),
     }
 }
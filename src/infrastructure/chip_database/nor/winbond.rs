@@ -3,12 +3,145 @@
 //! Winbond Electronics Corporation - Manufacturer ID: 0xEF
 
 use crate::domain::chip::*;
+use crate::domain::protection::*;
 use crate::domain::types::*;
 
 /// Winbond Manufacturer ID
 pub const MANUFACTURER_ID: u8 = 0xEF;
 pub const MANUFACTURER_NAME: &str = "Winbond";
 
+/// W25Q status registers. SR2 is written through the 2-byte 0x01 form,
+/// which every W25Q generation accepts (0x31 only exists on newer parts).
+pub const STATUS_REGISTERS: &[StatusRegister] = &[
+    StatusRegister {
+        name: "SR1",
+        read_opcode: 0x05,
+        write: StatusWrite::Opcode(0x01),
+        bits: ["SRP0", "SEC", "TB", "BP2", "BP1", "BP0", "WEL", "BUSY"],
+    },
+    StatusRegister {
+        name: "SR2",
+        read_opcode: 0x35,
+        write: StatusWrite::WithSr1,
+        bits: ["SUS", "CMP", "LB3", "LB2", "LB1", "", "QE", "SRP1"],
+    },
+    StatusRegister {
+        name: "SR3",
+        read_opcode: 0x15,
+        write: StatusWrite::Opcode(0x11),
+        bits: ["HOLD/RST", "DRV1", "DRV0", "", "", "WPS", "ADP", "ADS"],
+    },
+];
+
+/// W25Q256/W25Q512 status registers: SR1 trades SEC for BP3 and moves TB,
+/// as [`PROTECTION_4B`] decodes it
+pub const STATUS_REGISTERS_4B: &[StatusRegister] = &[
+    StatusRegister {
+        name: "SR1",
+        read_opcode: 0x05,
+        write: StatusWrite::Opcode(0x01),
+        bits: ["SRP0", "TB", "BP3", "BP2", "BP1", "BP0", "WEL", "BUSY"],
+    },
+    STATUS_REGISTERS[1],
+    STATUS_REGISTERS[2],
+];
+
+/// WPS (SR3 bit 2): individual block locks replace the BP bits when set
+pub const WPS: ProtectBit = ProtectBit::new(2, 2);
+
+/// Three 256-byte security registers (0x48/0x42/0x44), locked by LB1-LB3
+/// in SR2
+pub const OTP_LAYOUT: OtpLayout = OtpLayout {
+    region_count: 3,
+    region_size: 256,
+    enter_opcode: 0x48,
+    exit_opcode: 0x00,
+};
+
+/// Status registers for a Winbond part (the W25X series only has SR1)
+fn status_registers(name: &str) -> &'static [StatusRegister] {
+    if name.starts_with("W25X") {
+        &STATUS_REGISTERS[..1]
+    } else {
+        STATUS_REGISTERS
+    }
+}
+
+/// W25Q block protection: BP0-2, TB and SEC in SR1, CMP in SR2. BP=001
+/// protects 1/64 of the array on 64 Mbit and larger parts; use
+/// [`sized_protection`] for a specific part.
+pub const PROTECTION: ProtectionScheme = ProtectionScheme {
+    bp: &[
+        ProtectBit::new(0, 2),
+        ProtectBit::new(0, 3),
+        ProtectBit::new(0, 4),
+    ],
+    tb: Some(ProtectBit::new(0, 5)),
+    sec: Some(ProtectBit::new(0, 6)),
+    cmp: Some(ProtectBit::new(1, 6)),
+    unit: ProtectUnit::Fraction(6),
+};
+
+/// W25Q256/W25Q512 block protection: BP0-3 and TB in SR1, CMP in SR2.
+/// BP=0001 protects one 64 KiB block.
+pub const PROTECTION_4B: ProtectionScheme = ProtectionScheme {
+    bp: &[
+        ProtectBit::new(0, 2),
+        ProtectBit::new(0, 3),
+        ProtectBit::new(0, 4),
+        ProtectBit::new(0, 5),
+    ],
+    tb: Some(ProtectBit::new(0, 6)),
+    sec: None,
+    cmp: Some(ProtectBit::new(1, 6)),
+    unit: ProtectUnit::Bytes(64 * 1024),
+};
+
+/// [`PROTECTION`] for a part of `capacity` bytes. BP=001 protects 1/64 of
+/// the array, but never less than one 64 KiB block: on a W25Q80 it covers
+/// 1/16.
+pub fn sized_protection(capacity: u32) -> ProtectionScheme {
+    let unit = if capacity <= 64 * 64 * 1024 {
+        ProtectUnit::Bytes(64 * 1024)
+    } else {
+        ProtectUnit::Fraction(6)
+    };
+    ProtectionScheme { unit, ..PROTECTION }
+}
+
+/// Protection scheme for a 3-byte Winbond part (the W25X series has no SEC/CMP)
+fn protection(name: &str, capacity: u32) -> ProtectionScheme {
+    if name.starts_with("W25X") {
+        ProtectionScheme {
+            sec: None,
+            cmp: None,
+            ..sized_protection(capacity)
+        }
+    } else {
+        sized_protection(capacity)
+    }
+}
+
+/// W25Q256FV lacks the dedicated 4-byte opcodes and must enter 4-byte mode
+fn four_byte_mode(name: &str) -> FourByteMode {
+    if name == "W25Q256FV" {
+        FourByteMode::EnterExit {
+            write_enable: false,
+        }
+    } else {
+        FourByteMode::Opcodes
+    }
+}
+
+/// W25M SpiStack parts stack two dies behind Software Die Select
+fn die_count(name: &str) -> u32 {
+    if name.starts_with("W25M") {
+        2
+    } else {
+        1
+    }
+}
+
 pub fn get_chips() -> Vec<ChipSpec> {
     vec![
         // =========================================================================
@@ -39,7 +172,11 @@ pub fn get_chips() -> Vec<ChipSpec> {
         nor_chip("W25Q128BV", 0x4018, 256, 64), // 128Mbit = 16MB
         nor_chip("W25Q128FW", 0x6018, 256, 64),
         nor_chip_4b("W25Q256FV", 0x4019, 512, 64), // 256Mbit = 32MB, 4-byte
-        nor_chip_4b("W25Q512JV", 0x7119, 1024, 64), // 512Mbit = 64MB, 4-byte
+        nor_chip_4b("W25Q512JV", 0x4020, 1024, 64), // 512Mbit = 64MB, 4-byte
+        // =========================================================================
+        // W25M Series - SpiStack (stacked W25Q dies)
+        // =========================================================================
+        nor_chip_4b("W25M512JV", 0x7119, 1024, 64), // 2x W25Q256JV
     ]
 }
 
@@ -58,18 +195,26 @@ fn nor_chip(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -> C
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: false,
-            supports_quad_spi: true,
+            // The W25X series only has dual output reads
+            supports_quad_spi: !name.starts_with("W25X"),
+            supports_dual_spi: true,
+            quad_enable: QuadEnableMethod::Sr2Bit1ReadSr2,
+            quad_program: if name.starts_with("W25X") {
+                QuadProgram::None
+            } else {
+                QuadProgram::Input
+            },
+            status_registers: status_registers(name),
+            protection: Some(protection(name, capacity_bytes)),
+            wps: (!name.starts_with("W25X")).then_some(WPS),
             ..Default::default()
         },
-        otp: Some(OtpLayout {
-            region_count: 3,
-            region_size: 256,
-            enter_opcode: 0x48, // Use as Read command for now
-            exit_opcode: 0x00,
-        }),
+        otp: Some(OTP_LAYOUT),
     }
 }
 
@@ -88,17 +233,80 @@ fn nor_chip_4b(name: &str, jedec_id: u16, n_sectors: u32, sector_size_kb: u32) -
             block_size: sector_size_kb * 1024,
             oob_size: None,
             is_dataflash: false,
+            die_count: die_count(name),
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_4byte_addr: true,
+            four_byte_mode: four_byte_mode(name),
             supports_quad_spi: true,
+            supports_dual_spi: true,
+            quad_enable: QuadEnableMethod::Sr2Bit1ReadSr2,
+            quad_program: QuadProgram::Input,
+            status_registers: STATUS_REGISTERS_4B,
+            protection: Some(PROTECTION_4B),
+            wps: Some(WPS),
             ..Default::default()
         },
-        otp: Some(OtpLayout {
-            region_count: 3,
-            region_size: 256,
-            enter_opcode: 0x48,
-            exit_opcode: 0x00,
-        }),
+        otp: Some(OTP_LAYOUT),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chip(name: &str) -> ChipSpec {
+        get_chips()
+            .into_iter()
+            .find(|chip| chip.name == name)
+            .unwrap()
+    }
+
+    /// Each protection bit's position in the registers is named after its role
+    fn assert_bit_names(spec: &ChipSpec) {
+        let scheme = spec.capabilities.protection.unwrap();
+        let registers = spec.capabilities.status_registers;
+        let name = |bit: ProtectBit| registers[bit.register].bits[7 - bit.bit as usize];
+        for (i, &bit) in scheme.bp.iter().enumerate() {
+            assert_eq!(name(bit), format!("BP{}", i), "{}", spec.name);
+        }
+        let named = [
+            (scheme.tb, "TB"),
+            (scheme.sec, "SEC"),
+            (scheme.cmp, "CMP"),
+            (spec.capabilities.wps, "WPS"),
+        ];
+        for (bit, expected) in named {
+            if let Some(bit) = bit {
+                assert_eq!(name(bit), expected, "{}", spec.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_status_register_names_match_protection() {
+        for spec in get_chips() {
+            assert_bit_names(&spec);
+        }
+    }
+
+    #[test]
+    fn test_protection_unit_follows_capacity() {
+        let w25q80 = chip("W25Q80");
+        let scheme = w25q80.capabilities.protection.unwrap();
+        let capacity = w25q80.capacity.as_bytes();
+        // BP=001: the upper 64 KiB block, not 1/64 of the array
+        assert_eq!(scheme.decode(&[0x04, 0x00], capacity), 0xF0000..capacity);
+        // BP=101: everything
+        assert_eq!(scheme.decode(&[0x14, 0x00], capacity), 0..capacity);
+
+        let w25q128 = chip("W25Q128BV");
+        let scheme = w25q128.capabilities.protection.unwrap();
+        let capacity = w25q128.capacity.as_bytes();
+        assert_eq!(
+            scheme.decode(&[0x04, 0x00], capacity),
+            capacity - 0x40000..capacity
+        );
     }
 }
@@ -29,7 +29,39 @@ impl ChipRegistry {
         self.chips.iter().find(|c| c.jedec_id == id).cloned()
     }
 
+    /// Look up a manufacturer name from its JEDEC manufacturer ID
+    pub fn manufacturer_name(&self, manufacturer_id: u8) -> Option<String> {
+        self.chips
+            .iter()
+            .find(|c| c.jedec_id.manufacturer == manufacturer_id)
+            .map(|c| c.manufacturer.clone())
+    }
+
     pub fn list_all(&self) -> Vec<ChipSpec> {
         self.chips.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::FlashType;
+
+    #[test]
+    fn test_nand_oob_layouts_fit_their_chips() {
+        let registry = ChipRegistry::new();
+        let mut checked = 0;
+        for spec in registry.list_all() {
+            let Some(layout) = spec.layout.oob_layout else {
+                continue;
+            };
+            assert_eq!(spec.flash_type, FlashType::Nand, "{}", spec.name);
+            let oob_size = spec.layout.oob_size.unwrap_or(0);
+            if let Err(e) = layout.validate(oob_size) {
+                panic!("{}: {}", spec.name, e);
+            }
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+}
@@ -28,6 +28,10 @@ pub const CMD_RESET: u8 = 0xFF;
 /// Write status register (standard)
 pub const NOR_CMD_WRSR: u8 = 0x01;
 
+/// Software die select (multi-die packages such as Winbond W25M), followed
+/// by the die index
+pub const CMD_SOFTWARE_DIE_SELECT: u8 = 0xC2;
+
 // ============================================================================
 // SPI NOR Commands
 // ============================================================================
@@ -41,6 +45,18 @@ pub const CMD_NOR_FAST_READ: u8 = 0x0B;
 /// Page program (write)
 pub const CMD_NOR_PAGE_PROGRAM: u8 = 0x02;
 
+/// Dual output fast read (1-1-2, 8 dummy cycles)
+pub const CMD_NOR_DUAL_READ: u8 = 0x3B;
+
+/// Quad output fast read (1-1-4, 8 dummy cycles, needs QE)
+pub const CMD_NOR_QUAD_READ: u8 = 0x6B;
+
+/// Quad input page program (1-1-4, needs QE)
+pub const CMD_NOR_QUAD_PAGE_PROGRAM: u8 = 0x32;
+
+/// Quad I/O page program (1-4-4, Macronix 4PP)
+pub const CMD_NOR_QUAD_IO_PAGE_PROGRAM: u8 = 0x38;
+
 /// Sector erase (4KB)
 pub const CMD_NOR_SECTOR_ERASE_4K: u8 = 0x20;
 
@@ -56,6 +72,53 @@ pub const CMD_NOR_CHIP_ERASE: u8 = 0xC7;
 /// Chip erase (alternative)
 pub const CMD_NOR_CHIP_ERASE_ALT: u8 = 0x60;
 
+/// Read Serial Flash Discoverable Parameters (JESD216, 3-byte addr + dummy)
+pub const CMD_NOR_READ_SFDP: u8 = 0x5A;
+
+// ============================================================================
+// SPI NOR Individual Block Protection (WPS = 1)
+// ============================================================================
+
+/// Lock one block/sector
+pub const CMD_NOR_INDIVIDUAL_LOCK: u8 = 0x36;
+
+/// Unlock one block/sector
+pub const CMD_NOR_INDIVIDUAL_UNLOCK: u8 = 0x39;
+
+/// Read the lock bit of one block/sector
+pub const CMD_NOR_READ_BLOCK_LOCK: u8 = 0x3D;
+
+/// Lock every block/sector
+pub const CMD_NOR_GLOBAL_LOCK: u8 = 0x7E;
+
+/// Unlock every block/sector
+pub const CMD_NOR_GLOBAL_UNLOCK: u8 = 0x98;
+
+// ============================================================================
+// SPI NOR Security Register / OTP Commands
+// ============================================================================
+
+/// Read security register (Winbond style, 3-byte addr + dummy)
+pub const CMD_NOR_READ_SECURITY_REG: u8 = 0x48;
+
+/// Program security register / OTP array
+pub const CMD_NOR_PROGRAM_SECURITY_REG: u8 = 0x42;
+
+/// Erase security register
+pub const CMD_NOR_ERASE_SECURITY_REG: u8 = 0x44;
+
+/// Read OTP array (Spansion style, 3-byte addr + dummy)
+pub const CMD_NOR_READ_OTP: u8 = 0x4B;
+
+/// Read unique ID (Winbond/GigaDevice, 4 dummy bytes)
+pub const CMD_NOR_READ_UNIQUE_ID: u8 = 0x4B;
+
+/// Read status register 2
+pub const CMD_NOR_READ_STATUS2: u8 = 0x35;
+
+/// Security register lock bits LB1-LB3 in status register 2 (Winbond)
+pub const STATUS2_NOR_LB_SHIFT: u8 = 3;
+
 // ============================================================================
 // SPI NOR 4-Byte Address Mode Commands (for >16MB chips)
 // ============================================================================
@@ -66,6 +129,12 @@ pub const CMD_NOR_ENTER_4BYTE_MODE: u8 = 0xB7;
 /// Exit 4-byte address mode
 pub const CMD_NOR_EXIT_4BYTE_MODE: u8 = 0xE9;
 
+/// Write Extended Address Register (selects the 16MB bank in 3-byte mode)
+pub const CMD_NOR_WRITE_EAR: u8 = 0xC5;
+
+/// Read Extended Address Register
+pub const CMD_NOR_READ_EAR: u8 = 0xC8;
+
 /// Read data with 4-byte address
 pub const CMD_NOR_READ_4B: u8 = 0x13;
 
@@ -75,9 +144,24 @@ pub const CMD_NOR_FAST_READ_4B: u8 = 0x0C;
 /// Page program with 4-byte address
 pub const CMD_NOR_PAGE_PROGRAM_4B: u8 = 0x12;
 
+/// Dual output fast read with 4-byte address
+pub const CMD_NOR_DUAL_READ_4B: u8 = 0x3C;
+
+/// Quad output fast read with 4-byte address
+pub const CMD_NOR_QUAD_READ_4B: u8 = 0x6C;
+
+/// Quad input page program with 4-byte address
+pub const CMD_NOR_QUAD_PAGE_PROGRAM_4B: u8 = 0x34;
+
+/// Quad I/O page program with 4-byte address (Macronix 4PP4B)
+pub const CMD_NOR_QUAD_IO_PAGE_PROGRAM_4B: u8 = 0x3E;
+
 /// Sector erase (4KB) with 4-byte address
 pub const CMD_NOR_SECTOR_ERASE_4K_4B: u8 = 0x21;
 
+/// Block erase (32KB) with 4-byte address
+pub const CMD_NOR_BLOCK_ERASE_32K_4B: u8 = 0x5C;
+
 /// Block erase (64KB) with 4-byte address
 pub const CMD_NOR_BLOCK_ERASE_64K_4B: u8 = 0xDC;
 
@@ -91,6 +175,12 @@ pub const CMD_NAND_PAGE_READ: u8 = 0x13;
 /// Read from cache
 pub const CMD_NAND_READ_CACHE: u8 = 0x03;
 
+/// Cache read sequential (load next page while the cache is read out)
+pub const CMD_NAND_CACHE_READ_SEQUENTIAL: u8 = 0x31;
+
+/// Cache read end (move the last page of a sequential run into the cache)
+pub const CMD_NAND_CACHE_READ_END: u8 = 0x3F;
+
 /// Read from cache (x1, with column address)
 pub const CMD_NAND_READ_CACHE_X1: u8 = 0x0B;
 
@@ -106,6 +196,12 @@ pub const CMD_NAND_PROGRAM_LOAD: u8 = 0x02;
 /// Program load (random data input)
 pub const CMD_NAND_PROGRAM_LOAD_RANDOM: u8 = 0x84;
 
+/// Program load (x4, quad input)
+pub const CMD_NAND_PROGRAM_LOAD_X4: u8 = 0x32;
+
+/// Program load (random data input, x4)
+pub const CMD_NAND_PROGRAM_LOAD_RANDOM_X4: u8 = 0x34;
+
 /// Program execute (write cache to array)
 pub const CMD_NAND_PROGRAM_EXECUTE: u8 = 0x10;
 
@@ -118,6 +214,9 @@ pub const CMD_NAND_GET_FEATURE: u8 = 0x0F;
 /// Set feature (write configuration)
 pub const CMD_NAND_SET_FEATURE: u8 = 0x1F;
 
+/// Get ECC status register (Macronix; bit-flip count of the last read)
+pub const CMD_NAND_GET_ECCSR: u8 = 0x7C;
+
 // ============================================================================
 // Status Register Bits
 // ============================================================================
@@ -153,6 +252,9 @@ pub const STATUS_NAND_ECC_CORRECTED_ALT: u8 = 0x20;
 /// ECC Status: Uncorrectable errors
 pub const STATUS_NAND_ECC_UNCORRECTABLE: u8 = 0x30;
 
+/// Micron ECC status bits (ECCS2-0), a superset of the standard two
+pub const STATUS_NAND_MICRON_ECC_MASK: u8 = 0x70;
+
 /// SPI NOR Status Register - Write In Progress (WIP)
 pub const STATUS_NOR_WIP: u8 = 0x01;
 
@@ -175,6 +277,13 @@ pub const FEATURE_STATUS: u8 = 0xC0;
 /// Drive strength register
 pub const FEATURE_DRIVE_STRENGTH: u8 = 0xD0;
 
+/// Second status register (GigaDevice; ECCSE bits 5:4)
+pub const FEATURE_STATUS_2: u8 = 0xF0;
+
+/// Most bit flips corrected in any sector of the last page, bits 7:4
+/// (Toshiba/Kioxia)
+pub const FEATURE_ECC_MAX_BIT_FLIPS: u8 = 0x30;
+
 // ============================================================================
 // SPI EEPROM Commands (25xxx series)
 // ============================================================================
@@ -260,5 +369,8 @@ pub const CONFIG_OTP_ENABLE: u8 = 0x40;
 /// OTP Protect bit
 pub const CONFIG_OTP_PROTECT: u8 = 0x80;
 
-/// Buffer mode bit (some chips)
+/// Quad Enable bit (GigaDevice GD5F)
+pub const CONFIG_QUAD_ENABLE: u8 = 0x01;
+
+/// Buffer mode bit (Winbond; cleared for continuous read)
 pub const CONFIG_BUF_MODE: u8 = 0x08;
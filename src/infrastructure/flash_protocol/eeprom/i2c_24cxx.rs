@@ -4,7 +4,9 @@
 //! These EEPROMs use I2C for communication and have varying addressing modes.
 
 use crate::domain::chip::ChipSpec;
-use crate::domain::{EraseRequest, FlashOperation, Progress, ReadRequest, WriteRequest};
+use crate::domain::{
+    EraseRequest, FlashOperation, Progress, ReadRequest, WriteReport, WriteRequest,
+};
 use crate::error::{Error, Result};
 use crate::infrastructure::flash_protocol::commands::*;
 use crate::infrastructure::programmer::Programmer;
@@ -99,7 +101,11 @@ impl<P: Programmer> FlashOperation for I2cEeprom<P> {
         Ok(result)
     }
 
-    fn write(&mut self, request: WriteRequest, on_progress: &dyn Fn(Progress)) -> Result<()> {
+    fn write(
+        &mut self,
+        request: WriteRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<WriteReport> {
         let data = request.data;
         let page_size = self.page_size();
         let mut offset = 0usize;
@@ -135,10 +141,12 @@ impl<P: Programmer> FlashOperation for I2cEeprom<P> {
                 length: request.data.len() as u32,
                 use_ecc: request.use_ecc,
                 ignore_ecc_errors: request.ignore_ecc_errors,
+                soft_ecc: request.soft_ecc,
                 oob_mode: request.oob_mode,
                 bad_block_strategy: request.bad_block_strategy,
                 bbt: None,
                 retry_count: request.retry_count,
+                partitions: Vec::new(),
             };
             let read_back = self.read(verify_req, &|_| {})?;
             if read_back != request.data {
@@ -156,7 +164,7 @@ impl<P: Programmer> FlashOperation for I2cEeprom<P> {
             }
         }
 
-        Ok(())
+        Ok(WriteReport::default())
     }
 
     fn erase(&mut self, _request: EraseRequest, on_progress: &dyn Fn(Progress)) -> Result<()> {
@@ -6,7 +6,7 @@
 use crate::domain::chip::ChipSpec;
 use crate::domain::{
     bad_block::BadBlockStrategy, EraseRequest, FlashOperation, OobMode, Progress, ReadRequest,
-    WriteRequest,
+    WriteMode, WriteReport, WriteRequest,
 };
 use crate::error::{Error, Result};
 use crate::infrastructure::flash_protocol::commands::*;
@@ -176,7 +176,11 @@ impl<P: Programmer> FlashOperation for MicrowireEeprom<P> {
         Ok(data)
     }
 
-    fn write(&mut self, request: WriteRequest, on_progress: &dyn Fn(Progress)) -> Result<()> {
+    fn write(
+        &mut self,
+        request: WriteRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<WriteReport> {
         let address = request.address.as_u32();
         let data = request.data;
 
@@ -207,10 +211,12 @@ impl<P: Programmer> FlashOperation for MicrowireEeprom<P> {
                 length: request.data.len() as u32,
                 use_ecc: false,
                 ignore_ecc_errors: false,
+                soft_ecc: None,
                 oob_mode: OobMode::None,
                 bad_block_strategy: BadBlockStrategy::Fail,
                 bbt: None,
                 retry_count: request.retry_count,
+                partitions: Vec::new(),
             };
             let read_back = self.read(verify_req, &|_| {})?;
             if read_back != request.data {
@@ -228,7 +234,7 @@ impl<P: Programmer> FlashOperation for MicrowireEeprom<P> {
             }
         }
 
-        Ok(())
+        Ok(WriteReport::default())
     }
 
     fn erase(&mut self, request: EraseRequest, on_progress: &dyn Fn(Progress)) -> Result<()> {
@@ -239,11 +245,14 @@ impl<P: Programmer> FlashOperation for MicrowireEeprom<P> {
             verify: false,
             use_ecc: false,
             ignore_ecc_errors: false,
+            soft_ecc: None,
             oob_mode: OobMode::None,
             bad_block_strategy: BadBlockStrategy::Fail,
             bbt: None,
             retry_count: 0,
+            mode: WriteMode::Direct,
+            partitions: Vec::new(),
         };
-        self.write(write_req, on_progress)
+        self.write(write_req, on_progress).map(|_| ())
     }
 }
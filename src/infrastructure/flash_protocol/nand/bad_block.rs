@@ -0,0 +1,74 @@
+//! SPI NAND Bad Block Markers
+//!
+//! A block is marked bad by programming zeros over the bad block marker in
+//! the OOB area of its first page, raw (on-die ECC off) like Linux
+//! `spinand_markbad`. Only an erase clears a marker again, so unmarking a
+//! block erases it.
+
+use super::SpiNand;
+use crate::domain::bad_block::{BadBlockTable, BlockStatus};
+use crate::domain::OobRegion;
+use crate::error::{Error, Result};
+use crate::infrastructure::programmer::Programmer;
+
+/// Marker position for chips without a known OOB layout
+const DEFAULT_MARKER: OobRegion = OobRegion::new(0, 2);
+
+impl<P: Programmer> SpiNand<P> {
+    fn check_block(&self, block: u32) -> Result<()> {
+        let total_blocks = self.spec.capacity.as_bytes() / self.spec.layout.block_size;
+        if block >= total_blocks {
+            return Err(Error::InvalidParameter(format!(
+                "Block {} is beyond the end of the chip ({} blocks)",
+                block, total_blocks
+            )));
+        }
+        Ok(())
+    }
+
+    /// Program the bad block marker of `block`
+    pub(super) fn write_bad_block_marker(&mut self, block: u32) -> Result<()> {
+        self.check_block(block)?;
+        let marker = self
+            .spec
+            .layout
+            .oob_layout
+            .map_or(DEFAULT_MARKER, |layout| layout.bad_block_marker);
+        let page = block * self.spec.layout.pages_per_block();
+        let column = (self.spec.layout.page_size + marker.offset) as u16;
+
+        self.set_ecc(false)?;
+        self.program_page(page, column, &vec![0x00; marker.length as usize])
+    }
+
+    /// Erase `block`, which clears its marker, and check it reads good
+    pub(super) fn clear_bad_block_marker(&mut self, block: u32) -> Result<()> {
+        self.check_block(block)?;
+        self.erase_block(block)?;
+        if self.is_bad_block(block)? {
+            return Err(Error::Other(format!(
+                "Block {} still reads as bad after erase",
+                block
+            )));
+        }
+        Ok(())
+    }
+
+    /// Take a block that failed to program or erase out of use: record it
+    /// in the BBT and write its marker, then restore the ECC setting the
+    /// raw marker write changed
+    pub(super) fn retire_block(
+        &mut self,
+        block: u32,
+        bbt: &mut Option<BadBlockTable>,
+        use_ecc: bool,
+    ) -> Result<()> {
+        if let Some(bbt) = bbt {
+            bbt.set_status(block as usize, BlockStatus::BadRuntime);
+        }
+        if let Err(e) = self.write_bad_block_marker(block) {
+            log::warn!("Could not mark block {} bad: {}", block, e);
+        }
+        self.set_ecc(use_ecc)
+    }
+}
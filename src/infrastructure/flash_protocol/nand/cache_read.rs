@@ -0,0 +1,168 @@
+//! SPI NAND Cache Read
+//!
+//! Reading page after page with Page Read (0x13) leaves the bus idle while
+//! each page is loaded from the array. Chips with a cache read overlap the
+//! two: with Cache Read Sequential (0x31) the next page is fetched into the
+//! data register while the current one is clocked out of the cache, and
+//! Winbond's continuous read (BUF=0) streams whole pages with a single Read
+//! (0x03). Runs never cross a block, so bad block handling stays per block.
+
+use super::SpiNand;
+use crate::domain::chip::NandCacheRead;
+use crate::domain::EccStatus;
+use crate::error::Result;
+use crate::infrastructure::flash_protocol::commands::*;
+use crate::infrastructure::programmer::{BusWidth, MultiIoCommand, Programmer};
+
+/// Dummy cycles of the x2/x4 reads in continuous mode (no column address)
+const CONTINUOUS_DUMMY_CYCLES: u8 = 32;
+
+/// One page of a cache read run
+pub(super) struct CachedPage {
+    pub data: Vec<u8>,
+    /// `NotAvailable` when ECC is off
+    pub ecc: EccStatus,
+}
+
+impl<P: Programmer> SpiNand<P> {
+    /// The cache read mode usable for `len` bytes at `column` of each page
+    fn cache_read_mode(&self, column: u16, len: usize) -> NandCacheRead {
+        match self.spec.capabilities.cache_read {
+            // Continuous read ignores the column and skips the spare area
+            NandCacheRead::Continuous
+                if column != 0 || len != self.spec.layout.page_size as usize =>
+            {
+                NandCacheRead::None
+            }
+            mode => mode,
+        }
+    }
+
+    /// Read `count` consecutive pages from `first_page` through the cache
+    /// pipeline, with the ECC outcome of each; nothing is read when the chip
+    /// has no usable cache read.
+    pub(super) fn read_pages_cached(
+        &mut self,
+        first_page: u32,
+        count: u32,
+        column: u16,
+        len: usize,
+        use_ecc: bool,
+    ) -> Result<Vec<CachedPage>> {
+        match self.cache_read_mode(column, len) {
+            NandCacheRead::Sequential => {
+                self.read_pages_sequential(first_page, count, column, len, use_ecc)
+            }
+            NandCacheRead::Continuous => self.read_pages_continuous(first_page, count, use_ecc),
+            NandCacheRead::None => Ok(Vec::new()),
+        }
+    }
+
+    fn read_pages_sequential(
+        &mut self,
+        first_page: u32,
+        count: u32,
+        column: u16,
+        len: usize,
+        use_ecc: bool,
+    ) -> Result<Vec<CachedPage>> {
+        let row_addr = self.select_page(first_page)?;
+        self.programmer.spi_transaction_write(&[
+            CMD_NAND_PAGE_READ,
+            row_addr[0],
+            row_addr[1],
+            row_addr[2],
+        ])?;
+        self.wait_ready()?;
+
+        let mut pages = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            // 0x31 moves the loaded page into the cache and starts the next;
+            // 0x3F does the same without starting another
+            let opcode = if i + 1 == count {
+                CMD_NAND_CACHE_READ_END
+            } else {
+                CMD_NAND_CACHE_READ_SEQUENTIAL
+            };
+            self.programmer.spi_transaction_write(&[opcode])?;
+            self.wait_ready()?;
+
+            let data = self.read_cache(column, len)?;
+            let ecc = self.cached_ecc_status(first_page + i, use_ecc)?;
+            pages.push(CachedPage { data, ecc });
+        }
+        Ok(pages)
+    }
+
+    fn read_pages_continuous(
+        &mut self,
+        first_page: u32,
+        count: u32,
+        use_ecc: bool,
+    ) -> Result<Vec<CachedPage>> {
+        // BUF lives in the configuration of the die being read
+        let row_addr = self.select_page(first_page)?;
+        let config = self.get_feature(FEATURE_CONFIG)?;
+        self.set_feature(FEATURE_CONFIG, config & !CONFIG_BUF_MODE)?;
+        let result = self.stream_pages(row_addr, count);
+        // Always return to buffer mode, which every other path expects
+        let restored = self.set_feature(FEATURE_CONFIG, config | CONFIG_BUF_MODE);
+        let stream = result?;
+        restored?;
+
+        // The status bits cover the whole stream, so its worst outcome is
+        // reported for every page of the run
+        let ecc = self.cached_ecc_status(first_page, use_ecc)?;
+        let page_size = self.spec.layout.page_size as usize;
+        Ok(stream
+            .chunks(page_size)
+            .map(|data| CachedPage {
+                data: data.to_vec(),
+                ecc,
+            })
+            .collect())
+    }
+
+    fn cached_ecc_status(&mut self, page: u32, use_ecc: bool) -> Result<EccStatus> {
+        if use_ecc {
+            self.page_ecc_status(page)
+        } else {
+            Ok(EccStatus::NotAvailable)
+        }
+    }
+
+    fn stream_pages(&mut self, row_addr: [u8; 3], count: u32) -> Result<Vec<u8>> {
+        self.programmer.spi_transaction_write(&[
+            CMD_NAND_PAGE_READ,
+            row_addr[0],
+            row_addr[1],
+            row_addr[2],
+        ])?;
+        self.wait_ready()?;
+
+        let len = (count * self.spec.layout.page_size) as usize;
+        let width = self.data_width();
+        if width > BusWidth::Single {
+            let opcode = match width {
+                BusWidth::Quad => {
+                    self.enable_quad()?;
+                    CMD_NAND_READ_CACHE_X4
+                }
+                _ => CMD_NAND_READ_CACHE_X2,
+            };
+            let command = MultiIoCommand {
+                opcode,
+                opcode_width: BusWidth::Single,
+                address: &[],
+                address_width: BusWidth::Single,
+                dummy_cycles: CONTINUOUS_DUMMY_CYCLES,
+                data_width: width,
+            };
+            return self.programmer.spi_multi_io_read(&command, len);
+        }
+
+        // 0x03 followed by three dummy bytes in place of column and dummy
+        self.programmer
+            .spi_transaction(&[CMD_NAND_READ_CACHE, 0x00, 0x00, 0x00], len)
+    }
+}
@@ -0,0 +1,238 @@
+//! SPI NAND Linux On-Flash Bad Block Table
+//!
+//! Finds, reads and writes the mirrored tables Linux `nand_bbt` keeps in
+//! the last blocks of the chip (format in [`crate::domain::nand_bbt`]).
+//! Tables go through on-die ECC like any other data. Writing erases the
+//! blocks of the previous tables and bumps the version, so the kernel picks
+//! up the new copies instead of rescanning the chip.
+
+use super::SpiNand;
+use crate::domain::bad_block::{BadBlockTable, BlockStatus};
+use crate::domain::nand_bbt::{self, BbtDescriptor, BbtLocation, FlashBbt, BBT_MAIN, BBT_MIRROR};
+use crate::error::{Error, Result};
+use crate::infrastructure::programmer::Programmer;
+
+impl<P: Programmer> SpiNand<P> {
+    fn total_blocks(&self) -> u32 {
+        self.spec.capacity.as_bytes() / self.spec.layout.block_size
+    }
+
+    /// OOB size, checking the chip has room for the table tags
+    fn bbt_oob_size(&self) -> Result<usize> {
+        match self.spec.layout.oob_size {
+            Some(oob_size) if oob_size > BBT_MAIN.version_offset => Ok(oob_size as usize),
+            _ => Err(Error::NotSupported(format!(
+                "{} has no OOB area for an on-flash BBT",
+                self.spec.name
+            ))),
+        }
+    }
+
+    /// Check the on-die ECC leaves the OOB bytes of the table tags alone.
+    /// On layouts such as the W25N01GV one the ECC parity sits right where
+    /// Linux puts the tags, so they could never be read back.
+    fn check_bbt_tags_fit(&self) -> Result<()> {
+        let Some(layout) = self.spec.layout.oob_layout else {
+            return Ok(());
+        };
+        for desc in [&BBT_MAIN, &BBT_MIRROR] {
+            let tag = desc.tag_region();
+            if let Some(ecc) = layout.ecc_overlap(&tag) {
+                return Err(Error::NotSupported(format!(
+                    "{} keeps ECC parity in OOB bytes {}..{}, where the BBT tag ({}..{}) goes",
+                    self.spec.name,
+                    ecc.offset,
+                    ecc.offset + ecc.length,
+                    tag.offset,
+                    tag.offset + tag.length
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Search the last blocks of the chip for the table `desc` tags
+    fn find_bbt_copy(&mut self, desc: &BbtDescriptor) -> Result<Option<BbtLocation>> {
+        let total_blocks = self.total_blocks();
+        let page_size = self.spec.layout.page_size;
+        let oob_size = self.bbt_oob_size()?;
+
+        let first = total_blocks.saturating_sub(desc.max_blocks);
+        for block in (first..total_blocks).rev() {
+            let page = block * self.spec.layout.pages_per_block();
+            let oob = self.read_page_internal(page, page_size as u16, oob_size)?;
+            if let Some(version) = desc.version(&oob) {
+                return Ok(Some(BbtLocation { block, version }));
+            }
+        }
+        Ok(None)
+    }
+
+    fn read_bbt_copy(&mut self, location: BbtLocation) -> Result<BadBlockTable> {
+        let total_blocks = self.total_blocks() as usize;
+        let page_size = self.spec.layout.page_size as usize;
+        let len = nand_bbt::table_len(total_blocks);
+
+        let mut data = Vec::with_capacity(len);
+        let mut page = location.block * self.spec.layout.pages_per_block();
+        while data.len() < len {
+            let chunk = (len - data.len()).min(page_size);
+            data.extend(self.read_page_internal(page, 0, chunk)?);
+            self.check_ecc_status(page)?;
+            page += 1;
+        }
+        Ok(nand_bbt::decode(&data, total_blocks))
+    }
+
+    /// Find and read the on-flash BBT, or `None` when the chip has neither
+    /// copy. The newer copy wins, the main one on a tie; the other is the
+    /// fallback when it cannot be read. Blocks holding tables come back
+    /// reserved.
+    pub fn read_flash_bbt(&mut self) -> Result<Option<FlashBbt>> {
+        self.set_ecc(true)?;
+        let main = self.find_bbt_copy(&BBT_MAIN)?;
+        let mirror = self.find_bbt_copy(&BBT_MIRROR)?;
+
+        let mut copies: Vec<BbtLocation> = main.into_iter().chain(mirror).collect();
+        copies.sort_by_key(|copy| std::cmp::Reverse(copy.version));
+
+        let mut last_error = None;
+        for copy in copies {
+            match self.read_bbt_copy(copy) {
+                Ok(mut table) => {
+                    for location in main.iter().chain(mirror.iter()) {
+                        table.set_status(location.block as usize, BlockStatus::Reserved);
+                    }
+                    return Ok(Some(FlashBbt {
+                        table,
+                        main,
+                        mirror,
+                    }));
+                }
+                Err(e) => {
+                    log::warn!("Could not read the BBT in block {}: {}", copy.block, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        last_error.map_or(Ok(None), Err)
+    }
+
+    /// Pick a block for a new table copy among the last blocks of the chip
+    fn pick_bbt_block(
+        &mut self,
+        desc: &BbtDescriptor,
+        table: &BadBlockTable,
+        taken: Option<u32>,
+    ) -> Result<u32> {
+        let total_blocks = self.total_blocks();
+        let first = total_blocks.saturating_sub(desc.max_blocks);
+        for block in (first..total_blocks).rev() {
+            if Some(block) == taken {
+                continue;
+            }
+            let bad = match table.get_status(block as usize) {
+                BlockStatus::BadFactory | BlockStatus::BadRuntime => true,
+                BlockStatus::Unknown => self.is_bad_block(block)?,
+                BlockStatus::Good | BlockStatus::Reserved => false,
+            };
+            if !bad {
+                return Ok(block);
+            }
+        }
+        Err(Error::Other(format!(
+            "No good block left in the last {} blocks for the BBT",
+            desc.max_blocks
+        )))
+    }
+
+    fn write_bbt_copy(
+        &mut self,
+        desc: &BbtDescriptor,
+        location: BbtLocation,
+        data: &[u8],
+    ) -> Result<()> {
+        let page_size = self.spec.layout.page_size as usize;
+        let oob_size = self.bbt_oob_size()?;
+        let first_page = location.block * self.spec.layout.pages_per_block();
+
+        self.erase_block(location.block)?;
+        for (i, chunk) in data.chunks(page_size).enumerate() {
+            let mut buf = chunk.to_vec();
+            if i == 0 {
+                // The tag goes in the OOB area of the first page only
+                buf.resize(page_size, 0xFF);
+                buf.extend(desc.oob(location.version, oob_size));
+            }
+            self.program_page(first_page + i as u32, 0, &buf)?;
+        }
+
+        let oob = self.read_page_internal(first_page, page_size as u16, oob_size)?;
+        if desc.version(&oob) != Some(location.version) {
+            return Err(Error::Other(format!(
+                "The BBT tag in block {} did not read back",
+                location.block
+            )));
+        }
+        Ok(())
+    }
+
+    /// Write `table` as main and mirror BBT, reusing the blocks of the
+    /// tables already on the chip. Returns the table as written, its own
+    /// blocks reserved. Refused when the tags would overlap ECC parity.
+    pub fn write_flash_bbt(&mut self, table: &BadBlockTable) -> Result<FlashBbt> {
+        let total_blocks = self.total_blocks();
+        if table.len() != total_blocks as usize {
+            return Err(Error::InvalidParameter(format!(
+                "BBT has {} blocks but the chip has {}",
+                table.len(),
+                total_blocks
+            )));
+        }
+
+        self.check_bbt_tags_fit()?;
+        self.set_ecc(true)?;
+        let old_main = self.find_bbt_copy(&BBT_MAIN)?;
+        let old_mirror = self.find_bbt_copy(&BBT_MIRROR)?;
+        let version = match old_main
+            .iter()
+            .chain(old_mirror.iter())
+            .map(|c| c.version)
+            .max()
+        {
+            Some(0xFF) | None => 1,
+            Some(version) => version + 1,
+        };
+
+        let main_block = match old_main {
+            Some(copy) => copy.block,
+            None => self.pick_bbt_block(&BBT_MAIN, table, old_mirror.map(|c| c.block))?,
+        };
+        let mirror_block = match old_mirror {
+            Some(copy) => copy.block,
+            None => self.pick_bbt_block(&BBT_MIRROR, table, Some(main_block))?,
+        };
+        let main = BbtLocation {
+            block: main_block,
+            version,
+        };
+        let mirror = BbtLocation {
+            block: mirror_block,
+            version,
+        };
+
+        let mut table = table.clone();
+        table.set_status(main.block as usize, BlockStatus::Reserved);
+        table.set_status(mirror.block as usize, BlockStatus::Reserved);
+        let data = nand_bbt::encode(&table);
+
+        self.write_bbt_copy(&BBT_MAIN, main, &data)?;
+        self.write_bbt_copy(&BBT_MIRROR, mirror, &data)?;
+
+        Ok(FlashBbt {
+            table,
+            main: Some(main),
+            mirror: Some(mirror),
+        })
+    }
+}
@@ -0,0 +1,174 @@
+//! SPI NAND Health Survey and Stress Test
+//!
+//! Reads every good block with on-die ECC and records the bit flips it
+//! corrected (see [`crate::domain::nand_health`]). How exact the counts
+//! are depends on the chip: with [`EccStatusMethod::Standard`] a corrected
+//! page counts as the full ECC strength. Blocks the BBT marks bad or
+//! reserved for the table itself are left alone.
+//!
+//! [`EccStatusMethod::Standard`]: crate::domain::EccStatusMethod::Standard
+
+use std::ops::Range;
+
+use super::SpiNand;
+use crate::domain::bad_block::{BadBlockTable, BlockStatus};
+use crate::domain::nand_health::{stress_pattern, BlockHealth, HealthReport, StressResult};
+use crate::domain::{EccStatus, Progress};
+use crate::error::{Error, Result};
+use crate::infrastructure::programmer::Programmer;
+
+impl<P: Programmer> SpiNand<P> {
+    /// Read every page of every good block with ECC. Blocks count as worn
+    /// from `threshold` corrected bit flips, by default three quarters of
+    /// the ECC strength. Pages the ECC cannot correct make their block
+    /// failing; any other read error ends the survey.
+    pub fn survey_health(
+        &mut self,
+        threshold: Option<u8>,
+        bbt: &Option<BadBlockTable>,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<HealthReport> {
+        let pages_per_block = self.spec.layout.pages_per_block();
+        let page_size = self.spec.layout.page_size as usize;
+        let total_blocks = self.spec.capacity.as_bytes() / self.spec.layout.block_size;
+        let mut report = HealthReport::new(
+            self.spec.name.clone(),
+            pages_per_block,
+            self.spec.capabilities.ecc_strength,
+            threshold,
+        );
+
+        self.set_ecc(true)?;
+        for block in 0..total_blocks {
+            let health = if is_reserved(bbt, block) {
+                BlockHealth::reserved(block)
+            } else if self.block_is_bad(block, bbt)? {
+                BlockHealth::bad(block)
+            } else {
+                let first_page = block * pages_per_block;
+                let mut pages = Vec::with_capacity(pages_per_block as usize);
+                for page in first_page..first_page + pages_per_block {
+                    self.read_page_internal(page, 0, page_size)?;
+                    pages.push(self.read_ecc_status()?);
+                }
+                BlockHealth::from_pages(block, &pages, report.threshold)
+            };
+            report.blocks.push(health);
+            on_progress(Progress::new(block as u64 + 1, total_blocks as u64));
+        }
+        Ok(report)
+    }
+
+    /// Erase, program and read back every good block in `blocks` `cycles`
+    /// times. Destroys their contents; blocks that pass are left erased.
+    /// Blocks the BBT marks bad or reserved are skipped.
+    pub fn stress_test(
+        &mut self,
+        blocks: Range<u32>,
+        cycles: u32,
+        bbt: &Option<BadBlockTable>,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<Vec<StressResult>> {
+        let total_blocks = self.spec.capacity.as_bytes() / self.spec.layout.block_size;
+        if blocks.is_empty() || blocks.end > total_blocks {
+            return Err(Error::InvalidParameter(format!(
+                "Blocks {}..{} are not within the chip ({} blocks)",
+                blocks.start, blocks.end, total_blocks
+            )));
+        }
+        if cycles == 0 {
+            return Err(Error::InvalidParameter(
+                "The stress test needs at least one cycle".to_string(),
+            ));
+        }
+
+        self.set_ecc(true)?;
+        let total = blocks.len() as u64 * cycles as u64;
+        let mut results = Vec::new();
+        for (i, block) in blocks.enumerate() {
+            if self.block_is_bad(block, bbt)? {
+                log::info!("Skipping bad or reserved block {}", block);
+                on_progress(Progress::new((i as u64 + 1) * cycles as u64, total));
+                continue;
+            }
+
+            let mut result = StressResult {
+                block,
+                cycles: 0,
+                max_bit_flips: 0,
+                failure: None,
+            };
+            for cycle in 0..cycles {
+                on_progress(Progress::new(
+                    i as u64 * cycles as u64 + cycle as u64,
+                    total,
+                ));
+                match self.stress_cycle(block, cycle)? {
+                    Ok(bit_flips) => {
+                        result.cycles += 1;
+                        result.max_bit_flips = result.max_bit_flips.max(bit_flips);
+                    }
+                    Err(failure) => {
+                        result.failure = Some(failure);
+                        break;
+                    }
+                }
+            }
+            if result.failure.is_none() {
+                match self.erase_block(block) {
+                    Err(Error::EraseFailed { .. }) => {
+                        result.failure = Some("erase failed".to_string())
+                    }
+                    other => other?,
+                }
+            }
+            results.push(result);
+        }
+        on_progress(Progress::new(total, total));
+        Ok(results)
+    }
+
+    /// One erase/program/read cycle of `block`: the most bit flips any page
+    /// needed corrected, or why the block failed
+    fn stress_cycle(&mut self, block: u32, cycle: u32) -> Result<std::result::Result<u8, String>> {
+        let pages_per_block = self.spec.layout.pages_per_block();
+        let page_size = self.spec.layout.page_size as usize;
+        let first_page = block * pages_per_block;
+
+        match self.erase_block(block) {
+            Err(Error::EraseFailed { .. }) => return Ok(Err("erase failed".to_string())),
+            other => other?,
+        }
+        for page in 0..pages_per_block {
+            let data = stress_pattern(block, cycle, page, page_size);
+            match self.program_page(first_page + page, 0, &data) {
+                Err(Error::WriteFailed { .. }) => {
+                    return Ok(Err(format!("program failed at page {}", page)))
+                }
+                other => other?,
+            }
+        }
+
+        let mut max_bit_flips = 0;
+        for page in 0..pages_per_block {
+            let data = self.read_page_internal(first_page + page, 0, page_size)?;
+            match self.read_ecc_status()? {
+                EccStatus::Uncorrectable => {
+                    return Ok(Err(format!("uncorrectable ECC error at page {}", page)))
+                }
+                EccStatus::Corrected { bit_flips } => max_bit_flips = max_bit_flips.max(bit_flips),
+                EccStatus::NoError | EccStatus::NotAvailable => {}
+            }
+            if data != stress_pattern(block, cycle, page, page_size) {
+                return Ok(Err(format!("data mismatch at page {}", page)));
+            }
+        }
+        Ok(Ok(max_bit_flips))
+    }
+}
+
+/// Whether `block` holds the on-flash BBT
+fn is_reserved(bbt: &Option<BadBlockTable>, block: u32) -> bool {
+    bbt.as_ref()
+        .is_some_and(|bbt| bbt.get_status(block as usize) == BlockStatus::Reserved)
+}
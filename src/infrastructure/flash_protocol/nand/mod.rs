@@ -2,26 +2,49 @@
 //!
 //! This module implements the SPI NAND protocol according to infrastructure standards.
 
+mod bad_block;
+mod cache_read;
+mod flash_bbt;
+mod health;
+mod multi_io;
+mod otp;
+pub mod parameter_page;
+mod read_retry;
+mod soft_ecc;
 #[cfg(test)]
 mod tests;
 
 use std::time::{Duration, Instant};
 
 use crate::domain::bad_block::{BadBlockStrategy, BadBlockTable, BlockStatus};
-use crate::domain::chip::ChipSpec;
-use crate::domain::{EraseRequest, FlashOperation, OobMode, Progress, ReadRequest, WriteRequest};
+use crate::domain::chip::{ChipSpec, EccStatusMethod};
+use crate::domain::partition::{Partition, PartitionBounds};
+use crate::domain::{
+    BlockRelocation, EccStatus, EraseReport, EraseRequest, FlashOperation, OobLayout, OobMode,
+    PageEcc, Progress, ReadReport, ReadRequest, ReadRetryRecovery, ReadSegment, SoftEcc, WriteMode,
+    WriteReport, WriteRequest,
+};
 use crate::error::{Error, Result};
 use crate::infrastructure::flash_protocol::commands::*;
-use crate::infrastructure::programmer::Programmer;
+use crate::infrastructure::programmer::{BusWidth, Programmer};
 
 pub struct SpiNand<P: Programmer> {
     programmer: P,
     spec: ChipSpec,
+    /// QE has been checked/set during this session
+    quad_enabled: bool,
+    /// Last die selected with 0xC2; `None` until first selected
+    selected_die: Option<u8>,
 }
 
 impl<P: Programmer> SpiNand<P> {
     pub fn new(programmer: P, spec: ChipSpec) -> Self {
-        Self { programmer, spec }
+        Self {
+            programmer,
+            spec,
+            quad_enabled: false,
+            selected_die: None,
+        }
     }
 
     /// Get chip specification
@@ -78,27 +101,58 @@ impl<P: Programmer> SpiNand<P> {
         Ok(())
     }
 
-    fn page_to_row_addr(&self, page: u32) -> [u8; 3] {
-        [(page >> 16) as u8, (page >> 8) as u8, page as u8]
+    /// Select the die holding `page` and return the page's row address
+    /// within that die
+    fn select_page(&mut self, page: u32) -> Result<[u8; 3]> {
+        let page = if self.spec.layout.die_count > 1 {
+            let pages_per_die =
+                self.spec.layout.die_size(self.spec.capacity) / self.spec.layout.page_size;
+            self.select_die(page / pages_per_die)?;
+            page % pages_per_die
+        } else {
+            page
+        };
+        Ok([(page >> 16) as u8, (page >> 8) as u8, page as u8])
+    }
+
+    /// Switch multi-die packages to `die`; a no-op on single-die chips
+    fn select_die(&mut self, die: u32) -> Result<()> {
+        if self.spec.layout.die_count <= 1 || self.selected_die == Some(die as u8) {
+            return Ok(());
+        }
+        self.programmer
+            .spi_transaction_write(&[CMD_SOFTWARE_DIE_SELECT, die as u8])?;
+        self.selected_die = Some(die as u8);
+        Ok(())
     }
 
     fn column_to_addr(&self, column: u16) -> [u8; 2] {
         [(column >> 8) as u8, column as u8]
     }
 
+    /// Whether on-die ECC is enabled on the selected die
+    fn ecc_enabled(&mut self) -> Result<bool> {
+        Ok(self.get_feature(FEATURE_CONFIG)? & CONFIG_ECC_ENABLE != 0)
+    }
+
+    /// Turn on-die ECC on or off; every die has its own configuration
     fn set_ecc(&mut self, enabled: bool) -> Result<()> {
-        let config = self.get_feature(FEATURE_CONFIG)?;
-        if enabled {
-            self.set_feature(FEATURE_CONFIG, config | CONFIG_ECC_ENABLE)
-        } else {
-            self.set_feature(FEATURE_CONFIG, config & !CONFIG_ECC_ENABLE)
+        for die in 0..self.spec.layout.die_count.max(1) {
+            self.select_die(die)?;
+            let config = self.get_feature(FEATURE_CONFIG)?;
+            if enabled {
+                self.set_feature(FEATURE_CONFIG, config | CONFIG_ECC_ENABLE)?;
+            } else {
+                self.set_feature(FEATURE_CONFIG, config & !CONFIG_ECC_ENABLE)?;
+            }
         }
+        Ok(())
     }
 
     fn read_page_internal(&mut self, page: u32, column: u16, len: usize) -> Result<Vec<u8>> {
         // Step 1: Page Read to Cache (13h + row address)
         // Use spi_transaction_write for single USB round-trip
-        let row_addr = self.page_to_row_addr(page);
+        let row_addr = self.select_page(page)?;
         self.programmer.spi_transaction_write(&[
             CMD_NAND_PAGE_READ,
             row_addr[0],
@@ -109,7 +163,18 @@ impl<P: Programmer> SpiNand<P> {
         // Wait for page to be loaded into cache
         self.wait_ready()?;
 
-        // Step 2: Read from Cache (03h + column address + dummy)
+        // Step 2: Read from Cache
+        self.read_cache(column, len)
+    }
+
+    /// Read `len` bytes at `column` from the cache (03h + column address +
+    /// dummy), or its x2/x4 variant when the bus allows
+    fn read_cache(&mut self, column: u16, len: usize) -> Result<Vec<u8>> {
+        let width = self.data_width();
+        if width > BusWidth::Single {
+            return self.read_cache_multi_io(column, len, width);
+        }
+
         // Use optimized spi_transaction for bulk read
         let col_addr = self.column_to_addr(column);
         let cmd = [
@@ -145,63 +210,201 @@ impl<P: Programmer> SpiNand<P> {
         Ok(false)
     }
 
-    fn check_ecc_status(&mut self, page: u32) -> Result<()> {
-        let status = self.get_feature(FEATURE_STATUS)?;
-        let ecc_status = status & STATUS_NAND_ECC_MASK;
+    /// Check a block against the BBT, falling back to its bad block marker
+    fn block_is_bad(&mut self, block: u32, bbt: &Option<BadBlockTable>) -> Result<bool> {
+        match bbt {
+            Some(bbt) => match bbt.get_status(block as usize) {
+                BlockStatus::Unknown => self.is_bad_block(block),
+                BlockStatus::BadFactory | BlockStatus::BadRuntime | BlockStatus::Reserved => {
+                    Ok(true)
+                }
+                BlockStatus::Good => Ok(false),
+            },
+            None => self.is_bad_block(block),
+        }
+    }
 
-        match ecc_status {
-            STATUS_NAND_ECC_UNCORRECTABLE => {
-                log::error!("Uncorrectable ECC error at page {}", page);
-                Err(Error::EccError {
-                    address: page * self.spec.layout.page_size,
-                })
-            }
-            STATUS_NAND_ECC_CORRECTED | STATUS_NAND_ECC_CORRECTED_ALT => {
-                log::warn!("Corrected ECC errors at page {}", page);
-                Ok(())
-            }
-            _ => Ok(()),
+    /// The chip's OOB layout when the request accesses only the free OOB
+    /// bytes
+    fn free_oob_layout(&self, oob_mode: OobMode) -> Result<Option<OobLayout>> {
+        if oob_mode != OobMode::Free {
+            return Ok(None);
+        }
+        match self.spec.layout.oob_layout {
+            Some(layout) => Ok(Some(layout)),
+            None => Err(Error::NotSupported(format!(
+                "No OOB layout known for {}; use the raw OOB modes instead",
+                self.spec.name
+            ))),
         }
     }
-}
 
-impl<P: Programmer> FlashOperation for SpiNand<P> {
-    fn read(&mut self, request: ReadRequest, on_progress: &dyn Fn(Progress)) -> Result<Vec<u8>> {
+    /// Bytes each page adds to the result of a read in `oob_mode`
+    fn result_per_page(&self, oob_mode: OobMode) -> Result<u32> {
         let page_size = self.spec.layout.page_size;
         let oob_size = self.spec.layout.oob_size.unwrap_or(0);
+        Ok(match (oob_mode, self.free_oob_layout(oob_mode)?) {
+            (OobMode::None, _) => page_size,
+            (OobMode::Included, _) => page_size + oob_size,
+            (OobMode::Free, Some(layout)) => layout.free_bytes() as u32,
+            (OobMode::Only | OobMode::Free, _) => oob_size,
+        })
+    }
 
-        self.set_ecc(request.use_ecc)?;
+    fn partition_bounds<'a>(&self, partitions: &'a [Partition]) -> Result<PartitionBounds<'a>> {
+        PartitionBounds::new(
+            partitions,
+            self.spec.layout.block_size,
+            self.spec.capacity.as_bytes(),
+        )
+    }
+
+    /// Move `page`, reached by skipping bad blocks, into the partition of
+    /// `logical_page`; `None` when that partition has no blocks left
+    fn bounded_page(
+        &self,
+        bounds: &mut PartitionBounds,
+        logical_page: u32,
+        page: u32,
+    ) -> Option<u32> {
+        let pages_per_block = self.spec.layout.pages_per_block();
+        let block = bounds.place(logical_page / pages_per_block, page / pages_per_block)?;
+        Some(block * pages_per_block + page % pages_per_block)
+    }
+
+    /// Load `data` into the cache at `column` and program it to `page`
+    fn program_page(&mut self, page: u32, column: u16, data: &[u8]) -> Result<()> {
+        let row_addr = self.select_page(page)?;
+        let quad = self.data_width() == BusWidth::Quad;
+        if quad {
+            self.enable_quad()?;
+        }
+        self.write_enable()?;
 
+        // Program Load
+        if quad {
+            self.program_load_quad(column, data)?;
+        } else {
+            let col_addr = self.column_to_addr(column);
+            self.programmer.set_cs(true)?;
+            self.programmer
+                .spi_write(&[CMD_NAND_PROGRAM_LOAD, col_addr[0], col_addr[1]])?;
+            self.programmer.spi_write(data)?;
+            self.programmer.set_cs(false)?;
+        }
+
+        // Program Execute
+        self.programmer.set_cs(true)?;
+        self.programmer.spi_write(&[
+            CMD_NAND_PROGRAM_EXECUTE,
+            row_addr[0],
+            row_addr[1],
+            row_addr[2],
+        ])?;
+        self.programmer.set_cs(false)?;
+
+        self.wait_ready()?;
+
+        let status = self.get_feature(FEATURE_STATUS)?;
+        if status & STATUS_NAND_P_FAIL != 0 {
+            return Err(Error::WriteFailed {
+                address: page * self.spec.layout.page_size,
+            });
+        }
+        Ok(())
+    }
+
+    fn erase_block(&mut self, block: u32) -> Result<()> {
+        let page = block * (self.spec.layout.block_size / self.spec.layout.page_size);
+        let row_addr = self.select_page(page)?;
+
+        self.write_enable()?;
+
+        self.programmer.set_cs(true)?;
+        self.programmer.spi_write(&[
+            CMD_NAND_BLOCK_ERASE,
+            row_addr[0],
+            row_addr[1],
+            row_addr[2],
+        ])?;
+        self.programmer.set_cs(false)?;
+
+        self.wait_ready()?;
+
+        let status = self.get_feature(FEATURE_STATUS)?;
+        if status & STATUS_NAND_E_FAIL != 0 {
+            return Err(Error::EraseFailed { block });
+        }
+        Ok(())
+    }
+
+    /// Program the data page by page; the target range must already be erased.
+    ///
+    /// With the Skip strategy a block that fails to program is retired and
+    /// its data rewritten to the next good block. That shifts the rest of the
+    /// data past the range the caller erased, so every block entered after a
+    /// relocation is erased first.
+    fn write_direct(
+        &mut self,
+        request: &WriteRequest,
+        soft_ecc: Option<&SoftEcc>,
+        bbt: &mut Option<BadBlockTable>,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<Vec<BlockRelocation>> {
+        let page_size = self.spec.layout.page_size;
         let start_addr = request.address.as_u32();
         let start_page = start_addr / page_size;
+        let pages_per_block = self.spec.layout.block_size / page_size;
+        let oob_size = self.spec.layout.oob_size.unwrap_or(0);
+        let free_layout = self.free_oob_layout(request.oob_mode)?;
 
-        // Calculate read parameters based on OobMode
-        let (col_offset, read_len_per_page) = match request.oob_mode {
+        // Calculate write parameters based on OobMode
+        let (col_offset, write_len_per_page) = match request.oob_mode {
             OobMode::None => (0u16, page_size as usize),
             OobMode::Included => (0u16, (page_size + oob_size) as usize),
             OobMode::Only => (page_size as u16, oob_size as usize),
+            OobMode::Free => (
+                page_size as u16,
+                free_layout.map_or(0, |layout| layout.free_bytes()),
+            ),
         };
 
-        let total_pages = request.length.div_ceil(page_size);
-        let pages_per_block = self.spec.layout.block_size / page_size;
-        let mut result = Vec::with_capacity(request.length as usize);
-        let mut remaining = request.length as usize;
+        let data_len = request.data.len();
+        let total_pages = data_len.div_ceil(write_len_per_page);
 
+        let use_ecc = request.ecc_policy().is_hardware();
         let mut current_page = start_page;
-        let mut pages_read = 0;
+        let mut offset = 0usize;
+        let mut pages_written = 0;
 
-        while pages_read < total_pages {
-            let current_block = current_page / pages_per_block;
+        let mut relocations = Vec::new();
+        // Block being written, its logical index and where its data starts
+        let mut block_in_progress = None;
+        let mut logical_block = None;
+        let mut block_start = (0usize, 0usize);
+        // Logical and physical block of a failed program awaiting a new home
+        let mut pending_relocation: Option<(u32, u32)> = None;
+        let mut bounds = self.partition_bounds(&request.partitions)?;
 
-            let is_bad = if let Some(ref bbt) = request.bbt {
-                match bbt.get_status(current_block as usize) {
-                    BlockStatus::Unknown => self.is_bad_block(current_block)?,
-                    BlockStatus::BadFactory | BlockStatus::BadRuntime => true,
-                    BlockStatus::Good => false,
+        while pages_written < total_pages {
+            let logical_page = start_page + pages_written as u32;
+            let Some(page) = self.bounded_page(&mut bounds, logical_page, current_page) else {
+                // Only erased padding may be dropped at the end of a partition
+                let end_page = bounds.partition_end() * pages_per_block;
+                let end = ((end_page - start_page) as usize * write_len_per_page).min(data_len);
+                if request.data[offset..end].iter().any(|&b| b != 0xFF) {
+                    return Err(bounds.out_of_blocks());
                 }
-            } else {
-                self.is_bad_block(current_block)?
+                offset = end;
+                pages_written = (end_page - start_page) as usize;
+                current_page = end_page;
+                pending_relocation = None;
+                continue;
             };
+            current_page = page;
+            let current_block = current_page / pages_per_block;
+
+            let is_bad = self.block_is_bad(current_block, bbt)?;
 
             if request.bad_block_strategy != BadBlockStrategy::Include && is_bad {
                 match request.bad_block_strategy {
@@ -219,109 +422,453 @@ impl<P: Programmer> FlashOperation for SpiNand<P> {
                 }
             }
 
-            let mut attempts = 0;
-            let chunk = loop {
-                match self.read_page_internal(current_page, col_offset, read_len_per_page) {
-                    Ok(data) => {
-                        // Check ECC status if requested
-                        if request.use_ecc && !request.ignore_ecc_errors {
-                            if let Err(e) = self.check_ecc_status(current_page) {
-                                if attempts < request.retry_count {
-                                    attempts += 1;
-                                    log::warn!(
-                                        "ECC error on page {}, retrying (attempt {})",
-                                        current_page,
-                                        attempts
-                                    );
-                                    continue;
-                                } else {
-                                    return Err(e);
-                                }
-                            }
-                        }
-                        break data;
-                    }
-                    Err(e) => {
-                        if attempts < request.retry_count {
-                            attempts += 1;
-                            log::warn!(
-                                "Read error on page {}, retrying (attempt {}): {}",
-                                current_page,
-                                attempts,
-                                e
-                            );
+            if block_in_progress != Some(current_block) {
+                if pending_relocation.is_some() || !relocations.is_empty() {
+                    match self.erase_block(current_block) {
+                        Err(Error::EraseFailed { block }) => {
+                            log::warn!("Erase of block {} failed, marking it bad", block);
+                            self.retire_block(block, bbt, use_ecc)?;
+                            current_page = (current_block + 1) * pages_per_block;
                             continue;
-                        } else {
-                            return Err(e);
                         }
+                        result => result?,
                     }
                 }
-            };
+                match pending_relocation.take() {
+                    Some((logical, failed_block)) => relocations.push(BlockRelocation {
+                        logical_block: logical,
+                        failed_block,
+                        new_block: current_block,
+                    }),
+                    None => logical_block = Some(logical_block.map_or(0, |l: u32| l + 1)),
+                }
+                block_in_progress = Some(current_block);
+                block_start = (offset, pages_written);
+            }
+
+            let chunk_end = (offset + write_len_per_page).min(data_len);
+            let mut page_buf = vec![0xFFu8; write_len_per_page];
+            page_buf[..(chunk_end - offset)].copy_from_slice(&request.data[offset..chunk_end]);
+            if let Some(ecc) = soft_ecc {
+                // Main data alone still gets the OOB area for its ECC
+                page_buf.resize((page_size + oob_size) as usize, 0xFF);
+                ecc.encode_page(&mut page_buf, page_size as usize)?;
+            }
+            if let Some(layout) = &free_layout {
+                // Marker and ECC bytes are left erased
+                let mut oob = vec![0xFFu8; oob_size as usize];
+                layout.insert_free(&mut oob, &page_buf);
+                page_buf = oob;
+            }
 
-            // Log if ECC error was ignored after all retries or if ECC check disabled
-            if request.use_ecc && request.ignore_ecc_errors {
-                if let Err(e) = self.check_ecc_status(current_page) {
-                    log::debug!("Ignored ECC error: {}", e);
+            match self.program_page(current_page, col_offset, &page_buf) {
+                Err(Error::WriteFailed { .. })
+                    if request.bad_block_strategy == BadBlockStrategy::Skip =>
+                {
+                    log::warn!(
+                        "Program of page {} failed, relocating block {}",
+                        current_page,
+                        current_block
+                    );
+                    self.retire_block(current_block, bbt, use_ecc)?;
+                    pending_relocation = Some((logical_block.unwrap_or(0), current_block));
+                    // Rewrite everything this block held in the next one
+                    (offset, pages_written) = block_start;
+                    current_page = (current_block + 1) * pages_per_block;
+                    continue;
                 }
+                result => result?,
             }
 
-            let to_copy = remaining.min(chunk.len());
-            result.extend_from_slice(&chunk[..to_copy]);
-            remaining -= to_copy;
-            pages_read += 1;
+            on_progress(Progress::new(chunk_end as u64, data_len as u64));
+
+            offset += write_len_per_page;
+            pages_written += 1;
             current_page += 1;
+        }
 
-            on_progress(Progress::new(result.len() as u64, request.length as u64));
+        Ok(relocations)
+    }
 
-            if remaining == 0 {
-                break;
+    /// Compare each block against the image and only erase/program what differs.
+    ///
+    /// NAND pages can only be programmed once per erase, so a block is erased
+    /// whenever a page that is not blank has to change. Pages of the block
+    /// outside the written range are read back with their spare area and
+    /// restored; a kept page that is uncorrectable fails the write unless
+    /// ECC errors are ignored.
+    fn write_delta(
+        &mut self,
+        request: &WriteRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<WriteReport> {
+        if request.oob_mode != OobMode::None || request.soft_ecc.is_some() {
+            return Err(Error::NotSupported(
+                "Delta writes do not support OOB data or software ECC".to_string(),
+            ));
+        }
+
+        let page_size = self.spec.layout.page_size as usize;
+        let oob_size = self.spec.layout.oob_size.unwrap_or(0) as usize;
+        let pages_per_block = self.spec.layout.block_size / self.spec.layout.page_size;
+        let data_len = request.data.len();
+
+        let mut report = WriteReport::default();
+        let start_page = request.address.as_u32() / page_size as u32;
+        let mut current_page = start_page;
+        let mut offset = 0usize;
+        let mut bounds = self.partition_bounds(&request.partitions)?;
+
+        while offset < data_len {
+            let logical_page = start_page + (offset / page_size) as u32;
+            let Some(page) = self.bounded_page(&mut bounds, logical_page, current_page) else {
+                // Only erased padding may be dropped at the end of a partition
+                let end_page = bounds.partition_end() * pages_per_block;
+                let end = ((end_page - start_page) as usize * page_size).min(data_len);
+                if request.data[offset..end].iter().any(|&b| b != 0xFF) {
+                    return Err(bounds.out_of_blocks());
+                }
+                offset = end;
+                current_page = end_page;
+                continue;
+            };
+            current_page = page;
+            let block = current_page / pages_per_block;
+
+            if request.bad_block_strategy != BadBlockStrategy::Include
+                && self.block_is_bad(block, &request.bbt)?
+            {
+                match request.bad_block_strategy {
+                    BadBlockStrategy::Skip => {
+                        current_page = (block + 1) * pages_per_block;
+                        continue;
+                    }
+                    BadBlockStrategy::Fail => return Err(Error::BadBlock { block }),
+                    _ => {}
+                }
+            }
+
+            // Each page with its spare area, so rewritten blocks keep their
+            // OOB tags and cleanmarkers
+            let first_page = block * pages_per_block;
+            let mut current = Vec::with_capacity(pages_per_block as usize);
+            let mut uncorrectable = Vec::new();
+            for page in first_page..first_page + pages_per_block {
+                current.push(self.read_page_internal(page, 0, page_size + oob_size)?);
+                if request.use_ecc && self.page_ecc_status(page)? == EccStatus::Uncorrectable {
+                    uncorrectable.push(page);
+                }
             }
+
+            let mut merged = current.clone();
+            let mut replaced = vec![false; pages_per_block as usize];
+            while offset < data_len && current_page < first_page + pages_per_block {
+                let end = (offset + page_size).min(data_len);
+                let index = (current_page - first_page) as usize;
+                merged[index][..end - offset].copy_from_slice(&request.data[offset..end]);
+                replaced[index] = end - offset == page_size;
+                offset = end;
+                current_page += 1;
+            }
+
+            // Rewriting a kept page that did not decode would store its
+            // errors as if they were data
+            if let Some(&page) = uncorrectable
+                .iter()
+                .find(|&&page| !replaced[(page - first_page) as usize])
+            {
+                if !request.ignore_ecc_errors {
+                    return Err(Error::EccError {
+                        address: page * page_size as u32,
+                    });
+                }
+                log::warn!("Rewriting uncorrectable page {} as read", page);
+            }
+
+            report.units_total += 1;
+            if merged == current && uncorrectable.is_empty() {
+                report.units_skipped += 1;
+            } else {
+                let blank = |page: &[u8]| page.iter().all(|&b| b == 0xFF);
+                let needs_erase = !uncorrectable.is_empty()
+                    || current
+                        .iter()
+                        .zip(merged.iter())
+                        .any(|(old, new)| old != new && !blank(old));
+
+                if needs_erase {
+                    log::debug!("Erasing and rewriting block {}", block);
+                    self.erase_block(block)?;
+                    report.units_erased += 1;
+                }
+
+                for (i, (old, new)) in current.iter().zip(merged.iter()).enumerate() {
+                    if (needs_erase && !blank(new)) || (!needs_erase && old != new) {
+                        self.program_page(first_page + i as u32, 0, new)?;
+                        report.pages_programmed += 1;
+                    } else {
+                        report.pages_skipped += 1;
+                    }
+                }
+            }
+
+            on_progress(Progress::new(offset as u64, data_len as u64));
         }
 
-        Ok(result)
+        Ok(report)
     }
 
-    fn write(&mut self, request: WriteRequest, on_progress: &dyn Fn(Progress)) -> Result<()> {
-        let page_size = self.spec.layout.page_size;
-        self.set_ecc(request.use_ecc)?;
+    /// Decode the ECC outcome of the page just read
+    fn read_ecc_status(&mut self) -> Result<EccStatus> {
+        let status = self.get_feature(FEATURE_STATUS)?;
+        let ecc_status = status & STATUS_NAND_ECC_MASK;
 
-        let start_addr = request.address.as_u32();
-        if !start_addr.is_multiple_of(page_size) {
-            return Err(Error::InvalidParameter(
-                "NAND write address must be page-aligned".to_string(),
-            ));
+        let status = match self.spec.capabilities.ecc_status {
+            EccStatusMethod::Standard => match ecc_status {
+                STATUS_NAND_ECC_UNCORRECTABLE => EccStatus::Uncorrectable,
+                STATUS_NAND_ECC_CORRECTED | STATUS_NAND_ECC_CORRECTED_ALT => {
+                    // The exact count is unknown, so assume the worst like
+                    // Linux does
+                    EccStatus::Corrected {
+                        bit_flips: self.spec.capabilities.ecc_strength.max(1),
+                    }
+                }
+                _ => EccStatus::NoError,
+            },
+            EccStatusMethod::MacronixEccsr => match ecc_status {
+                STATUS_NAND_ECC_OK => EccStatus::NoError,
+                STATUS_NAND_ECC_CORRECTED => {
+                    let eccsr = self
+                        .programmer
+                        .spi_transaction(&[CMD_NAND_GET_ECCSR, 0x00], 1)?;
+                    EccStatus::Corrected {
+                        bit_flips: (eccsr[0] & 0x0F).max(1),
+                    }
+                }
+                _ => EccStatus::Uncorrectable,
+            },
+            EccStatusMethod::GigaDeviceEccse => match ecc_status {
+                STATUS_NAND_ECC_OK => EccStatus::NoError,
+                STATUS_NAND_ECC_CORRECTED => {
+                    // Bits 3..0 of the count are ECCS1, ECCS0, ECCSE1, ECCSE0
+                    let status_2 = self.get_feature(FEATURE_STATUS_2)?;
+                    EccStatus::Corrected {
+                        bit_flips: (ecc_status >> 2) | ((status_2 & 0x30) >> 4),
+                    }
+                }
+                // ECCS 11 is the correction limit, ECCS 10 a failure
+                0x30 => EccStatus::Corrected { bit_flips: 8 },
+                _ => EccStatus::Uncorrectable,
+            },
+            EccStatusMethod::MicronEccs => match status & STATUS_NAND_MICRON_ECC_MASK {
+                0x00 => EccStatus::NoError,
+                0x10 => EccStatus::Corrected { bit_flips: 3 },
+                0x30 => EccStatus::Corrected { bit_flips: 6 },
+                0x50 => EccStatus::Corrected { bit_flips: 8 },
+                _ => EccStatus::Uncorrectable,
+            },
+            EccStatusMethod::KioxiaMaxBitFlips => match ecc_status {
+                STATUS_NAND_ECC_OK => EccStatus::NoError,
+                STATUS_NAND_ECC_CORRECTED | STATUS_NAND_ECC_UNCORRECTABLE => {
+                    let strength = self.spec.capabilities.ecc_strength.max(1);
+                    let bit_flips = self.get_feature(FEATURE_ECC_MAX_BIT_FLIPS)? >> 4;
+                    // Assume the worst for a count of zero or past the
+                    // strength, like Linux does
+                    EccStatus::Corrected {
+                        bit_flips: if (1..=strength).contains(&bit_flips) {
+                            bit_flips
+                        } else {
+                            strength
+                        },
+                    }
+                }
+                _ => EccStatus::Uncorrectable,
+            },
+        };
+        Ok(status)
+    }
+
+    /// ECC outcome of the page just read, logging any errors
+    fn page_ecc_status(&mut self, page: u32) -> Result<EccStatus> {
+        let status = self.read_ecc_status()?;
+        Self::log_ecc_status(page, status);
+        Ok(status)
+    }
+
+    fn log_ecc_status(page: u32, status: EccStatus) {
+        match status {
+            EccStatus::Uncorrectable => {
+                log::error!("Uncorrectable ECC error at page {}", page);
+            }
+            EccStatus::Corrected { bit_flips } => {
+                log::warn!("Corrected {} bit flips at page {}", bit_flips, page);
+            }
+            _ => {}
         }
+    }
 
-        let start_page = start_addr / page_size;
-        let pages_per_block = self.spec.layout.block_size / page_size;
+    /// Like [`page_ecc_status`](Self::page_ecc_status), but uncorrectable
+    /// pages are an error
+    fn check_ecc_status(&mut self, page: u32) -> Result<EccStatus> {
+        match self.page_ecc_status(page)? {
+            EccStatus::Uncorrectable => Err(Error::EccError {
+                address: page * self.spec.layout.page_size,
+            }),
+            status => Ok(status),
+        }
+    }
+
+    /// Read a single page, retrying read and ECC errors up to the request's
+    /// retry count. A page that stays uncorrectable with on-die ECC is
+    /// re-read at the chip's read-retry levels; recoveries are added to
+    /// `read_retries`.
+    fn read_page_checked(
+        &mut self,
+        page: u32,
+        column: u16,
+        len: usize,
+        request: &ReadRequest,
+        soft_ecc: Option<&SoftEcc>,
+        read_retries: &mut Vec<ReadRetryRecovery>,
+    ) -> Result<(Vec<u8>, EccStatus)> {
+        let result = self.read_page_with_retries(page, column, len, request, soft_ecc);
+
+        let uncorrectable = matches!(
+            result,
+            Err(Error::EccError { .. }) | Ok((_, EccStatus::Uncorrectable))
+        );
+        if uncorrectable && request.ecc_policy().is_hardware() {
+            if let Some((data, status, level)) = self.read_retry_page(page, column, len)? {
+                read_retries.push(ReadRetryRecovery { page, level });
+                return Ok((data, status));
+            }
+        }
+        result
+    }
+
+    fn read_page_with_retries(
+        &mut self,
+        page: u32,
+        column: u16,
+        len: usize,
+        request: &ReadRequest,
+        soft_ecc: Option<&SoftEcc>,
+    ) -> Result<(Vec<u8>, EccStatus)> {
+        let mut attempts = 0;
+        loop {
+            let result = self
+                .read_page_internal(page, column, len)
+                .and_then(|mut data| {
+                    if let Some(ecc) = soft_ecc {
+                        return match self.correct_soft_ecc(
+                            ecc,
+                            page,
+                            &mut data,
+                            request.oob_mode,
+                        )? {
+                            EccStatus::Uncorrectable if !request.ignore_ecc_errors => {
+                                Err(Error::EccError {
+                                    address: page * self.spec.layout.page_size,
+                                })
+                            }
+                            status => Ok((data, status)),
+                        };
+                    }
+                    if !request.use_ecc {
+                        return Ok((data, EccStatus::NotAvailable));
+                    }
+                    match self.check_ecc_status(page) {
+                        Ok(status) => Ok((data, status)),
+                        Err(e @ Error::EccError { .. }) if request.ignore_ecc_errors => {
+                            log::debug!("Ignored ECC error: {}", e);
+                            Ok((data, EccStatus::Uncorrectable))
+                        }
+                        Err(e) => Err(e),
+                    }
+                });
+
+            match result {
+                Ok(page_data) => return Ok(page_data),
+                Err(e) if attempts < request.retry_count => {
+                    attempts += 1;
+                    log::warn!(
+                        "Read error on page {}, retrying (attempt {}): {}",
+                        page,
+                        attempts,
+                        e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<P: Programmer> FlashOperation for SpiNand<P> {
+    fn read(&mut self, request: ReadRequest, on_progress: &dyn Fn(Progress)) -> Result<Vec<u8>> {
+        Ok(self.read_with_report(request, on_progress)?.0)
+    }
+
+    fn read_with_report(
+        &mut self,
+        request: ReadRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<(Vec<u8>, ReadReport)> {
+        let page_size = self.spec.layout.page_size;
         let oob_size = self.spec.layout.oob_size.unwrap_or(0);
 
-        // Calculate write parameters based on OobMode
-        let (col_offset, write_len_per_page) = match request.oob_mode {
-            OobMode::None => (0u16, page_size as usize),
-            OobMode::Included => (0u16, (page_size + oob_size) as usize),
-            OobMode::Only => (page_size as u16, oob_size as usize),
+        // Software ECC reads raw pages with their OOB
+        let soft_ecc = self.soft_ecc(request.soft_ecc, request.oob_mode)?;
+        let policy = request.ecc_policy();
+        let use_ecc = policy.is_hardware();
+        self.set_ecc(use_ecc)?;
+
+        let start_addr = request.address.as_u32();
+        let start_page = start_addr / page_size;
+
+        let free_layout = self.free_oob_layout(request.oob_mode)?;
+
+        // Calculate read parameters based on OobMode, and how many bytes of
+        // each page read end up in the result
+        let (col_offset, read_len_per_page) = match request.oob_mode {
+            OobMode::None if soft_ecc.is_none() => (0u16, page_size as usize),
+            OobMode::None | OobMode::Included => (0u16, (page_size + oob_size) as usize),
+            OobMode::Only | OobMode::Free => (page_size as u16, oob_size as usize),
         };
+        let result_per_page = self.result_per_page(request.oob_mode)?;
 
-        let data_len = request.data.len();
-        let total_pages = data_len.div_ceil(write_len_per_page);
+        let total_pages = request.length.div_ceil(result_per_page.max(1));
+        let pages_per_block = self.spec.layout.block_size / page_size;
+        let mut result = Vec::with_capacity(request.length as usize);
+        let mut remaining = request.length as usize;
 
         let mut current_page = start_page;
-        let mut offset = 0usize;
-        let mut pages_written = 0;
+        let mut pages_read = 0;
+        let mut report = ReadReport::default();
+        report.ecc.strength = match &soft_ecc {
+            Some(ecc) => ecc.strength(),
+            None => self.spec.capabilities.ecc_strength,
+        };
 
-        while pages_written < total_pages {
-            let current_block = current_page / pages_per_block;
+        let mut bounds = self.partition_bounds(&request.partitions)?;
 
-            let is_bad = if let Some(ref bbt) = request.bbt {
-                match bbt.get_status(current_block as usize) {
-                    BlockStatus::Unknown => self.is_bad_block(current_block)?,
-                    BlockStatus::BadFactory | BlockStatus::BadRuntime => true,
-                    BlockStatus::Good => false,
-                }
-            } else {
-                self.is_bad_block(current_block)?
+        while pages_read < total_pages {
+            let Some(page) = self.bounded_page(&mut bounds, start_page + pages_read, current_page)
+            else {
+                // The blocks a partition lost to bad ones read as erased
+                let end_page = bounds.partition_end() * pages_per_block;
+                let pages = (end_page - start_page - pages_read).min(total_pages - pages_read);
+                let fill = (pages as usize * result_per_page as usize).min(remaining);
+                result.resize(result.len() + fill, 0xFF);
+                remaining -= fill;
+                pages_read += pages;
+                current_page = end_page;
+                continue;
             };
+            current_page = page;
+            let current_block = current_page / pages_per_block;
+
+            let is_bad = self.block_is_bad(current_block, &request.bbt)?;
 
             if request.bad_block_strategy != BadBlockStrategy::Include && is_bad {
                 match request.bad_block_strategy {
@@ -339,47 +886,223 @@ impl<P: Programmer> FlashOperation for SpiNand<P> {
                 }
             }
 
-            let chunk_end = (offset + write_len_per_page).min(data_len);
-            let mut page_buf = vec![0xFFu8; write_len_per_page];
-            page_buf[..(chunk_end - offset)].copy_from_slice(&request.data[offset..chunk_end]);
+            // Pages wanted from this block, read as one run
+            let run =
+                (pages_per_block - current_page % pages_per_block).min(total_pages - pages_read);
+            let mut cached = Vec::new();
+            if run > 1 {
+                match self.read_pages_cached(
+                    current_page,
+                    run,
+                    col_offset,
+                    read_len_per_page,
+                    use_ecc,
+                ) {
+                    Ok(pages) => cached = pages,
+                    Err(e) => log::warn!(
+                        "Cache read of pages {}-{} failed, reading page by page: {}",
+                        current_page,
+                        current_page + run - 1,
+                        e
+                    ),
+                }
+            }
+            let mut cached = cached.into_iter();
+
+            for _ in 0..run {
+                let cached_page = match cached.next() {
+                    Some(mut page) => {
+                        if let Some(ecc) = &soft_ecc {
+                            page.ecc = self.correct_soft_ecc(
+                                ecc,
+                                current_page,
+                                &mut page.data,
+                                request.oob_mode,
+                            )?;
+                        }
+                        Some(page)
+                    }
+                    None => None,
+                };
+
+                // Pages the cache read could not deliver cleanly go through
+                // the single page path with its retries and read retry
+                let read_retry = self.spec.capabilities.read_retry.is_some();
+                let (chunk, ecc) = match cached_page {
+                    Some(page)
+                        if page.ecc != EccStatus::Uncorrectable
+                            || (request.ignore_ecc_errors && !read_retry) =>
+                    {
+                        (page.data, page.ecc)
+                    }
+                    _ => self.read_page_checked(
+                        current_page,
+                        col_offset,
+                        read_len_per_page,
+                        &request,
+                        soft_ecc.as_ref(),
+                        &mut report.ecc.read_retries,
+                    )?,
+                };
+                if policy.is_enabled() {
+                    report.ecc.pages.push(PageEcc {
+                        page: current_page,
+                        status: ecc,
+                    });
+                }
+                let chunk = match &free_layout {
+                    Some(layout) => layout.extract_free(&chunk),
+                    None => chunk,
+                };
 
-            self.write_enable()?;
+                let to_copy = remaining.min(chunk.len());
+                result.extend_from_slice(&chunk[..to_copy]);
+                remaining -= to_copy;
+                pages_read += 1;
+                current_page += 1;
 
-            // Program Load
-            let col_addr = self.column_to_addr(col_offset);
-            self.programmer.set_cs(true)?;
-            self.programmer
-                .spi_write(&[CMD_NAND_PROGRAM_LOAD, col_addr[0], col_addr[1]])?;
-            self.programmer.spi_write(&page_buf)?;
-            self.programmer.set_cs(false)?;
+                on_progress(Progress::new(result.len() as u64, request.length as u64));
 
-            // Program Execute
-            let row_addr = self.page_to_row_addr(current_page);
-            self.programmer.set_cs(true)?;
-            self.programmer.spi_write(&[
-                CMD_NAND_PROGRAM_EXECUTE,
-                row_addr[0],
-                row_addr[1],
-                row_addr[2],
-            ])?;
-            self.programmer.set_cs(false)?;
+                if remaining == 0 {
+                    break;
+                }
+            }
 
-            self.wait_ready()?;
+            if remaining == 0 {
+                break;
+            }
+        }
 
-            let status = self.get_feature(FEATURE_STATUS)?;
-            if status & STATUS_NAND_P_FAIL != 0 {
-                return Err(Error::WriteFailed {
-                    address: current_page * page_size,
-                });
+        Ok((result, report))
+    }
+
+    /// Walk the range like [`read_with_report`](Self::read_with_report)
+    /// does, without reading it
+    fn read_segments(&mut self, request: &ReadRequest) -> Result<Vec<ReadSegment>> {
+        let page_size = self.spec.layout.page_size;
+        let pages_per_block = self.spec.layout.pages_per_block();
+        let result_per_page = self.result_per_page(request.oob_mode)?;
+        let start_addr = request.address.as_u32();
+        let start_page = start_addr / page_size;
+        let total_pages = request.length.div_ceil(result_per_page.max(1));
+
+        let mut segments = Vec::new();
+        let mut remaining = request.length;
+        let mut current_page = start_page;
+        let mut pages_read = 0;
+        let mut bounds = self.partition_bounds(&request.partitions)?;
+
+        while pages_read < total_pages {
+            let Some(page) = self.bounded_page(&mut bounds, start_page + pages_read, current_page)
+            else {
+                let end_page = bounds.partition_end() * pages_per_block;
+                let pages = (end_page - start_page - pages_read).min(total_pages - pages_read);
+                let length = (pages * result_per_page).min(remaining);
+                segments.push(ReadSegment::Erased { length });
+                remaining -= length;
+                pages_read += pages;
+                current_page = end_page;
+                continue;
+            };
+            current_page = page;
+            let current_block = current_page / pages_per_block;
+
+            if request.bad_block_strategy != BadBlockStrategy::Include
+                && self.block_is_bad(current_block, &request.bbt)?
+            {
+                match request.bad_block_strategy {
+                    BadBlockStrategy::Skip => {
+                        current_page = (current_block + 1) * pages_per_block;
+                        continue;
+                    }
+                    BadBlockStrategy::Fail => {
+                        return Err(Error::BadBlock {
+                            block: current_block,
+                        });
+                    }
+                    _ => {}
+                }
             }
 
-            on_progress(Progress::new(chunk_end as u64, data_len as u64));
+            let run =
+                (pages_per_block - current_page % pages_per_block).min(total_pages - pages_read);
+            let length = (run * result_per_page).min(remaining);
+            // The first page keeps the column the range starts at
+            let column = if pages_read == 0 {
+                start_addr % page_size
+            } else {
+                0
+            };
+            segments.push(ReadSegment::Read {
+                address: current_page * page_size + column,
+                length,
+            });
+            remaining -= length;
+            pages_read += run;
+            current_page += run;
+        }
+        Ok(segments)
+    }
 
-            offset += write_len_per_page;
-            pages_written += 1;
-            current_page += 1;
+    fn write(
+        &mut self,
+        request: WriteRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<WriteReport> {
+        let soft_ecc = self.soft_ecc(request.soft_ecc, request.oob_mode)?;
+        self.set_ecc(request.ecc_policy().is_hardware())?;
+
+        // Pages with OOB from the image get their ECC bytes replaced, so
+        // compare the read back against what was actually programmed
+        let encoded;
+        let request = match &soft_ecc {
+            Some(ecc) if request.oob_mode == OobMode::Included => {
+                encoded = self.soft_ecc_image(ecc, request.data)?;
+                WriteRequest {
+                    data: &encoded,
+                    ..request
+                }
+            }
+            _ => request,
+        };
+
+        if !request
+            .address
+            .as_u32()
+            .is_multiple_of(self.spec.layout.page_size)
+        {
+            return Err(Error::InvalidParameter(
+                "NAND write address must be page-aligned".to_string(),
+            ));
         }
 
+        // Blocks retired during the write are skipped by the read back too,
+        // even when their marker could not be written
+        let total_blocks = self.spec.capacity.as_bytes() / self.spec.layout.block_size;
+        let mut bbt = Some(
+            request
+                .bbt
+                .clone()
+                .unwrap_or_else(|| BadBlockTable::new(total_blocks as usize)),
+        );
+        let report = match request.mode {
+            WriteMode::Direct => WriteReport {
+                relocated_blocks: self.write_direct(
+                    &request,
+                    soft_ecc.as_ref(),
+                    &mut bbt,
+                    on_progress,
+                )?,
+                ..Default::default()
+            },
+            WriteMode::Delta => self.write_delta(&request, on_progress)?,
+            WriteMode::Patch => {
+                return Err(Error::NotSupported(
+                    "Patch mode is only supported for NOR flash".to_string(),
+                ));
+            }
+        };
+
         if request.verify {
             // Self-verify by reading back
             let verify_req = ReadRequest {
@@ -387,10 +1110,12 @@ impl<P: Programmer> FlashOperation for SpiNand<P> {
                 length: request.data.len() as u32,
                 use_ecc: request.use_ecc,
                 ignore_ecc_errors: request.ignore_ecc_errors,
+                soft_ecc: request.soft_ecc,
                 oob_mode: request.oob_mode,
                 bad_block_strategy: request.bad_block_strategy,
-                bbt: request.bbt.clone(),
+                bbt,
                 retry_count: request.retry_count,
+                partitions: request.partitions.clone(),
             };
             let read_back = self.read(verify_req, &|_| {})?;
             if read_back != request.data {
@@ -408,12 +1133,19 @@ impl<P: Programmer> FlashOperation for SpiNand<P> {
             }
         }
 
-        Ok(())
+        Ok(report)
     }
 
     fn erase(&mut self, request: EraseRequest, on_progress: &dyn Fn(Progress)) -> Result<()> {
+        self.erase_with_report(request, on_progress).map(|_| ())
+    }
+
+    fn erase_with_report(
+        &mut self,
+        request: EraseRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<EraseReport> {
         let block_size = self.spec.layout.block_size;
-        let page_size = self.spec.layout.page_size;
 
         let start_addr = request.address.as_u32();
         if !start_addr.is_multiple_of(block_size) {
@@ -427,17 +1159,22 @@ impl<P: Programmer> FlashOperation for SpiNand<P> {
 
         let mut blocks_erased = 0;
         let mut current_block = start_block;
+        let mut bounds = self.partition_bounds(&request.partitions)?;
+        let mut report = EraseReport {
+            bbt: request.bbt,
+            ..Default::default()
+        };
 
         while blocks_erased < total_blocks {
-            let is_bad = if let Some(ref bbt) = request.bbt {
-                match bbt.get_status(current_block as usize) {
-                    BlockStatus::Unknown => self.is_bad_block(current_block)?,
-                    BlockStatus::BadFactory | BlockStatus::BadRuntime => true,
-                    BlockStatus::Good => false,
-                }
-            } else {
-                self.is_bad_block(current_block)?
+            let Some(block) = bounds.place(start_block + blocks_erased, current_block) else {
+                // All good blocks of the partition are erased, go on with the next
+                let end = bounds.partition_end();
+                blocks_erased = (end - start_block).min(total_blocks);
+                current_block = end;
+                continue;
             };
+            current_block = block;
+            let is_bad = self.block_is_bad(current_block, &report.bbt)?;
 
             if request.bad_block_strategy != BadBlockStrategy::Include && is_bad {
                 match request.bad_block_strategy {
@@ -456,27 +1193,20 @@ impl<P: Programmer> FlashOperation for SpiNand<P> {
                 }
             }
 
-            let page = current_block * (block_size / page_size);
-
-            self.write_enable()?;
-
-            let row_addr = self.page_to_row_addr(page);
-            self.programmer.set_cs(true)?;
-            self.programmer.spi_write(&[
-                CMD_NAND_BLOCK_ERASE,
-                row_addr[0],
-                row_addr[1],
-                row_addr[2],
-            ])?;
-            self.programmer.set_cs(false)?;
-
-            self.wait_ready()?;
-
-            let status = self.get_feature(FEATURE_STATUS)?;
-            if status & STATUS_NAND_E_FAIL != 0 {
-                return Err(Error::EraseFailed {
-                    block: current_block,
-                });
+            match self.erase_block(current_block) {
+                Err(Error::EraseFailed { block }) if request.mark_bad => {
+                    log::warn!("Erase of block {} failed, marking it bad", block);
+                    let use_ecc = self.ecc_enabled()?;
+                    self.retire_block(block, &mut report.bbt, use_ecc)?;
+                    report.retired_blocks.push(block);
+                    if request.bad_block_strategy != BadBlockStrategy::Skip {
+                        return Err(Error::EraseFailed { block });
+                    }
+                    // Erase the next good block in its place
+                    current_block += 1;
+                    continue;
+                }
+                result => result?,
             }
 
             blocks_erased += 1;
@@ -485,7 +1215,7 @@ impl<P: Programmer> FlashOperation for SpiNand<P> {
             on_progress(Progress::new(blocks_erased as u64, total_blocks as u64));
         }
 
-        Ok(())
+        Ok(report)
     }
 
     fn scan_bbt(&mut self, on_progress: &dyn Fn(Progress)) -> Result<BadBlockTable> {
@@ -510,8 +1240,44 @@ impl<P: Programmer> FlashOperation for SpiNand<P> {
         Ok(bbt)
     }
 
+    fn mark_bad_block(&mut self, block: u32) -> Result<()> {
+        // The marker is written raw; leave the ECC setting as it was
+        let use_ecc = self.ecc_enabled()?;
+        let marked = self.write_bad_block_marker(block);
+        self.set_ecc(use_ecc)?;
+        marked
+    }
+
+    fn unmark_bad_block(&mut self, block: u32) -> Result<()> {
+        self.clear_bad_block_marker(block)
+    }
+
+    fn otp_read(&mut self, region: u32, offset: u32, length: u32) -> Result<Vec<u8>> {
+        self.read_otp(region, offset, length)
+    }
+
+    fn otp_program(&mut self, region: u32, offset: u32, data: &[u8]) -> Result<()> {
+        self.program_otp(region, offset, data)
+    }
+
+    fn otp_erase(&mut self, _region: u32) -> Result<()> {
+        Err(Error::NotSupported(
+            "SPI NAND OTP pages cannot be erased".to_string(),
+        ))
+    }
+
+    fn otp_lock(&mut self, region: u32) -> Result<()> {
+        self.lock_otp(region)
+    }
+
+    fn otp_is_locked(&mut self, _region: u32) -> Result<bool> {
+        self.otp_locked()
+    }
+
     fn get_status(&mut self) -> Result<Vec<u8>> {
         // Return Protection (0xA0), Config (0xB0), and Status (0xC0) as a 3-byte vector
+        // (of die 0 on multi-die packages)
+        self.select_die(0)?;
         let prot = self.get_feature(FEATURE_PROTECTION)?;
         let conf = self.get_feature(FEATURE_CONFIG)?;
         let stat = self.get_feature(FEATURE_STATUS)?;
@@ -526,13 +1292,17 @@ impl<P: Programmer> FlashOperation for SpiNand<P> {
         // status[0] -> Protection (0xA0)
         // status[1] -> Config (0xB0)
         // status[2] -> Status (0xC0) - though Status is usually read-only, some chips allow it
+        // Multi-die packages get the same values on every die.
 
-        self.set_feature(FEATURE_PROTECTION, status[0])?;
-        if status.len() > 1 {
-            self.set_feature(FEATURE_CONFIG, status[1])?;
-        }
-        if status.len() > 2 {
-            self.set_feature(FEATURE_STATUS, status[2])?;
+        for die in 0..self.spec.layout.die_count.max(1) {
+            self.select_die(die)?;
+            self.set_feature(FEATURE_PROTECTION, status[0])?;
+            if status.len() > 1 {
+                self.set_feature(FEATURE_CONFIG, status[1])?;
+            }
+            if status.len() > 2 {
+                self.set_feature(FEATURE_STATUS, status[2])?;
+            }
         }
         Ok(())
     }
@@ -0,0 +1,84 @@
+//! SPI NAND Dual/Quad I/O
+//!
+//! Cache reads use the x2 (0x3B) or x4 (0x6B) read-from-cache commands and
+//! program loads the x4 (0x32) form when the chip and the programmer both
+//! support the wider bus. GigaDevice parts gate quad transfers behind the QE
+//! bit in the configuration feature.
+
+use super::SpiNand;
+use crate::domain::chip::QuadEnableMethod;
+use crate::error::Result;
+use crate::infrastructure::flash_protocol::commands::*;
+use crate::infrastructure::programmer::{BusWidth, MultiIoCommand, Programmer};
+
+/// Dummy cycles of the read-from-cache commands
+const READ_CACHE_DUMMY_CYCLES: u8 = 8;
+
+impl<P: Programmer> SpiNand<P> {
+    /// Widest bus supported by both the chip and the programmer
+    pub fn data_width(&self) -> BusWidth {
+        let chip = if self.spec.capabilities.supports_quad_spi {
+            BusWidth::Quad
+        } else if self.spec.capabilities.supports_dual_spi {
+            BusWidth::Dual
+        } else {
+            BusWidth::Single
+        };
+        chip.min(self.programmer.max_bus_width())
+    }
+
+    /// Set the QE bit if the chip has one
+    pub fn enable_quad(&mut self) -> Result<()> {
+        if self.quad_enabled {
+            return Ok(());
+        }
+        if self.spec.capabilities.quad_enable == QuadEnableMethod::ConfigBit0 {
+            let config = self.get_feature(FEATURE_CONFIG)?;
+            if config & CONFIG_QUAD_ENABLE == 0 {
+                self.set_feature(FEATURE_CONFIG, config | CONFIG_QUAD_ENABLE)?;
+            }
+        }
+        self.quad_enabled = true;
+        Ok(())
+    }
+
+    /// Read `len` bytes from the cache at `column` on `width` data lines
+    pub(super) fn read_cache_multi_io(
+        &mut self,
+        column: u16,
+        len: usize,
+        width: BusWidth,
+    ) -> Result<Vec<u8>> {
+        let opcode = match width {
+            BusWidth::Quad => {
+                self.enable_quad()?;
+                CMD_NAND_READ_CACHE_X4
+            }
+            _ => CMD_NAND_READ_CACHE_X2,
+        };
+        let address = self.column_to_addr(column);
+        let command = MultiIoCommand {
+            opcode,
+            opcode_width: BusWidth::Single,
+            address: &address,
+            address_width: BusWidth::Single,
+            dummy_cycles: READ_CACHE_DUMMY_CYCLES,
+            data_width: width,
+        };
+        self.programmer.spi_multi_io_read(&command, len)
+    }
+
+    /// Quad program load of `data` into the cache at `column`
+    pub(super) fn program_load_quad(&mut self, column: u16, data: &[u8]) -> Result<()> {
+        let address = self.column_to_addr(column);
+        let command = MultiIoCommand {
+            opcode: CMD_NAND_PROGRAM_LOAD_X4,
+            opcode_width: BusWidth::Single,
+            address: &address,
+            address_width: BusWidth::Single,
+            dummy_cycles: 0,
+            data_width: BusWidth::Quad,
+        };
+        self.programmer.spi_multi_io_write(&command, data)
+    }
+}
@@ -0,0 +1,158 @@
+//! SPI NAND OTP Area Access
+//!
+//! Setting OTP_EN in the configuration feature (0xB0) maps the OTP area over
+//! the normal page address space. Page 0 holds the unique ID, page 1 the
+//! parameter page and the following pages are user OTP. Setting OTP_PRT
+//! together with OTP_EN and issuing Program Execute permanently locks the
+//! whole OTP area.
+
+use super::parameter_page::{ParameterPage, PARAMETER_PAGE_COPIES, PARAMETER_PAGE_SIZE};
+use super::SpiNand;
+use crate::domain::chip::OtpLayout;
+use crate::error::{Error, Result};
+use crate::infrastructure::flash_protocol::commands::*;
+use crate::infrastructure::programmer::Programmer;
+
+/// OTP page holding the factory unique ID
+const UNIQUE_ID_PAGE: u32 = 0;
+
+/// Length of the unique ID; each copy is followed by its complement
+const UNIQUE_ID_SIZE: usize = 16;
+
+/// Number of ID/complement copies stored in the unique ID page
+const UNIQUE_ID_COPIES: usize = 16;
+
+/// OTP page holding the ONFI parameter page
+const PARAMETER_PAGE: u32 = 1;
+
+/// First user OTP page
+const FIRST_USER_OTP_PAGE: u32 = 2;
+
+impl<P: Programmer> SpiNand<P> {
+    /// Run `f` with OTP_EN set, restoring the configuration afterwards.
+    /// Multi-die packages use the OTP area of die 0.
+    fn with_otp_mode<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.select_die(0)?;
+        let config = self.get_feature(FEATURE_CONFIG)?;
+        self.set_feature(FEATURE_CONFIG, config | CONFIG_OTP_ENABLE)?;
+        let result = f(self);
+        self.set_feature(FEATURE_CONFIG, config)?;
+        result
+    }
+
+    fn otp_layout(&self) -> Result<OtpLayout> {
+        self.spec
+            .otp
+            .ok_or_else(|| Error::NotSupported(format!("{} has no user OTP pages", self.spec.name)))
+    }
+
+    /// Validate a region/range and return the OTP page backing `region`
+    fn otp_page(&self, region: u32, offset: u32, length: u32) -> Result<u32> {
+        let layout = self.otp_layout()?;
+        if region >= layout.region_count {
+            return Err(Error::InvalidParameter(format!(
+                "OTP region {} out of range (chip has {})",
+                region, layout.region_count
+            )));
+        }
+        if offset as u64 + length as u64 > layout.region_size as u64 {
+            return Err(Error::InvalidParameter(format!(
+                "OTP access past the end of the {}-byte region",
+                layout.region_size
+            )));
+        }
+        Ok(FIRST_USER_OTP_PAGE + region)
+    }
+
+    pub(super) fn read_otp(&mut self, region: u32, offset: u32, length: u32) -> Result<Vec<u8>> {
+        let page = self.otp_page(region, offset, length)?;
+        self.with_otp_mode(|nand| nand.read_page_internal(page, offset as u16, length as usize))
+    }
+
+    pub(super) fn program_otp(&mut self, region: u32, offset: u32, data: &[u8]) -> Result<()> {
+        let page = self.otp_page(region, offset, data.len() as u32)?;
+        if self.otp_locked()? {
+            return Err(Error::InvalidParameter("OTP area is locked".to_string()));
+        }
+        self.with_otp_mode(|nand| nand.program_page(page, offset as u16, data))
+    }
+
+    /// Lock the whole OTP area (SPI NAND can't lock individual pages)
+    pub(super) fn lock_otp(&mut self, region: u32) -> Result<()> {
+        self.otp_page(region, 0, 0)?;
+
+        self.select_die(0)?;
+        let config = self.get_feature(FEATURE_CONFIG)?;
+        self.set_feature(
+            FEATURE_CONFIG,
+            config | CONFIG_OTP_ENABLE | CONFIG_OTP_PROTECT,
+        )?;
+
+        let result = self.write_enable().and_then(|_| {
+            let row_addr = self.select_page(0)?;
+            self.programmer.spi_transaction_write(&[
+                CMD_NAND_PROGRAM_EXECUTE,
+                row_addr[0],
+                row_addr[1],
+                row_addr[2],
+            ])?;
+            self.wait_ready()
+        });
+
+        self.set_feature(FEATURE_CONFIG, config)?;
+        result
+    }
+
+    pub(super) fn otp_locked(&mut self) -> Result<bool> {
+        self.select_die(0)?;
+        Ok(self.get_feature(FEATURE_CONFIG)? & CONFIG_OTP_PROTECT != 0)
+    }
+
+    /// Read the factory unique ID
+    ///
+    /// Winbond, Micron and GigaDevice store 16 copies of a 16-byte ID, each
+    /// followed by its bitwise complement; the first consistent copy is used.
+    pub fn read_unique_id(&mut self) -> Result<Vec<u8>> {
+        if !matches!(self.spec.jedec_id.manufacturer, 0xEF | 0x2C | 0xC8) {
+            return Err(Error::NotSupported(format!(
+                "Unique ID read not supported for {} SPI NAND",
+                self.spec.manufacturer
+            )));
+        }
+
+        let data = self.with_otp_mode(|nand| {
+            nand.read_page_internal(UNIQUE_ID_PAGE, 0, UNIQUE_ID_SIZE * 2 * UNIQUE_ID_COPIES)
+        })?;
+
+        data.chunks_exact(UNIQUE_ID_SIZE * 2)
+            .find(|copy| {
+                let (id, complement) = copy.split_at(UNIQUE_ID_SIZE);
+                id.iter().zip(complement).all(|(a, b)| a ^ b == 0xFF)
+            })
+            .map(|copy| copy[..UNIQUE_ID_SIZE].to_vec())
+            .ok_or_else(|| Error::Other("No valid unique ID copy found".to_string()))
+    }
+
+    /// Read and decode the ONFI parameter page
+    pub fn read_parameter_page(&mut self) -> Result<ParameterPage> {
+        let data = self.with_otp_mode(|nand| {
+            nand.read_page_internal(
+                PARAMETER_PAGE,
+                0,
+                PARAMETER_PAGE_SIZE * PARAMETER_PAGE_COPIES,
+            )
+        })?;
+        ParameterPage::parse(&data)
+    }
+
+    /// Read the parameter page and warn about any disagreement with the
+    /// database entry. Returns the decoded page and the list of mismatches.
+    pub fn check_parameter_page(&mut self) -> Result<(ParameterPage, Vec<String>)> {
+        let page = self.read_parameter_page()?;
+        let mismatches = page.mismatches(&self.spec);
+        for issue in &mismatches {
+            log::warn!("{} parameter page mismatch: {}", self.spec.name, issue);
+        }
+        Ok((page, mismatches))
+    }
+}
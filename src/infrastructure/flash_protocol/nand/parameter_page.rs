@@ -0,0 +1,204 @@
+//! ONFI-style Parameter Page
+//!
+//! SPI NAND parts from Winbond, Micron, GigaDevice and others expose an
+//! ONFI-formatted parameter page in their OTP area (page 1, with OTP_EN set).
+//! It is stored as several redundant 256-byte copies, each protected by a
+//! CRC-16; the first copy with a valid CRC is used.
+
+use crate::domain::chip::ChipSpec;
+use crate::error::{Error, Result};
+
+/// Size of one parameter page copy
+pub const PARAMETER_PAGE_SIZE: usize = 256;
+
+/// Number of redundant copies read from the chip
+pub const PARAMETER_PAGE_COPIES: usize = 3;
+
+/// Decoded geometry and requirements from the parameter page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterPage {
+    pub manufacturer: String,
+    pub model: String,
+    pub page_size: u32,
+    pub oob_size: u32,
+    pub pages_per_block: u32,
+    pub blocks_per_lun: u32,
+    pub lun_count: u32,
+    /// Number of bits ECC must be able to correct (per codeword)
+    pub ecc_bits: u8,
+}
+
+impl ParameterPage {
+    /// Parse the first copy with a valid signature and CRC
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        data.chunks_exact(PARAMETER_PAGE_SIZE)
+            .find(|copy| &copy[0..4] == b"ONFI" && crc16(&copy[..254]) == le16(copy, 254))
+            .map(Self::decode)
+            .ok_or_else(|| Error::Other("No valid ONFI parameter page found".to_string()))
+    }
+
+    fn decode(p: &[u8]) -> Self {
+        let text =
+            |range: std::ops::Range<usize>| String::from_utf8_lossy(&p[range]).trim().to_string();
+
+        Self {
+            manufacturer: text(32..44),
+            model: text(44..64),
+            page_size: le32(p, 80),
+            oob_size: le16(p, 84) as u32,
+            pages_per_block: le32(p, 92),
+            blocks_per_lun: le32(p, 96),
+            lun_count: p[100] as u32,
+            ecc_bits: p[112],
+        }
+    }
+
+    pub fn block_size(&self) -> u32 {
+        self.page_size * self.pages_per_block
+    }
+
+    pub fn capacity_bytes(&self) -> u64 {
+        self.block_size() as u64 * self.blocks_per_lun as u64 * self.lun_count as u64
+    }
+
+    /// Describe every field that disagrees with the database entry
+    pub fn mismatches(&self, spec: &ChipSpec) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        if self.page_size != spec.layout.page_size {
+            issues.push(format!(
+                "page size: chip reports {}, database has {}",
+                self.page_size, spec.layout.page_size
+            ));
+        }
+        if Some(self.oob_size) != spec.layout.oob_size {
+            issues.push(format!(
+                "OOB size: chip reports {}, database has {}",
+                self.oob_size,
+                spec.layout.oob_size.unwrap_or(0)
+            ));
+        }
+        if self.block_size() != spec.layout.block_size {
+            issues.push(format!(
+                "block size: chip reports {}, database has {}",
+                self.block_size(),
+                spec.layout.block_size
+            ));
+        }
+        if self.capacity_bytes() != spec.capacity.as_bytes() as u64 {
+            issues.push(format!(
+                "capacity: chip reports {} bytes, database has {}",
+                self.capacity_bytes(),
+                spec.capacity.as_bytes()
+            ));
+        }
+
+        issues
+    }
+}
+
+/// ONFI CRC-16 (polynomial 0x8005, initial value 0x4F4E, MSB first)
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0x4F4E;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn le16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn le32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::chip::{ChipCapabilities, ChipLayout};
+    use crate::domain::types::{Capacity, FlashType, JedecId};
+
+    /// Parameter page for a 1Gbit part: 2048+64 pages, 64 pages/block, 1024 blocks
+    fn sample_page() -> Vec<u8> {
+        let mut p = vec![0u8; PARAMETER_PAGE_SIZE];
+        p[0..4].copy_from_slice(b"ONFI");
+        p[32..44].copy_from_slice(b"WINBOND     ");
+        p[44..64].copy_from_slice(b"W25N01GV            ");
+        p[80..84].copy_from_slice(&2048u32.to_le_bytes());
+        p[84..86].copy_from_slice(&64u16.to_le_bytes());
+        p[92..96].copy_from_slice(&64u32.to_le_bytes());
+        p[96..100].copy_from_slice(&1024u32.to_le_bytes());
+        p[100] = 1;
+        p[112] = 1;
+        let crc = crc16(&p[..254]);
+        p[254..256].copy_from_slice(&crc.to_le_bytes());
+        p
+    }
+
+    fn sample_spec(oob_size: u32) -> ChipSpec {
+        ChipSpec {
+            name: "W25N01GV".to_string(),
+            manufacturer: "Winbond".to_string(),
+            jedec_id: JedecId::new([0xEF, 0xAA, 0x21]),
+            flash_type: FlashType::Nand,
+            capacity: Capacity::gigabits(1),
+            layout: ChipLayout {
+                page_size: 2048,
+                block_size: 128 * 1024,
+                oob_size: Some(oob_size),
+                is_dataflash: false,
+                die_count: 1,
+                oob_layout: None,
+            },
+            capabilities: ChipCapabilities::default(),
+            otp: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_parameter_page() {
+        let page = ParameterPage::parse(&sample_page()).unwrap();
+        assert_eq!(page.manufacturer, "WINBOND");
+        assert_eq!(page.model, "W25N01GV");
+        assert_eq!(page.page_size, 2048);
+        assert_eq!(page.oob_size, 64);
+        assert_eq!(page.block_size(), 128 * 1024);
+        assert_eq!(page.capacity_bytes(), 128 * 1024 * 1024);
+        assert_eq!(page.ecc_bits, 1);
+    }
+
+    #[test]
+    fn test_parse_falls_back_to_redundant_copy() {
+        let mut data = sample_page();
+        data[80] ^= 0xFF; // Corrupt the first copy
+        data.extend_from_slice(&sample_page());
+        assert_eq!(ParameterPage::parse(&data).unwrap().page_size, 2048);
+
+        data.truncate(PARAMETER_PAGE_SIZE);
+        assert!(ParameterPage::parse(&data).is_err());
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let page = ParameterPage::parse(&sample_page()).unwrap();
+        assert!(page.mismatches(&sample_spec(64)).is_empty());
+
+        let issues = page.mismatches(&sample_spec(128));
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].starts_with("OOB size"));
+    }
+}
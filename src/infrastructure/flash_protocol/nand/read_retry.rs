@@ -0,0 +1,71 @@
+//! SPI NAND Read Retry
+//!
+//! Worn cells leak charge, so their threshold voltages drift until the
+//! default read references land in the wrong place and the page comes back
+//! uncorrectable. Chips with read retry ([`ReadRetry`]) take a level number
+//! in a vendor feature register that shifts the read references; stepping
+//! through the levels often finds one at which the page decodes again.
+//! Level 0 is the default and is always restored afterwards.
+//!
+//! [`ReadRetry`]: crate::domain::chip::ReadRetry
+
+use super::SpiNand;
+use crate::domain::chip::ReadRetry;
+use crate::domain::EccStatus;
+use crate::error::Result;
+use crate::infrastructure::programmer::Programmer;
+
+impl<P: Programmer> SpiNand<P> {
+    /// Re-read an uncorrectable page at each read-retry level: the data,
+    /// its ECC outcome and the level of the first read that decoded.
+    /// `None` when the chip has no read retry or no level helped.
+    pub(super) fn read_retry_page(
+        &mut self,
+        page: u32,
+        column: u16,
+        len: usize,
+    ) -> Result<Option<(Vec<u8>, EccStatus, u8)>> {
+        let Some(retry) = self.spec.capabilities.read_retry else {
+            return Ok(None);
+        };
+
+        let result = self.try_retry_levels(retry, page, column, len);
+        // Leave the chip at the default level even when a read failed
+        let restored = self.set_feature(retry.feature, 0);
+        let recovered = result?;
+        restored?;
+        Ok(recovered)
+    }
+
+    fn try_retry_levels(
+        &mut self,
+        retry: ReadRetry,
+        page: u32,
+        column: u16,
+        len: usize,
+    ) -> Result<Option<(Vec<u8>, EccStatus, u8)>> {
+        for level in 1..=retry.levels {
+            self.set_feature(retry.feature, level)?;
+            let data = self.read_page_internal(page, column, len)?;
+            match self.read_ecc_status()? {
+                EccStatus::Uncorrectable => {
+                    log::debug!(
+                        "Page {} still uncorrectable at read-retry level {}",
+                        page,
+                        level
+                    );
+                }
+                status => {
+                    log::info!("Recovered page {} at read-retry level {}", page, level);
+                    return Ok(Some((data, status, level)));
+                }
+            }
+        }
+        log::error!(
+            "Page {} stayed uncorrectable through {} read-retry levels",
+            page,
+            retry.levels
+        );
+        Ok(None)
+    }
+}
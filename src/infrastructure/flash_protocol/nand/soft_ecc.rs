@@ -0,0 +1,71 @@
+//! SPI NAND Software ECC
+//!
+//! For parts without usable on-die ECC, or images laid out for a host
+//! controller, pages are accessed raw (on-die ECC off) and protected by
+//! Hamming or BCH codes computed on the host and stored in the OOB area,
+//! compatible with Linux `nand_ecc`/`nand_bch`.
+
+use super::SpiNand;
+use crate::domain::{EccStatus, OobMode, SoftEcc, SoftEccConfig};
+use crate::error::{Error, Result};
+use crate::infrastructure::programmer::Programmer;
+
+impl<P: Programmer> SpiNand<P> {
+    /// Set up the software ECC of a request, checking that its layout fits
+    /// this chip
+    pub(super) fn soft_ecc(
+        &self,
+        config: Option<SoftEccConfig>,
+        oob_mode: OobMode,
+    ) -> Result<Option<SoftEcc>> {
+        let Some(config) = config else {
+            return Ok(None);
+        };
+        if matches!(oob_mode, OobMode::Only | OobMode::Free) {
+            return Err(Error::InvalidParameter(
+                "Software ECC needs the page data, not only OOB".to_string(),
+            ));
+        }
+
+        let ecc = SoftEcc::new(config)?;
+        ecc.ecc_range(
+            self.spec.layout.page_size as usize,
+            self.spec.layout.oob_size.unwrap_or(0) as usize,
+        )?;
+        Ok(Some(ecc))
+    }
+
+    /// Correct a raw page (data + OOB) in place, dropping the OOB area
+    /// unless the request wants it
+    pub(super) fn correct_soft_ecc(
+        &self,
+        ecc: &SoftEcc,
+        page: u32,
+        raw: &mut Vec<u8>,
+        oob_mode: OobMode,
+    ) -> Result<EccStatus> {
+        let page_size = self.spec.layout.page_size as usize;
+        let status = ecc.decode_page(raw, page_size)?;
+        Self::log_ecc_status(page, status);
+        if oob_mode == OobMode::None {
+            raw.truncate(page_size);
+        }
+        Ok(status)
+    }
+
+    /// `data` (pages with their OOB) with the ECC bytes filled in, as it
+    /// will read back
+    pub(super) fn soft_ecc_image(&self, ecc: &SoftEcc, data: &[u8]) -> Result<Vec<u8>> {
+        let page_size = self.spec.layout.page_size as usize;
+        let raw_size = page_size + self.spec.layout.oob_size.unwrap_or(0) as usize;
+
+        let mut image = Vec::with_capacity(data.len());
+        for chunk in data.chunks(raw_size) {
+            let mut raw = chunk.to_vec();
+            raw.resize(raw_size, 0xFF);
+            ecc.encode_page(&mut raw, page_size)?;
+            image.extend_from_slice(&raw[..chunk.len()]);
+        }
+        Ok(image)
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! These tests verify the NAND protocol implementation using mock programmers.
 
-use crate::domain::chip::{ChipCapabilities, ChipLayout, ChipSpec};
-use crate::domain::types::{Capacity, FlashType, JedecId};
+use crate::domain::bad_block::{BadBlockStrategy, BadBlockTable, BlockStatus};
+use crate::domain::chip::{
+    ChipCapabilities, ChipLayout, ChipSpec, EccStatusMethod, NandCacheRead, OtpLayout,
+};
+use crate::domain::types::{Address, Capacity, FlashType, JedecId};
+use crate::domain::{EccStatus, FlashOperation, OobMode, PageEcc, ReadRequest};
 use crate::infrastructure::flash_protocol::nand::SpiNand;
 use crate::infrastructure::programmer::mock::MockProgrammer;
 use crate::infrastructure::programmer::Programmer; // Import trait for method access
@@ -21,6 +25,8 @@ fn create_test_nand_spec() -> ChipSpec {
             block_size: 128 * 1024, // 128KB (64 pages per block)
             oob_size: Some(64),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities {
             supports_ecc_control: true,
@@ -84,3 +90,273 @@ fn test_mock_programmer_max_bulk_size() {
 //
 // These would be better suited for integration tests with actual hardware
 // or a more sophisticated mock framework.
+
+#[test]
+fn test_nand_otp_read_toggles_otp_enable() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_nand_spec();
+    spec.otp = Some(OtpLayout {
+        region_count: 8,
+        region_size: 2048,
+        enter_opcode: 0x00,
+        exit_opcode: 0x00,
+    });
+
+    mock.expect_reads(vec![
+        vec![0xFF; 2],    // GET FEATURE 0xB0 command
+        vec![0x10],       // config: ECC enabled
+        vec![0xFF; 3],    // SET FEATURE 0xB0
+        vec![0xFF; 4],    // PAGE READ
+        vec![0xFF; 2],    // GET FEATURE 0xC0 command
+        vec![0x00],       // status: ready
+        vec![0xFF; 4],    // READ CACHE command
+        vec![0xAA, 0x55], // data
+        vec![0xFF; 3],    // SET FEATURE 0xB0 (restore)
+    ]);
+
+    let mut nand = SpiNand::new(mock, spec);
+    assert_eq!(nand.otp_read(0, 4, 2).unwrap(), vec![0xAA, 0x55]);
+
+    let writes = nand.programmer_mut().get_writes();
+    assert!(writes.contains(&vec![0x1F, 0xB0, 0x50]));
+    // User OTP region 0 is OTP page 2
+    assert!(writes.contains(&vec![0x13, 0x00, 0x00, 0x02]));
+    assert_eq!(writes.last().unwrap(), &vec![0x1F, 0xB0, 0x10]);
+
+    assert!(nand.otp_read(8, 0, 1).is_err());
+}
+
+#[test]
+fn test_nand_unique_id_skips_corrupt_copy() {
+    let mock = MockProgrammer::new();
+    let id: Vec<u8> = (0..16).collect();
+
+    // First copy has a bad complement, second copy is valid
+    let mut page = vec![0u8; 32];
+    page.extend(id.iter().copied());
+    page.extend(id.iter().map(|b| !b));
+    page.resize(512, 0xFF);
+
+    mock.expect_reads(vec![
+        vec![0xFF; 2], // GET FEATURE 0xB0 command
+        vec![0x10],    // config: ECC enabled
+        vec![0xFF; 3], // SET FEATURE 0xB0
+        vec![0xFF; 4], // PAGE READ
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x00],    // status: ready
+        vec![0xFF; 4], // READ CACHE command
+        page,
+        vec![0xFF; 3], // SET FEATURE 0xB0 (restore)
+    ]);
+
+    let mut nand = SpiNand::new(mock, create_test_nand_spec());
+    assert_eq!(nand.read_unique_id().unwrap(), id);
+
+    // Unique ID lives in OTP page 0
+    let writes = nand.programmer_mut().get_writes();
+    assert!(writes.contains(&vec![0x13, 0x00, 0x00, 0x00]));
+}
+
+#[test]
+fn test_nand_sequential_cache_read_rereads_ecc_failures() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_nand_spec();
+    spec.capabilities.cache_read = NandCacheRead::Sequential;
+
+    mock.expect_reads(vec![
+        vec![0xFF; 2], // GET FEATURE 0xB0 command
+        vec![0x10],    // config: ECC enabled
+        vec![0xFF; 3], // SET FEATURE 0xB0
+        vec![0xFF; 4], // PAGE READ page 0
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x00],    // status: ready
+        vec![0xFF],    // CACHE READ SEQUENTIAL
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x00],    // status: ready
+        vec![0xFF; 4], // READ CACHE command
+        vec![0x11; 2048],
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x00],    // ECC clean
+        vec![0xFF],    // CACHE READ END
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x00],    // status: ready
+        vec![0xFF; 4], // READ CACHE command
+        vec![0x00; 2048],
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x30],    // ECC uncorrectable
+        vec![0xFF; 4], // PAGE READ page 1 (re-read)
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x00],    // status: ready
+        vec![0xFF; 4], // READ CACHE command
+        vec![0x22; 2048],
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x10],    // ECC corrected
+    ]);
+
+    let mut bbt = BadBlockTable::new(1024);
+    bbt.set_status(0, BlockStatus::Good);
+    let request = ReadRequest {
+        address: Address::new(0),
+        length: 4096,
+        use_ecc: true,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: Some(bbt),
+        retry_count: 0,
+        partitions: Vec::new(),
+    };
+
+    let mut nand = SpiNand::new(mock, spec);
+    let data = nand.read(request, &|_| {}).unwrap();
+    assert_eq!(&data[..2048], &[0x11; 2048][..]);
+    assert_eq!(&data[2048..], &[0x22; 2048][..]);
+
+    let writes = nand.programmer_mut().get_writes();
+    assert!(writes.contains(&vec![0x31]));
+    assert!(writes.contains(&vec![0x3F]));
+    assert!(writes.contains(&vec![0x13, 0x00, 0x00, 0x01]));
+}
+
+#[test]
+fn test_nand_read_report_macronix_bit_flips() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_nand_spec();
+    spec.capabilities.ecc_strength = 4;
+    spec.capabilities.ecc_status = EccStatusMethod::MacronixEccsr;
+
+    mock.expect_reads(vec![
+        vec![0xFF; 2], // GET FEATURE 0xB0 command
+        vec![0x10],    // config: ECC enabled
+        vec![0xFF; 3], // SET FEATURE 0xB0
+        vec![0xFF; 4], // PAGE READ page 0
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x00],    // status: ready
+        vec![0xFF; 4], // READ CACHE command
+        vec![0x11; 2048],
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x10],    // ECC corrected
+        vec![0xFF; 2], // GET ECCSR command
+        vec![0x03],    // 3 bit flips
+        vec![0xFF; 4], // PAGE READ page 1
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x00],    // status: ready
+        vec![0xFF; 4], // READ CACHE command
+        vec![0x22; 2048],
+        vec![0xFF; 2], // GET FEATURE 0xC0 command
+        vec![0x00],    // ECC clean
+    ]);
+
+    let mut bbt = BadBlockTable::new(1024);
+    bbt.set_status(0, BlockStatus::Good);
+    let request = ReadRequest {
+        address: Address::new(0),
+        length: 4096,
+        use_ecc: true,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: Some(bbt),
+        retry_count: 0,
+        partitions: Vec::new(),
+    };
+
+    let mut nand = SpiNand::new(mock, spec);
+    let (data, report) = nand.read_with_report(request, &|_| {}).unwrap();
+    assert_eq!(data.len(), 4096);
+    assert_eq!(report.ecc.strength, 4);
+    assert_eq!(
+        report.ecc.pages,
+        vec![
+            PageEcc {
+                page: 0,
+                status: EccStatus::Corrected { bit_flips: 3 },
+            },
+            PageEcc {
+                page: 1,
+                status: EccStatus::NoError,
+            },
+        ]
+    );
+    assert_eq!(report.ecc.near_threshold().len(), 1);
+
+    let writes = nand.programmer_mut().get_writes();
+    assert!(writes.contains(&vec![0x7C, 0x00]));
+}
+
+#[test]
+fn test_nand_ecc_status_micron_ranges() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_nand_spec();
+    spec.capabilities.ecc_strength = 8;
+    spec.capabilities.ecc_status = EccStatusMethod::MicronEccs;
+
+    // ECCS2-0 in status bits 6:4, each read as GET FEATURE 0xC0 + value
+    let cases = [
+        (0x00, EccStatus::NoError),
+        (0x10, EccStatus::Corrected { bit_flips: 3 }),
+        (0x30, EccStatus::Corrected { bit_flips: 6 }),
+        (0x50, EccStatus::Corrected { bit_flips: 8 }),
+        (0x20, EccStatus::Uncorrectable),
+    ];
+    mock.expect_reads(
+        cases
+            .iter()
+            .flat_map(|&(status, _)| [vec![0xFF; 2], vec![status]])
+            .collect(),
+    );
+
+    let mut nand = SpiNand::new(mock, spec);
+    for (status, expected) in cases {
+        assert_eq!(
+            nand.read_ecc_status().unwrap(),
+            expected,
+            "0x{:02X}",
+            status
+        );
+    }
+}
+
+#[test]
+fn test_nand_ecc_status_kioxia_max_bit_flips() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_nand_spec();
+    spec.capabilities.ecc_strength = 8;
+    spec.capabilities.ecc_status = EccStatusMethod::KioxiaMaxBitFlips;
+
+    mock.expect_reads(vec![
+        vec![0xFF; 2],
+        vec![0x00], // clean
+        vec![0xFF; 2],
+        vec![0x10], // corrected...
+        vec![0xFF; 2],
+        vec![0x20], // ...2 flips in feature 0x30
+        vec![0xFF; 2],
+        vec![0x30], // corrected at the limit...
+        vec![0xFF; 2],
+        vec![0x50], // ...5 flips
+        vec![0xFF; 2],
+        vec![0x10], // corrected, but no count
+        vec![0xFF; 2],
+        vec![0x00],
+        vec![0xFF; 2],
+        vec![0x20], // uncorrectable
+    ]);
+
+    let mut nand = SpiNand::new(mock, spec);
+    let statuses: Vec<_> = (0..5).map(|_| nand.read_ecc_status().unwrap()).collect();
+    assert_eq!(
+        statuses,
+        [
+            EccStatus::NoError,
+            EccStatus::Corrected { bit_flips: 2 },
+            EccStatus::Corrected { bit_flips: 5 },
+            EccStatus::Corrected { bit_flips: 8 },
+            EccStatus::Uncorrectable,
+        ]
+    );
+    let writes = nand.programmer_mut().get_writes();
+    assert!(writes.contains(&vec![0x0F, 0x30]));
+}
@@ -0,0 +1,183 @@
+//! SPI NOR Addressing Beyond 16 MiB
+//!
+//! Parts larger than 16 MiB reach their upper banks in one of three ways
+//! (see [`FourByteMode`]): dedicated 4-byte opcodes, a 4-byte mode entered
+//! with 0xB7, or a bank register (EAR) on top of 3-byte addressing. The two
+//! stateful strategies are undone after every operation so the chip is left
+//! in 3-byte mode with bank 0 selected, which is what boot ROMs expect.
+//!
+//! Multi-die packages (W25M SpiStack) are addressed the same way: each die
+//! sees die-relative addresses, Software Die Select (0xC2) switches between
+//! them and die 0 is selected again afterwards.
+
+use super::SpiNor;
+use crate::domain::chip::FourByteMode;
+use crate::error::Result;
+use crate::infrastructure::flash_protocol::commands::*;
+use crate::infrastructure::programmer::Programmer;
+
+/// Bytes addressable with 3 address bytes
+pub(super) const BANK_SIZE: u32 = 16 * 1024 * 1024;
+
+/// Addressing state the chip was put into by this driver
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct AddressState {
+    /// 0xB7 was sent and not yet undone
+    four_byte_entered: bool,
+    /// Last value written to the EAR; `None` until first written
+    bank: Option<u8>,
+    /// Last die selected with 0xC2; `None` until first selected
+    die: Option<u8>,
+}
+
+impl<P: Programmer> SpiNor<P> {
+    /// The strategy in effect, or `None` for chips that fit in 3 bytes
+    pub(super) fn four_byte_mode(&self) -> Option<FourByteMode> {
+        (self.spec.capabilities.supports_4byte_addr && !self.spec.layout.is_dataflash)
+            .then_some(self.spec.capabilities.four_byte_mode)
+    }
+
+    /// Number of address bytes sent with each command
+    pub(super) fn address_width(&self) -> usize {
+        match self.four_byte_mode() {
+            Some(FourByteMode::Opcodes) | Some(FourByteMode::EnterExit { .. }) => 4,
+            Some(FourByteMode::ExtendedAddress) | None => 3,
+        }
+    }
+
+    /// `four_byte` when the chip uses dedicated 4-byte opcodes, else `legacy`
+    pub(super) fn opcode(&self, legacy: u8, four_byte: u8) -> u8 {
+        if self.four_byte_mode() == Some(FourByteMode::Opcodes) {
+            four_byte
+        } else {
+            legacy
+        }
+    }
+
+    fn die_count(&self) -> u32 {
+        self.spec.layout.die_count.max(1)
+    }
+
+    fn die_size(&self) -> u32 {
+        self.spec.layout.die_size(self.spec.capacity)
+    }
+
+    /// `address` relative to the die it lies in
+    pub(super) fn die_address(&self, address: u32) -> u32 {
+        if self.die_count() > 1 {
+            address % self.die_size()
+        } else {
+            address
+        }
+    }
+
+    /// End of the region a single command starting at `address` may cover:
+    /// the end of its EAR bank or die
+    pub(super) fn bank_end(&self, address: u32) -> u32 {
+        let bank_end = if self.four_byte_mode() == Some(FourByteMode::ExtendedAddress) {
+            address.saturating_add(BANK_SIZE - self.die_address(address) % BANK_SIZE)
+        } else {
+            u32::MAX
+        };
+        let die_end = if self.die_count() > 1 {
+            address.saturating_add(self.die_size() - self.die_address(address))
+        } else {
+            u32::MAX
+        };
+        bank_end.min(die_end)
+    }
+
+    /// Put the chip into a state where `address` can be sent: select its die,
+    /// enter 4-byte mode or switch the EAR bank as needed
+    pub(super) fn select_address(&mut self, address: u32) -> Result<()> {
+        if self.die_count() > 1 {
+            self.select_die((address / self.die_size()) as u8)?;
+        }
+        let address = self.die_address(address);
+
+        match self.four_byte_mode() {
+            Some(FourByteMode::EnterExit { write_enable })
+                if !self.address_state.four_byte_entered =>
+            {
+                if write_enable {
+                    self.write_enable()?;
+                }
+                self.programmer
+                    .spi_transaction_write(&[CMD_NOR_ENTER_4BYTE_MODE])?;
+                self.address_state.four_byte_entered = true;
+            }
+            Some(FourByteMode::ExtendedAddress) => {
+                self.write_bank((address / BANK_SIZE) as u8)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn write_bank(&mut self, bank: u8) -> Result<()> {
+        if self.address_state.bank == Some(bank) {
+            return Ok(());
+        }
+        self.write_enable()?;
+        self.programmer
+            .spi_transaction_write(&[CMD_NOR_WRITE_EAR, bank])?;
+        self.address_state.bank = Some(bank);
+        Ok(())
+    }
+
+    fn select_die(&mut self, die: u8) -> Result<()> {
+        if self.address_state.die == Some(die) {
+            return Ok(());
+        }
+        // Only the selected die is ever left in 4-byte mode or a higher bank
+        self.restore_die_address_mode()?;
+        self.programmer
+            .spi_transaction_write(&[CMD_SOFTWARE_DIE_SELECT, die])?;
+        self.address_state.die = Some(die);
+        self.address_state.bank = None;
+        Ok(())
+    }
+
+    /// Leave 4-byte mode and select bank 0 and die 0 again, if this driver
+    /// changed them
+    pub fn restore_address_mode(&mut self) -> Result<()> {
+        self.restore_die_address_mode()?;
+        if self.address_state.die.is_some_and(|die| die != 0) {
+            self.select_die(0)?;
+        }
+        Ok(())
+    }
+
+    /// Undo 4-byte mode and EAR bank switching on the selected die
+    fn restore_die_address_mode(&mut self) -> Result<()> {
+        match self.four_byte_mode() {
+            Some(FourByteMode::EnterExit { write_enable })
+                if self.address_state.four_byte_entered =>
+            {
+                if write_enable {
+                    self.write_enable()?;
+                }
+                self.programmer
+                    .spi_transaction_write(&[CMD_NOR_EXIT_4BYTE_MODE])?;
+                self.address_state.four_byte_entered = false;
+            }
+            Some(FourByteMode::ExtendedAddress) if self.address_state.bank.is_some() => {
+                self.write_bank(0)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Run `operation`, then restore 3-byte addressing even if it failed
+    pub(super) fn with_address_mode<T>(
+        &mut self,
+        operation: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let result = operation(self);
+        let restored = self.restore_address_mode();
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+}
@@ -0,0 +1,147 @@
+//! SPI NOR Individual Block Protection
+//!
+//! With the WPS bit set (SR3 on Winbond/GigaDevice) the BP bits are ignored
+//! and every block carries its own volatile lock bit. The first and last
+//! 64 KiB blocks are split into sixteen individually lockable 4 KiB sectors.
+//! Locked blocks silently ignore program and erase commands.
+
+use std::ops::Range;
+
+use super::SpiNor;
+use crate::error::{Error, Result};
+use crate::infrastructure::flash_protocol::commands::*;
+use crate::infrastructure::programmer::Programmer;
+
+const LOCK_BLOCK_SIZE: u32 = 64 * 1024;
+const LOCK_SECTOR_SIZE: u32 = 4 * 1024;
+
+impl<P: Programmer> SpiNor<P> {
+    /// Whether the chip is in individual block protection mode
+    pub fn wps_enabled(&mut self) -> Result<bool> {
+        let Some(wps) = self.spec.capabilities.wps else {
+            return Ok(false);
+        };
+        let Some(register) = self.status_registers().get(wps.register) else {
+            return Ok(false);
+        };
+        let value = self.read_register(register)?;
+        Ok(value & (1 << wps.bit) != 0)
+    }
+
+    /// Independently lockable units, in address order
+    pub fn block_lock_units(&self) -> Vec<Range<u32>> {
+        let capacity = self.spec.capacity.as_bytes();
+        let sectors = |base: u32| {
+            (base..base + LOCK_BLOCK_SIZE)
+                .step_by(LOCK_SECTOR_SIZE as usize)
+                .map(|start| start..start + LOCK_SECTOR_SIZE)
+        };
+
+        if capacity <= 2 * LOCK_BLOCK_SIZE {
+            return sectors(0)
+                .chain(sectors(LOCK_BLOCK_SIZE))
+                .filter(|unit| unit.end <= capacity)
+                .collect();
+        }
+
+        let last = capacity - LOCK_BLOCK_SIZE;
+        sectors(0)
+            .chain(
+                (LOCK_BLOCK_SIZE..last)
+                    .step_by(LOCK_BLOCK_SIZE as usize)
+                    .map(|start| start..start + LOCK_BLOCK_SIZE),
+            )
+            .chain(sectors(last))
+            .collect()
+    }
+
+    fn block_locked(&mut self, address: u32) -> Result<bool> {
+        self.select_address(address)?;
+        let mut command = vec![CMD_NOR_READ_BLOCK_LOCK];
+        command.extend(self.addr_to_bytes(address));
+        let data = self.programmer.spi_transaction(&command, 1)?;
+        Ok(data[0] & 0x01 != 0)
+    }
+
+    /// Lock state of every unit overlapping `range`
+    pub fn block_lock_map(&mut self, range: Range<u32>) -> Result<Vec<(Range<u32>, bool)>> {
+        let units: Vec<_> = self
+            .block_lock_units()
+            .into_iter()
+            .filter(|unit| unit.start < range.end && range.start < unit.end)
+            .collect();
+
+        self.with_address_mode(|nor| {
+            units
+                .into_iter()
+                .map(|unit| Ok((unit.clone(), nor.block_locked(unit.start)?)))
+                .collect()
+        })
+    }
+
+    /// Locked units overlapping `range`; empty when WPS is off
+    pub fn locked_blocks(&mut self, range: Range<u32>) -> Result<Vec<Range<u32>>> {
+        if !self.wps_enabled()? {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .block_lock_map(range)?
+            .into_iter()
+            .filter(|(_, locked)| *locked)
+            .map(|(unit, _)| unit)
+            .collect())
+    }
+
+    /// Lock every unit inside `range`, which must start and end on unit
+    /// boundaries
+    pub fn lock_blocks(&mut self, range: Range<u32>) -> Result<()> {
+        let units: Vec<_> = self
+            .block_lock_units()
+            .into_iter()
+            .filter(|unit| unit.start < range.end && range.start < unit.end)
+            .collect();
+
+        if let Some(unit) = units
+            .iter()
+            .find(|unit| unit.start < range.start || unit.end > range.end)
+        {
+            return Err(Error::InvalidParameter(format!(
+                "Range must be aligned to lock units (0x{:08X}-0x{:08X} is only partly covered)",
+                unit.start,
+                unit.end - 1
+            )));
+        }
+
+        self.with_address_mode(|nor| {
+            for unit in units {
+                nor.block_lock_command(CMD_NOR_INDIVIDUAL_LOCK, unit.start)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn block_lock_command(&mut self, opcode: u8, address: u32) -> Result<()> {
+        self.select_address(address)?;
+        let mut command = vec![opcode];
+        command.extend(self.addr_to_bytes(address));
+        self.write_enable()?;
+        self.programmer.spi_transaction_write(&command)?;
+        self.wait_ready()
+    }
+
+    /// Lock every block (0x7E)
+    pub fn global_lock(&mut self) -> Result<()> {
+        self.write_enable()?;
+        self.programmer
+            .spi_transaction_write(&[CMD_NOR_GLOBAL_LOCK])?;
+        self.wait_ready()
+    }
+
+    /// Unlock every block (0x98)
+    pub fn global_unlock(&mut self) -> Result<()> {
+        self.write_enable()?;
+        self.programmer
+            .spi_transaction_write(&[CMD_NOR_GLOBAL_UNLOCK])?;
+        self.wait_ready()
+    }
+}
@@ -0,0 +1,146 @@
+//! NOR Erase Planner
+//!
+//! Covers an erase range with the fewest sector/block/chip erase commands.
+//! Each position uses the largest erase unit that is aligned there and fits
+//! in the remaining range, which is optimal for the nested power-of-two
+//! sizes (4K/32K/64K) used by SPI NOR parts.
+
+use crate::domain::chip::EraseType;
+use crate::error::{Error, Result};
+
+/// A single erase command in an erase plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EraseOp {
+    /// Erase one unit of `size` bytes at `address` using `opcode`
+    Region { address: u32, size: u32, opcode: u8 },
+    /// Erase the entire chip
+    Chip,
+}
+
+impl EraseOp {
+    /// Number of bytes erased by this operation
+    pub fn size(&self, chip_size: u32) -> u32 {
+        match self {
+            EraseOp::Region { size, .. } => *size,
+            EraseOp::Chip => chip_size,
+        }
+    }
+}
+
+/// Plan the erase commands needed to cover `[address, address + length)`.
+///
+/// `erase_types` must be sorted by size (smallest first). The start address
+/// must be aligned to the smallest erase unit; the end is rounded up to it.
+pub fn plan_erase(
+    address: u32,
+    length: u32,
+    erase_types: &[EraseType],
+    chip_size: u32,
+) -> Result<Vec<EraseOp>> {
+    let smallest = erase_types
+        .first()
+        .ok_or_else(|| Error::NotSupported("Chip reports no erase commands".to_string()))?
+        .size;
+
+    if !address.is_multiple_of(smallest) {
+        return Err(Error::InvalidParameter(format!(
+            "NOR erase address must be {}K-aligned",
+            smallest / 1024
+        )));
+    }
+
+    let end =
+        (address as u64 + length.div_ceil(smallest) as u64 * smallest as u64).min(chip_size as u64);
+
+    if address == 0 && end == chip_size as u64 && length > 0 {
+        return Ok(vec![EraseOp::Chip]);
+    }
+
+    let mut ops = Vec::new();
+    let mut current = address as u64;
+    while current < end {
+        let erase = erase_types
+            .iter()
+            .rev()
+            .find(|e| current.is_multiple_of(e.size as u64) && current + e.size as u64 <= end)
+            .unwrap_or(&erase_types[0]);
+
+        ops.push(EraseOp::Region {
+            address: current as u32,
+            size: erase.size,
+            opcode: erase.opcode,
+        });
+        current += erase.size as u64;
+    }
+
+    Ok(ops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const K: u32 = 1024;
+
+    fn standard_types() -> Vec<EraseType> {
+        vec![
+            EraseType {
+                size: 4 * K,
+                opcode: 0x20,
+            },
+            EraseType {
+                size: 32 * K,
+                opcode: 0x52,
+            },
+            EraseType {
+                size: 64 * K,
+                opcode: 0xD8,
+            },
+        ]
+    }
+
+    fn sizes(ops: &[EraseOp]) -> Vec<u32> {
+        ops.iter().map(|op| op.size(0) / K).collect()
+    }
+
+    #[test]
+    fn test_single_sector() {
+        let ops = plan_erase(0x11000, 4 * K, &standard_types(), 8 * 1024 * K).unwrap();
+        assert_eq!(
+            ops,
+            vec![EraseOp::Region {
+                address: 0x11000,
+                size: 4 * K,
+                opcode: 0x20
+            }]
+        );
+    }
+
+    #[test]
+    fn test_mixed_granularity() {
+        // 0x7000..0x29000: 4K up to the 32K boundary, 32K up to the 64K boundary,
+        // one 64K block, then a 32K + 4K tail
+        let ops = plan_erase(0x7000, 0x22000, &standard_types(), 8 * 1024 * K).unwrap();
+        assert_eq!(sizes(&ops), vec![4, 32, 64, 32, 4]);
+        // 0x7000..0x31000: two full 64K blocks in the middle
+        let ops = plan_erase(0x7000, 0x2A000, &standard_types(), 8 * 1024 * K).unwrap();
+        assert_eq!(sizes(&ops), vec![4, 32, 64, 64, 4]);
+    }
+
+    #[test]
+    fn test_full_chip_uses_chip_erase() {
+        let ops = plan_erase(0, 8 * 1024 * K, &standard_types(), 8 * 1024 * K).unwrap();
+        assert_eq!(ops, vec![EraseOp::Chip]);
+    }
+
+    #[test]
+    fn test_unaligned_start_rejected() {
+        assert!(plan_erase(0x100, 4 * K, &standard_types(), 8 * 1024 * K).is_err());
+    }
+
+    #[test]
+    fn test_length_rounded_up() {
+        let ops = plan_erase(0, 100, &standard_types(), 8 * 1024 * K).unwrap();
+        assert_eq!(sizes(&ops), vec![4]);
+    }
+}
@@ -2,25 +2,65 @@
 //!
 //! This module implements the SPI NOR protocol according to infrastructure standards.
 
+mod address_mode;
+mod block_lock;
+pub mod erase_planner;
+mod multi_io;
+mod otp;
+pub mod sfdp;
+mod status;
+mod unique_id;
+
 #[cfg(test)]
 mod tests;
 
 use std::time::{Duration, Instant};
 
-use crate::domain::chip::ChipSpec;
-use crate::domain::{EraseRequest, FlashOperation, Progress, ReadRequest, WriteRequest};
+use crate::domain::chip::{ChipSpec, EraseType, FourByteMode};
+use crate::domain::types::Address;
+use crate::domain::{
+    BadBlockStrategy, EraseRequest, FlashOperation, OobMode, Progress, ReadRequest, WriteMode,
+    WriteReport, WriteRequest,
+};
 use crate::error::{Error, Result};
 use crate::infrastructure::flash_protocol::commands::*;
-use crate::infrastructure::programmer::Programmer;
+use crate::infrastructure::programmer::{BusWidth, Programmer};
+use address_mode::AddressState;
+use erase_planner::{plan_erase, EraseOp};
+
+/// Timeout for a full chip erase (large parts can take several minutes)
+const CHIP_ERASE_TIMEOUT: Duration = Duration::from_secs(400);
+
+/// NOR flash must be written in 256-byte pages
+const PAGE_SIZE: usize = 256;
 
 pub struct SpiNor<P: Programmer> {
     programmer: P,
     spec: ChipSpec,
+    address_state: AddressState,
+    /// QE has been checked/set during this session
+    quad_enabled: bool,
+    /// QE was clear before `enable_quad` set it
+    restore_qe: bool,
+}
+
+impl<P: Programmer> Drop for SpiNor<P> {
+    fn drop(&mut self) {
+        if let Err(e) = self.restore_quad() {
+            log::warn!("Could not clear the QE bit of {}: {}", self.spec.name, e);
+        }
+    }
 }
 
 impl<P: Programmer> SpiNor<P> {
     pub fn new(programmer: P, spec: ChipSpec) -> Self {
-        Self { programmer, spec }
+        Self {
+            programmer,
+            spec,
+            address_state: AddressState::default(),
+            quad_enabled: false,
+            restore_qe: false,
+        }
     }
 
     /// Get chip specification
@@ -38,8 +78,11 @@ impl<P: Programmer> SpiNor<P> {
     // =========================================================================
 
     fn wait_ready(&mut self) -> Result<()> {
+        self.wait_ready_timeout(Duration::from_secs(30))
+    }
+
+    fn wait_ready_timeout(&mut self, timeout: Duration) -> Result<()> {
         let start = Instant::now();
-        let timeout = Duration::from_secs(30); // NOR chip erase can take longer
         loop {
             let status = self.read_status()?;
             if status & STATUS_NOR_WIP == 0 {
@@ -68,6 +111,7 @@ impl<P: Programmer> SpiNor<P> {
     }
 
     fn addr_to_bytes(&self, addr: u32) -> Vec<u8> {
+        let addr = self.die_address(addr);
         if self.spec.layout.is_dataflash {
             let page_size = self.spec.layout.page_size;
             let page_offset_bits = match page_size {
@@ -86,7 +130,7 @@ impl<P: Programmer> SpiNor<P> {
             let df_addr = (page << page_offset_bits) | offset;
 
             vec![(df_addr >> 16) as u8, (df_addr >> 8) as u8, df_addr as u8]
-        } else if self.spec.capabilities.supports_4byte_addr {
+        } else if self.address_width() == 4 {
             vec![
                 (addr >> 24) as u8,
                 (addr >> 16) as u8,
@@ -98,28 +142,293 @@ impl<P: Programmer> SpiNor<P> {
         }
     }
 
-    #[allow(dead_code)]
-    fn read_internal(&mut self, address: u32, len: usize) -> Result<Vec<u8>> {
-        let addr_bytes = self.addr_to_bytes(address);
-        let cmd = if self.spec.capabilities.supports_4byte_addr {
-            CMD_NOR_READ_4B
-        } else {
-            CMD_NOR_READ
+    /// Erase granularities available on this chip, smallest first.
+    ///
+    /// Uses the types from the chip database/SFDP when present, otherwise
+    /// assumes the standard JEDEC 4K/32K/64K set for 64K-block parts.
+    pub fn erase_types(&self) -> Vec<EraseType> {
+        let mut types: Vec<EraseType> = self
+            .spec
+            .capabilities
+            .erase_types
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+
+        if types.is_empty() {
+            types = if self.spec.layout.block_size == 64 * 1024 && !self.spec.layout.is_dataflash {
+                vec![
+                    EraseType {
+                        size: 4 * 1024,
+                        opcode: CMD_NOR_SECTOR_ERASE_4K,
+                    },
+                    EraseType {
+                        size: 32 * 1024,
+                        opcode: CMD_NOR_BLOCK_ERASE_32K,
+                    },
+                    EraseType {
+                        size: 64 * 1024,
+                        opcode: CMD_NOR_BLOCK_ERASE_64K,
+                    },
+                ]
+            } else {
+                vec![EraseType {
+                    size: self.spec.layout.block_size,
+                    opcode: CMD_NOR_BLOCK_ERASE_64K,
+                }]
+            };
+        }
+
+        if self.four_byte_mode() == Some(FourByteMode::Opcodes) {
+            for erase in types.iter_mut() {
+                erase.opcode = match erase.opcode {
+                    CMD_NOR_SECTOR_ERASE_4K => CMD_NOR_SECTOR_ERASE_4K_4B,
+                    CMD_NOR_BLOCK_ERASE_32K => CMD_NOR_BLOCK_ERASE_32K_4B,
+                    CMD_NOR_BLOCK_ERASE_64K => CMD_NOR_BLOCK_ERASE_64K_4B,
+                    other => other,
+                };
+            }
+        }
+
+        types.sort_by_key(|e| e.size);
+        types
+    }
+
+    /// Issue a single planned erase command and wait for it to finish
+    fn execute_erase_op(&mut self, op: EraseOp) -> Result<()> {
+        match op {
+            EraseOp::Chip => {
+                // Chip erase only covers the selected die
+                let layout = self.spec.layout;
+                for die in 0..layout.die_count.max(1) {
+                    if layout.die_count > 1 {
+                        self.select_address(die * layout.die_size(self.spec.capacity))?;
+                    }
+                    self.write_enable()?;
+                    self.programmer
+                        .spi_transaction_write(&[CMD_NOR_CHIP_ERASE])?;
+                    self.wait_ready_timeout(CHIP_ERASE_TIMEOUT)?;
+                }
+                Ok(())
+            }
+            EraseOp::Region {
+                address, opcode, ..
+            } => {
+                self.select_address(address)?;
+                self.write_enable()?;
+                let mut cmd = vec![opcode];
+                cmd.extend_from_slice(&self.addr_to_bytes(address));
+                self.programmer.spi_transaction_write(&cmd)?;
+                self.wait_ready()
+            }
+        }
+    }
+
+    /// Program `data` at `address`, splitting at page boundaries
+    fn program_range(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<()> {
+        let mut offset = 0usize;
+        let mut current_addr = address;
+        let quad = self.quad_program_enabled();
+        if quad {
+            self.enable_quad()?;
+        }
+
+        while offset < data.len() {
+            // Calculate bytes remaining in current page
+            let page_offset = (current_addr as usize) % PAGE_SIZE;
+            let bytes_in_page = PAGE_SIZE - page_offset;
+            let bytes_to_write = bytes_in_page.min(data.len() - offset);
+
+            self.select_address(current_addr)?;
+            self.write_enable()?;
+
+            let page = &data[offset..offset + bytes_to_write];
+            if quad {
+                self.program_quad(current_addr, page)?;
+            } else {
+                let addr_bytes = self.addr_to_bytes(current_addr);
+                let cmd_byte = self.opcode(CMD_NOR_PAGE_PROGRAM, CMD_NOR_PAGE_PROGRAM_4B);
+
+                let mut cmd = vec![cmd_byte];
+                cmd.extend_from_slice(&addr_bytes);
+
+                self.programmer.set_cs(true)?;
+                self.programmer.spi_write(&cmd)?;
+                self.programmer.spi_write(page)?;
+                self.programmer.set_cs(false)?;
+            }
+
+            self.wait_ready()?;
+
+            offset += bytes_to_write;
+            current_addr += bytes_to_write as u32;
+
+            on_progress(Progress::new(offset as u64, data.len() as u64));
+        }
+
+        Ok(())
+    }
+
+    /// Read a range without progress reporting
+    fn read_range(&mut self, address: u32, length: u32, retry_count: u32) -> Result<Vec<u8>> {
+        let request = ReadRequest {
+            address: Address::new(address),
+            length,
+            use_ecc: false,
+            ignore_ecc_errors: false,
+            soft_ecc: None,
+            oob_mode: OobMode::None,
+            bad_block_strategy: BadBlockStrategy::Fail,
+            bbt: None,
+            retry_count,
+            partitions: Vec::new(),
         };
+        self.read_chunks(request, &|_| {})
+    }
 
-        let mut tx = vec![cmd];
-        tx.extend_from_slice(&addr_bytes);
+    /// Read-modify-write: update `request.data` in place, preserving the rest
+    /// of every erase unit it touches. Used for both patch and delta writes.
+    ///
+    /// Units that already hold the data are skipped entirely. Units are only
+    /// erased when a bit has to go from 0 to 1; otherwise the changed pages
+    /// are simply reprogrammed on top of the existing data. Adjacent units
+    /// that need erasing are erased together, so whole 32K/64K blocks use
+    /// the larger erase commands.
+    fn write_patch(
+        &mut self,
+        request: &WriteRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<WriteReport> {
+        let start = request.address.as_u32();
+        let end = start as u64 + request.data.len() as u64;
+        let chip_size = self.spec.capacity.as_bytes();
+        if end > chip_size as u64 {
+            return Err(Error::InvalidParameter(
+                "Write extends past the end of the chip".to_string(),
+            ));
+        }
 
-        self.programmer.set_cs(true)?;
-        self.programmer.spi_write(&tx)?;
-        let data = self.programmer.spi_read(len)?;
-        self.programmer.set_cs(false)?;
-        Ok(data)
+        let erase_types = self.erase_types();
+        let unit = erase_types[0].size;
+        let mut unit_addr = start - start % unit;
+        let mut report = WriteReport::default();
+        // Merged contents of the run of adjacent units waiting to be erased
+        let mut run_start = unit_addr;
+        let mut run: Vec<Vec<u8>> = Vec::new();
+
+        while (unit_addr as u64) < end {
+            let current = self.read_range(unit_addr, unit, request.retry_count)?;
+            report.units_total += 1;
+
+            // Overlay the new bytes onto the unit's current contents
+            let mut merged = current.clone();
+            let copy_start = start.max(unit_addr);
+            let copy_end = (end as u32).min(unit_addr + unit);
+            merged[(copy_start - unit_addr) as usize..(copy_end - unit_addr) as usize]
+                .copy_from_slice(
+                    &request.data[(copy_start - start) as usize..(copy_end - start) as usize],
+                );
+
+            let needs_erase = current
+                .iter()
+                .zip(merged.iter())
+                .any(|(&old, &new)| old & new != new);
+
+            if needs_erase {
+                if run.is_empty() {
+                    run_start = unit_addr;
+                }
+                run.push(merged);
+            } else {
+                self.rewrite_erase_run(run_start, &mut run, &erase_types, &mut report)?;
+                if merged == current {
+                    report.units_skipped += 1;
+                } else {
+                    for (i, page) in merged.chunks(PAGE_SIZE).enumerate() {
+                        let old_page = &current[i * PAGE_SIZE..i * PAGE_SIZE + page.len()];
+                        if page != old_page {
+                            let page_addr = unit_addr + (i * PAGE_SIZE) as u32;
+                            self.program_range(page_addr, page, &|_| {})?;
+                            report.pages_programmed += 1;
+                        } else {
+                            report.pages_skipped += 1;
+                        }
+                    }
+                }
+            }
+
+            unit_addr += unit;
+            if run.is_empty() {
+                on_progress(Progress::new(
+                    (unit_addr.min(end as u32) - start) as u64,
+                    request.data.len() as u64,
+                ));
+            }
+        }
+
+        if !run.is_empty() {
+            self.rewrite_erase_run(run_start, &mut run, &erase_types, &mut report)?;
+            on_progress(Progress::new(
+                request.data.len() as u64,
+                request.data.len() as u64,
+            ));
+        }
+
+        Ok(report)
     }
-}
 
-impl<P: Programmer> FlashOperation for SpiNor<P> {
-    fn read(&mut self, request: ReadRequest, on_progress: &dyn Fn(Progress)) -> Result<Vec<u8>> {
+    /// Erase the adjacent units in `run`, starting at `address`, with the
+    /// fewest commands [`plan_erase`] finds, then program their non-blank
+    /// pages. Leaves `run` empty.
+    fn rewrite_erase_run(
+        &mut self,
+        address: u32,
+        run: &mut Vec<Vec<u8>>,
+        erase_types: &[EraseType],
+        report: &mut WriteReport,
+    ) -> Result<()> {
+        if run.is_empty() {
+            return Ok(());
+        }
+
+        let unit = erase_types[0].size;
+        let length = unit * run.len() as u32;
+        log::debug!(
+            "Erasing and rewriting 0x{:X} bytes at 0x{:08X}",
+            length,
+            address
+        );
+        for op in plan_erase(address, length, erase_types, self.spec.capacity.as_bytes())? {
+            self.execute_erase_op(op)?;
+        }
+        report.units_erased += run.len() as u32;
+
+        for (n, merged) in run.drain(..).enumerate() {
+            let unit_addr = address + n as u32 * unit;
+            for (i, page) in merged.chunks(PAGE_SIZE).enumerate() {
+                if page.iter().all(|&b| b == 0xFF) {
+                    report.pages_skipped += 1;
+                } else {
+                    self.program_range(unit_addr + (i * PAGE_SIZE) as u32, page, &|_| {})?;
+                    report.pages_programmed += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fast-read `request` in bulk-sized chunks
+    fn read_chunks(
+        &mut self,
+        request: ReadRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<Vec<u8>> {
         // NOR flash supports arbitrary address reads with continuous read mode
         let address = request.address.as_u32();
         let length = request.length as usize;
@@ -131,18 +440,19 @@ impl<P: Programmer> FlashOperation for SpiNor<P> {
         let mut result = Vec::with_capacity(length);
         let mut remaining = length;
         let mut current_addr = address;
+        let width = self.data_width();
 
         while remaining > 0 {
-            let read_size = remaining.min(chunk_size);
+            // A read must not cross into the next EAR bank
+            let read_size = remaining
+                .min(chunk_size)
+                .min((self.bank_end(current_addr) - current_addr) as usize);
 
             // Use Fast Read command (0x0B) with dummy byte for higher speed
             // Format: CMD + 3-byte addr + 1 dummy byte, then read data
+            self.select_address(current_addr)?;
             let addr_bytes = self.addr_to_bytes(current_addr);
-            let cmd_byte = if self.spec.capabilities.supports_4byte_addr {
-                CMD_NOR_FAST_READ_4B
-            } else {
-                CMD_NOR_FAST_READ
-            };
+            let cmd_byte = self.opcode(CMD_NOR_FAST_READ, CMD_NOR_FAST_READ_4B);
 
             let mut cmd = vec![cmd_byte];
             cmd.extend_from_slice(&addr_bytes);
@@ -150,7 +460,12 @@ impl<P: Programmer> FlashOperation for SpiNor<P> {
 
             let mut attempts = 0;
             let chunk = loop {
-                match self.programmer.spi_transaction(&cmd, read_size) {
+                let data = if width > BusWidth::Single {
+                    self.read_multi_io(current_addr, read_size, width)
+                } else {
+                    self.programmer.spi_transaction(&cmd, read_size)
+                };
+                match data {
                     Ok(data) => break data,
                     Err(e) => {
                         if attempts < request.retry_count {
@@ -179,126 +494,128 @@ impl<P: Programmer> FlashOperation for SpiNor<P> {
         Ok(result)
     }
 
-    fn write(&mut self, request: WriteRequest, on_progress: &dyn Fn(Progress)) -> Result<()> {
-        // NOR flash must be written in 256-byte pages
-        const PAGE_SIZE: usize = 256;
+    #[allow(dead_code)]
+    fn read_internal(&mut self, address: u32, len: usize) -> Result<Vec<u8>> {
+        self.select_address(address)?;
+        let addr_bytes = self.addr_to_bytes(address);
+        let cmd = self.opcode(CMD_NOR_READ, CMD_NOR_READ_4B);
 
-        let data = request.data;
-        let mut offset = 0usize;
-        let mut current_addr = request.address.as_u32();
+        let mut tx = vec![cmd];
+        tx.extend_from_slice(&addr_bytes);
 
-        while offset < data.len() {
-            // Calculate bytes remaining in current page
-            let page_offset = (current_addr as usize) % PAGE_SIZE;
-            let bytes_in_page = PAGE_SIZE - page_offset;
-            let bytes_to_write = bytes_in_page.min(data.len() - offset);
+        self.programmer.set_cs(true)?;
+        self.programmer.spi_write(&tx)?;
+        let data = self.programmer.spi_read(len)?;
+        self.programmer.set_cs(false)?;
+        Ok(data)
+    }
+}
 
-            self.write_enable()?;
+impl<P: Programmer> FlashOperation for SpiNor<P> {
+    fn read(&mut self, request: ReadRequest, on_progress: &dyn Fn(Progress)) -> Result<Vec<u8>> {
+        self.with_address_mode(|nor| nor.read_chunks(request, on_progress))
+    }
 
-            let addr_bytes = self.addr_to_bytes(current_addr);
-            let cmd_byte = if self.spec.capabilities.supports_4byte_addr {
-                CMD_NOR_PAGE_PROGRAM_4B
-            } else {
-                CMD_NOR_PAGE_PROGRAM
+    fn write(
+        &mut self,
+        request: WriteRequest,
+        on_progress: &dyn Fn(Progress),
+    ) -> Result<WriteReport> {
+        self.with_address_mode(|nor| {
+            let report = match request.mode {
+                WriteMode::Direct => {
+                    nor.program_range(request.address.as_u32(), request.data, on_progress)?;
+                    WriteReport::default()
+                }
+                WriteMode::Patch | WriteMode::Delta => nor.write_patch(&request, on_progress)?,
             };
 
-            let mut cmd = vec![cmd_byte];
-            cmd.extend_from_slice(&addr_bytes);
-
-            self.programmer.set_cs(true)?;
-            self.programmer.spi_write(&cmd)?;
-            self.programmer
-                .spi_write(&data[offset..offset + bytes_to_write])?;
-            self.programmer.set_cs(false)?;
-
-            self.wait_ready()?;
-
-            offset += bytes_to_write;
-            current_addr += bytes_to_write as u32;
-
-            on_progress(Progress::new(offset as u64, data.len() as u64));
-        }
-
-        if request.verify {
-            let verify_req = ReadRequest {
-                address: request.address,
-                length: request.data.len() as u32,
-                use_ecc: request.use_ecc,
-                ignore_ecc_errors: request.ignore_ecc_errors,
-                oob_mode: request.oob_mode,
-                bad_block_strategy: request.bad_block_strategy,
-                bbt: None,
-                retry_count: request.retry_count,
-            };
-            let read_back = self.read(verify_req, &|_| {})?;
-            if read_back != request.data {
-                for (i, (&actual, &expected)) in
-                    read_back.iter().zip(request.data.iter()).enumerate()
-                {
-                    if actual != expected {
-                        return Err(Error::VerificationFailed {
-                            address: request.address.as_u32() + i as u32,
-                            expected,
-                            actual,
-                        });
+            if request.verify {
+                let verify_req = ReadRequest {
+                    address: request.address,
+                    length: request.data.len() as u32,
+                    use_ecc: request.use_ecc,
+                    ignore_ecc_errors: request.ignore_ecc_errors,
+                    soft_ecc: request.soft_ecc,
+                    oob_mode: request.oob_mode,
+                    bad_block_strategy: request.bad_block_strategy,
+                    bbt: None,
+                    retry_count: request.retry_count,
+                    partitions: Vec::new(),
+                };
+                let read_back = nor.read_chunks(verify_req, &|_| {})?;
+                if read_back != request.data {
+                    for (i, (&actual, &expected)) in
+                        read_back.iter().zip(request.data.iter()).enumerate()
+                    {
+                        if actual != expected {
+                            return Err(Error::VerificationFailed {
+                                address: request.address.as_u32() + i as u32,
+                                expected,
+                                actual,
+                            });
+                        }
                     }
                 }
             }
-        }
 
-        Ok(())
+            Ok(report)
+        })
     }
 
     fn erase(&mut self, request: EraseRequest, on_progress: &dyn Fn(Progress)) -> Result<()> {
-        let block_size = self.spec.layout.block_size;
-        let address = request.address.as_u32();
-
-        if !address.is_multiple_of(block_size) {
-            return Err(Error::InvalidParameter(
-                "NOR erase address must be block-aligned".to_string(),
-            ));
-        }
-
-        let total_blocks = request.length.div_ceil(block_size);
-
-        for i in 0..total_blocks {
-            let block_addr = address + (i * block_size);
-
-            self.write_enable()?;
-
-            let addr_bytes = self.addr_to_bytes(block_addr);
-            let cmd_byte = if self.spec.capabilities.supports_4byte_addr {
-                CMD_NOR_BLOCK_ERASE_64K_4B
-            } else {
-                CMD_NOR_BLOCK_ERASE_64K
-            };
+        let chip_size = self.spec.capacity.as_bytes();
+        let plan = plan_erase(
+            request.address.as_u32(),
+            request.length,
+            &self.erase_types(),
+            chip_size,
+        )?;
+
+        let total: u64 = plan.iter().map(|op| op.size(chip_size) as u64).sum();
+        let mut erased = 0u64;
+
+        self.with_address_mode(|nor| {
+            for op in plan {
+                nor.execute_erase_op(op)?;
+                erased += op.size(chip_size) as u64;
+                on_progress(Progress::new(erased, total));
+            }
+            Ok(())
+        })
+    }
 
-            let mut cmd = vec![cmd_byte];
-            cmd.extend_from_slice(&addr_bytes);
+    fn get_status(&mut self) -> Result<Vec<u8>> {
+        self.status_registers()
+            .iter()
+            .map(|reg| self.read_register(reg))
+            .collect()
+    }
 
-            self.programmer.set_cs(true)?;
-            self.programmer.spi_write(&cmd)?;
-            self.programmer.set_cs(false)?;
+    fn otp_read(&mut self, region: u32, offset: u32, length: u32) -> Result<Vec<u8>> {
+        self.read_otp(region, offset, length)
+    }
 
-            self.wait_ready()?;
+    fn otp_program(&mut self, region: u32, offset: u32, data: &[u8]) -> Result<()> {
+        self.program_otp(region, offset, data)
+    }
 
-            on_progress(Progress::new((i + 1) as u64, total_blocks as u64));
-        }
+    fn otp_erase(&mut self, region: u32) -> Result<()> {
+        self.erase_otp(region)
+    }
 
-        Ok(())
+    fn otp_lock(&mut self, region: u32) -> Result<()> {
+        self.lock_otp(region)
     }
 
-    fn get_status(&mut self) -> Result<Vec<u8>> {
-        Ok(vec![self.read_status()?])
+    fn otp_is_locked(&mut self, region: u32) -> Result<bool> {
+        self.otp_locked(region)
     }
 
+    /// Write status registers in the order given by the chip's register
+    /// definitions (SR1 first)
     fn set_status(&mut self, status: &[u8]) -> Result<()> {
-        if status.is_empty() {
-            return Ok(());
-        }
-        self.write_enable()?;
-        let cmd = vec![NOR_CMD_WRSR, status[0]];
-        self.programmer.spi_transfer(&cmd, &mut [])?;
-        self.wait_ready()
+        self.forget_quad();
+        self.write_status_registers(status)
     }
 }
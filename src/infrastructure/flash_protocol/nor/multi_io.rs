@@ -0,0 +1,203 @@
+//! SPI NOR Dual/Quad I/O
+//!
+//! Reads use the 1-1-2 (0x3B) or 1-1-4 (0x6B) fast read and programs use
+//! the chip's quad page program when both the chip and the programmer
+//! support the wider bus. Quad commands need the QE bit, which is set before
+//! the first quad transfer of a session. QE is non-volatile on most parts
+//! and turns WP# and HOLD# into data lines, so it is cleared again when the
+//! session ends if it was clear to begin with. Setting it once per session
+//! rather than per operation spares the status register's write cycles.
+
+use super::SpiNor;
+use crate::domain::chip::{QuadEnableMethod, QuadProgram};
+use crate::error::{Error, Result};
+use crate::infrastructure::flash_protocol::commands::*;
+use crate::infrastructure::programmer::{BusWidth, MultiIoCommand, Programmer};
+
+/// Dummy cycles of the 0x3B/0x6B fast reads
+const FAST_READ_DUMMY_CYCLES: u8 = 8;
+
+impl<P: Programmer> SpiNor<P> {
+    /// Widest bus supported by both the chip and the programmer
+    pub fn data_width(&self) -> BusWidth {
+        let chip = if self.spec.layout.is_dataflash {
+            BusWidth::Single
+        } else if self.spec.capabilities.supports_quad_spi {
+            BusWidth::Quad
+        } else if self.spec.capabilities.supports_dual_spi {
+            BusWidth::Dual
+        } else {
+            BusWidth::Single
+        };
+        chip.min(self.programmer.max_bus_width())
+    }
+
+    /// Set the QE bit if the chip has one and it is not already set
+    pub fn enable_quad(&mut self) -> Result<()> {
+        if self.quad_enabled {
+            return Ok(());
+        }
+
+        self.restore_qe = self.write_qe_bit(true)?;
+        self.quad_enabled = true;
+        Ok(())
+    }
+
+    /// End the quad session: clear the QE bit again if
+    /// [`enable_quad`](Self::enable_quad) set it, so WP# and HOLD# work as
+    /// before. Dropping the `SpiNor` does the same.
+    pub fn restore_quad(&mut self) -> Result<()> {
+        self.quad_enabled = false;
+        if std::mem::take(&mut self.restore_qe) {
+            self.write_qe_bit(false)?;
+        }
+        Ok(())
+    }
+
+    /// Forget the QE state after the status registers were written by hand:
+    /// the next quad transfer checks QE again, and the caller's value is
+    /// left in place at the end of the session
+    pub(super) fn forget_quad(&mut self) {
+        self.quad_enabled = false;
+        self.restore_qe = false;
+    }
+
+    /// Set or clear the QE bit; whether its value changed
+    fn write_qe_bit(&mut self, enable: bool) -> Result<bool> {
+        let update = |value: u8, mask: u8| {
+            if enable {
+                value | mask
+            } else {
+                value & !mask
+            }
+        };
+
+        match self.spec.capabilities.quad_enable {
+            QuadEnableMethod::None | QuadEnableMethod::ConfigBit0 => Ok(false),
+            QuadEnableMethod::Sr1Bit6 => {
+                let sr1 = self.read_status()?;
+                let new = update(sr1, 0x40);
+                if new == sr1 {
+                    return Ok(false);
+                }
+                self.write_qe(&[NOR_CMD_WRSR, new])?;
+                Ok(true)
+            }
+            QuadEnableMethod::Sr2Bit1WriteSr1Sr2 => {
+                // SR2 can't be read back, so it is always rewritten when
+                // enabling and never cleared: its old value is unknown
+                if enable {
+                    let sr1 = self.read_status()?;
+                    self.write_qe(&[NOR_CMD_WRSR, sr1, 0x02])?;
+                }
+                Ok(false)
+            }
+            QuadEnableMethod::Sr2Bit1ReadSr2 => {
+                let sr1 = self.read_status()?;
+                let sr2 = self.read_opcode(0x35)?;
+                let new = update(sr2, 0x02);
+                if new == sr2 {
+                    return Ok(false);
+                }
+                self.write_qe(&[NOR_CMD_WRSR, sr1, new])?;
+                Ok(true)
+            }
+            QuadEnableMethod::Sr2Bit1WriteSr2 => {
+                let sr2 = self.read_opcode(0x35)?;
+                let new = update(sr2, 0x02);
+                if new == sr2 {
+                    return Ok(false);
+                }
+                self.write_qe(&[0x31, new])?;
+                Ok(true)
+            }
+            QuadEnableMethod::Sr2Bit7 => {
+                let sr2 = self.read_opcode(0x3F)?;
+                let new = update(sr2, 0x80);
+                if new == sr2 {
+                    return Ok(false);
+                }
+                self.write_qe(&[0x3E, new])?;
+                Ok(true)
+            }
+        }
+    }
+
+    fn read_opcode(&mut self, opcode: u8) -> Result<u8> {
+        let data = self.programmer.spi_transaction(&[opcode], 1)?;
+        Ok(data[0])
+    }
+
+    fn write_qe(&mut self, command: &[u8]) -> Result<()> {
+        log::debug!("Writing QE bit on {}", self.spec.name);
+        self.write_enable()?;
+        self.programmer.spi_transaction_write(command)?;
+        self.wait_ready()
+    }
+
+    /// Fast-read `len` bytes at `address` on `width` data lines
+    pub(super) fn read_multi_io(
+        &mut self,
+        address: u32,
+        len: usize,
+        width: BusWidth,
+    ) -> Result<Vec<u8>> {
+        let opcode = match width {
+            BusWidth::Quad => {
+                self.enable_quad()?;
+                self.opcode(CMD_NOR_QUAD_READ, CMD_NOR_QUAD_READ_4B)
+            }
+            _ => self.opcode(CMD_NOR_DUAL_READ, CMD_NOR_DUAL_READ_4B),
+        };
+        let address = self.addr_to_bytes(address);
+        let command = MultiIoCommand {
+            opcode,
+            opcode_width: BusWidth::Single,
+            address: &address,
+            address_width: BusWidth::Single,
+            dummy_cycles: FAST_READ_DUMMY_CYCLES,
+            data_width: width,
+        };
+        self.programmer.spi_multi_io_read(&command, len)
+    }
+
+    /// Whether pages can be programmed on four lines
+    pub(super) fn quad_program_enabled(&self) -> bool {
+        self.spec.capabilities.quad_program != QuadProgram::None
+            && self.data_width() == BusWidth::Quad
+    }
+
+    /// Quad page program. The caller enables QE beforehand (setting it
+    /// clears WEL), then handles write enable and page splitting.
+    pub(super) fn program_quad(&mut self, address: u32, data: &[u8]) -> Result<()> {
+        let (opcode, address_width) = match self.spec.capabilities.quad_program {
+            QuadProgram::Io => (
+                self.opcode(
+                    CMD_NOR_QUAD_IO_PAGE_PROGRAM,
+                    CMD_NOR_QUAD_IO_PAGE_PROGRAM_4B,
+                ),
+                BusWidth::Quad,
+            ),
+            QuadProgram::Input => (
+                self.opcode(CMD_NOR_QUAD_PAGE_PROGRAM, CMD_NOR_QUAD_PAGE_PROGRAM_4B),
+                BusWidth::Single,
+            ),
+            QuadProgram::None => {
+                return Err(Error::NotSupported(format!(
+                    "{} has no quad page program",
+                    self.spec.name
+                )))
+            }
+        };
+        let address = self.addr_to_bytes(address);
+        let command = MultiIoCommand {
+            opcode,
+            opcode_width: BusWidth::Single,
+            address: &address,
+            address_width,
+            dummy_cycles: 0,
+            data_width: BusWidth::Quad,
+        };
+        self.programmer.spi_multi_io_write(&command, data)
+    }
+}
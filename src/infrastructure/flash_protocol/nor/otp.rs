@@ -0,0 +1,230 @@
+//! SPI NOR OTP / Security Register Access
+//!
+//! Vendors expose their one-time programmable areas in one of two ways,
+//! selected by [`OtpLayout::enter_opcode`]:
+//!
+//! - **Security registers** (0x48): Winbond style. Region `n` lives
+//!   at address `(n + 1) << 12` and is read/programmed/erased with
+//!   0x48/0x42/0x44. Lock bits LB1-LB3 sit in status register 2.
+//! - **OTP array** (0x4B): Spansion style. One flat array of 32-byte regions
+//!   read with 0x4B and programmed with 0x42. It cannot be erased; bit `n` of
+//!   the lock word at 0x10-0x13 locks region `n` when programmed to zero.
+//!   Bytes 0x00-0x13 (factory data and lock word) are never programmed as
+//!   data.
+
+use super::SpiNor;
+use crate::domain::chip::OtpLayout;
+use crate::error::{Error, Result};
+use crate::infrastructure::flash_protocol::commands::*;
+use crate::infrastructure::programmer::Programmer;
+
+/// Address of the lock word in a Spansion OTP array
+const OTP_ARRAY_LOCK_OFFSET: u32 = 0x10;
+
+/// End of the factory bytes (0x00-0x0F) and lock word at the start of a
+/// Spansion OTP array, which only `lock_otp` may program
+const OTP_ARRAY_RESERVED_END: u32 = OTP_ARRAY_LOCK_OFFSET + 4;
+
+/// Regions the 32-bit lock word of an OTP array can lock
+const OTP_ARRAY_LOCK_BITS: u32 = 32;
+
+/// Security registers can't be programmed across a 256-byte boundary
+const OTP_PAGE_SIZE: u32 = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OtpScheme {
+    SecurityRegisters,
+    OtpArray,
+}
+
+impl<P: Programmer> SpiNor<P> {
+    fn otp_layout(&self) -> Result<(OtpLayout, OtpScheme)> {
+        let layout = self.spec.otp.ok_or_else(|| {
+            Error::NotSupported(format!("{} has no OTP/security registers", self.spec.name))
+        })?;
+
+        let scheme = match layout.enter_opcode {
+            CMD_NOR_READ_SECURITY_REG => OtpScheme::SecurityRegisters,
+            CMD_NOR_READ_OTP => OtpScheme::OtpArray,
+            other => {
+                return Err(Error::NotSupported(format!(
+                    "Unknown OTP access opcode 0x{:02X}",
+                    other
+                )))
+            }
+        };
+
+        Ok((layout, scheme))
+    }
+
+    /// Validate a region/range and return the chip address of `offset`
+    fn otp_address(&self, region: u32, offset: u32, length: u32) -> Result<(OtpScheme, u32)> {
+        let (layout, scheme) = self.otp_layout()?;
+
+        if region >= layout.region_count {
+            return Err(Error::InvalidParameter(format!(
+                "OTP region {} out of range (chip has {})",
+                region, layout.region_count
+            )));
+        }
+        if offset as u64 + length as u64 > layout.region_size as u64 {
+            return Err(Error::InvalidParameter(format!(
+                "OTP access past the end of the {}-byte region",
+                layout.region_size
+            )));
+        }
+
+        let address = match scheme {
+            OtpScheme::SecurityRegisters => ((region + 1) << 12) | offset,
+            OtpScheme::OtpArray => region * layout.region_size + offset,
+        };
+        Ok((scheme, address))
+    }
+
+    fn otp_addr_bytes(address: u32) -> [u8; 3] {
+        [(address >> 16) as u8, (address >> 8) as u8, address as u8]
+    }
+
+    pub(super) fn read_otp(&mut self, region: u32, offset: u32, length: u32) -> Result<Vec<u8>> {
+        let (scheme, address) = self.otp_address(region, offset, length)?;
+        let a = Self::otp_addr_bytes(address);
+
+        match scheme {
+            OtpScheme::SecurityRegisters => self.programmer.spi_transaction(
+                &[CMD_NOR_READ_SECURITY_REG, a[0], a[1], a[2], 0x00],
+                length as usize,
+            ),
+            OtpScheme::OtpArray => self
+                .programmer
+                .spi_transaction(&[CMD_NOR_READ_OTP, a[0], a[1], a[2], 0x00], length as usize),
+        }
+    }
+
+    pub(super) fn program_otp(&mut self, region: u32, offset: u32, data: &[u8]) -> Result<()> {
+        let (scheme, address) = self.otp_address(region, offset, data.len() as u32)?;
+        if scheme == OtpScheme::OtpArray && address < OTP_ARRAY_RESERVED_END && !data.is_empty() {
+            return Err(Error::InvalidParameter(format!(
+                "OTP bytes 0x00-0x{:02X} hold the factory data and lock word; use otp lock to lock a region",
+                OTP_ARRAY_RESERVED_END - 1
+            )));
+        }
+        if self.otp_locked(region)? {
+            return Err(Error::InvalidParameter(format!(
+                "OTP region {} is locked",
+                region
+            )));
+        }
+
+        let mut written = 0usize;
+        while written < data.len() {
+            let current = address + written as u32;
+            let chunk =
+                ((OTP_PAGE_SIZE - current % OTP_PAGE_SIZE) as usize).min(data.len() - written);
+
+            let a = Self::otp_addr_bytes(current);
+            let mut tx = vec![CMD_NOR_PROGRAM_SECURITY_REG, a[0], a[1], a[2]];
+            tx.extend_from_slice(&data[written..written + chunk]);
+
+            self.write_enable()?;
+            self.programmer.spi_transaction_write(&tx)?;
+            self.wait_ready()?;
+            written += chunk;
+        }
+        Ok(())
+    }
+
+    pub(super) fn erase_otp(&mut self, region: u32) -> Result<()> {
+        let (scheme, address) = self.otp_address(region, 0, 0)?;
+        if scheme != OtpScheme::SecurityRegisters {
+            return Err(Error::NotSupported(
+                "This chip's OTP area cannot be erased".to_string(),
+            ));
+        }
+        if self.otp_locked(region)? {
+            return Err(Error::InvalidParameter(format!(
+                "OTP region {} is locked",
+                region
+            )));
+        }
+
+        let a = Self::otp_addr_bytes(address);
+        self.write_enable()?;
+        self.programmer
+            .spi_transaction_write(&[CMD_NOR_ERASE_SECURITY_REG, a[0], a[1], a[2]])?;
+        self.wait_ready()
+    }
+
+    pub(super) fn lock_otp(&mut self, region: u32) -> Result<()> {
+        let (scheme, _) = self.otp_address(region, 0, 0)?;
+
+        match scheme {
+            OtpScheme::SecurityRegisters => {
+                let lock_bit = Self::lock_bit(region)?;
+                let sr1 = self.read_status()?;
+                let sr2 = self.read_status2()?;
+                self.write_enable()?;
+                self.programmer
+                    .spi_transaction_write(&[NOR_CMD_WRSR, sr1, sr2 | lock_bit])?;
+                self.wait_ready()
+            }
+            OtpScheme::OtpArray => {
+                // Programming 0xFF leaves the other regions' bits alone
+                let (byte, mask) = Self::otp_array_lock_bit(region)?;
+                let mut tx = vec![CMD_NOR_PROGRAM_SECURITY_REG];
+                tx.extend_from_slice(&Self::otp_addr_bytes(OTP_ARRAY_LOCK_OFFSET));
+                tx.extend((0..4).map(|i| if i == byte { !mask } else { 0xFF }));
+                self.write_enable()?;
+                self.programmer.spi_transaction_write(&tx)?;
+                self.wait_ready()
+            }
+        }
+    }
+
+    pub(super) fn otp_locked(&mut self, region: u32) -> Result<bool> {
+        let (scheme, _) = self.otp_address(region, 0, 0)?;
+
+        match scheme {
+            OtpScheme::SecurityRegisters => {
+                let sr2 = self.read_status2()?;
+                Ok(sr2 & Self::lock_bit(region)? != 0)
+            }
+            OtpScheme::OtpArray => {
+                let (byte, mask) = Self::otp_array_lock_bit(region)?;
+                let a = Self::otp_addr_bytes(OTP_ARRAY_LOCK_OFFSET);
+                let lock = self
+                    .programmer
+                    .spi_transaction(&[CMD_NOR_READ_OTP, a[0], a[1], a[2], 0x00], 4)?;
+                Ok(lock[byte] & mask == 0)
+            }
+        }
+    }
+
+    /// Status register 2 lock bit (LB1-LB3) guarding security register `region`
+    fn lock_bit(region: u32) -> Result<u8> {
+        if region >= 3 {
+            return Err(Error::NotSupported(format!(
+                "No lock bit for security register {}",
+                region
+            )));
+        }
+        Ok(1 << (STATUS2_NOR_LB_SHIFT as u32 + region))
+    }
+
+    /// Byte of the OTP array lock word and bit within it locking `region`
+    fn otp_array_lock_bit(region: u32) -> Result<(usize, u8)> {
+        if region >= OTP_ARRAY_LOCK_BITS {
+            return Err(Error::NotSupported(format!(
+                "No lock bit for OTP region {}",
+                region
+            )));
+        }
+        Ok(((region / 8) as usize, 1 << (region % 8)))
+    }
+
+    fn read_status2(&mut self) -> Result<u8> {
+        let data = self
+            .programmer
+            .spi_transaction(&[CMD_NOR_READ_STATUS2], 1)?;
+        Ok(data[0])
+    }
+}
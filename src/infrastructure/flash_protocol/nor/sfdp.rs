@@ -0,0 +1,390 @@
+//! SFDP (Serial Flash Discoverable Parameters) Support
+//!
+//! Reads the JESD216 SFDP table (opcode 0x5A) and decodes the Basic Flash
+//! Parameter Table so that NOR chips missing from the database can still be
+//! programmed using the parameters they report about themselves.
+
+use crate::domain::chip::{
+    ChipCapabilities, ChipLayout, ChipSpec, EraseType, FourByteMode, QuadEnableMethod,
+};
+use crate::domain::types::{Capacity, FlashType, JedecId};
+use crate::error::{Error, Result};
+use crate::infrastructure::flash_protocol::commands::CMD_NOR_READ_SFDP;
+use crate::infrastructure::programmer::Programmer;
+
+/// "SFDP" signature, little-endian
+const SFDP_SIGNATURE: u32 = 0x5044_4653;
+
+/// Parameter ID of the Basic Flash Parameter Table
+const BFPT_ID: u16 = 0xFF00;
+
+/// Maximum number of parameter headers we are willing to walk
+const MAX_PARAM_HEADERS: usize = 32;
+
+/// Address width supported by the chip (BFPT DWORD 1, bits 18:17)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SfdpAddressMode {
+    /// 3-byte addressing only
+    ThreeByteOnly,
+    /// 3-byte by default, 4-byte available
+    ThreeOrFourByte,
+    /// 4-byte addressing only
+    FourByteOnly,
+}
+
+/// Decoded contents of the Basic Flash Parameter Table
+#[derive(Debug, Clone)]
+pub struct BasicFlashParams {
+    /// SFDP revision (major, minor)
+    pub revision: (u8, u8),
+    /// Chip density in bytes
+    pub density_bytes: u64,
+    /// Program page size in bytes
+    pub page_size: u32,
+    pub address_mode: SfdpAddressMode,
+    /// How the chip enters 4-byte addressing
+    pub four_byte_mode: FourByteMode,
+    /// Supported erase types, sorted by size
+    pub erase_types: Vec<EraseType>,
+    pub supports_dual_spi: bool,
+    pub supports_quad_spi: bool,
+    pub quad_enable: QuadEnableMethod,
+}
+
+impl BasicFlashParams {
+    /// Decode the BFPT from its raw DWORDs (little-endian, 1-based in JESD216)
+    pub fn parse(revision: (u8, u8), dwords: &[u32]) -> Result<Self> {
+        if dwords.len() < 9 {
+            return Err(Error::Other(format!(
+                "SFDP basic parameter table too short ({} DWORDs)",
+                dwords.len()
+            )));
+        }
+        let dw = |n: usize| dwords[n - 1];
+
+        // DWORD 1: address bytes and fast read modes
+        let address_mode = match (dw(1) >> 17) & 0x3 {
+            0b00 => SfdpAddressMode::ThreeByteOnly,
+            0b01 => SfdpAddressMode::ThreeOrFourByte,
+            0b10 => SfdpAddressMode::FourByteOnly,
+            _ => {
+                return Err(Error::Other(
+                    "SFDP reports a reserved address mode".to_string(),
+                ))
+            }
+        };
+        let supports_dual_spi = dw(1) & (1 << 16) != 0 || dw(1) & (1 << 20) != 0;
+        let supports_quad_spi = dw(1) & (1 << 21) != 0 || dw(1) & (1 << 22) != 0;
+
+        // DWORD 2: density in bits
+        let density_bits = if dw(2) & 0x8000_0000 != 0 {
+            let n = dw(2) & 0x7FFF_FFFF;
+            if n >= 64 {
+                return Err(Error::Other(format!(
+                    "SFDP density 2^{} bits is invalid",
+                    n
+                )));
+            }
+            1u64 << n
+        } else {
+            dw(2) as u64 + 1
+        };
+        let density_bytes = density_bits / 8;
+
+        // DWORD 8-9: erase types 1-4 (size as power of two, opcode)
+        let mut erase_types: Vec<EraseType> = [dw(8), dw(9)]
+            .iter()
+            .flat_map(|&d| [(d & 0xFF, (d >> 8) & 0xFF), ((d >> 16) & 0xFF, d >> 24)])
+            .filter(|&(size_exp, _)| size_exp != 0 && size_exp < 32)
+            .map(|(size_exp, opcode)| EraseType {
+                size: 1 << size_exp,
+                opcode: opcode as u8,
+            })
+            .collect();
+
+        // Older tables may leave DWORD 8/9 empty and only report the 4K opcode in DWORD 1
+        if erase_types.is_empty() && dw(1) & 0x3 == 0b01 {
+            erase_types.push(EraseType {
+                size: 4 * 1024,
+                opcode: ((dw(1) >> 8) & 0xFF) as u8,
+            });
+        }
+        erase_types.sort_by_key(|e| e.size);
+        erase_types.dedup_by_key(|e| e.size);
+
+        // DWORD 11 (JESD216A+): page size
+        let page_size = if dwords.len() >= 11 {
+            let exp = (dw(11) >> 4) & 0xF;
+            if exp != 0 {
+                1 << exp
+            } else {
+                256
+            }
+        } else {
+            256
+        };
+
+        // DWORD 15 (JESD216A+): quad enable requirements
+        let quad_enable = if dwords.len() >= 15 {
+            match (dw(15) >> 20) & 0x7 {
+                0b001 | 0b100 => QuadEnableMethod::Sr2Bit1WriteSr1Sr2,
+                0b010 => QuadEnableMethod::Sr1Bit6,
+                0b011 => QuadEnableMethod::Sr2Bit7,
+                0b101 => QuadEnableMethod::Sr2Bit1ReadSr2,
+                0b110 => QuadEnableMethod::Sr2Bit1WriteSr2,
+                _ => QuadEnableMethod::None,
+            }
+        } else {
+            QuadEnableMethod::None
+        };
+
+        // DWORD 16 (JESD216B+): 4-byte address entry methods, bits 31:24
+        let four_byte_mode = if dwords.len() >= 16 {
+            let entry = dw(16) >> 24;
+            if entry & (1 << 5) != 0 {
+                FourByteMode::Opcodes
+            } else if entry & (1 << 0) != 0 {
+                FourByteMode::EnterExit {
+                    write_enable: false,
+                }
+            } else if entry & (1 << 1) != 0 {
+                FourByteMode::EnterExit { write_enable: true }
+            } else if entry & (1 << 2) != 0 {
+                FourByteMode::ExtendedAddress
+            } else {
+                FourByteMode::Opcodes
+            }
+        } else {
+            FourByteMode::Opcodes
+        };
+
+        Ok(Self {
+            revision,
+            density_bytes,
+            page_size,
+            address_mode,
+            four_byte_mode,
+            erase_types,
+            supports_dual_spi,
+            supports_quad_spi,
+            quad_enable,
+        })
+    }
+
+    /// Build a chip specification from the decoded parameters
+    pub fn to_chip_spec(&self, jedec_id: JedecId, manufacturer: &str) -> Result<ChipSpec> {
+        let capacity = u32::try_from(self.density_bytes).map_err(|_| {
+            Error::NotSupported(format!(
+                "SFDP density of {} bytes exceeds the supported range",
+                self.density_bytes
+            ))
+        })?;
+
+        // Use the 64K erase as the nominal block size when available,
+        // otherwise the largest erase the chip offers.
+        let block_size = self
+            .erase_types
+            .iter()
+            .find(|e| e.size == 64 * 1024)
+            .or(self.erase_types.last())
+            .map(|e| e.size)
+            .unwrap_or(64 * 1024);
+
+        let mut erase_types = [None; 4];
+        for (slot, erase) in erase_types.iter_mut().zip(self.erase_types.iter()) {
+            *slot = Some(*erase);
+        }
+
+        let supports_4byte_addr = match self.address_mode {
+            SfdpAddressMode::ThreeByteOnly => false,
+            SfdpAddressMode::ThreeOrFourByte => capacity > 16 * 1024 * 1024,
+            SfdpAddressMode::FourByteOnly => true,
+        };
+
+        Ok(ChipSpec {
+            name: format!("SFDP {}", jedec_id),
+            manufacturer: manufacturer.to_string(),
+            jedec_id,
+            flash_type: FlashType::Nor,
+            capacity: Capacity::bytes(capacity),
+            layout: ChipLayout {
+                page_size: self.page_size,
+                block_size,
+                oob_size: None,
+                is_dataflash: false,
+                die_count: 1,
+                oob_layout: None,
+            },
+            capabilities: ChipCapabilities {
+                supports_4byte_addr,
+                four_byte_mode: self.four_byte_mode,
+                supports_quad_spi: self.supports_quad_spi,
+                supports_dual_spi: self.supports_dual_spi,
+                erase_types,
+                quad_enable: self.quad_enable,
+                ..Default::default()
+            },
+            otp: None,
+        })
+    }
+}
+
+/// Read `len` bytes of the SFDP address space
+fn read_sfdp_bytes<P: Programmer + ?Sized>(
+    programmer: &mut P,
+    addr: u32,
+    len: usize,
+) -> Result<Vec<u8>> {
+    let cmd = [
+        CMD_NOR_READ_SFDP,
+        (addr >> 16) as u8,
+        (addr >> 8) as u8,
+        addr as u8,
+        0x00, // Dummy byte
+    ];
+    programmer.spi_transaction(&cmd, len)
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Read and decode the Basic Flash Parameter Table of the connected chip
+pub fn read_basic_params<P: Programmer + ?Sized>(programmer: &mut P) -> Result<BasicFlashParams> {
+    let header = read_sfdp_bytes(programmer, 0, 8)?;
+    if header.len() < 8 || le_u32(&header) != SFDP_SIGNATURE {
+        return Err(Error::NotSupported(
+            "Chip does not provide an SFDP table".to_string(),
+        ));
+    }
+    let revision = (header[5], header[4]);
+    let num_headers = (header[6] as usize + 1).min(MAX_PARAM_HEADERS);
+
+    let param_headers = read_sfdp_bytes(programmer, 8, num_headers * 8)?;
+    let bfpt = param_headers
+        .chunks_exact(8)
+        .find(|h| u16::from_le_bytes([h[0], h[7]]) == BFPT_ID)
+        .ok_or_else(|| {
+            Error::NotSupported("SFDP table has no basic parameter table".to_string())
+        })?;
+
+    let length_dwords = bfpt[3] as usize;
+    let pointer = u32::from_le_bytes([bfpt[4], bfpt[5], bfpt[6], 0]);
+    log::debug!(
+        "SFDP v{}.{}: BFPT v{}.{} at 0x{:06X}, {} DWORDs",
+        revision.0,
+        revision.1,
+        bfpt[2],
+        bfpt[1],
+        pointer,
+        length_dwords
+    );
+
+    let table = read_sfdp_bytes(programmer, pointer, length_dwords * 4)?;
+    let dwords: Vec<u32> = table.chunks_exact(4).map(le_u32).collect();
+
+    BasicFlashParams::parse(revision, &dwords)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// BFPT of a W25Q128JV-like 16MB part (JESD216B, 16 DWORDs)
+    fn sample_bfpt() -> Vec<u32> {
+        vec![
+            0xFFF1_20E5, // 4K erase 0x20, 1-1-2/1-2-2/1-4-4/1-1-4, 3-byte only
+            0x07FF_FFFF, // 128 Mbit
+            0x6B08_EB44,
+            0x3B42_BB08,
+            0xFFFF_FFEE,
+            0xFF00_FFFF,
+            0xEB44_FFFF,
+            0x520F_200C, // 4K/0x20, 32K/0x52
+            0xFF00_D810, // 64K/0xD8
+            0x0000_0000,
+            0x0000_0080, // Page size 2^8
+            0x0000_0000,
+            0x0000_0000,
+            0x0000_0000,
+            0x0040_0000, // QER = 100 (SR2 bit 1, written with SR1)
+            0x0000_0000,
+        ]
+    }
+
+    #[test]
+    fn test_parse_basic_params() {
+        let params = BasicFlashParams::parse((1, 6), &sample_bfpt()).unwrap();
+        assert_eq!(params.density_bytes, 16 * 1024 * 1024);
+        assert_eq!(params.page_size, 256);
+        assert_eq!(params.address_mode, SfdpAddressMode::ThreeByteOnly);
+        assert!(params.supports_dual_spi);
+        assert!(params.supports_quad_spi);
+        assert_eq!(params.quad_enable, QuadEnableMethod::Sr2Bit1WriteSr1Sr2);
+        assert_eq!(
+            params.erase_types,
+            vec![
+                EraseType {
+                    size: 4 * 1024,
+                    opcode: 0x20
+                },
+                EraseType {
+                    size: 32 * 1024,
+                    opcode: 0x52
+                },
+                EraseType {
+                    size: 64 * 1024,
+                    opcode: 0xD8
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_chip_spec() {
+        let params = BasicFlashParams::parse((1, 6), &sample_bfpt()).unwrap();
+        let spec = params
+            .to_chip_spec(JedecId::new([0xEF, 0x70, 0x18]), "Winbond")
+            .unwrap();
+        assert_eq!(spec.flash_type, FlashType::Nor);
+        assert_eq!(spec.capacity.as_megabytes(), 16);
+        assert_eq!(spec.layout.block_size, 64 * 1024);
+        assert!(!spec.capabilities.supports_4byte_addr);
+        assert_eq!(
+            spec.capabilities.erase_types[0],
+            Some(EraseType {
+                size: 4 * 1024,
+                opcode: 0x20
+            })
+        );
+        assert_eq!(spec.capabilities.erase_types[3], None);
+    }
+
+    #[test]
+    fn test_parse_power_of_two_density_and_4byte() {
+        let mut bfpt = sample_bfpt();
+        bfpt[0] = (bfpt[0] & !(0x3 << 17)) | (0b01 << 17);
+        bfpt[1] = 0x8000_0000 | 28; // 2^28 bits = 32MB
+        let params = BasicFlashParams::parse((1, 6), &bfpt).unwrap();
+        assert_eq!(params.density_bytes, 32 * 1024 * 1024);
+
+        let spec = params
+            .to_chip_spec(JedecId::new([0xEF, 0x40, 0x19]), "Winbond")
+            .unwrap();
+        assert!(spec.capabilities.supports_4byte_addr);
+        assert_eq!(spec.capabilities.four_byte_mode, FourByteMode::Opcodes);
+
+        // Only B7h with WREN and the EAR advertised
+        bfpt[15] = 0x0600_0000;
+        let params = BasicFlashParams::parse((1, 6), &bfpt).unwrap();
+        assert_eq!(
+            params.four_byte_mode,
+            FourByteMode::EnterExit { write_enable: true }
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_short_table() {
+        assert!(BasicFlashParams::parse((1, 0), &[0; 4]).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+//! SPI NOR Status / Configuration Registers
+//!
+//! Register definitions come from the chip database
+//! ([`ChipCapabilities::status_registers`]). Registers marked
+//! [`StatusWrite::WithSr1`] are written together with SR1 in one multi-byte
+//! 0x01 command; registers with their own opcode are written individually.
+//!
+//! [`ChipCapabilities::status_registers`]: crate::domain::ChipCapabilities
+
+use super::SpiNor;
+use crate::domain::chip::{StatusRegister, StatusWrite, GENERIC_SR1};
+use crate::error::{Error, Result};
+use crate::infrastructure::programmer::Programmer;
+
+impl<P: Programmer> SpiNor<P> {
+    /// The chip's status registers, SR1 first
+    pub fn status_registers(&self) -> &'static [StatusRegister] {
+        match self.spec.capabilities.status_registers {
+            [] => std::slice::from_ref(&GENERIC_SR1),
+            registers => registers,
+        }
+    }
+
+    pub(super) fn read_register(&mut self, register: &StatusRegister) -> Result<u8> {
+        let data = self
+            .programmer
+            .spi_transaction(&[register.read_opcode], 1)?;
+        Ok(data[0])
+    }
+
+    pub(super) fn write_status_registers(&mut self, values: &[u8]) -> Result<()> {
+        let registers = self.status_registers();
+        if values.is_empty() {
+            return Ok(());
+        }
+        if values.len() > registers.len() {
+            return Err(Error::InvalidParameter(format!(
+                "{} has {} status register(s), got {} value(s)",
+                self.spec.name,
+                registers.len(),
+                values.len()
+            )));
+        }
+
+        // SR1 plus every register that rides along in the same 0x01 write
+        let combined = 1 + registers[1..]
+            .iter()
+            .take_while(|reg| reg.write == StatusWrite::WithSr1)
+            .count();
+
+        let wrsr = match registers[0].write {
+            StatusWrite::Opcode(opcode) => opcode,
+            _ => {
+                return Err(Error::NotSupported(format!(
+                    "{} is not writable",
+                    registers[0].name
+                )))
+            }
+        };
+        let mut command = vec![wrsr];
+        command.extend_from_slice(&values[..values.len().min(combined)]);
+        // Fill in registers the caller left out so a short write can't clear them
+        for register in &registers[command.len() - 1..combined] {
+            let current = self.read_register(register)?;
+            command.push(current);
+        }
+        self.write_enable()?;
+        self.programmer.spi_transaction_write(&command)?;
+        self.wait_ready()?;
+
+        for (register, &value) in registers.iter().zip(values).skip(combined) {
+            match register.write {
+                StatusWrite::Opcode(opcode) => {
+                    self.write_enable()?;
+                    self.programmer.spi_transaction_write(&[opcode, value])?;
+                    self.wait_ready()?;
+                }
+                StatusWrite::WithSr1 | StatusWrite::ReadOnly => {
+                    log::debug!("Skipping read-only register {}", register.name);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
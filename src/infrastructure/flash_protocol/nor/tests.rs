@@ -3,12 +3,16 @@
 //! These tests verify the NOR protocol implementation using mock programmers.
 
 use crate::domain::bad_block::BadBlockStrategy;
-use crate::domain::chip::{ChipCapabilities, ChipLayout, ChipSpec};
+use crate::domain::chip::{
+    ChipCapabilities, ChipLayout, ChipSpec, FourByteMode, OtpLayout, QuadEnableMethod, QuadProgram,
+};
 use crate::domain::types::{Capacity, FlashType, JedecId};
-use crate::domain::{Address, FlashOperation, OobMode, Progress, ReadRequest};
+use crate::domain::{
+    Address, EraseRequest, FlashOperation, OobMode, Progress, ReadRequest, WriteMode, WriteRequest,
+};
 use crate::infrastructure::flash_protocol::nor::SpiNor;
 use crate::infrastructure::programmer::mock::MockProgrammer;
-use crate::infrastructure::programmer::Programmer; // Import trait for method access
+use crate::infrastructure::programmer::{BusWidth, Programmer}; // Import trait for method access
 use std::cell::RefCell;
 
 /// Create a test chip spec for a typical NOR flash (W25Q64)
@@ -24,6 +28,8 @@ fn create_test_nor_spec() -> ChipSpec {
             block_size: 64 * 1024, // 64KB
             oob_size: None,
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities::default(),
         otp: None,
@@ -53,10 +59,12 @@ fn test_nor_read_basic() {
         length: 4,
         use_ecc: false,
         ignore_ecc_errors: false,
+        soft_ecc: None,
         oob_mode: OobMode::None,
         bad_block_strategy: BadBlockStrategy::Fail,
         bbt: None,
         retry_count: 0,
+        partitions: Vec::new(),
     };
 
     // Execute read
@@ -86,10 +94,12 @@ fn test_nor_read_progress_callback() {
         length: 8190, // Two chunks worth
         use_ecc: false,
         ignore_ecc_errors: false,
+        soft_ecc: None,
         oob_mode: OobMode::None,
         bad_block_strategy: BadBlockStrategy::Fail,
         bbt: None,
         retry_count: 0,
+        partitions: Vec::new(),
     };
 
     // Track progress calls using RefCell for interior mutability
@@ -143,3 +153,596 @@ fn test_mock_programmer_transactions() {
     let writes = mock.get_writes();
     assert!(!writes.is_empty());
 }
+
+#[test]
+fn test_nor_erase_uses_4k_sector_erase() {
+    let mock = MockProgrammer::new();
+    let spec = create_test_nor_spec();
+
+    // WREN, erase command, RDSR command, then the status byte (not busy)
+    mock.expect_reads(vec![vec![0xFF], vec![0xFF; 4], vec![0xFF], vec![0x00]]);
+
+    let mut nor = SpiNor::new(mock, spec);
+    let request = EraseRequest {
+        address: Address::new(0x3000),
+        length: 4096,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        mark_bad: false,
+        partitions: Vec::new(),
+    };
+    nor.erase(request, &|_| {}).unwrap();
+
+    let writes = nor.programmer_mut().get_writes();
+    assert_eq!(writes[0], vec![0x06]);
+    assert_eq!(writes[1], vec![0x20, 0x00, 0x30, 0x00]);
+}
+
+#[test]
+fn test_nor_patch_programs_without_erase_when_possible() {
+    let mock = MockProgrammer::new();
+    let spec = create_test_nor_spec();
+
+    // Read back the 4K unit (two chunks), all blank
+    mock.expect_reads(vec![
+        vec![0xFF; 5],
+        vec![0xFF; 4095],
+        vec![0xFF; 5],
+        vec![0xFF; 1],
+    ]);
+    // WREN, page program command, page data, RDSR command, status (not busy)
+    mock.expect_reads(vec![
+        vec![0xFF],
+        vec![0xFF; 4],
+        vec![0xFF; 256],
+        vec![0xFF],
+        vec![0x00],
+    ]);
+
+    let mut nor = SpiNor::new(mock, spec);
+    let data = [0x00u8; 16];
+    let request = WriteRequest {
+        address: Address::new(0x10),
+        data: &data,
+        use_ecc: false,
+        verify: false,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        mode: WriteMode::Patch,
+        partitions: Vec::new(),
+    };
+    nor.write(request, &|_| {}).unwrap();
+
+    let writes = nor.programmer_mut().get_writes();
+    // Only 1 -> 0 transitions, so no erase command is issued
+    assert!(!writes.iter().any(|w| matches!(w[0], 0x20 | 0x52 | 0xD8)));
+    let programs: Vec<_> = writes.iter().filter(|w| w[0] == 0x02).collect();
+    assert_eq!(programs.len(), 1);
+    assert_eq!(programs[0], &vec![0x02, 0x00, 0x00, 0x00]);
+}
+
+#[test]
+fn test_nor_delta_skips_matching_unit() {
+    // The mock returns 0xFF for every read, so a blank image already matches
+    let mock = MockProgrammer::new();
+    let spec = create_test_nor_spec();
+    let mut nor = SpiNor::new(mock, spec);
+
+    let data = [0xFFu8; 512];
+    let request = WriteRequest {
+        address: Address::new(0x1000),
+        data: &data,
+        use_ecc: false,
+        verify: false,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        mode: WriteMode::Delta,
+        partitions: Vec::new(),
+    };
+    let report = nor.write(request, &|_| {}).unwrap();
+
+    assert_eq!(report.units_total, 1);
+    assert_eq!(report.units_skipped, 1);
+    assert_eq!(report.pages_programmed, 0);
+
+    // No write enable means no erase or program was issued
+    let writes = nor.programmer_mut().get_writes();
+    assert!(!writes.iter().any(|w| w == &vec![0x06]));
+}
+
+#[test]
+fn test_nor_delta_erases_changed_64k_block_at_once() {
+    let mock = MockProgrammer::new();
+    let spec = create_test_nor_spec();
+
+    // Read back the sixteen 4K units of the block (two chunks each), all zero
+    for _ in 0..16 {
+        mock.expect_reads(vec![
+            vec![0xFF; 5],
+            vec![0x00; 4095],
+            vec![0xFF; 5],
+            vec![0x00; 1],
+        ]);
+    }
+    // WREN, erase command, RDSR command, status (not busy)
+    mock.expect_reads(vec![vec![0xFF], vec![0xFF; 4], vec![0xFF], vec![0x00]]);
+
+    let mut nor = SpiNor::new(mock, spec);
+    let data = vec![0xFFu8; 64 * 1024];
+    let request = WriteRequest {
+        address: Address::new(0x10000),
+        data: &data,
+        use_ecc: false,
+        verify: false,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        mode: WriteMode::Delta,
+        partitions: Vec::new(),
+    };
+    let report = nor.write(request, &|_| {}).unwrap();
+
+    assert_eq!(report.units_total, 16);
+    assert_eq!(report.units_erased, 16);
+    assert_eq!(report.pages_programmed, 0);
+
+    // One 64K erase instead of sixteen 4K ones
+    let writes = nor.programmer_mut().get_writes();
+    let erases: Vec<_> = writes
+        .iter()
+        .filter(|w| matches!(w[0], 0x20 | 0x52 | 0xD8))
+        .collect();
+    assert_eq!(erases, vec![&vec![0xD8, 0x01, 0x00, 0x00]]);
+}
+
+#[test]
+fn test_nor_otp_read_security_register() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_nor_spec();
+    spec.otp = Some(OtpLayout {
+        region_count: 3,
+        region_size: 256,
+        enter_opcode: 0x48,
+        exit_opcode: 0x00,
+    });
+
+    mock.expect_reads(vec![vec![0xFF; 5], vec![0x12, 0x34]]);
+    let mut nor = SpiNor::new(mock, spec);
+
+    // Region 1 is Winbond security register #2 at 0x002000
+    assert_eq!(nor.otp_read(1, 0x10, 2).unwrap(), vec![0x12, 0x34]);
+    let writes = nor.programmer_mut().get_writes();
+    assert_eq!(writes[0], vec![0x48, 0x00, 0x20, 0x10, 0x00]);
+
+    assert!(nor.otp_read(3, 0, 1).is_err());
+    assert!(nor.otp_read(0, 0xF0, 0x20).is_err());
+}
+
+#[test]
+fn test_nor_otp_array_locks_single_region() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_nor_spec();
+    spec.otp = Some(OtpLayout {
+        region_count: 32,
+        region_size: 32,
+        enter_opcode: 0x4B,
+        exit_opcode: 0x00,
+    });
+    // WREN, program, then a ready status
+    mock.expect_reads(vec![vec![0xFF], vec![0xFF], vec![0xFF], vec![0x00]]);
+    let mut nor = SpiNor::new(mock, spec);
+
+    // Only bit 1 of the lock word is programmed
+    nor.otp_lock(1).unwrap();
+    let writes = nor.programmer_mut().get_writes();
+    assert_eq!(
+        writes[1],
+        vec![0x42, 0x00, 0x00, 0x10, 0xFD, 0xFF, 0xFF, 0xFF]
+    );
+
+    // The lock word now reads back with bit 1 clear
+    let lock_word = vec![0xFD, 0xFF, 0xFF, 0xFF];
+    nor.programmer_mut().expect_reads(vec![
+        vec![0xFF; 5],
+        lock_word.clone(),
+        vec![0xFF; 5],
+        lock_word.clone(),
+        vec![0xFF], // WREN
+        vec![0xFF], // Program
+        vec![0xFF],
+        vec![0x00], // Ready
+        vec![0xFF; 5],
+        lock_word,
+    ]);
+    assert!(!nor.otp_is_locked(2).unwrap());
+
+    // Region 2 (0x40) is still writable, region 1 is not
+    nor.otp_program(2, 0, &[0xA5]).unwrap();
+    let writes = nor.programmer_mut().get_writes();
+    assert!(writes.contains(&vec![0x42, 0x00, 0x00, 0x40, 0xA5]));
+    assert!(nor.otp_program(1, 0, &[0xA5]).is_err());
+}
+
+#[test]
+fn test_nor_otp_array_refuses_lock_word_writes() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_nor_spec();
+    spec.otp = Some(OtpLayout {
+        region_count: 32,
+        region_size: 32,
+        enter_opcode: 0x4B,
+        exit_opcode: 0x00,
+    });
+    let mut nor = SpiNor::new(mock, spec);
+
+    // The lock word and the factory bytes before it are not data
+    for (offset, len) in [(0x10, 4), (0x12, 1), (0x00, 1), (0x0C, 8)] {
+        let err = nor
+            .otp_program(0, offset, &vec![0x00; len])
+            .expect_err("Program over the lock word succeeded");
+        assert!(err.to_string().contains("lock word"));
+    }
+    assert!(nor.programmer_mut().get_writes().is_empty());
+}
+
+#[test]
+fn test_nor_status_registers_read_and_write() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_nor_spec();
+    spec.capabilities.status_registers =
+        crate::infrastructure::chip_database::nor::winbond::STATUS_REGISTERS;
+
+    mock.expect_reads(vec![
+        vec![0xFF],
+        vec![0x1C], // SR1 (0x05)
+        vec![0xFF],
+        vec![0x02], // SR2 (0x35)
+        vec![0xFF],
+        vec![0x60], // SR3 (0x15)
+    ]);
+    let mut nor = SpiNor::new(mock, spec);
+    assert_eq!(nor.get_status().unwrap(), vec![0x1C, 0x02, 0x60]);
+
+    // Writing SR1 alone must carry the current SR2 so QE isn't cleared
+    nor.programmer_mut().reset();
+    nor.programmer_mut().expect_reads(vec![
+        vec![0xFF],
+        vec![0x02], // SR2 read-back
+        vec![0xFF], // WREN
+        vec![0xFF], // WRSR
+        vec![0xFF],
+        vec![0x00], // ready
+    ]);
+    nor.set_status(&[0x00]).unwrap();
+    let writes = nor.programmer_mut().get_writes();
+    assert!(writes.contains(&vec![0x01, 0x00, 0x02]));
+
+    // SR3 has its own write opcode
+    nor.programmer_mut().reset();
+    nor.programmer_mut().expect_reads(vec![
+        vec![0xFF], // WREN
+        vec![0xFF], // WRSR
+        vec![0xFF],
+        vec![0x00], // ready
+        vec![0xFF], // WREN
+        vec![0xFF], // WRSR3
+        vec![0xFF],
+        vec![0x00], // ready
+    ]);
+    nor.set_status(&[0x00, 0x02, 0x40]).unwrap();
+    let writes = nor.programmer_mut().get_writes();
+    assert!(writes.contains(&vec![0x01, 0x00, 0x02]));
+    assert!(writes.contains(&vec![0x11, 0x40]));
+
+    assert!(nor.set_status(&[0; 4]).is_err());
+}
+
+#[test]
+fn test_nor_locked_blocks_with_wps() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_nor_spec();
+    spec.capacity = Capacity::bytes(256 * 1024);
+    spec.capabilities.status_registers =
+        crate::infrastructure::chip_database::nor::winbond::STATUS_REGISTERS;
+    spec.capabilities.wps = Some(crate::infrastructure::chip_database::nor::winbond::WPS);
+
+    mock.expect_reads(vec![
+        vec![0xFF],
+        vec![0x04], // SR3: WPS set
+        vec![0xFF],
+        vec![0x01], // block 0x10000 locked
+        vec![0xFF],
+        vec![0x00], // block 0x20000 unlocked
+    ]);
+    let mut nor = SpiNor::new(mock, spec);
+
+    // 16 sectors + 2 blocks + 16 sectors
+    assert_eq!(nor.block_lock_units().len(), 34);
+
+    let locked = nor.locked_blocks(0x10000..0x30000).unwrap();
+    assert_eq!(locked, vec![0x10000..0x20000]);
+    let writes = nor.programmer_mut().get_writes();
+    assert!(writes.contains(&vec![0x3D, 0x01, 0x00, 0x00]));
+
+    // Locking must cover whole units
+    assert!(nor.lock_blocks(0x1000..0x1800).is_err());
+}
+
+/// A 32MB part using the given 4-byte addressing strategy
+fn create_test_4byte_spec(mode: FourByteMode) -> ChipSpec {
+    let mut spec = create_test_nor_spec();
+    spec.capacity = Capacity::megabytes(32);
+    spec.capabilities.supports_4byte_addr = true;
+    spec.capabilities.four_byte_mode = mode;
+    spec
+}
+
+fn read_request(address: u32, length: u32) -> ReadRequest {
+    ReadRequest {
+        address: Address::new(address),
+        length,
+        use_ecc: false,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        partitions: Vec::new(),
+    }
+}
+
+#[test]
+fn test_nor_4byte_opcodes() {
+    let mock = MockProgrammer::new();
+    mock.expect_reads(vec![vec![0xFF], vec![0xFF; 5], vec![0xFF], vec![0x00]]);
+    let mut nor = SpiNor::new(mock, create_test_4byte_spec(FourByteMode::Opcodes));
+
+    let request = EraseRequest {
+        address: Address::new(0x0100_0000),
+        length: 4096,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        mark_bad: false,
+        partitions: Vec::new(),
+    };
+    nor.erase(request, &|_| {}).unwrap();
+
+    let writes = nor.programmer_mut().get_writes();
+    assert_eq!(writes[0], vec![0x06]);
+    assert_eq!(writes[1], vec![0x21, 0x01, 0x00, 0x00, 0x00]);
+    assert!(!writes.contains(&vec![0xB7]));
+}
+
+#[test]
+fn test_nor_4byte_mode_entered_and_exited() {
+    let mock = MockProgrammer::new();
+    mock.expect_reads(vec![vec![0xFF], vec![0xFF; 6], vec![0xDE, 0xAD]]);
+    let mut nor = SpiNor::new(
+        mock,
+        create_test_4byte_spec(FourByteMode::EnterExit {
+            write_enable: false,
+        }),
+    );
+
+    let data = nor.read(read_request(0x0100_0000, 2), &|_| {}).unwrap();
+    assert_eq!(data, vec![0xDE, 0xAD]);
+
+    // Legacy fast read with a 4-byte address, then back to 3-byte mode
+    let writes = nor.programmer_mut().get_writes();
+    assert_eq!(
+        writes,
+        vec![
+            vec![0xB7],
+            vec![0x0B, 0x01, 0x00, 0x00, 0x00, 0x00],
+            vec![0xFF, 0xFF],
+            vec![0xE9],
+        ]
+    );
+}
+
+#[test]
+fn test_nor_extended_address_register_banks() {
+    let mock = MockProgrammer::new();
+    let mut nor = SpiNor::new(mock, create_test_4byte_spec(FourByteMode::ExtendedAddress));
+
+    // Crosses from bank 0 into bank 1
+    let data = nor.read(read_request(0x00FF_FFFE, 4), &|_| {}).unwrap();
+    assert_eq!(data.len(), 4);
+
+    let commands: Vec<Vec<u8>> = nor
+        .programmer_mut()
+        .get_writes()
+        .into_iter()
+        .filter(|w| w[0] != 0xFF)
+        .collect();
+    assert_eq!(
+        commands,
+        vec![
+            vec![0x06],
+            vec![0xC5, 0x00],
+            vec![0x0B, 0xFF, 0xFF, 0xFE, 0x00],
+            vec![0x06],
+            vec![0xC5, 0x01],
+            vec![0x0B, 0x00, 0x00, 0x00, 0x00],
+            // Bank 0 restored for the boot ROM
+            vec![0x06],
+            vec![0xC5, 0x00],
+        ]
+    );
+}
+
+#[test]
+fn test_nor_multi_die_select() {
+    let mock = MockProgrammer::new();
+    let mut spec = create_test_4byte_spec(FourByteMode::EnterExit {
+        write_enable: false,
+    });
+    spec.capacity = Capacity::megabytes(64);
+    spec.layout.die_count = 2;
+    let mut nor = SpiNor::new(mock, spec);
+
+    // Crosses from die 0 into die 1
+    let data = nor.read(read_request(0x01FF_FFFE, 4), &|_| {}).unwrap();
+    assert_eq!(data.len(), 4);
+
+    let commands: Vec<Vec<u8>> = nor
+        .programmer_mut()
+        .get_writes()
+        .into_iter()
+        .filter(|w| w[0] != 0xFF)
+        .collect();
+    assert_eq!(
+        commands,
+        vec![
+            vec![0xC2, 0x00],
+            vec![0xB7],
+            vec![0x0B, 0x01, 0xFF, 0xFF, 0xFE, 0x00],
+            // Die 0 leaves 4-byte mode before die 1 is selected
+            vec![0xE9],
+            vec![0xC2, 0x01],
+            vec![0xB7],
+            vec![0x0B, 0x00, 0x00, 0x00, 0x00, 0x00],
+            vec![0xE9],
+            vec![0xC2, 0x00],
+        ]
+    );
+}
+
+#[test]
+fn test_nor_quad_read_sets_qe_once_per_session() {
+    let mock = MockProgrammer::new().with_bus_width(BusWidth::Quad);
+    let mut spec = create_test_nor_spec();
+    spec.capabilities.supports_quad_spi = true;
+    spec.capabilities.quad_enable = QuadEnableMethod::Sr2Bit1ReadSr2;
+
+    mock.expect_reads(vec![
+        vec![0xFF],
+        vec![0x00], // SR1
+        vec![0xFF],
+        vec![0x00], // SR2: QE clear
+        vec![0xFF], // WREN
+        vec![0xFF], // 0x01 write
+        vec![0xFF],
+        vec![0x00], // Not busy
+        vec![0xCA, 0xFE],
+        vec![0xBE, 0xEF],
+        vec![0xFF],
+        vec![0x00], // SR1
+        vec![0xFF],
+        vec![0x02], // SR2: QE set
+        vec![0xFF], // WREN
+        vec![0xFF], // 0x01 write
+        vec![0xFF],
+        vec![0x00], // Not busy
+    ]);
+    let mut nor = SpiNor::new(mock, spec);
+    assert_eq!(nor.data_width(), BusWidth::Quad);
+
+    let data = nor.read(read_request(0x1000, 2), &|_| {}).unwrap();
+    assert_eq!(data, vec![0xCA, 0xFE]);
+    let data = nor.read(read_request(0x2000, 2), &|_| {}).unwrap();
+    assert_eq!(data, vec![0xBE, 0xEF]);
+    nor.restore_quad().unwrap();
+
+    let writes = nor.programmer_mut().get_writes();
+    let position = |cmd: &[u8]| writes.iter().position(|w| w == cmd).unwrap();
+    let count = |cmd: &[u8]| writes.iter().filter(|w| *w == cmd).count();
+    // QE is set before the first read only, and cleared once at the end
+    assert_eq!(count(&[0x01, 0x00, 0x02]), 1);
+    assert_eq!(count(&[0x01, 0x00, 0x00]), 1);
+    let set = position(&[0x01, 0x00, 0x02]);
+    let second_read = position(&[0x6B, 0x00, 0x20, 0x00]);
+    let cleared = position(&[0x01, 0x00, 0x00]);
+    assert!(set < position(&[0x6B, 0x00, 0x10, 0x00]));
+    assert!(second_read < cleared);
+}
+
+#[test]
+fn test_nor_quad_read_keeps_qe_already_set() {
+    let mock = MockProgrammer::new().with_bus_width(BusWidth::Quad);
+    let mut spec = create_test_nor_spec();
+    spec.capabilities.supports_quad_spi = true;
+    spec.capabilities.quad_enable = QuadEnableMethod::Sr2Bit1ReadSr2;
+
+    mock.expect_reads(vec![
+        vec![0xFF],
+        vec![0x00], // SR1
+        vec![0xFF],
+        vec![0x02], // SR2: QE already set
+        vec![0xCA, 0xFE],
+    ]);
+    let mut nor = SpiNor::new(mock, spec);
+    nor.read(read_request(0x1000, 2), &|_| {}).unwrap();
+
+    let writes = nor.programmer_mut().get_writes();
+    assert!(!writes.iter().any(|w| w.first() == Some(&0x01)));
+}
+
+/// Command phase of a page program on a quad-capable chip offering
+/// `quad_program`
+fn quad_program_command(quad_program: QuadProgram) -> Vec<u8> {
+    let mock = MockProgrammer::new().with_bus_width(BusWidth::Quad);
+    let mut spec = create_test_nor_spec();
+    spec.capabilities.supports_quad_spi = true;
+    spec.capabilities.quad_program = quad_program;
+
+    // Enough "not busy" responses for WREN, the program and the status poll
+    mock.expect_reads(vec![vec![0x00]; 5]);
+    let mut nor = SpiNor::new(mock, spec);
+    let request = WriteRequest {
+        address: Address::new(0x100),
+        data: &[0x12; 4],
+        use_ecc: false,
+        verify: false,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        mode: WriteMode::Direct,
+        partitions: Vec::new(),
+    };
+    nor.write(request, &|_| {}).unwrap();
+
+    let writes = nor.programmer_mut().get_writes();
+    let data = writes.iter().position(|w| w == &[0x12; 4]).unwrap();
+    writes[data - 1].clone()
+}
+
+#[test]
+fn test_nor_quad_program_follows_chip_capability() {
+    assert_eq!(
+        quad_program_command(QuadProgram::Input),
+        [0x32, 0x00, 0x01, 0x00]
+    );
+    assert_eq!(
+        quad_program_command(QuadProgram::Io),
+        [0x38, 0x00, 0x01, 0x00]
+    );
+    // Without a quad program the page goes out on one line
+    assert_eq!(
+        quad_program_command(QuadProgram::None),
+        [0x02, 0x00, 0x01, 0x00]
+    );
+}
+
+#[test]
+fn test_nor_single_bus_without_programmer_support() {
+    let mut spec = create_test_nor_spec();
+    spec.capabilities.supports_quad_spi = true;
+    let nor = SpiNor::new(MockProgrammer::new(), spec);
+    assert_eq!(nor.data_width(), BusWidth::Single);
+}
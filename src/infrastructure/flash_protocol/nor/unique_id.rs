@@ -0,0 +1,29 @@
+//! SPI NOR Factory Unique ID
+//!
+//! Winbond and GigaDevice parts return a factory-programmed serial number in
+//! response to 0x4B followed by four dummy bytes. Winbond IDs are 64 bits,
+//! GigaDevice IDs 128 bits.
+
+use super::SpiNor;
+use crate::error::{Error, Result};
+use crate::infrastructure::flash_protocol::commands::CMD_NOR_READ_UNIQUE_ID;
+use crate::infrastructure::programmer::Programmer;
+
+impl<P: Programmer> SpiNor<P> {
+    /// Read the factory unique ID using the manufacturer's rules
+    pub fn read_unique_id(&mut self) -> Result<Vec<u8>> {
+        let length = match self.spec.jedec_id.manufacturer {
+            0xEF => 8,  // Winbond
+            0xC8 => 16, // GigaDevice
+            _ => {
+                return Err(Error::NotSupported(format!(
+                    "Unique ID read not supported for {} NOR flash",
+                    self.spec.manufacturer
+                )))
+            }
+        };
+
+        self.programmer
+            .spi_transaction(&[CMD_NOR_READ_UNIQUE_ID, 0x00, 0x00, 0x00, 0x00], length)
+    }
+}
@@ -6,7 +6,7 @@
 pub mod mpsse;
 
 use crate::error::{Error, Result};
-use crate::infrastructure::programmer::traits::Programmer;
+use crate::infrastructure::programmer::traits::{BusWidth, MultiIoCommand, Programmer};
 use futures_lite::future::block_on;
 use log::debug;
 use nusb::transfer::{ControlType, Recipient, RequestBuffer};
@@ -42,6 +42,13 @@ const PIN_TDI: u8 = 1 << 1;
 const PIN_TDO: u8 = 1 << 2;
 const PIN_CS: u8 = 1 << 3;
 const PIN_GPIO_L: u8 = 1 << 4;
+const PIN_GPIO_L1: u8 = 1 << 5;
+
+/// IO0-IO3 in Dual/Quad mode: TDI, TDO, then GPIOL0/GPIOL1 wired to WP#/HOLD#
+const PIN_IO: [u8; 4] = [PIN_TDI, PIN_TDO, PIN_GPIO_L, PIN_GPIO_L1];
+
+/// Data bytes clocked per USB round trip when bit-banging Dual/Quad I/O
+const MULTI_IO_CHUNK: usize = 512;
 
 // Default Direction (1=Out, 0=In)
 // TCK=Out, TDI=Out, TDO=In, CS=Out, Logic=Out
@@ -56,6 +63,7 @@ pub struct FtdiProgrammer {
     #[allow(dead_code)]
     current_speed: u32,
     current_gpio_low: u8, // Cache for ADBUS state
+    bus_width: BusWidth,
 }
 
 impl FtdiProgrammer {
@@ -97,6 +105,7 @@ impl FtdiProgrammer {
             ep_in,
             current_speed: 1_000_000,
             current_gpio_low: 0,
+            bus_width: BusWidth::Single,
         };
 
         programmer.reset_mpsse()?;
@@ -197,6 +206,114 @@ impl FtdiProgrammer {
         let cmd = mpsse::build_set_low_gpio_cmd(self.current_gpio_low, DIRECTION_DEFAULT);
         self.bulk_write(&cmd)
     }
+
+    /// Enable bit-banged Dual/Quad I/O (off by default: it is slower than
+    /// the MPSSE single-bit shifter at high clock rates). Dual only needs
+    /// the standard SPI wiring; Quad requires WP#/HOLD# on ADBUS4/ADBUS5.
+    /// Selected with the `ftdi:dual` and `ftdi:quad` driver names.
+    pub fn set_bus_width(&mut self, bus_width: BusWidth) {
+        self.bus_width = bus_width;
+    }
+
+    /// ADBUS bits carrying data on `width` lines
+    fn io_mask(width: BusWidth) -> u8 {
+        PIN_IO[..width.lanes() as usize]
+            .iter()
+            .fold(0, |mask, pin| mask | pin)
+    }
+
+    /// ADBUS value with CS asserted, TCK low and every IO line low
+    fn bitbang_base(&self) -> u8 {
+        self.current_gpio_low & !(PIN_CS | PIN_TCK | Self::io_mask(BusWidth::Quad))
+    }
+
+    /// Queue GPIO writes clocking `bytes` out on `width` lines, MSB first.
+    /// MPSSE has no multi-I/O shifter, so each clock is two pin updates.
+    fn bitbang_out(&self, bytes: &[u8], width: BusWidth, cmd: &mut Vec<u8>) {
+        let lanes = width.lanes();
+        let direction = DIRECTION_DEFAULT | Self::io_mask(width);
+        for &byte in bytes {
+            for step in (0..8 / lanes).rev() {
+                let value = (byte >> (step * lanes)) & ((1 << lanes) - 1);
+                let pins = PIN_IO[..lanes as usize]
+                    .iter()
+                    .enumerate()
+                    .filter(|(lane, _)| value & (1 << lane) != 0)
+                    .fold(self.bitbang_base(), |pins, (_, pin)| pins | pin);
+                cmd.extend(mpsse::build_set_low_gpio_cmd(pins, direction));
+                cmd.extend(mpsse::build_set_low_gpio_cmd(pins | PIN_TCK, direction));
+            }
+        }
+    }
+
+    /// Queue `cycles` clocks with the `width` IO lines released
+    fn bitbang_idle(&self, cycles: usize, width: BusWidth, cmd: &mut Vec<u8>) {
+        let direction = DIRECTION_DEFAULT & !Self::io_mask(width);
+        for _ in 0..cycles {
+            cmd.extend(mpsse::build_set_low_gpio_cmd(
+                self.bitbang_base(),
+                direction,
+            ));
+            cmd.extend(mpsse::build_set_low_gpio_cmd(
+                self.bitbang_base() | PIN_TCK,
+                direction,
+            ));
+        }
+    }
+
+    /// Queue one pin sample per clock for `len` bytes on `width` lines
+    fn bitbang_in(&self, len: usize, width: BusWidth, cmd: &mut Vec<u8>) {
+        let direction = DIRECTION_DEFAULT & !Self::io_mask(width);
+        for _ in 0..len * 8 / width.lanes() as usize {
+            cmd.extend(mpsse::build_set_low_gpio_cmd(
+                self.bitbang_base(),
+                direction,
+            ));
+            cmd.push(mpsse::CMD_READ_BITS_LOW);
+            cmd.extend(mpsse::build_set_low_gpio_cmd(
+                self.bitbang_base() | PIN_TCK,
+                direction,
+            ));
+        }
+    }
+
+    /// Reassemble bytes from ADBUS samples taken by `bitbang_in`
+    fn decode_samples(samples: &[u8], width: BusWidth) -> Vec<u8> {
+        let lanes = width.lanes() as usize;
+        samples
+            .chunks(8 / lanes)
+            .map(|cycles| {
+                cycles.iter().fold(0u8, |byte, &sample| {
+                    let bits = PIN_IO[..lanes]
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, &pin)| sample & pin != 0)
+                        .fold(0u8, |bits, (lane, _)| bits | (1 << lane));
+                    (byte << lanes) | bits
+                })
+            })
+            .collect()
+    }
+
+    /// Assert CS and queue the opcode, address and dummy phases
+    fn multi_io_header(&self, command: &MultiIoCommand) -> Result<Vec<u8>> {
+        let widest = command
+            .opcode_width
+            .max(command.address_width)
+            .max(command.data_width);
+        if widest > self.bus_width {
+            return Err(Error::NotSupported(format!(
+                "FTDI configured for {:?} I/O, command needs {:?}",
+                self.bus_width, widest
+            )));
+        }
+
+        let mut cmd = Vec::new();
+        self.bitbang_out(&[command.opcode], command.opcode_width, &mut cmd);
+        self.bitbang_out(command.address, command.address_width, &mut cmd);
+        self.bitbang_idle(command.dummy_cycles as usize, command.data_width, &mut cmd);
+        Ok(cmd)
+    }
 }
 
 impl Programmer for FtdiProgrammer {
@@ -290,4 +407,48 @@ impl Programmer for FtdiProgrammer {
         let cmd = mpsse::build_set_divisor_cmd(divisor);
         self.bulk_write(&cmd) // Use bulk_write, not control
     }
+
+    fn max_bus_width(&self) -> BusWidth {
+        self.bus_width
+    }
+
+    fn spi_multi_io_read(&mut self, command: &MultiIoCommand, len: usize) -> Result<Vec<u8>> {
+        let mut cmd = self.multi_io_header(command)?;
+        let width = command.data_width;
+        let mut data = Vec::with_capacity(len);
+
+        while data.len() < len {
+            let chunk_len = (len - data.len()).min(MULTI_IO_CHUNK);
+            self.bitbang_in(chunk_len, width, &mut cmd);
+            cmd.push(mpsse::CMD_SEND_IMMEDIATE);
+            self.bulk_write(&cmd)?;
+            cmd.clear();
+
+            let expected = chunk_len * 8 / width.lanes() as usize;
+            let mut samples = Vec::with_capacity(expected);
+            while samples.len() < expected {
+                let received = self.bulk_read(expected - samples.len())?;
+                if received.is_empty() {
+                    return Err(Error::Other("FTDI Read Stalled".to_string()));
+                }
+                samples.extend_from_slice(&received);
+            }
+            data.extend(Self::decode_samples(&samples, width));
+        }
+
+        // Release CS and the IO lines
+        self.update_gpio_low()?;
+        Ok(data)
+    }
+
+    fn spi_multi_io_write(&mut self, command: &MultiIoCommand, data: &[u8]) -> Result<()> {
+        let mut cmd = self.multi_io_header(command)?;
+        for chunk in data.chunks(MULTI_IO_CHUNK) {
+            self.bitbang_out(chunk, command.data_width, &mut cmd);
+            self.bulk_write(&cmd)?;
+            cmd.clear();
+        }
+        self.bulk_write(&cmd)?;
+        self.update_gpio_low()
+    }
 }
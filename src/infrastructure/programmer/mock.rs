@@ -4,7 +4,7 @@
 //! for unit testing flash protocol implementations without actual hardware.
 
 use crate::error::Result;
-use crate::infrastructure::programmer::Programmer;
+use crate::infrastructure::programmer::{BusWidth, MultiIoCommand, Programmer};
 use std::cell::RefCell;
 use std::collections::VecDeque;
 
@@ -21,6 +21,8 @@ pub struct MockProgrammer {
     read_responses: RefCell<VecDeque<Vec<u8>>>,
     /// Transaction log for debugging
     transaction_log: RefCell<Vec<Transaction>>,
+    /// Widest bus reported by `max_bus_width`
+    bus_width: BusWidth,
 }
 
 /// Record of a single SPI transaction
@@ -30,6 +32,7 @@ pub enum Transaction {
     Write(Vec<u8>),
     Read { len: usize, data: Vec<u8> },
     Transfer { tx: Vec<u8>, rx: Vec<u8> },
+    MultiIo { width: BusWidth, len: usize },
 }
 
 impl MockProgrammer {
@@ -41,9 +44,29 @@ impl MockProgrammer {
             write_log: RefCell::new(Vec::new()),
             read_responses: RefCell::new(VecDeque::new()),
             transaction_log: RefCell::new(Vec::new()),
+            bus_width: BusWidth::Single,
         }
     }
 
+    /// Report Dual/Quad support so multi-I/O paths are used
+    pub fn with_bus_width(mut self, bus_width: BusWidth) -> Self {
+        self.bus_width = bus_width;
+        self
+    }
+
+    /// Log a multi-I/O command phase as a write of opcode + address
+    fn log_multi_io(&self, command: &MultiIoCommand, len: usize) {
+        let mut tx = vec![command.opcode];
+        tx.extend_from_slice(command.address);
+        self.write_log.borrow_mut().push(tx);
+        self.transaction_log
+            .borrow_mut()
+            .push(Transaction::MultiIo {
+                width: command.data_width,
+                len,
+            });
+    }
+
     /// Set the name of this mock programmer
     pub fn with_name(mut self, name: &str) -> Self {
         self.name = name.to_string();
@@ -150,6 +173,27 @@ impl Programmer for MockProgrammer {
     fn max_bulk_transfer_size(&self) -> usize {
         4096 // Simulate CH341A bulk size
     }
+
+    fn max_bus_width(&self) -> BusWidth {
+        self.bus_width
+    }
+
+    fn spi_multi_io_read(&mut self, command: &MultiIoCommand, len: usize) -> Result<Vec<u8>> {
+        self.log_multi_io(command, len);
+        let mut response = self
+            .read_responses
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or_else(|| vec![0xFF; len]);
+        response.resize(len, 0xFF);
+        Ok(response)
+    }
+
+    fn spi_multi_io_write(&mut self, command: &MultiIoCommand, data: &[u8]) -> Result<()> {
+        self.log_multi_io(command, data.len());
+        self.write_log.borrow_mut().push(data.to_vec());
+        Ok(())
+    }
 }
 
 #[cfg(test)]
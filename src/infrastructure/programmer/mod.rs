@@ -20,7 +20,9 @@ pub use device_database::{DeviceCompatibility, DeviceInfo, WchDeviceDatabase};
 pub use ftdi::FtdiProgrammer;
 pub use serial::Ch340Serial;
 pub use spidev::SpidevProgrammer;
-pub use traits::{Parity, Programmer, SerialConfig, SerialPort, StopBits};
+pub use traits::{
+    BusWidth, MultiIoCommand, Parity, Programmer, SerialConfig, SerialPort, StopBits,
+};
 
 use crate::error::{Error, Result};
 use log::debug;
@@ -37,30 +39,18 @@ pub fn discover(driver_name: Option<&str>) -> Result<Box<dyn Programmer>> {
             let p = SpidevProgrammer::new_raspberry_pi_default()?;
             Ok(Box::new(p))
         }
-        "ch341a" | "ch347" | "ftdi" => {
-            // For USB devices, we might want to still scan but look for specific VIDs/PIDs
-            // For now, let's reuse the auto-discovery but filter post-facto or modify find logic.
-            // Simplified: existing WCH logic covers CH341A/CH347
-            if driver == "ftdi" {
-                // FTDI usually 0x0403
-                // Call FTDI constructor directly if possible, or scan for FTDI
-                // Since FtdiProgrammer::new takes a device, we need to find it.
-                // But FtdiProgrammer is a stub that returns Error, so let's just use a dummy device or find one.
-                // Actually the stub takes `nusb::Device`.
-                // Let's implement a basic find_usb_device for FTDI.
-                let device = find_usb_device(0x0403, 0x6014) // FT232H default
-                    .or_else(|_| find_usb_device(0x0403, 0x6010)) // FT2232H
-                    .map_err(|_| Error::ProgrammerNotFound)?;
-                let p = FtdiProgrammer::new(device)?;
-                Ok(Box::new(p))
-            } else {
-                // For CH34x, use the existing robust logic but verify the result
-                let p = auto_discover_wch()?;
-                // weak check: if user asked for CH347 but got CH341A, warn or error?
-                // The auto_discover_wch returns a Box<dyn Programmer>. We can't easily check type.
-                // But the user can see the name.
-                Ok(p)
-            }
+        "ftdi" => open_ftdi(BusWidth::Single),
+        // Bit-banged multi-I/O: dual works with standard wiring, quad needs
+        // WP#/HOLD# on ADBUS4/ADBUS5
+        "ftdi:dual" => open_ftdi(BusWidth::Dual),
+        "ftdi:quad" => open_ftdi(BusWidth::Quad),
+        "ch341a" | "ch347" => {
+            // For CH34x, use the existing robust logic but verify the result
+            let p = auto_discover_wch()?;
+            // weak check: if user asked for CH347 but got CH341A, warn or error?
+            // The auto_discover_wch returns a Box<dyn Programmer>. We can't easily check type.
+            // But the user can see the name.
+            Ok(p)
         }
         "sim" | "simulator" => {
             debug!("Initializing simulated programmer");
@@ -72,6 +62,17 @@ pub fn discover(driver_name: Option<&str>) -> Result<Box<dyn Programmer>> {
     }
 }
 
+/// Open the first FT232H/FT2232H, driving multi-I/O commands on up to
+/// `bus_width` data lines
+fn open_ftdi(bus_width: BusWidth) -> Result<Box<dyn Programmer>> {
+    let device = find_usb_device(0x0403, 0x6014) // FT232H default
+        .or_else(|_| find_usb_device(0x0403, 0x6010)) // FT2232H
+        .map_err(|_| Error::ProgrammerNotFound)?;
+    let mut p = FtdiProgrammer::new(device)?;
+    p.set_bus_width(bus_width);
+    Ok(Box::new(p))
+}
+
 /// Find and open the first available WCH programmer (Auto-detect)
 fn auto_discover_wch() -> Result<Box<dyn Programmer>> {
     debug!("Starting WCH programmer discovery...");
@@ -3,8 +3,8 @@
 //! A high-level simulator for SPI Flash chips (NAND/NOR) to enable
 //! end-to-end integration testing without hardware.
 
-use crate::error::Result;
-use crate::infrastructure::programmer::Programmer;
+use crate::error::{Error, Result};
+use crate::infrastructure::programmer::{BusWidth, MultiIoCommand, Programmer};
 use std::cell::RefCell;
 
 /// Represents the internal state of a simulated SPI NAND chip
@@ -12,12 +12,13 @@ use std::cell::RefCell;
 struct SpiNandState {
     /// Main storage array (flat byte vector)
     memory: Vec<u8>,
+    /// Spare (OOB) area of every page, `oob_size` bytes each
+    spare: Vec<u8>,
     /// Page Data Buffer (for Read/Program)
     page_buffer: Vec<u8>,
     /// Status Register (Register C0h)
     status_register: u8,
-    /// Configuration Register
-    #[allow(dead_code)]
+    /// Configuration Register (Register B0h)
     config_register: u8,
     /// Write Enable Latch
     write_enabled: bool,
@@ -25,26 +26,87 @@ struct SpiNandState {
     current_row_addr: u32,
     /// Current column address pointer
     column_ptr: u16,
+    /// Page held in the data register during a sequential cache read
+    sequential_row: Option<u32>,
+    /// Number of stacked dies the array is split into
+    die_count: u32,
+    /// Die selected with Software Die Select (0xC2)
+    selected_die: u32,
     /// Page Size
     page_size: u32,
+    /// Spare bytes per page
+    oob_size: u32,
     /// Block Size
     block_size: u32,
+    /// Blocks whose erase reports E_FAIL
+    failing_erase_blocks: Vec<u32>,
+    /// Blocks whose next page program reports P_FAIL
+    failing_program_blocks: Vec<u32>,
+    /// Pages whose PAGE READ fails the transfer
+    failing_read_pages: Vec<u32>,
+    /// Set when the current transfer hit a failing page
+    read_fault: bool,
+    /// ECC status bits (C0h bits 4-5) reported after reading a page
+    page_ecc_status: Vec<(u32, u8)>,
+    /// Feature address of the read-retry level register, if any
+    read_retry_feature: Option<u8>,
+    /// Current read-retry level; 0 is the default read voltage
+    read_retry_level: u8,
+    /// Pages that read back cleanly at one read-retry level
+    read_retry_pages: Vec<(u32, u8)>,
 }
 
 impl SpiNandState {
     fn new(capacity: usize, page_size: u32, block_size: u32) -> Self {
         Self {
             memory: vec![0xFF; capacity],
+            spare: Vec::new(),
             page_buffer: vec![0xFF; page_size as usize],
             status_register: 0,
-            config_register: 0,
+            config_register: 0x18, // ECC-E and BUF, as after power-up
             write_enabled: false,
             current_row_addr: 0,
             column_ptr: 0,
+            sequential_row: None,
+            die_count: 1,
+            selected_die: 0,
             page_size,
+            oob_size: 0,
             block_size,
+            failing_erase_blocks: Vec::new(),
+            failing_program_blocks: Vec::new(),
+            failing_read_pages: Vec::new(),
+            read_fault: false,
+            page_ecc_status: Vec::new(),
+            read_retry_feature: None,
+            read_retry_level: 0,
+            read_retry_pages: Vec::new(),
         }
     }
+
+    /// Array page addressed by the die-relative `row_addr`
+    fn array_page(&self, row_addr: u32) -> u32 {
+        let pages_per_die = self.memory.len() as u32 / self.die_count / self.page_size;
+        self.selected_die * pages_per_die + row_addr
+    }
+
+    /// Copy page `row_addr` and its spare area from the array into the cache
+    fn load_page(&mut self, row_addr: u32) {
+        self.current_row_addr = row_addr;
+        let page_size = self.page_size as usize;
+        let flat_addr = row_addr as usize * page_size;
+        let end_addr = flat_addr + page_size;
+        if end_addr <= self.memory.len() {
+            self.page_buffer[..page_size].copy_from_slice(&self.memory[flat_addr..end_addr]);
+            let spare = self.spare_range(row_addr);
+            self.page_buffer[page_size..].copy_from_slice(&self.spare[spare]);
+        }
+    }
+
+    fn spare_range(&self, row_addr: u32) -> std::ops::Range<usize> {
+        let start = (row_addr * self.oob_size) as usize;
+        start..start + self.oob_size as usize
+    }
 }
 
 /// A programmer implementation that simulates a connected SPI Flash chip
@@ -55,6 +117,10 @@ pub struct SimulatedProgrammer {
     current_command: RefCell<Option<u8>>,
     /// Buffer for collecting address/dummy bytes after a command
     cmd_buffer: RefCell<Vec<u8>>,
+    /// Widest bus the simulated programmer advertises
+    bus_width: BusWidth,
+    /// Number of multi-I/O transactions executed
+    multi_io_count: RefCell<usize>,
 }
 
 impl SimulatedProgrammer {
@@ -63,7 +129,125 @@ impl SimulatedProgrammer {
             state: RefCell::new(SpiNandState::new(capacity, page_size, block_size)),
             current_command: RefCell::new(None),
             cmd_buffer: RefCell::new(Vec::new()),
+            bus_width: BusWidth::Single,
+            multi_io_count: RefCell::new(0),
+        }
+    }
+
+    /// Advertise Dual/Quad support so the multi-I/O path is exercised
+    pub fn with_bus_width(mut self, bus_width: BusWidth) -> Self {
+        self.bus_width = bus_width;
+        self
+    }
+
+    /// Give every page `oob_size` spare bytes, erased to 0xFF
+    pub fn with_oob(self, oob_size: u32) -> Self {
+        {
+            let mut state = self.state.borrow_mut();
+            let pages = state.memory.len() / state.page_size as usize;
+            state.oob_size = oob_size;
+            state.spare = vec![0xFF; pages * oob_size as usize];
+            state.page_buffer = vec![0xFF; (state.page_size + oob_size) as usize];
         }
+        self
+    }
+
+    /// Split the array into `die_count` dies behind Software Die Select
+    pub fn with_dies(self, die_count: u32) -> Self {
+        self.state.borrow_mut().die_count = die_count;
+        self
+    }
+
+    /// Accept read-retry levels at feature address `feature`
+    pub fn with_read_retry(self, feature: u8) -> Self {
+        self.state.borrow_mut().read_retry_feature = Some(feature);
+        self
+    }
+
+    /// Number of multi-I/O transactions executed so far
+    pub fn multi_io_count(&self) -> usize {
+        *self.multi_io_count.borrow()
+    }
+
+    /// Start a multi-I/O command by replaying it as its single-bit equivalent
+    /// (lane widths only affect timing, not the data)
+    fn begin_multi_io(&mut self, command: &MultiIoCommand) -> Result<()> {
+        if command.data_width > self.bus_width {
+            return Err(Error::NotSupported(format!(
+                "Simulator limited to {:?} I/O",
+                self.bus_width
+            )));
+        }
+        let opcode = match command.opcode {
+            0x3B | 0x6B => 0x03, // Read from cache x2/x4
+            0x32 => 0x02,        // Program load x4
+            opcode => {
+                return Err(Error::NotSupported(format!(
+                    "Simulator has no multi-I/O opcode 0x{:02X}",
+                    opcode
+                )))
+            }
+        };
+        *self.multi_io_count.borrow_mut() += 1;
+
+        self.set_cs(true)?;
+        self.handle_spi_byte(opcode);
+        // The x1 replay of a read takes exactly three bytes before the data
+        // (column and dummy, or the three dummies of a continuous read)
+        let mut prefix = command.address.to_vec();
+        prefix.resize(
+            command.address.len() + command.dummy_cycles as usize / 8,
+            0x00,
+        );
+        if opcode == 0x03 {
+            prefix.truncate(3);
+        }
+        for byte in prefix {
+            self.handle_spi_byte(byte);
+        }
+        Ok(())
+    }
+
+    /// Make every erase of array block `block` fail with E_FAIL
+    pub fn fail_erase(&self, block: u32) {
+        self.state.borrow_mut().failing_erase_blocks.push(block);
+    }
+
+    /// Make the next page program in array block `block` fail with P_FAIL
+    pub fn fail_program(&self, block: u32) {
+        self.state.borrow_mut().failing_program_blocks.push(block);
+    }
+
+    /// Make every PAGE READ of array page `page` fail the transfer, as a
+    /// dropped USB packet would
+    pub fn fail_read(&self, page: u32) {
+        self.state.borrow_mut().failing_read_pages.push(page);
+    }
+
+    /// Report ECC status bits `ecc_bits` (C0h bits 4-5) whenever array page
+    /// `page` is read
+    pub fn set_ecc_status(&self, page: u32, ecc_bits: u8) {
+        let mut state = self.state.borrow_mut();
+        state.page_ecc_status.retain(|&(other, _)| other != page);
+        state.page_ecc_status.push((page, ecc_bits & 0x30));
+    }
+
+    /// Make array page `page` read back without ECC errors at read-retry
+    /// level `level`, whatever [`set_ecc_status`](Self::set_ecc_status) says
+    pub fn recover_at_level(&self, page: u32, level: u8) {
+        let mut state = self.state.borrow_mut();
+        state.read_retry_pages.retain(|&(other, _)| other != page);
+        state.read_retry_pages.push((page, level));
+    }
+
+    /// Read-retry level currently set
+    pub fn read_retry_level(&self) -> u8 {
+        self.state.borrow().read_retry_level
+    }
+
+    /// Whether on-die ECC is enabled (ECC-E in the configuration feature)
+    pub fn ecc_enabled(&self) -> bool {
+        self.state.borrow().config_register & 0x10 != 0
     }
 
     /// Get a reference to the internal memory for verification
@@ -78,6 +262,20 @@ impl SimulatedProgrammer {
         state.memory[..len].copy_from_slice(&data[..len]);
     }
 
+    /// Spare area of array page `page`
+    pub fn get_oob(&self, page: u32) -> Vec<u8> {
+        let state = self.state.borrow();
+        state.spare[state.spare_range(page)].to_vec()
+    }
+
+    /// Overwrite the spare area of array page `page`
+    pub fn set_oob(&self, page: u32, data: &[u8]) {
+        let mut state = self.state.borrow_mut();
+        let range = state.spare_range(page);
+        let len = data.len().min(range.len());
+        state.spare[range.start..range.start + len].copy_from_slice(&data[..len]);
+    }
+
     fn handle_spi_byte(&self, byte: u8) -> u8 {
         let mut state_ref = self.state.borrow_mut();
         let state = &mut *state_ref;
@@ -96,6 +294,14 @@ impl SimulatedProgrammer {
             } else if byte == 0x04 {
                 state.write_enabled = false;
                 return 0xFF;
+            } else if byte == 0x31 || byte == 0x3F {
+                // Cache read sequential/end: the page in the data register
+                // moves to the cache, and 0x31 starts loading the next one
+                if let Some(row_addr) = state.sequential_row {
+                    state.load_page(row_addr);
+                    state.sequential_row = (byte == 0x31).then_some(row_addr + 1);
+                }
+                return 0xFF;
             }
 
             return 0xFF; // Hi-Z / Dummy return
@@ -115,17 +321,15 @@ impl SimulatedProgrammer {
                 }
             }
             // GET FEATURE (0x0F)
-            0x0F => {
-                if buf.len() == 2 {
-                    // Address (buf[0]) received in previous cycle
-                    // Now receiving dummy/clock for data, return the value
-                    let addr = buf[0];
-                    match addr {
-                        0xC0 => state.status_register, // Status
-                        _ => 0x00,
-                    }
-                } else {
-                    0xFF
+            0x0F if buf.len() == 2 => {
+                // Address (buf[0]) received in previous cycle
+                // Now receiving dummy/clock for data, return the value
+                let addr = buf[0];
+                match addr {
+                    0xB0 => state.config_register, // Configuration
+                    0xC0 => state.status_register, // Status
+                    addr if Some(addr) == state.read_retry_feature => state.read_retry_level,
+                    _ => 0x00,
                 }
             }
             // SET FEATURE (0x1F)
@@ -133,8 +337,13 @@ impl SimulatedProgrammer {
                 if buf.len() == 2 {
                     let addr = buf[0];
                     let val = buf[1];
-                    if addr == 0xC0 {
-                        state.status_register = val;
+                    match addr {
+                        0xB0 => state.config_register = val,
+                        0xC0 => state.status_register = val,
+                        addr if Some(addr) == state.read_retry_feature => {
+                            state.read_retry_level = val
+                        }
+                        _ => {}
                     }
                 }
                 0xFF
@@ -142,25 +351,37 @@ impl SimulatedProgrammer {
             // WRITE ENABLE/DISABLE managed above
             0x06 | 0x04 => 0xFF,
 
+            // SOFTWARE DIE SELECT (0xC2)
+            0xC2 => {
+                if buf.len() == 1 && (byte as u32) < state.die_count {
+                    state.selected_die = byte as u32;
+                }
+                0xFF
+            }
+
             // PAGE READ (0x13)
             0x13 => {
                 if buf.len() == 3 {
                     let row_addr =
                         ((buf[0] as u32) << 16) | ((buf[1] as u32) << 8) | (buf[2] as u32);
-                    state.current_row_addr = row_addr;
-
-                    // Simulate load from array to cache
-                    // Calculate flat address
-                    let flat_addr = row_addr * state.page_size;
-                    let end_addr = flat_addr + state.page_size;
 
-                    if (end_addr as usize) <= state.memory.len() {
-                        state
-                            .page_buffer
-                            .copy_from_slice(&state.memory[flat_addr as usize..end_addr as usize]);
+                    // Simulate load from array to cache (instantly, so the
+                    // chip is never busy)
+                    let row_addr = state.array_page(row_addr);
+                    if state.failing_read_pages.contains(&row_addr) {
+                        state.read_fault = true;
                     }
+                    state.load_page(row_addr);
+                    state.sequential_row = Some(row_addr);
 
-                    // Not busy (simulated instant)
+                    let level = state.read_retry_level;
+                    let recovered = state.read_retry_pages.contains(&(row_addr, level));
+                    let ecc_bits = state
+                        .page_ecc_status
+                        .iter()
+                        .find(|&&(page, _)| page == row_addr && !recovered)
+                        .map_or(0, |&(_, ecc_bits)| ecc_bits);
+                    state.status_register = (state.status_register & !0x30) | ecc_bits;
                 }
                 0xFF
             }
@@ -170,9 +391,21 @@ impl SimulatedProgrammer {
                 if buf.len() == 3 {
                     // Setup column pointer
                     let col_addr = ((buf[0] as u16) << 8) | (buf[1] as u16);
-                    state.column_ptr = col_addr;
+                    // Continuous read ignores the column address
+                    state.column_ptr = if state.config_register & 0x08 == 0 {
+                        0
+                    } else {
+                        col_addr
+                    };
                     0xFF // Dummy byte return
                 } else if buf.len() > 3 {
+                    // With BUF=0 the read continues into the following pages
+                    let continuous = state.config_register & 0x08 == 0;
+                    if continuous && state.column_ptr as u32 == state.page_size {
+                        let next_row = state.current_row_addr + 1;
+                        state.load_page(next_row);
+                        state.column_ptr = 0;
+                    }
                     // Return data
                     let ptr = state.column_ptr as usize;
                     if ptr < state.page_buffer.len() {
@@ -189,9 +422,10 @@ impl SimulatedProgrammer {
             // PROGRAM LOAD (0x02)
             0x02 => {
                 if buf.len() == 2 {
-                    // Col Addr set
+                    // Col Addr set; Program Load clears the rest of the cache
                     let col_addr = ((buf[0] as u16) << 8) | (buf[1] as u16);
                     state.column_ptr = col_addr;
+                    state.page_buffer.fill(0xFF);
                 } else if buf.len() > 2 {
                     // Data incoming
                     let ptr = state.column_ptr as usize;
@@ -207,13 +441,29 @@ impl SimulatedProgrammer {
                 if buf.len() == 3 && state.write_enabled {
                     let row_addr =
                         ((buf[0] as u32) << 16) | ((buf[1] as u32) << 8) | (buf[2] as u32);
+                    let row_addr = state.array_page(row_addr);
 
                     let flat_addr = row_addr * state.page_size;
                     let end_addr = flat_addr + state.page_size;
 
-                    if (end_addr as usize) <= state.memory.len() {
+                    let pages_per_block = state.block_size / state.page_size;
+                    let failing_index = state
+                        .failing_program_blocks
+                        .iter()
+                        .position(|&block| block == row_addr / pages_per_block);
+                    let failing = failing_index.is_some();
+                    if let Some(index) = failing_index {
+                        state.failing_program_blocks.remove(index);
+                    }
+                    state.status_register &= !0x08;
+                    if failing {
+                        state.status_register |= 0x08; // P_FAIL
+                    } else if (end_addr as usize) <= state.memory.len() {
+                        let page_size = state.page_size as usize;
                         state.memory[flat_addr as usize..end_addr as usize]
-                            .copy_from_slice(&state.page_buffer);
+                            .copy_from_slice(&state.page_buffer[..page_size]);
+                        let spare = state.spare_range(row_addr);
+                        state.spare[spare].copy_from_slice(&state.page_buffer[page_size..]);
                     }
 
                     // Clear WEL
@@ -226,6 +476,7 @@ impl SimulatedProgrammer {
                 if buf.len() == 3 && state.write_enabled {
                     let row_addr =
                         ((buf[0] as u32) << 16) | ((buf[1] as u32) << 8) | (buf[2] as u32);
+                    let row_addr = state.array_page(row_addr);
                     // Block erase ignores lower bits of row addr usually
                     let pages_per_block = state.block_size / state.page_size;
                     let block_start_page = (row_addr / pages_per_block) * pages_per_block;
@@ -233,10 +484,19 @@ impl SimulatedProgrammer {
                     let start_addr = block_start_page * state.page_size;
                     let end_addr = start_addr + state.block_size;
 
-                    if (end_addr as usize) <= state.memory.len() {
+                    let failing = state
+                        .failing_erase_blocks
+                        .contains(&(block_start_page / pages_per_block));
+                    state.status_register &= !0x04;
+                    if failing {
+                        state.status_register |= 0x04; // E_FAIL
+                    } else if (end_addr as usize) <= state.memory.len() {
                         for i in start_addr as usize..end_addr as usize {
                             state.memory[i] = 0xFF; // Erase to 0xFF
                         }
+                        let spare_start = state.spare_range(block_start_page).start;
+                        let spare_end = state.spare_range(block_start_page + pages_per_block).start;
+                        state.spare[spare_start..spare_end].fill(0xFF);
                     }
                     state.write_enabled = false;
                 }
@@ -259,6 +519,13 @@ impl Programmer for SimulatedProgrammer {
                 rx[i] = ret;
             }
         }
+        if std::mem::take(&mut self.state.borrow_mut().read_fault) {
+            // The transaction is lost, CS included
+            self.set_cs(false)?;
+            return Err(Error::Io(std::io::Error::other(
+                "simulated transfer failure",
+            )));
+        }
         Ok(())
     }
 
@@ -282,4 +549,23 @@ impl Programmer for SimulatedProgrammer {
     fn max_bulk_transfer_size(&self) -> usize {
         1024 * 1024 // Unlimited for sim
     }
+
+    fn max_bus_width(&self) -> BusWidth {
+        self.bus_width
+    }
+
+    fn spi_multi_io_read(&mut self, command: &MultiIoCommand, len: usize) -> Result<Vec<u8>> {
+        self.begin_multi_io(command)?;
+        let data = (0..len).map(|_| self.handle_spi_byte(0x00)).collect();
+        self.set_cs(false)?;
+        Ok(data)
+    }
+
+    fn spi_multi_io_write(&mut self, command: &MultiIoCommand, data: &[u8]) -> Result<()> {
+        self.begin_multi_io(command)?;
+        for &byte in data {
+            self.handle_spi_byte(byte);
+        }
+        self.set_cs(false)
+    }
 }
@@ -7,6 +7,40 @@ use crate::error::Result;
 /// Default bulk transfer chunk size (32KB for optimal USB throughput)
 pub const DEFAULT_BULK_CHUNK_SIZE: usize = 32 * 1024;
 
+/// Number of data lines used by one phase of a multi-I/O transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum BusWidth {
+    #[default]
+    Single,
+    /// IO0-IO1
+    Dual,
+    /// IO0-IO3 (WP#/HOLD# become IO2/IO3)
+    Quad,
+}
+
+impl BusWidth {
+    /// Bits transferred per clock cycle
+    pub fn lanes(self) -> u8 {
+        match self {
+            BusWidth::Single => 1,
+            BusWidth::Dual => 2,
+            BusWidth::Quad => 4,
+        }
+    }
+}
+
+/// Command phase of a multi-I/O transaction (e.g. 1-1-4 quad output read)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MultiIoCommand<'a> {
+    pub opcode: u8,
+    pub opcode_width: BusWidth,
+    pub address: &'a [u8],
+    pub address_width: BusWidth,
+    /// Clock cycles between the address and the data phase
+    pub dummy_cycles: u8,
+    pub data_width: BusWidth,
+}
+
 /// Traits defining the interface for hardware programmers
 pub trait Programmer {
     /// Get the identification name of the programmer
@@ -98,6 +132,34 @@ pub trait Programmer {
         Ok(())
     }
 
+    // =========================================================================
+    // Multi-I/O Methods (Optional, for Dual/Quad SPI)
+    // =========================================================================
+
+    /// Widest bus the programmer can drive; `Single` means the multi-I/O
+    /// methods are not implemented
+    fn max_bus_width(&self) -> BusWidth {
+        BusWidth::Single
+    }
+
+    /// Execute `command` and read `len` bytes on `command.data_width` lines,
+    /// with embedded CS control
+    fn spi_multi_io_read(&mut self, _command: &MultiIoCommand, _len: usize) -> Result<Vec<u8>> {
+        use crate::error::Error;
+        Err(Error::NotSupported(
+            "Multi-I/O SPI not supported by this programmer".to_string(),
+        ))
+    }
+
+    /// Execute `command` and write `data` on `command.data_width` lines,
+    /// with embedded CS control
+    fn spi_multi_io_write(&mut self, _command: &MultiIoCommand, _data: &[u8]) -> Result<()> {
+        use crate::error::Error;
+        Err(Error::NotSupported(
+            "Multi-I/O SPI not supported by this programmer".to_string(),
+        ))
+    }
+
     // =========================================================================
     // I2C Methods (Optional)
     // =========================================================================
@@ -188,6 +250,18 @@ impl Programmer for Box<dyn Programmer> {
         self.as_mut().set_speed(speed)
     }
 
+    fn max_bus_width(&self) -> BusWidth {
+        self.as_ref().max_bus_width()
+    }
+
+    fn spi_multi_io_read(&mut self, command: &MultiIoCommand, len: usize) -> Result<Vec<u8>> {
+        self.as_mut().spi_multi_io_read(command, len)
+    }
+
+    fn spi_multi_io_write(&mut self, command: &MultiIoCommand, data: &[u8]) -> Result<()> {
+        self.as_mut().spi_multi_io_write(command, data)
+    }
+
     fn i2c_write(&mut self, addr: u8, data: &[u8]) -> Result<()> {
         self.as_mut().i2c_write(addr, data)
     }
@@ -250,6 +324,18 @@ impl<P: Programmer + ?Sized> Programmer for &mut P {
         (**self).set_speed(speed)
     }
 
+    fn max_bus_width(&self) -> BusWidth {
+        (**self).max_bus_width()
+    }
+
+    fn spi_multi_io_read(&mut self, command: &MultiIoCommand, len: usize) -> Result<Vec<u8>> {
+        (**self).spi_multi_io_read(command, len)
+    }
+
+    fn spi_multi_io_write(&mut self, command: &MultiIoCommand, data: &[u8]) -> Result<()> {
+        (**self).spi_multi_io_write(command, data)
+    }
+
     fn i2c_write(&mut self, addr: u8, data: &[u8]) -> Result<()> {
         (**self).i2c_write(addr, data)
     }
@@ -3,8 +3,12 @@
 //! This module defines the command-line argument structure.
 
 use clap::{Parser, Subcommand};
+use std::ops::Range;
 use std::path::PathBuf;
 
+use crate::domain::bbt_format::{BbtFileOptions, BbtFormat};
+use crate::domain::SoftEccConfig;
+
 /// nander-rs - A modern SPI NAND/NOR Flash programmer
 ///
 /// Supports CH341A-based programmers for reading, writing, and erasing
@@ -23,7 +27,8 @@ pub struct Args {
     #[arg(long = "speed", global = true, default_value = "5", value_parser = clap::value_parser!(u8).range(0..8))]
     pub spi_speed: u8,
 
-    /// Force specific programmer driver (auto, ch341a, ch347, ftdi, spidev)
+    /// Force specific programmer driver (auto, ch341a, ch347, ftdi, ftdi:dual, ftdi:quad, spidev).
+    /// ftdi:dual and ftdi:quad enable bit-banged Dual/Quad I/O; quad needs WP#/HOLD# on ADBUS4/ADBUS5
     #[arg(long = "driver", short = 'D', global = true, default_value = "auto")]
     pub driver: String,
 
@@ -37,7 +42,11 @@ pub struct Args {
 pub enum Command {
     /// Detect and display information about the connected flash chip
     #[command(alias = "i")]
-    Info,
+    Info {
+        /// Also read the chip's factory unique ID
+        #[arg(long)]
+        unique_id: bool,
+    },
 
     /// List all supported flash chips
     #[command(alias = "L")]
@@ -78,17 +87,39 @@ pub enum Command {
         #[arg(short = 'O', long = "oob-only")]
         oob_only: bool,
 
+        /// Read only the free OOB bytes of the chip's OOB layout (NAND only)
+        #[arg(long = "oob-free", conflicts_with_all = ["oob", "oob_only"])]
+        oob_free: bool,
+
         /// Ignore ECC errors and continue reading (NAND only)
         #[arg(short = 'I', long = "ignore-ecc")]
         ignore_ecc: bool,
 
+        /// Use host-side ECC in the OOB area instead of on-die ECC (NAND
+        /// only): "hamming" or "bch<bits>", optionally followed by
+        /// ":<step size>" and "@<OOB offset>", e.g. "bch8" or "hamming:512"
+        #[arg(long = "soft-ecc", value_parser = parse_soft_ecc)]
+        soft_ecc: Option<SoftEccConfig>,
+
         /// Number of retries for read operations (default: 0)
         #[arg(short = 'R', long = "retries", default_value = "0")]
         retries: u32,
 
+        /// Read each chunk this many times and keep the bytewise majority,
+        /// for unreliable connections such as test clips; fails where the
+        /// passes reach no consensus
+        #[arg(long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+        passes: u32,
+
         /// Use a pre-saved bad block table file
         #[arg(long = "bbt")]
         bbt_file: Option<PathBuf>,
+
+        /// Skip bad blocks within these partitions, mtdparts style (e.g.
+        /// "512K(u-boot),4M(kernel),-(rootfs)"): each partition absorbs its
+        /// own bad blocks and fails when it runs out of good ones
+        #[arg(long)]
+        partitions: Option<String>,
     },
 
     /// Write a file to flash
@@ -106,6 +137,16 @@ pub enum Command {
         #[arg(short = 'V', long, default_value = "true")]
         verify: bool,
 
+        /// Patch mode: read-modify-write the affected erase units, preserving
+        /// surrounding data (NOR only, no prior erase needed)
+        #[arg(short = 'p', long = "patch")]
+        patch: bool,
+
+        /// Delta mode: compare against the current contents and skip erase
+        /// units and pages that already match (no prior erase needed)
+        #[arg(long = "delta", conflicts_with = "patch")]
+        delta: bool,
+
         /// Disable internal ECC (for NAND flash, writes raw page data including ECC bytes)
         #[arg(short = 'd', long = "no-ecc")]
         disable_ecc: bool,
@@ -126,10 +167,20 @@ pub enum Command {
         #[arg(short = 'O', long = "oob-only")]
         oob_only: bool,
 
+        /// Write only the free OOB bytes of the chip's OOB layout (NAND only)
+        #[arg(long = "oob-free", conflicts_with_all = ["oob", "oob_only"])]
+        oob_free: bool,
+
         /// Ignore ECC errors during verify (NAND only)
         #[arg(short = 'I', long = "ignore-ecc")]
         ignore_ecc: bool,
 
+        /// Use host-side ECC in the OOB area instead of on-die ECC (NAND
+        /// only): "hamming" or "bch<bits>", optionally followed by
+        /// ":<step size>" and "@<OOB offset>", e.g. "bch8" or "hamming:512"
+        #[arg(long = "soft-ecc", value_parser = parse_soft_ecc)]
+        soft_ecc: Option<SoftEccConfig>,
+
         /// Number of retries for read operations (default: 0)
         #[arg(short = 'R', long = "retries", default_value = "0")]
         retries: u32,
@@ -137,6 +188,16 @@ pub enum Command {
         /// Use a pre-saved bad block table file
         #[arg(long = "bbt")]
         bbt_file: Option<PathBuf>,
+
+        /// Skip bad blocks within these partitions, mtdparts style (e.g.
+        /// "512K(u-boot),4M(kernel),-(rootfs)"): each partition absorbs its
+        /// own bad blocks and fails when it runs out of good ones
+        #[arg(long)]
+        partitions: Option<String>,
+
+        /// Globally unlock individually locked NOR blocks (WPS) without asking
+        #[arg(long)]
+        unlock: bool,
     },
 
     /// Erase flash contents
@@ -165,6 +226,20 @@ pub enum Command {
         /// Use a pre-saved bad block table file
         #[arg(long = "bbt")]
         bbt_file: Option<PathBuf>,
+
+        /// Skip bad blocks within these partitions, mtdparts style (e.g.
+        /// "512K(u-boot),4M(kernel),-(rootfs)"): each partition absorbs its
+        /// own bad blocks and fails when it runs out of good ones
+        #[arg(long)]
+        partitions: Option<String>,
+
+        /// Globally unlock individually locked NOR blocks (WPS) without asking
+        #[arg(long)]
+        unlock: bool,
+
+        /// Write a bad block marker to blocks that fail to erase (NAND only)
+        #[arg(long = "mark-bad")]
+        mark_bad: bool,
     },
 
     /// Verify flash contents against a file
@@ -198,10 +273,20 @@ pub enum Command {
         #[arg(short = 'O', long = "oob-only")]
         oob_only: bool,
 
+        /// Verify only the free OOB bytes of the chip's OOB layout (NAND only)
+        #[arg(long = "oob-free", conflicts_with_all = ["oob", "oob_only"])]
+        oob_free: bool,
+
         /// Ignore ECC errors and continue verifying (NAND only)
         #[arg(short = 'I', long = "ignore-ecc")]
         ignore_ecc: bool,
 
+        /// Use host-side ECC in the OOB area instead of on-die ECC (NAND
+        /// only): "hamming" or "bch<bits>", optionally followed by
+        /// ":<step size>" and "@<OOB offset>", e.g. "bch8" or "hamming:512"
+        #[arg(long = "soft-ecc", value_parser = parse_soft_ecc)]
+        soft_ecc: Option<SoftEccConfig>,
+
         /// Number of retries for read operations (default: 0)
         #[arg(short = 'R', long = "retries", default_value = "0")]
         retries: u32,
@@ -209,6 +294,12 @@ pub enum Command {
         /// Use a pre-saved bad block table file
         #[arg(long = "bbt")]
         bbt_file: Option<PathBuf>,
+
+        /// Skip bad blocks within these partitions, mtdparts style (e.g.
+        /// "512K(u-boot),4M(kernel),-(rootfs)"): each partition absorbs its
+        /// own bad blocks and fails when it runs out of good ones
+        #[arg(long)]
+        partitions: Option<String>,
     },
 
     /// Manage flash write protection (BP bits)
@@ -216,11 +307,21 @@ pub enum Command {
         /// Operation: status, enable, disable
         #[arg(value_name = "OPERATION", default_value = "status")]
         operation: String,
+
+        /// Start of the range to protect with 'enable' (e.g. 0, 0x7C0000)
+        #[arg(long, value_parser = parse_size)]
+        start: Option<u32>,
+
+        /// Length of the range to protect with 'enable' (e.g. 256K, 1M)
+        #[arg(long, value_parser = parse_size)]
+        length: Option<u32>,
     },
 
     /// Directly read or write status register(s)
     Status {
-        /// New status register value(s) in hex (optional, if provided it performs a write)
+        /// New status register value(s) in hex, one byte per register starting
+        /// with SR1 (e.g. "0002" writes SR1=0x00, SR2=0x02). Omitted registers
+        /// keep their current value.
         #[arg(value_name = "VALUE")]
         value: Option<String>,
     },
@@ -231,6 +332,18 @@ pub enum Command {
         command: BbtCommand,
     },
 
+    /// NAND flash maintenance
+    Nand {
+        #[command(subcommand)]
+        command: NandCommand,
+    },
+
+    /// OTP / security register access
+    Otp {
+        #[command(subcommand)]
+        command: OtpCommand,
+    },
+
     /// Run diagnostic tests on the programmer (no flash chip needed)
     #[command(alias = "test")]
     Diagnostic {
@@ -262,6 +375,10 @@ pub enum Command {
         /// Save the template script to a file instead of executing
         #[arg(long)]
         save_to: Option<PathBuf>,
+
+        /// Save a JSON report of the run (chip, unique ID, steps)
+        #[arg(long, conflicts_with = "save_to")]
+        report: Option<PathBuf>,
     },
 
     /// Launch the Graphical User Interface
@@ -296,18 +413,283 @@ pub enum BbtCommand {
         /// Save the scanned table to a file
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        #[command(flatten)]
+        file: BbtFileArgs,
     },
     /// Load and display a bad block table file
     Load {
         /// Path to the BBT file
         #[arg(short, long)]
         input: PathBuf,
+
+        #[command(flatten)]
+        file: BbtFileArgs,
+    },
+    /// Compare two bad block table files, e.g. a factory scan and today's
+    Diff {
+        /// Older table
+        old: PathBuf,
+
+        /// Newer table
+        new: PathBuf,
+
+        #[command(flatten)]
+        file: BbtFileArgs,
+    },
+    /// Write a bad block marker to a block (or mark it bad in a BBT file)
+    Mark {
+        /// Block index (0-based)
+        block: u32,
+
+        /// Update this BBT file instead of the chip
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+
+        #[command(flatten)]
+        format: BbtFileArgs,
+    },
+    /// Clear a block's bad block marker by erasing it (or mark it good in a
+    /// BBT file)
+    Unmark {
+        /// Block index (0-based)
+        block: u32,
+
+        /// Update this BBT file instead of the chip
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+
+        #[command(flatten)]
+        format: BbtFileArgs,
+
+        /// Erase the block without prompting
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Find and read the Linux on-flash BBT ("Bbt0"/"1tbB") at the end of
+    /// the chip
+    ReadLinux {
+        /// Save the table read to a file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        #[command(flatten)]
+        file: BbtFileArgs,
+    },
+    /// Write the Linux on-flash BBT, main and mirror, from a BBT file or a
+    /// fresh scan of the chip
+    WriteLinux {
+        /// BBT file to write instead of scanning the chip
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+
+        #[command(flatten)]
+        file: BbtFileArgs,
+    },
+}
+
+/// Format options for bad block table files
+#[derive(clap::Args, Debug, Clone, Copy, Default)]
+pub struct BbtFileArgs {
+    /// File format: json, text, binary (2 bits per block) or uboot
+    /// ('nand bad' output). Detected from the extension (.json, .txt,
+    /// .bin/.bbt) or the contents when omitted; new files default to JSON
+    #[arg(long, value_parser = parse_bbt_format)]
+    pub format: Option<BbtFormat>,
+
+    /// Number of blocks, for text lists without a header and U-Boot files
+    #[arg(long)]
+    pub blocks: Option<usize>,
+
+    /// Block size for U-Boot offsets (e.g. 128K); defaults to the chip's
+    /// when one is detected
+    #[arg(long, value_parser = parse_size)]
+    pub block_size: Option<u32>,
+}
+
+impl BbtFileArgs {
+    pub fn options(&self) -> BbtFileOptions {
+        BbtFileOptions {
+            format: self.format,
+            total_blocks: self.blocks,
+            block_size: self.block_size,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NandCommand {
+    /// Survey the bit flips of every good block to judge wear, optionally
+    /// stress testing a range of blocks
+    Health {
+        /// Save the report as JSON
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Corrected bit flips from which a block counts as worn (default:
+        /// three quarters of the ECC strength)
+        #[arg(long)]
+        threshold: Option<u8>,
+
+        /// Erase, program and read back these blocks, e.g. "1000-1023" or
+        /// "42". Destroys their contents!
+        #[arg(long, value_parser = parse_block_range)]
+        stress: Option<Range<u32>>,
+
+        /// Stress test cycles per block
+        #[arg(long, default_value = "10", requires = "stress")]
+        cycles: u32,
+
+        /// Run the stress test without prompting
+        #[arg(long, requires = "stress")]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum OtpCommand {
+    /// List OTP regions and their lock state
+    Status,
+    /// Read an OTP region (hex dump, or save to a file)
+    Read {
+        /// Region index (0-based)
+        #[arg(short, long)]
+        region: u32,
+
+        /// Offset within the region
+        #[arg(long, default_value = "0")]
+        offset: u32,
+
+        /// Number of bytes (default: rest of the region)
+        #[arg(short, long)]
+        length: Option<u32>,
+
+        /// Save the data to a file instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Program a file into an OTP region
+    Program {
+        /// Region index (0-based)
+        #[arg(short, long)]
+        region: u32,
+
+        /// Offset within the region
+        #[arg(long, default_value = "0")]
+        offset: u32,
+
+        /// Input file path
+        #[arg(short, long)]
+        input: PathBuf,
     },
+    /// Erase an OTP region (security registers only, while unlocked)
+    Erase {
+        /// Region index (0-based)
+        #[arg(short, long)]
+        region: u32,
+    },
+    /// Permanently lock an OTP region. This cannot be undone!
+    Lock {
+        /// Region index (0-based)
+        #[arg(short, long)]
+        region: u32,
+
+        /// Confirm the lock without prompting
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+/// Parse a byte count or address: decimal, 0x-prefixed hex, or with a
+/// K/M suffix (binary units)
+fn parse_size(value: &str) -> Result<u32, String> {
+    let value = value.trim();
+    let (digits, multiplier) = match value.chars().last() {
+        Some('k' | 'K') => (&value[..value.len() - 1], 1024),
+        Some('m' | 'M') => (&value[..value.len() - 1], 1024 * 1024),
+        _ => (value, 1),
+    };
+    let number = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => digits.parse::<u32>(),
+    }
+    .map_err(|e| format!("invalid size '{}': {}", value, e))?;
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size '{}' is too large", value))
+}
+
+/// Parse an inclusive block range `<first>-<last>`, or a single block
+fn parse_block_range(value: &str) -> Result<Range<u32>, String> {
+    let parse = |block: &str| {
+        let block = block.trim();
+        match block.strip_prefix("0x").or(block.strip_prefix("0X")) {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => block.parse::<u32>(),
+        }
+        .map_err(|e| format!("invalid block '{}': {}", block, e))
+    };
+    let (first, last) = match value.split_once('-') {
+        Some((first, last)) => (parse(first)?, parse(last)?),
+        None => (parse(value)?, parse(value)?),
+    };
+    if last < first {
+        return Err(format!("block range '{}' is reversed", value));
+    }
+    Ok(first..last.saturating_add(1))
+}
+
+/// Parse a BBT file format name
+fn parse_bbt_format(value: &str) -> Result<BbtFormat, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "json" => Ok(BbtFormat::Json),
+        "text" | "txt" => Ok(BbtFormat::Text),
+        "binary" | "bin" => Ok(BbtFormat::Binary),
+        "uboot" | "u-boot" => Ok(BbtFormat::UBoot),
+        _ => Err(format!(
+            "Unknown BBT format '{}' (expected json, text, binary or uboot)",
+            value
+        )),
+    }
+}
+
+/// Parse a software ECC scheme: `hamming` or `bch<bits>`, then optionally
+/// `:<step size>` and `@<OOB offset>`
+fn parse_soft_ecc(value: &str) -> Result<SoftEccConfig, String> {
+    let value = value.trim().to_ascii_lowercase();
+    let (scheme, offset) = match value.split_once('@') {
+        Some((scheme, offset)) => (scheme, Some(parse_size(offset)?)),
+        None => (value.as_str(), None),
+    };
+    let (name, step_size) = match scheme.split_once(':') {
+        Some((name, step)) => (name, Some(parse_size(step)?)),
+        None => (scheme, None),
+    };
+
+    let mut config = if name == "hamming" {
+        SoftEccConfig::hamming()
+    } else if let Some(bits) = name.strip_prefix("bch") {
+        let strength = bits
+            .parse::<u8>()
+            .map_err(|_| format!("invalid BCH strength in '{}'", value))?;
+        SoftEccConfig::bch(strength)
+    } else {
+        return Err(format!(
+            "unknown ECC scheme '{}' (expected hamming or bch<bits>)",
+            name
+        ));
+    };
+    if let Some(step_size) = step_size {
+        config.step_size = step_size;
+    }
+    config.ecc_offset = offset;
+    Ok(config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::SoftEccAlgorithm;
     use clap::Parser;
 
     #[test]
@@ -316,7 +698,7 @@ mod tests {
         assert_eq!(args.driver, "auto");
         assert_eq!(args.spi_speed, 5);
         match args.command {
-            Command::Info => (),
+            Command::Info { unique_id } => assert!(!unique_id),
             _ => panic!("Expected Info command"),
         }
     }
@@ -334,6 +716,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_read_passes() {
+        let args = Args::parse_from(["nander", "read", "-o", "out.bin", "--passes", "5"]);
+        match args.command {
+            Command::Read { passes, .. } => assert_eq!(passes, 5),
+            _ => panic!("Expected Read command"),
+        }
+        let args = Args::parse_from(["nander", "read", "-o", "out.bin"]);
+        assert!(matches!(args.command, Command::Read { passes: 1, .. }));
+        assert!(
+            Args::try_parse_from(["nander", "read", "-o", "out.bin", "--passes", "0"]).is_err()
+        );
+    }
+
     #[test]
     fn test_parse_args_with_passthrough() {
         let args = Args::parse_from(["nander", "pass", "--mode", "spi", "--tx", "9F", "--rx", "3"]);
@@ -346,4 +742,162 @@ mod tests {
             _ => panic!("Expected Passthrough command"),
         }
     }
+
+    #[test]
+    fn test_parse_otp_lock() {
+        let args = Args::parse_from(["nander", "otp", "lock", "--region", "1", "--yes"]);
+        match args.command {
+            Command::Otp {
+                command: OtpCommand::Lock { region, yes },
+            } => {
+                assert_eq!(region, 1);
+                assert!(yes);
+            }
+            _ => panic!("Expected Otp Lock command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_bbt_mark() {
+        let args = Args::parse_from(["nander", "bbt", "mark", "17", "--file", "bbt.json"]);
+        match args.command {
+            Command::Bbt {
+                command: BbtCommand::Mark { block, file, .. },
+            } => {
+                assert_eq!(block, 17);
+                assert_eq!(file, Some(PathBuf::from("bbt.json")));
+            }
+            _ => panic!("Expected Bbt Mark command"),
+        }
+
+        let args = Args::parse_from(["nander", "bbt", "unmark", "3", "--yes"]);
+        match args.command {
+            Command::Bbt {
+                command:
+                    BbtCommand::Unmark {
+                        block, file, yes, ..
+                    },
+            } => {
+                assert_eq!(block, 3);
+                assert_eq!(file, None);
+                assert!(yes);
+            }
+            _ => panic!("Expected Bbt Unmark command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_bbt_write_linux() {
+        let args = Args::parse_from(["nander", "bbt", "write-linux", "-i", "bbt.json"]);
+        match args.command {
+            Command::Bbt {
+                command: BbtCommand::WriteLinux { input, .. },
+            } => assert_eq!(input, Some(PathBuf::from("bbt.json"))),
+            _ => panic!("Expected Bbt WriteLinux command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_bbt_diff() {
+        let args = Args::parse_from([
+            "nander",
+            "bbt",
+            "diff",
+            "factory.txt",
+            "today.log",
+            "--format",
+            "uboot",
+            "--blocks",
+            "1024",
+            "--block-size",
+            "128K",
+        ]);
+        match args.command {
+            Command::Bbt {
+                command: BbtCommand::Diff { old, new, file },
+            } => {
+                assert_eq!(old, PathBuf::from("factory.txt"));
+                assert_eq!(new, PathBuf::from("today.log"));
+                assert_eq!(file.format, Some(BbtFormat::UBoot));
+                assert_eq!(file.options().total_blocks, Some(1024));
+                assert_eq!(file.options().block_size, Some(128 * 1024));
+            }
+            _ => panic!("Expected Bbt Diff command"),
+        }
+    }
+
+    #[test]
+    fn test_parse_nand_health() {
+        let args = Args::parse_from([
+            "nander",
+            "nand",
+            "health",
+            "-o",
+            "health.json",
+            "--stress",
+            "8-11",
+            "--cycles",
+            "3",
+        ]);
+        match args.command {
+            Command::Nand {
+                command:
+                    NandCommand::Health {
+                        output,
+                        threshold,
+                        stress,
+                        cycles,
+                        yes,
+                    },
+            } => {
+                assert_eq!(output, Some(PathBuf::from("health.json")));
+                assert_eq!(threshold, None);
+                assert_eq!(stress, Some(8..12));
+                assert_eq!(cycles, 3);
+                assert!(!yes);
+            }
+            _ => panic!("Expected Nand Health command"),
+        }
+
+        assert_eq!(parse_block_range("42"), Ok(42..43));
+        assert!(parse_block_range("9-8").is_err());
+        assert!(Args::try_parse_from(["nander", "nand", "health", "--yes"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_protect_range() {
+        let args = Args::parse_from(["nander", "protect", "enable", "--length", "256K"]);
+        match args.command {
+            Command::Protect {
+                operation,
+                start,
+                length,
+            } => {
+                assert_eq!(operation, "enable");
+                assert_eq!(start, None);
+                assert_eq!(length, Some(256 * 1024));
+            }
+            _ => panic!("Expected Protect command"),
+        }
+
+        assert_eq!(parse_size("0x7C0000"), Ok(0x7C0000));
+        assert_eq!(parse_size("1M"), Ok(1024 * 1024));
+        assert!(parse_size("12Q").is_err());
+    }
+
+    #[test]
+    fn test_parse_soft_ecc() {
+        let config = parse_soft_ecc("bch8").unwrap();
+        assert_eq!(config.algorithm, SoftEccAlgorithm::Bch { strength: 8 });
+        assert_eq!(config.step_size, 512);
+        assert_eq!(config.ecc_offset, None);
+
+        let config = parse_soft_ecc("hamming:512@0x10").unwrap();
+        assert_eq!(config.algorithm, SoftEccAlgorithm::Hamming);
+        assert_eq!(config.step_size, 512);
+        assert_eq!(config.ecc_offset, Some(16));
+
+        assert!(parse_soft_ecc("reed-solomon").is_err());
+        assert!(parse_soft_ecc("bchx").is_err());
+    }
 }
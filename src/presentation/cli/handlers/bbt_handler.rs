@@ -1,13 +1,19 @@
 //! CLI Handler - Bad Block Table
 //!
-//! Handles 'bbt scan' and other BBT commands.
+//! Handles 'bbt scan' and other BBT commands, including the Linux
+//! on-flash tables.
 
 use crate::application::use_cases::detect_chip::DetectChipUseCase;
-use crate::domain::{bad_block::BlockStatus, FlashOperation, FlashType};
+use crate::domain::bad_block::{BadBlockTable, BlockStatus};
+use crate::domain::bbt_format::BbtFileOptions;
+use crate::domain::chip::ChipSpec;
+use crate::domain::nand_bbt::FlashBbt;
+use crate::domain::{FlashOperation, FlashType};
 use crate::error::{Error, Result};
 use crate::infrastructure::chip_database::ChipRegistry;
 use crate::infrastructure::flash_protocol::nand::SpiNand;
 use colored::*;
+use std::io::{self, BufRead, Write};
 use std::path::PathBuf;
 
 pub struct BbtHandler {
@@ -31,6 +37,7 @@ impl BbtHandler {
         &self,
         speed: Option<u8>,
         output: Option<PathBuf>,
+        options: BbtFileOptions,
         driver: Option<&str>,
     ) -> Result<()> {
         println!("Detecting flash chip...");
@@ -85,37 +92,251 @@ impl BbtHandler {
 
         if let Some(path) = output {
             println!("\nSaving BBT to: {:?}", path);
-            let file = std::fs::File::create(&path).map_err(Error::Io)?;
-            serde_json::to_writer_pretty(file, &bbt)
-                .map_err(|e| Error::Other(format!("Failed to save BBT file: {}", e)))?;
+            super::save_bbt_with(&path, &bbt, with_chip_block_size(options, &spec))?;
             println!("{}", "BBT saved successfully!".green().bold());
         }
 
         Ok(())
     }
 
-    pub fn handle_load(&self, input: PathBuf) -> Result<()> {
+    /// Open the detected chip, which must be SPI NAND
+    pub fn handle_mark(
+        &self,
+        block: u32,
+        file: Option<PathBuf>,
+        options: BbtFileOptions,
+        speed: Option<u8>,
+        driver: Option<&str>,
+    ) -> Result<()> {
+        if let Some(path) = file {
+            super::edit_bbt_file(&path, options, |bbt| bbt.mark_bad(block as usize))?;
+            println!("Block {} marked bad in {:?}", block, path);
+            return Ok(());
+        }
+
+        let mut nand = super::open_nand(&self.detect_use_case, speed, driver)?;
+        nand.mark_bad_block(block)?;
+        println!("{}", format!("Block {} marked bad", block).green().bold());
+        Ok(())
+    }
+
+    pub fn handle_unmark(
+        &self,
+        block: u32,
+        file: Option<PathBuf>,
+        options: BbtFileOptions,
+        yes: bool,
+        speed: Option<u8>,
+        driver: Option<&str>,
+    ) -> Result<()> {
+        if let Some(path) = file {
+            super::edit_bbt_file(&path, options, |bbt| {
+                if bbt.get_status(block as usize) == BlockStatus::BadFactory {
+                    println!(
+                        "{} block {} was factory bad",
+                        "WARNING:".yellow().bold(),
+                        block
+                    );
+                }
+                bbt.mark_good(block as usize)
+            })?;
+            println!("Block {} marked good in {:?}", block, path);
+            return Ok(());
+        }
+
+        let mut nand = super::open_nand(&self.detect_use_case, speed, driver)?;
+        println!(
+            "{} clearing the marker erases block {}. Factory bad blocks may not hold data reliably.",
+            "WARNING:".yellow().bold(),
+            block
+        );
+        if !yes {
+            print!("Erase block {}? [y/N] ", block);
+            io::stdout().flush().map_err(Error::Io)?;
+            let mut answer = String::new();
+            io::stdin()
+                .lock()
+                .read_line(&mut answer)
+                .map_err(Error::Io)?;
+            if !answer.trim().eq_ignore_ascii_case("y") {
+                return Err(Error::Other("Unmark aborted".to_string()));
+            }
+        }
+
+        nand.unmark_bad_block(block)?;
+        println!("{}", format!("Block {} marked good", block).green().bold());
+        Ok(())
+    }
+
+    pub fn handle_load(&self, input: PathBuf, options: BbtFileOptions) -> Result<()> {
         println!("Loading BBT from: {:?}", input);
-        let bbt = super::load_bbt(&input)?;
+        let bbt = super::load_bbt_with(&input, options)?;
 
-        let bad_count = bbt.bad_block_count();
-        println!("Loaded table with {} bad blocks.", bad_count);
+        println!("Loaded table with {} bad blocks.", bbt.bad_block_count());
+        print_blocks(&bbt);
+        Ok(())
+    }
 
-        if bad_count > 0 {
-            println!("--------------------------------");
-            println!("{:<10} {:<15}", "Block", "Status");
-            println!("--------------------------------");
+    pub fn handle_diff(&self, old: PathBuf, new: PathBuf, options: BbtFileOptions) -> Result<()> {
+        let before = super::load_bbt_with(&old, options)?;
+        let after = super::load_bbt_with(&new, options)?;
+        println!("Comparing {:?} -> {:?}", old, new);
+        if before.len() != after.len() {
+            println!(
+                "{} the tables cover {} and {} blocks",
+                "WARNING:".yellow().bold(),
+                before.len(),
+                after.len()
+            );
+        }
 
-            for block in 0..bbt.len() {
-                let status = bbt.get_status(block);
-                if status == BlockStatus::BadFactory {
-                    println!("{:<10} {}", block, "Factory Bad".red());
-                } else if status == BlockStatus::BadRuntime {
-                    println!("{:<10} {}", block, "Runtime Bad".red());
-                }
+        let changes = before.diff(&after);
+        if changes.is_empty() {
+            println!("{}", "No differences".green());
+            return Ok(());
+        }
+
+        println!("--------------------------------------------");
+        println!("{:<10} {:<16} {:<16}", "Block", "Before", "After");
+        println!("--------------------------------------------");
+        for change in &changes {
+            let line = format!(
+                "{:<10} {:<16} {:<16}",
+                change.block,
+                status_label(change.before),
+                status_label(change.after)
+            );
+            if change.went_bad() {
+                println!("{}", line.red().bold());
+            } else {
+                println!("{}", line);
             }
         }
 
+        let went_bad = changes.iter().filter(|change| change.went_bad()).count();
+        println!(
+            "\n{} block(s) went bad, {} other change(s)",
+            went_bad.to_string().red().bold(),
+            changes.len() - went_bad
+        );
+        Ok(())
+    }
+
+    pub fn handle_read_linux(
+        &self,
+        output: Option<PathBuf>,
+        options: BbtFileOptions,
+        speed: Option<u8>,
+        driver: Option<&str>,
+    ) -> Result<()> {
+        let mut nand = super::open_nand(&self.detect_use_case, speed, driver)?;
+        println!("Searching the last blocks for a Linux BBT...");
+        let Some(flash_bbt) = nand.read_flash_bbt()? else {
+            println!("{}", "No on-flash BBT found".yellow());
+            return Ok(());
+        };
+
+        print_locations(&flash_bbt);
+        println!(
+            "Table lists {} bad blocks.",
+            flash_bbt.table.bad_block_count()
+        );
+        print_blocks(&flash_bbt.table);
+
+        if let Some(path) = output {
+            println!("\nSaving BBT to: {:?}", path);
+            let options = with_chip_block_size(options, nand.spec());
+            super::save_bbt_with(&path, &flash_bbt.table, options)?;
+            println!("{}", "BBT saved successfully!".green().bold());
+        }
+        Ok(())
+    }
+
+    pub fn handle_write_linux(
+        &self,
+        input: Option<PathBuf>,
+        options: BbtFileOptions,
+        speed: Option<u8>,
+        driver: Option<&str>,
+    ) -> Result<()> {
+        let mut nand = super::open_nand(&self.detect_use_case, speed, driver)?;
+        let bbt = match input {
+            Some(path) => {
+                println!("Loading BBT from: {:?}", path);
+                let spec = nand.spec();
+                let total_blocks = (spec.capacity.as_bytes() / spec.layout.block_size) as usize;
+                let options = BbtFileOptions {
+                    total_blocks: options.total_blocks.or(Some(total_blocks)),
+                    ..with_chip_block_size(options, spec)
+                };
+                super::load_bbt_with(&path, options)?
+            }
+            None => {
+                println!("Scanning for bad blocks... (This may take a while)");
+                nand.scan_bbt(&|_| {})?
+            }
+        };
+
+        let flash_bbt = nand.write_flash_bbt(&bbt)?;
+        print_locations(&flash_bbt);
+        println!(
+            "{}",
+            format!(
+                "Linux BBT written with {} bad blocks",
+                flash_bbt.table.bad_block_count()
+            )
+            .green()
+            .bold()
+        );
         Ok(())
     }
 }
+
+/// List the blocks of `bbt` that are bad or reserved
+fn print_blocks(bbt: &BadBlockTable) {
+    if (0..bbt.len()).all(|block| !bbt.is_bad(block)) {
+        return;
+    }
+    println!("--------------------------------");
+    println!("{:<10} {:<15}", "Block", "Status");
+    println!("--------------------------------");
+
+    for block in (0..bbt.len()).filter(|&block| bbt.is_bad(block)) {
+        let status = bbt.get_status(block);
+        let label = status_label(status);
+        if status == BlockStatus::Reserved {
+            println!("{:<10} {}", block, label.yellow());
+        } else {
+            println!("{:<10} {}", block, label.red());
+        }
+    }
+}
+
+fn status_label(status: BlockStatus) -> &'static str {
+    match status {
+        BlockStatus::Unknown | BlockStatus::Good => "Good",
+        BlockStatus::BadFactory => "Factory Bad",
+        BlockStatus::BadRuntime => "Runtime Bad",
+        BlockStatus::Reserved => "Reserved (BBT)",
+    }
+}
+
+/// Fill in the chip's block size for U-Boot offsets unless one was given
+fn with_chip_block_size(options: BbtFileOptions, spec: &ChipSpec) -> BbtFileOptions {
+    BbtFileOptions {
+        block_size: options.block_size.or(Some(spec.layout.block_size)),
+        ..options
+    }
+}
+
+fn print_locations(flash_bbt: &FlashBbt) {
+    for (name, location) in [("Main", flash_bbt.main), ("Mirror", flash_bbt.mirror)] {
+        match location {
+            Some(location) => println!(
+                "{:<8} block {}, version {}",
+                name, location.block, location.version
+            ),
+            None => println!("{:<8} {}", name, "missing".yellow()),
+        }
+    }
+}
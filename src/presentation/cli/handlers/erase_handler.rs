@@ -4,12 +4,14 @@
 
 use crate::application::use_cases::detect_chip::DetectChipUseCase;
 use crate::application::use_cases::erase_flash::{EraseFlashUseCase, EraseParams};
-use crate::domain::FlashType;
-use crate::error::Result;
+use crate::domain::bbt_format::BbtFileOptions;
+use crate::domain::{EraseReport, FlashType};
+use crate::error::{Error, Result};
 use crate::infrastructure::chip_database::ChipRegistry;
 use crate::infrastructure::flash_protocol::eeprom::{I2cEeprom, MicrowireEeprom, SpiEeprom};
 use crate::infrastructure::flash_protocol::nand::SpiNand;
 use crate::infrastructure::flash_protocol::nor::SpiNor;
+use std::path::PathBuf;
 
 pub struct EraseHandler {
     detect_use_case: DetectChipUseCase,
@@ -49,6 +51,8 @@ impl EraseHandler {
             length: erase_len,
             bad_block_strategy: options.bad_block_strategy,
             bbt,
+            mark_bad: options.mark_bad,
+            partitions: options.partitions.clone(),
         };
 
         println!("Erasing {} bytes starting at 0x{:08X}...", erase_len, start);
@@ -59,12 +63,19 @@ impl EraseHandler {
             FlashType::Nand => {
                 let protocol = SpiNand::new(programmer, spec);
                 let mut use_case = EraseFlashUseCase::new(protocol);
-                use_case.execute(params, |progress| {
+                let result = use_case.execute_with_report(params, |progress| {
                     pb.set_position(progress.current);
-                })?
+                });
+                if let Some(path) = &options.bbt_file {
+                    pb.suspend(|| record_retired_blocks(path, &result, options.mark_bad))?;
+                }
+                result?;
             }
             FlashType::Nor => {
-                let protocol = SpiNor::new(programmer, spec);
+                let mut protocol = SpiNor::new(programmer, spec);
+                pb.suspend(|| {
+                    super::ensure_unlocked(&mut protocol, start..start + erase_len, options.unlock)
+                })?;
                 let mut use_case = EraseFlashUseCase::new(protocol);
                 use_case.execute(params, |progress| {
                     pb.set_position(progress.current);
@@ -111,3 +122,33 @@ impl EraseHandler {
         Ok(())
     }
 }
+
+/// Record the blocks an erase marked bad in the BBT file it was given,
+/// keeping the file's format. A failed erase that was not skipped still
+/// retired its block when `mark_bad` is set.
+fn record_retired_blocks(
+    path: &PathBuf,
+    result: &Result<EraseReport>,
+    mark_bad: bool,
+) -> Result<()> {
+    let retired = match result {
+        Ok(report) => report.retired_blocks.clone(),
+        Err(Error::EraseFailed { block }) if mark_bad => vec![*block],
+        Err(_) => return Ok(()),
+    };
+    if retired.is_empty() {
+        return Ok(());
+    }
+
+    super::edit_bbt_file(path, BbtFileOptions::default(), |bbt| {
+        retired
+            .iter()
+            .try_for_each(|&block| bbt.mark_bad(block as usize))
+    })?;
+    println!(
+        "Recorded {} newly bad block(s) in {}",
+        retired.len(),
+        path.display()
+    );
+    Ok(())
+}
@@ -2,9 +2,11 @@
 //!
 //! Handles the 'info' command by invoking the detect chip use case.
 
-use crate::application::use_cases::detect_chip::DetectChipUseCase;
+use crate::application::use_cases::detect_chip::{format_unique_id, DetectChipUseCase};
+use crate::domain::{Capacity, FlashType};
 use crate::error::Result;
 use crate::infrastructure::chip_database::ChipRegistry;
+use crate::infrastructure::flash_protocol::nand::SpiNand;
 
 pub struct InfoHandler {
     use_case: DetectChipUseCase,
@@ -23,13 +25,13 @@ impl InfoHandler {
         }
     }
 
-    pub fn handle(&self, speed: Option<u8>, driver: Option<&str>) -> Result<()> {
+    pub fn handle(&self, speed: Option<u8>, driver: Option<&str>, unique_id: bool) -> Result<()> {
         use colored::*;
 
         println!("Detecting flash chip...");
 
         match self.use_case.execute(speed, driver) {
-            Ok((programmer, spec)) => {
+            Ok((mut programmer, spec)) => {
                 println!("Programmer:   {}", programmer.name().cyan());
                 println!("----------------------------------");
                 println!("Manufacturer: {}", spec.manufacturer.green());
@@ -48,7 +50,48 @@ impl InfoHandler {
                     println!("Page Size:    {}", spec.layout.page_size);
                 }
 
+                if let Some(layout) = spec.layout.oob_layout {
+                    println!(
+                        "OOB Layout:   {} free, {} ECC bytes",
+                        layout.free_bytes(),
+                        layout.ecc_bytes()
+                    );
+                }
+
                 println!("Block Size:   {}", spec.layout.block_size);
+                if spec.layout.die_count > 1 {
+                    println!(
+                        "Dies:         {} x {}",
+                        spec.layout.die_count,
+                        Capacity::bytes(spec.layout.die_size(spec.capacity))
+                    );
+                }
+
+                if unique_id {
+                    match self.use_case.read_unique_id(programmer.as_mut(), &spec) {
+                        Ok(id) => println!("Unique ID:    {}", format_unique_id(&id).magenta()),
+                        Err(e) => println!("Unique ID:    {}", e.to_string().red()),
+                    }
+                }
+
+                if spec.flash_type == FlashType::Nand {
+                    let mut nand = SpiNand::new(programmer, spec);
+                    match nand.check_parameter_page() {
+                        Ok((page, mismatches)) => {
+                            println!("----------------------------------");
+                            println!(
+                                "Parameter page: {} {}",
+                                page.manufacturer.green(),
+                                page.model.green()
+                            );
+                            println!("ECC required: {} bit(s)", page.ecc_bits);
+                            for issue in mismatches {
+                                println!("{} {}", "WARNING:".yellow().bold(), issue);
+                            }
+                        }
+                        Err(e) => log::debug!("No parameter page: {}", e),
+                    }
+                }
                 Ok(())
             }
             Err(e) => Err(e),
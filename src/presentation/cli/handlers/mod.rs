@@ -6,6 +6,8 @@ pub mod bbt_handler;
 pub mod erase_handler;
 pub mod info_handler;
 pub mod list_handler;
+pub mod nand_handler;
+pub mod otp_handler;
 pub mod protect_handler;
 pub mod read_handler;
 pub mod verify_handler;
@@ -15,6 +17,8 @@ pub use bbt_handler::BbtHandler;
 pub use erase_handler::EraseHandler;
 pub use info_handler::InfoHandler;
 pub use list_handler::ListHandler;
+pub use nand_handler::NandHandler;
+pub use otp_handler::OtpHandler;
 pub use passthrough_handler::PassthroughHandler;
 pub use protect_handler::ProtectHandler;
 pub use read_handler::ReadHandler;
@@ -23,12 +27,21 @@ pub use write_handler::WriteHandler;
 
 pub mod passthrough_handler;
 
+use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
 use std::path::PathBuf;
 use std::time::Duration;
 
+use crate::application::use_cases::detect_chip::DetectChipUseCase;
 use crate::domain::bad_block::BadBlockTable;
+use crate::domain::bbt_format::BbtFileOptions;
+use crate::domain::FlashType;
 use crate::error::{Error, Result};
+use crate::infrastructure::flash_protocol::nand::SpiNand;
+use crate::infrastructure::flash_protocol::nor::SpiNor;
+use crate::infrastructure::programmer::Programmer;
 
 /// Create a standardized, stylish progress bar for flash operations
 pub fn create_progress_bar(total_size: u64, message: &'static str) -> ProgressBar {
@@ -46,8 +59,124 @@ pub fn create_progress_bar(total_size: u64, message: &'static str) -> ProgressBa
 
 /// Load a Bad Block Table from a JSON file
 pub fn load_bbt(path: &PathBuf) -> Result<BadBlockTable> {
-    let file = std::fs::File::open(path).map_err(Error::Io)?;
-    let bbt: BadBlockTable = serde_json::from_reader(file)
-        .map_err(|e| Error::Other(format!("Failed to parse BBT file: {}", e)))?;
-    Ok(bbt)
+    load_bbt_with(path, BbtFileOptions::default())
+}
+
+/// Load a BBT file in the format `options` selects or detected from it
+pub fn load_bbt_with(path: &PathBuf, options: BbtFileOptions) -> Result<BadBlockTable> {
+    let data = std::fs::read(path).map_err(Error::Io)?;
+    options.decode(options.load_format(path, &data), &data)
+}
+
+pub fn save_bbt(path: &PathBuf, bbt: &BadBlockTable) -> Result<()> {
+    save_bbt_with(path, bbt, BbtFileOptions::default())
+}
+
+pub fn save_bbt_with(path: &PathBuf, bbt: &BadBlockTable, options: BbtFileOptions) -> Result<()> {
+    let data = options.encode(options.save_format(path), bbt)?;
+    std::fs::write(path, data).map_err(Error::Io)
+}
+
+/// Apply `edit` to the BBT file `path` and save it back in the format it
+/// was read in
+pub fn edit_bbt_file(
+    path: &PathBuf,
+    options: BbtFileOptions,
+    edit: impl FnOnce(&mut BadBlockTable) -> Result<()>,
+) -> Result<()> {
+    let data = std::fs::read(path).map_err(Error::Io)?;
+    let options = BbtFileOptions {
+        format: Some(options.load_format(path, &data)),
+        ..options
+    };
+    let mut bbt = load_bbt_with(path, options)?;
+    edit(&mut bbt)?;
+    save_bbt_with(path, &bbt, options)
+}
+
+/// Detect the chip and open it as SPI NAND, refusing any other flash type
+pub fn open_nand(
+    detect_use_case: &DetectChipUseCase,
+    speed: Option<u8>,
+    driver: Option<&str>,
+) -> Result<SpiNand<Box<dyn Programmer>>> {
+    println!("Detecting flash chip...");
+    let (programmer, spec) = detect_use_case.execute(speed, driver)?;
+    if spec.flash_type != FlashType::Nand {
+        return Err(Error::NotSupported(
+            "This command needs a NAND flash chip".to_string(),
+        ));
+    }
+    println!(
+        "Detected: {} ({})",
+        spec.name.green().bold(),
+        spec.manufacturer.green()
+    );
+    Ok(SpiNand::new(programmer, spec))
+}
+
+/// Make sure no individually locked (WPS) NOR blocks sit in `range`. Locked
+/// blocks silently ignore program/erase, so offer a global unlock first, or
+/// do it straight away when `unlock` is set.
+pub fn ensure_unlocked<P: Programmer>(
+    nor: &mut SpiNor<P>,
+    range: Range<u32>,
+    unlock: bool,
+) -> Result<()> {
+    let locked = nor.locked_blocks(range.clone())?;
+    if locked.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "{} {} individually locked block(s) in the target range",
+        "WARNING:".yellow().bold(),
+        locked.len()
+    );
+    if !unlock {
+        print!("Globally unlock all blocks? [y/N] ");
+        io::stdout().flush().map_err(Error::Io)?;
+        let mut answer = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut answer)
+            .map_err(Error::Io)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            return Err(Error::Other(format!(
+                "{} locked block(s) in range; rerun with --unlock or use 'protect disable'",
+                locked.len()
+            )));
+        }
+    }
+
+    nor.global_unlock()?;
+    if !nor.locked_blocks(range)?.is_empty() {
+        return Err(Error::Other(
+            "Blocks are still locked after global unlock".to_string(),
+        ));
+    }
+    println!("All blocks unlocked.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::nand_bbt;
+
+    #[test]
+    fn test_edit_bbt_file_keeps_detected_format() {
+        let dir = tempfile::tempdir().unwrap();
+        // No known extension: the format comes from the contents
+        let path = dir.path().join("table.dat");
+        std::fs::write(&path, nand_bbt::encode(&BadBlockTable::new(16))).unwrap();
+
+        edit_bbt_file(&path, BbtFileOptions::default(), |bbt| bbt.mark_bad(3)).unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(data.len(), 4);
+        let bbt = nand_bbt::decode(&data, 16);
+        assert!(bbt.is_bad(3));
+        assert_eq!(bbt.bad_block_count(), 1);
+    }
 }
@@ -0,0 +1,168 @@
+//! NAND Command Handler
+//!
+//! Handles the NAND maintenance commands: the health survey and stress test.
+
+use crate::application::use_cases::detect_chip::DetectChipUseCase;
+use crate::domain::nand_health::{BlockCondition, HealthReport, HealthVerdict};
+use crate::error::{Error, Result};
+use crate::infrastructure::chip_database::ChipRegistry;
+use colored::*;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+
+pub struct NandHandler {
+    detect_use_case: DetectChipUseCase,
+}
+
+impl Default for NandHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NandHandler {
+    pub fn new() -> Self {
+        Self {
+            detect_use_case: DetectChipUseCase::new(ChipRegistry::new()),
+        }
+    }
+
+    /// Survey the chip, then stress test `stress`: the blocks and the
+    /// cycles per block
+    pub fn handle_health(
+        &self,
+        output: Option<PathBuf>,
+        threshold: Option<u8>,
+        stress: Option<(Range<u32>, u32)>,
+        yes: bool,
+        speed: Option<u8>,
+        driver: Option<&str>,
+    ) -> Result<()> {
+        let mut nand = super::open_nand(&self.detect_use_case, speed, driver)?;
+
+        if let Some((blocks, _)) = &stress {
+            println!(
+                "{} the stress test erases blocks {}-{}.",
+                "WARNING:".yellow().bold(),
+                blocks.start,
+                blocks.end - 1
+            );
+            if !yes {
+                print!("Destroy the data in {} block(s)? [y/N] ", blocks.len());
+                io::stdout().flush().map_err(Error::Io)?;
+                let mut answer = String::new();
+                io::stdin()
+                    .lock()
+                    .read_line(&mut answer)
+                    .map_err(Error::Io)?;
+                if !answer.trim().eq_ignore_ascii_case("y") {
+                    return Err(Error::Other("Health check aborted".to_string()));
+                }
+            }
+        }
+
+        // Leave the blocks of an on-flash BBT, and those it lists as bad, alone
+        println!("Searching the last blocks for a Linux BBT...");
+        let bbt = nand.read_flash_bbt()?.map(|flash_bbt| flash_bbt.table);
+
+        let spec = nand.spec();
+        let total_blocks = (spec.capacity.as_bytes() / spec.layout.block_size) as u64;
+        println!("Reading every good block with ECC... (This may take a while)");
+        let pb = super::create_progress_bar(total_blocks, "Surveying Blocks");
+        let mut report = nand.survey_health(threshold, &bbt, &|progress| {
+            pb.set_position(progress.current);
+        })?;
+        pb.finish_with_message("Survey Complete");
+
+        if let Some((blocks, cycles)) = stress {
+            let total = blocks.len() as u64 * cycles as u64;
+            let pb = super::create_progress_bar(total, "Stress Testing");
+            report.stress = nand.stress_test(blocks, cycles, &bbt, &|progress| {
+                pb.set_position(progress.current);
+            })?;
+            pb.finish_with_message("Stress Test Complete");
+        }
+
+        print_report(&report);
+
+        if let Some(path) = output {
+            std::fs::write(&path, report.to_json()?).map_err(Error::Io)?;
+            println!("\nReport saved to: {:?}", path);
+        }
+        Ok(())
+    }
+}
+
+fn print_report(report: &HealthReport) {
+    let summary = report.summary();
+    println!("\n{}", "Health Survey:".cyan().bold());
+    println!(
+        "ECC strength {} bits, blocks count as worn from {} bit flips",
+        report.ecc_strength, report.threshold
+    );
+    println!(
+        "Good: {}  Worn: {}  Failing: {}  Bad: {}",
+        summary.good.to_string().green(),
+        summary.worn.to_string().yellow(),
+        summary.failing.to_string().red(),
+        summary.bad
+    );
+
+    let attention: Vec<_> = report
+        .blocks
+        .iter()
+        .filter(|block| {
+            matches!(
+                block.condition,
+                BlockCondition::Worn | BlockCondition::Failing
+            )
+        })
+        .collect();
+    if !attention.is_empty() {
+        println!("--------------------------------------------");
+        println!(
+            "{:<10} {:<10} {:<10} {:<15}",
+            "Block", "Condition", "Max flips", "Uncorrectable"
+        );
+        for block in attention {
+            let condition = match block.condition {
+                BlockCondition::Failing => "failing".red(),
+                _ => "worn".yellow(),
+            };
+            println!(
+                "{:<10} {:<10} {:<10} {:<15}",
+                block.block,
+                condition,
+                block.max_bit_flips(),
+                block.uncorrectable_pages()
+            );
+        }
+        println!("--------------------------------------------");
+    }
+
+    if !report.stress.is_empty() {
+        println!("\n{}", "Stress Test:".cyan().bold());
+        for result in &report.stress {
+            match &result.failure {
+                Some(failure) => println!(
+                    "  Block {}: {} after {} cycles",
+                    result.block,
+                    failure.red(),
+                    result.cycles
+                ),
+                None => println!(
+                    "  Block {}: {} cycles passed, at most {} bit flips",
+                    result.block, result.cycles, result.max_bit_flips
+                ),
+            }
+        }
+    }
+
+    let verdict = match summary.verdict {
+        HealthVerdict::Healthy => "HEALTHY".green().bold(),
+        HealthVerdict::Degraded => "DEGRADED - blocks are wearing out".yellow().bold(),
+        HealthVerdict::Replace => "REPLACE - the flash is unreliable".red().bold(),
+    };
+    println!("\nVerdict: {}", verdict);
+}
@@ -0,0 +1,200 @@
+//! CLI Handler - OTP / Security Registers
+//!
+//! Handles the 'otp' subcommands for reading, programming, erasing and
+//! locking one-time programmable regions.
+
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use crate::application::use_cases::detect_chip::DetectChipUseCase;
+use crate::application::use_cases::otp_flash::OtpUseCase;
+use crate::domain::{ChipSpec, FlashOperation, FlashType};
+use crate::error::{Error, Result};
+use crate::infrastructure::chip_database::ChipRegistry;
+use crate::infrastructure::flash_protocol::nand::SpiNand;
+use crate::infrastructure::flash_protocol::nor::SpiNor;
+use colored::*;
+
+pub struct OtpHandler {
+    detect_use_case: DetectChipUseCase,
+}
+
+impl Default for OtpHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OtpHandler {
+    pub fn new() -> Self {
+        Self {
+            detect_use_case: DetectChipUseCase::new(ChipRegistry::new()),
+        }
+    }
+
+    fn open(
+        &self,
+        speed: Option<u8>,
+        driver: Option<&str>,
+    ) -> Result<(OtpUseCase<Box<dyn FlashOperation>>, ChipSpec)> {
+        let (programmer, spec) = self.detect_use_case.execute(speed, driver)?;
+        println!(
+            "Detected: {} ({})",
+            spec.name.green().bold(),
+            spec.manufacturer.green()
+        );
+
+        let flash: Box<dyn FlashOperation> = match spec.flash_type {
+            FlashType::Nor => Box::new(SpiNor::new(programmer, spec.clone())),
+            FlashType::Nand => Box::new(SpiNand::new(programmer, spec.clone())),
+            _ => {
+                return Err(Error::NotSupported(
+                    "OTP access not implemented for this flash type".to_string(),
+                ))
+            }
+        };
+
+        Ok((OtpUseCase::new(flash), spec))
+    }
+
+    fn region_size(spec: &ChipSpec) -> Result<(u32, u32)> {
+        spec.otp
+            .map(|otp| (otp.region_count, otp.region_size))
+            .ok_or_else(|| Error::NotSupported(format!("{} has no OTP regions", spec.name)))
+    }
+
+    pub fn handle_status(&self, speed: Option<u8>, driver: Option<&str>) -> Result<()> {
+        let (mut use_case, spec) = self.open(speed, driver)?;
+        let (count, size) = Self::region_size(&spec)?;
+
+        println!("{} region(s) of {} bytes", count, size);
+        for region in 0..count {
+            let state = match use_case.is_locked(region) {
+                Ok(true) => "LOCKED".red().bold(),
+                Ok(false) => "unlocked".green(),
+                Err(_) => "unknown".yellow(),
+            };
+            println!("  Region {}: {}", region, state);
+        }
+        Ok(())
+    }
+
+    pub fn handle_read(
+        &self,
+        region: u32,
+        offset: u32,
+        length: Option<u32>,
+        output: Option<PathBuf>,
+        speed: Option<u8>,
+        driver: Option<&str>,
+    ) -> Result<()> {
+        let (mut use_case, spec) = self.open(speed, driver)?;
+        let (_, size) = Self::region_size(&spec)?;
+        let length = length.unwrap_or(size.saturating_sub(offset));
+
+        let data = use_case.read(region, offset, length)?;
+
+        if let Some(path) = output {
+            std::fs::write(&path, &data).map_err(Error::Io)?;
+            println!("Saved {} bytes to {:?}", data.len(), path);
+        } else {
+            for (i, line) in data.chunks(16).enumerate() {
+                let hex: Vec<String> = line.iter().map(|b| format!("{:02X}", b)).collect();
+                println!("{:04X}: {}", offset as usize + i * 16, hex.join(" "));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn handle_program(
+        &self,
+        region: u32,
+        offset: u32,
+        input: PathBuf,
+        speed: Option<u8>,
+        driver: Option<&str>,
+    ) -> Result<()> {
+        let data = std::fs::read(input).map_err(Error::Io)?;
+        let (mut use_case, _) = self.open(speed, driver)?;
+
+        println!(
+            "Programming {} bytes into OTP region {} at offset 0x{:X}...",
+            data.len(),
+            region,
+            offset
+        );
+        use_case.program(region, offset, &data)?;
+        println!("{}", "OTP program verified.".green().bold());
+        Ok(())
+    }
+
+    pub fn handle_erase(&self, region: u32, speed: Option<u8>, driver: Option<&str>) -> Result<()> {
+        let (mut use_case, _) = self.open(speed, driver)?;
+
+        println!("Erasing OTP region {}...", region);
+        use_case.erase(region)?;
+        println!("{}", "Done.".green());
+        Ok(())
+    }
+
+    pub fn handle_lock(
+        &self,
+        region: u32,
+        yes: bool,
+        speed: Option<u8>,
+        driver: Option<&str>,
+    ) -> Result<()> {
+        let (mut use_case, spec) = self.open(speed, driver)?;
+
+        if use_case.is_locked(region)? {
+            println!("OTP region {} is already locked.", region);
+            return Ok(());
+        }
+
+        println!(
+            "{}",
+            format!(
+                "WARNING: locking OTP region {} is PERMANENT. It can never be programmed or erased again.",
+                region
+            )
+            .red()
+            .bold()
+        );
+        if spec.flash_type == FlashType::Nand {
+            println!(
+                "{}",
+                "SPI NAND locks the ENTIRE OTP area, not just this region."
+                    .red()
+                    .bold()
+            );
+        }
+        if !yes && !Self::confirm("Type LOCK to continue: ")? {
+            return Err(Error::Other("OTP lock aborted".to_string()));
+        }
+
+        use_case.lock(region)?;
+        if !use_case.is_locked(region)? {
+            return Err(Error::Other(format!(
+                "OTP region {} did not report locked after the lock command",
+                region
+            )));
+        }
+        println!(
+            "{}",
+            format!("OTP region {} locked.", region).green().bold()
+        );
+        Ok(())
+    }
+
+    fn confirm(prompt: &str) -> Result<bool> {
+        print!("{}", prompt);
+        io::stdout().flush().map_err(Error::Io)?;
+
+        let mut answer = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut answer)
+            .map_err(Error::Io)?;
+        Ok(answer.trim() == "LOCK")
+    }
+}
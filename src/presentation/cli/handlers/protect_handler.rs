@@ -10,7 +10,9 @@ use crate::infrastructure::chip_database::ChipRegistry;
 use crate::infrastructure::flash_protocol::eeprom::SpiEeprom;
 use crate::infrastructure::flash_protocol::nand::SpiNand;
 use crate::infrastructure::flash_protocol::nor::SpiNor;
+use crate::infrastructure::programmer::Programmer;
 use colored::*;
+use std::ops::Range;
 
 pub struct ProtectHandler {
     detect_use_case: DetectChipUseCase,
@@ -37,8 +39,14 @@ impl ProtectHandler {
     ) -> Result<()> {
         let (programmer, spec) = self.detect_use_case.execute(speed, driver)?;
 
+        // Register definitions for decoding (NOR only)
+        let mut registers = Vec::new();
         let status_use_case: Box<dyn crate::domain::FlashOperation> = match spec.flash_type {
-            FlashType::Nor => Box::new(SpiNor::new(programmer, spec)),
+            FlashType::Nor => {
+                let nor = SpiNor::new(programmer, spec);
+                registers.extend_from_slice(nor.status_registers());
+                Box::new(nor)
+            }
             FlashType::Nand => Box::new(SpiNand::new(programmer, spec)),
             FlashType::SpiEeprom => Box::new(SpiEeprom::new(programmer, spec)),
             _ => {
@@ -59,11 +67,24 @@ impl ProtectHandler {
             println!("{}", "SUCCESS!".green().bold());
         } else {
             let status = use_case.get_status()?;
-            print!("{}", "Status register(s):".cyan().bold());
-            for b in status {
-                print!(" 0x{:02X}", b);
+            if registers.is_empty() {
+                print!("{}", "Status register(s):".cyan().bold());
+                for b in status {
+                    print!(" 0x{:02X}", b);
+                }
+                println!();
+            } else {
+                println!("{}", "Status register(s):".cyan().bold());
+                for (register, value) in registers.iter().zip(status) {
+                    println!(
+                        "  {:<4} (0x{:02X}): 0x{:02X}  {}",
+                        register.name,
+                        register.read_opcode,
+                        value,
+                        register.set_bits(value).join(" ").yellow()
+                    );
+                }
             }
-            println!();
         }
 
         Ok(())
@@ -72,121 +93,186 @@ impl ProtectHandler {
     pub fn handle_protect(
         &self,
         operation: &str,
+        start: Option<u32>,
+        length: Option<u32>,
         speed: Option<u8>,
         driver: Option<&str>,
     ) -> Result<()> {
         let (programmer, spec) = self.detect_use_case.execute(speed, driver)?;
+        let scheme = spec.protection_scheme().ok_or_else(|| {
+            Error::NotSupported("Protect command not supported for this flash type".to_string())
+        })?;
+        let capacity = spec.capacity.as_bytes();
 
-        match spec.flash_type {
+        let mut flash: Box<dyn FlashOperation> = match spec.flash_type {
             FlashType::Nor => {
-                let mut protocol = SpiNor::new(programmer, spec);
-                let current_sr = protocol.read_status()?;
-                let bp_mask = 0x3C; // BP0-BP3
-
-                match operation {
-                    "status" => {
-                        println!("Current Status Register: 0x{:02X}", current_sr);
-                        let locked = (current_sr & bp_mask) != 0;
-                        if locked {
-                            println!("Write Protection: {}", "ENABLED".red().bold());
-                        } else {
-                            println!("Write Protection: {}", "DISABLED".green().bold());
-                        }
-                    }
-                    "enable" => {
-                        println!("Enabling write protection (setting BP bits)...");
-                        protocol.set_status(&[current_sr | bp_mask])?;
-                        println!("{}", "Done.".green());
-                    }
-                    "disable" => {
-                        println!("Disabling write protection (clearing BP bits)...");
-                        protocol.set_status(&[current_sr & !bp_mask])?;
-                        println!("{}", "Done.".green());
-                    }
-                    _ => {
-                        return Err(Error::Validation(format!(
-                            "Unknown operation: {}",
-                            operation
-                        )))
-                    }
+                let mut nor = SpiNor::new(programmer, spec.clone());
+                if nor.wps_enabled()? {
+                    return Self::handle_block_lock(&mut nor, operation, start, length);
                 }
+                Box::new(nor)
             }
-            FlashType::Nand => {
-                let mut protocol = SpiNand::new(programmer, spec);
-                let status = protocol.get_status()?;
-                let prot_reg = status[0]; // 0xA0 is first in NAND get_status
-                let bp_mask = 0x7C; // BP0, BP1, BP2, BP3, CMP, INV etc vary but 0x7C covers main ones
-
-                match operation {
-                    "status" => {
-                        println!("Protection Register (0xA0): 0x{:02X}", prot_reg);
-                        println!("Config Register (0xB0):     0x{:02X}", status[1]);
-                        println!("Status Register (0xC0):     0x{:02X}", status[2]);
-                        let locked = (prot_reg & bp_mask) != 0;
-                        if locked {
-                            println!("Write Protection: {}", "ENABLED".red().bold());
-                        } else {
-                            println!("Write Protection: {}", "DISABLED".green().bold());
-                        }
-                    }
-                    "enable" => {
-                        println!("Enabling NAND write protection...");
-                        protocol.set_status(&[prot_reg | bp_mask, status[1], status[2]])?;
-                        println!("{}", "Done.".green());
-                    }
-                    "disable" => {
-                        println!("Disabling NAND write protection...");
-                        protocol.set_status(&[prot_reg & !bp_mask, status[1], status[2]])?;
-                        println!("{}", "Done.".green());
-                    }
-                    _ => {
-                        return Err(Error::Validation(format!(
-                            "Unknown operation: {}",
-                            operation
-                        )))
-                    }
+            FlashType::Nand => Box::new(SpiNand::new(programmer, spec.clone())),
+            FlashType::SpiEeprom => Box::new(SpiEeprom::new(programmer, spec.clone())),
+            _ => {
+                return Err(Error::NotSupported(
+                    "Protect command not supported for this flash type".to_string(),
+                ))
+            }
+        };
+        let registers = flash.get_status()?;
+
+        let target = match operation {
+            "status" => {
+                print!("Protection register(s):");
+                for value in &registers[..scheme.registers_used().min(registers.len())] {
+                    print!(" 0x{:02X}", value);
                 }
+                println!();
+                Self::print_range(&scheme.decode(&registers, capacity), scheme.is_exact());
+                return Ok(());
             }
-            FlashType::SpiEeprom => {
-                let mut protocol = SpiEeprom::new(programmer, spec);
-                let current_sr = protocol.get_status()?[0];
-                let bp_mask = 0x0C; // BP0-BP1 for most 25xxx
-
-                match operation {
-                    "status" => {
-                        println!("Status Register: 0x{:02X}", current_sr);
-                        let locked = (current_sr & bp_mask) != 0;
-                        if locked {
-                            println!("Write Protection: {}", "ENABLED (BP bits set)".red().bold());
-                        } else {
-                            println!("Write Protection: {}", "DISABLED".green().bold());
-                        }
-                    }
-                    "enable" => {
-                        println!("Enabling EEPROM write protection...");
-                        protocol.set_status(&[current_sr | bp_mask])?;
-                        println!("{}", "Done.".green());
-                    }
-                    "disable" => {
-                        println!("Disabling EEPROM write protection...");
-                        protocol.set_status(&[current_sr & !bp_mask])?;
-                        println!("{}", "Done.".green());
-                    }
-                    _ => {
-                        return Err(Error::Validation(format!(
-                            "Unknown operation: {}",
-                            operation
-                        )))
-                    }
+            "enable" => {
+                let start = start.unwrap_or(0);
+                let length = length.unwrap_or(capacity.saturating_sub(start));
+                if start as u64 + length as u64 > capacity as u64 {
+                    return Err(Error::InvalidParameter(format!(
+                        "Range 0x{:08X}+0x{:X} exceeds chip capacity",
+                        start, length
+                    )));
                 }
+                let range = start..start + length;
+                if !scheme.is_exact() && range != (0..capacity) {
+                    return Err(Error::NotSupported(format!(
+                        "{} has no known block protection map; only whole-chip protection is available",
+                        spec.name
+                    )));
+                }
+                println!(
+                    "Protecting 0x{:08X}-0x{:08X}...",
+                    range.start,
+                    range.end.saturating_sub(1)
+                );
+                range
+            }
+            "disable" => {
+                println!("Disabling write protection...");
+                0..0
             }
             _ => {
-                return Err(Error::NotSupported(
-                    "Protect command not supported for this flash type".to_string(),
-                ))
+                return Err(Error::Validation(format!(
+                    "Unknown operation: {}",
+                    operation
+                )))
+            }
+        };
+
+        let updated = scheme.encode(&registers, target.clone(), capacity)?;
+        flash.set_status(&updated[..scheme.registers_used()])?;
+
+        let actual = scheme.decode(&flash.get_status()?, capacity);
+        if actual != target {
+            return Err(Error::Other(format!(
+                "Protection bits did not take effect (now 0x{:08X}-0x{:08X}); check WP# and SRP",
+                actual.start,
+                actual.end.saturating_sub(1)
+            )));
+        }
+        println!("{}", "Done.".green());
+        Self::print_range(&actual, scheme.is_exact());
+        Ok(())
+    }
+
+    /// Protection in individual block lock mode (WPS = 1), where the BP
+    /// bits are ignored
+    fn handle_block_lock<P: Programmer>(
+        nor: &mut SpiNor<P>,
+        operation: &str,
+        start: Option<u32>,
+        length: Option<u32>,
+    ) -> Result<()> {
+        let capacity = nor.spec().capacity.as_bytes();
+        println!("Individual block protection (WPS=1) is active; BP bits are ignored.");
+
+        match operation {
+            "status" => {}
+            "enable" if start.is_none() && length.is_none() => {
+                println!("Locking all blocks...");
+                nor.global_lock()?;
+            }
+            "enable" => {
+                let start = start.unwrap_or(0);
+                let end = length.map_or(capacity, |length| start.saturating_add(length));
+                println!("Locking 0x{:08X}-0x{:08X}...", start, end.saturating_sub(1));
+                nor.lock_blocks(start..end)?;
+            }
+            "disable" => {
+                println!("Unlocking all blocks...");
+                nor.global_unlock()?;
+            }
+            _ => {
+                return Err(Error::Validation(format!(
+                    "Unknown operation: {}",
+                    operation
+                )))
             }
         }
 
+        let map = nor.block_lock_map(0..capacity)?;
+        let total = map.len();
+        let locked_units: Vec<Range<u32>> = map
+            .into_iter()
+            .filter(|(_, is_locked)| *is_locked)
+            .map(|(unit, _)| unit)
+            .collect();
+        let count = locked_units.len();
+
+        // Merge adjacent locked units into ranges
+        let mut locked: Vec<Range<u32>> = Vec::new();
+        for unit in locked_units {
+            match locked.last_mut() {
+                Some(last) if last.end == unit.start => last.end = unit.end,
+                _ => locked.push(unit),
+            }
+        }
+
+        if locked.is_empty() {
+            println!("Write Protection: {}", "DISABLED".green().bold());
+        } else {
+            println!(
+                "Write Protection: {} ({} of {} lock units)",
+                "ENABLED".red().bold(),
+                count,
+                total
+            );
+            for range in locked {
+                println!(
+                    "Locked:           0x{:08X}-0x{:08X} ({} KiB)",
+                    range.start,
+                    range.end - 1,
+                    range.len() / 1024
+                );
+            }
+        }
         Ok(())
     }
+
+    fn print_range(range: &Range<u32>, exact: bool) {
+        if range.is_empty() {
+            println!("Write Protection: {}", "DISABLED".green().bold());
+        } else if !exact {
+            println!(
+                "Write Protection: {} (range not known for this chip)",
+                "ENABLED".red().bold()
+            );
+        } else {
+            println!("Write Protection: {}", "ENABLED".red().bold());
+            println!(
+                "Protected:        0x{:08X}-0x{:08X} ({} KiB)",
+                range.start,
+                range.end - 1,
+                range.len() / 1024
+            );
+        }
+    }
 }
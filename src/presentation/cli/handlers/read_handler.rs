@@ -7,8 +7,10 @@ use std::io::Write;
 use std::path::PathBuf;
 
 use crate::application::use_cases::detect_chip::DetectChipUseCase;
-use crate::application::use_cases::read_flash::{ReadFlashUseCase, ReadParams};
-use crate::domain::FlashType;
+use crate::application::use_cases::read_flash::{
+    format_consensus_report, format_ecc_report, ReadFlashUseCase, ReadParams,
+};
+use crate::domain::{FlashType, Progress};
 use crate::error::{Error, Result};
 use crate::infrastructure::chip_database::ChipRegistry;
 use crate::infrastructure::flash_protocol::eeprom::{I2cEeprom, MicrowireEeprom, SpiEeprom};
@@ -54,64 +56,69 @@ impl ReadHandler {
             length: read_len,
             use_ecc: options.use_ecc,
             ignore_ecc_errors: options.ignore_ecc_errors,
+            soft_ecc: options.soft_ecc,
             oob_mode: options.oob_mode,
             bad_block_strategy: options.bad_block_strategy,
             bbt,
             retry_count: options.retry_count,
+            partitions: options.partitions.clone(),
+            passes: options.passes,
         };
 
         println!("Reading {} bytes starting at 0x{:08X}...", read_len, start);
+        if options.passes > 1 {
+            println!(
+                "Reading each chunk {} times for a majority vote",
+                options.passes
+            );
+        }
 
-        let pb = super::create_progress_bar(read_len as u64, "Reading");
+        let pb = super::create_progress_bar(read_len as u64 * options.passes as u64, "Reading");
 
-        let data = match spec.flash_type {
+        let on_progress = |progress: Progress| pb.set_position(progress.current);
+        let (data, report) = match spec.flash_type {
             FlashType::Nand => {
                 let protocol = SpiNand::new(programmer, spec);
-                let mut use_case = ReadFlashUseCase::new(protocol);
-                use_case.execute(params, |progress| {
-                    pb.set_position(progress.current);
-                })?
+                ReadFlashUseCase::new(protocol).execute_with_report(params, on_progress)?
             }
             FlashType::Nor => {
                 let protocol = SpiNor::new(programmer, spec);
-                let mut use_case = ReadFlashUseCase::new(protocol);
-                use_case.execute(params, |progress| {
-                    pb.set_position(progress.current);
-                })?
+                ReadFlashUseCase::new(protocol).execute_with_report(params, on_progress)?
             }
             FlashType::SpiEeprom => {
                 let protocol = SpiEeprom::new(programmer, spec);
-                let mut use_case = ReadFlashUseCase::new(protocol);
-                use_case.execute(params, |progress| {
-                    pb.set_position(progress.current);
-                })?
+                ReadFlashUseCase::new(protocol).execute_with_report(params, on_progress)?
             }
             FlashType::I2cEeprom => {
                 let protocol = I2cEeprom::new(programmer, spec);
-                let mut use_case = ReadFlashUseCase::new(protocol);
-                use_case.execute(params, |progress| {
-                    pb.set_position(progress.current);
-                })?
+                ReadFlashUseCase::new(protocol).execute_with_report(params, on_progress)?
             }
             FlashType::MicrowireEeprom => {
                 let protocol = MicrowireEeprom::new(programmer, spec);
-                let mut use_case = ReadFlashUseCase::new(protocol);
-                use_case.execute(params, |progress| {
-                    pb.set_position(progress.current);
-                })?
+                ReadFlashUseCase::new(protocol).execute_with_report(params, on_progress)?
             }
             FlashType::SpiFram => {
                 // FRAM uses same protocol as SPI EEPROM
                 let protocol = SpiEeprom::new(programmer, spec);
-                let mut use_case = ReadFlashUseCase::new(protocol);
-                use_case.execute(params, |progress| {
-                    pb.set_position(progress.current);
-                })?
+                ReadFlashUseCase::new(protocol).execute_with_report(params, on_progress)?
             }
         };
 
         pb.finish_with_message("Read Complete");
 
+        if !report.ecc.pages.is_empty() {
+            println!();
+            for line in format_ecc_report(&report.ecc) {
+                println!("{}", line);
+            }
+        }
+        if options.passes > 1 {
+            println!();
+            for line in format_consensus_report(options.passes, &report.disagreements) {
+                println!("{}", line);
+            }
+        }
+
         println!("\nWriting to file: {:?}", output);
         let mut file = File::create(output).map_err(Error::Io)?;
         file.write_all(&data).map_err(Error::Io)?;
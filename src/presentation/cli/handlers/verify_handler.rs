@@ -50,10 +50,12 @@ impl VerifyHandler {
             data: &expected_data,
             use_ecc: options.use_ecc,
             ignore_ecc_errors: options.ignore_ecc_errors,
+            soft_ecc: options.soft_ecc,
             oob_mode: options.oob_mode,
             bad_block_strategy: options.bad_block_strategy,
             bbt,
             retry_count: options.retry_count,
+            partitions: options.partitions.clone(),
         };
 
         let pb = super::create_progress_bar(length as u64, "Verifying");
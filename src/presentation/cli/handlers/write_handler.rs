@@ -5,8 +5,10 @@
 use std::path::PathBuf;
 
 use crate::application::use_cases::detect_chip::DetectChipUseCase;
-use crate::application::use_cases::write_flash::{WriteFlashUseCase, WriteParams};
-use crate::domain::FlashType;
+use crate::application::use_cases::write_flash::{
+    format_relocations, WriteFlashUseCase, WriteParams,
+};
+use crate::domain::{FlashType, WriteMode};
 use crate::error::{Error, Result};
 use crate::infrastructure::chip_database::ChipRegistry;
 use crate::infrastructure::flash_protocol::eeprom::{I2cEeprom, MicrowireEeprom, SpiEeprom};
@@ -43,6 +45,13 @@ impl WriteHandler {
             data.len(),
             start
         );
+        match options.write_mode {
+            WriteMode::Patch => {
+                println!("Patch mode: preserving surrounding data in each erase unit")
+            }
+            WriteMode::Delta => println!("Delta mode: skipping erase units that already match"),
+            WriteMode::Direct => {}
+        }
 
         // Load BBT if provided
         let bbt = if let Some(ref path) = options.bbt_file {
@@ -57,15 +66,18 @@ impl WriteHandler {
             use_ecc: options.use_ecc,
             verify: options.verify,
             ignore_ecc_errors: options.ignore_ecc_errors,
+            soft_ecc: options.soft_ecc,
             oob_mode: options.oob_mode,
             bad_block_strategy: options.bad_block_strategy,
             bbt,
             retry_count: options.retry_count,
+            mode: options.write_mode,
+            partitions: options.partitions.clone(),
         };
 
         let pb = super::create_progress_bar(data.len() as u64, "Writing");
 
-        match spec.flash_type {
+        let report = match spec.flash_type {
             FlashType::Nand => {
                 let protocol = SpiNand::new(programmer, spec);
                 let mut use_case = WriteFlashUseCase::new(protocol);
@@ -74,7 +86,14 @@ impl WriteHandler {
                 })?
             }
             FlashType::Nor => {
-                let protocol = SpiNor::new(programmer, spec);
+                let mut protocol = SpiNor::new(programmer, spec);
+                pb.suspend(|| {
+                    super::ensure_unlocked(
+                        &mut protocol,
+                        start..start + data.len() as u32,
+                        options.unlock,
+                    )
+                })?;
                 let mut use_case = WriteFlashUseCase::new(protocol);
                 use_case.execute(params, |progress| {
                     pb.set_position(progress.current);
@@ -113,6 +132,21 @@ impl WriteHandler {
 
         pb.finish_with_message("Write Complete");
 
+        for line in format_relocations(&report.relocated_blocks) {
+            println!("{}", line);
+        }
+
+        if options.write_mode != WriteMode::Direct {
+            println!(
+                "Skipped {} of {} erase units ({} erased), programmed {} pages, skipped {} pages",
+                report.units_skipped,
+                report.units_total,
+                report.units_erased,
+                report.pages_programmed,
+                report.pages_skipped
+            );
+        }
+
         use colored::*;
         println!("{}", "\nWrite SUCCESSFUL!".green().bold());
         Ok(())
@@ -6,7 +6,8 @@ pub mod args;
 pub mod handlers;
 
 use crate::domain::bad_block::BadBlockStrategy;
-use crate::domain::{FlashOptions, OobMode};
+use crate::domain::partition::parse_mtdparts;
+use crate::domain::{FlashOptions, OobMode, Partition, WriteMode};
 use crate::error::Result;
 use args::{Args, Command};
 use handlers::*;
@@ -21,8 +22,10 @@ fn get_bad_block_strategy(skip: bool, include: bool) -> BadBlockStrategy {
     }
 }
 
-fn get_oob_mode(oob: bool, oob_only: bool) -> OobMode {
-    if oob_only {
+fn get_oob_mode(oob: bool, oob_only: bool, oob_free: bool) -> OobMode {
+    if oob_free {
+        OobMode::Free
+    } else if oob_only {
         OobMode::Only
     } else if oob {
         OobMode::Included
@@ -31,12 +34,20 @@ fn get_oob_mode(oob: bool, oob_only: bool) -> OobMode {
     }
 }
 
+fn get_partitions(partitions: Option<String>) -> Result<Vec<Partition>> {
+    partitions
+        .as_deref()
+        .map(parse_mtdparts)
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
 /// Execute the command specified by CLI arguments using the new architecture
 pub fn execute(args: Args) -> Result<()> {
     match args.command {
-        Command::Info => {
+        Command::Info { unique_id } => {
             let handler = InfoHandler::new();
-            handler.handle(Some(args.spi_speed), Some(&args.driver))
+            handler.handle(Some(args.spi_speed), Some(&args.driver), unique_id)
         }
         Command::List => {
             let handler = ListHandler::new();
@@ -51,9 +62,13 @@ pub fn execute(args: Args) -> Result<()> {
             include_bad,
             oob,
             oob_only,
+            oob_free,
             ignore_ecc,
+            soft_ecc,
             retries,
+            passes,
             bbt_file,
+            partitions,
         } => {
             let handler = ReadHandler::new();
             let options = FlashOptions {
@@ -61,13 +76,19 @@ pub fn execute(args: Args) -> Result<()> {
                 length,
                 use_ecc: !disable_ecc,
                 ignore_ecc_errors: ignore_ecc,
+                soft_ecc,
                 bad_block_strategy: get_bad_block_strategy(skip_bad, include_bad),
-                oob_mode: get_oob_mode(oob, oob_only),
+                oob_mode: get_oob_mode(oob, oob_only, oob_free),
                 speed: Some(args.spi_speed),
                 verify: false,
+                write_mode: WriteMode::Direct,
                 retry_count: retries,
+                passes,
                 bbt_file,
                 driver: Some(args.driver.clone()),
+                unlock: false,
+                mark_bad: false,
+                partitions: get_partitions(partitions)?,
             };
             handler.handle(output, options)
         }
@@ -75,14 +96,20 @@ pub fn execute(args: Args) -> Result<()> {
             input,
             start,
             verify,
+            patch,
+            delta,
             disable_ecc,
             skip_bad,
             include_bad,
             oob,
             oob_only,
+            oob_free,
             ignore_ecc,
+            soft_ecc,
             retries,
             bbt_file,
+            partitions,
+            unlock,
         } => {
             let handler = WriteHandler::new();
             let options = FlashOptions {
@@ -90,13 +117,25 @@ pub fn execute(args: Args) -> Result<()> {
                 length: None, // Write uses input file length
                 use_ecc: !disable_ecc,
                 ignore_ecc_errors: ignore_ecc,
+                soft_ecc,
                 bad_block_strategy: get_bad_block_strategy(skip_bad, include_bad),
-                oob_mode: get_oob_mode(oob, oob_only),
+                oob_mode: get_oob_mode(oob, oob_only, oob_free),
                 speed: Some(args.spi_speed),
                 verify,
+                write_mode: if patch {
+                    WriteMode::Patch
+                } else if delta {
+                    WriteMode::Delta
+                } else {
+                    WriteMode::Direct
+                },
                 retry_count: retries,
+                passes: 1,
                 bbt_file,
                 driver: Some(args.driver.clone()),
+                unlock,
+                mark_bad: false,
+                partitions: get_partitions(partitions)?,
             };
             handler.handle(input, options)
         }
@@ -107,6 +146,9 @@ pub fn execute(args: Args) -> Result<()> {
             skip_bad,
             include_bad,
             bbt_file,
+            partitions,
+            unlock,
+            mark_bad,
         } => {
             let handler = EraseHandler::new();
             let options = FlashOptions {
@@ -116,6 +158,9 @@ pub fn execute(args: Args) -> Result<()> {
                 speed: Some(args.spi_speed),
                 bbt_file,
                 driver: Some(args.driver.clone()),
+                unlock,
+                mark_bad,
+                partitions: get_partitions(partitions)?,
                 ..Default::default()
             };
             handler.handle(options)
@@ -128,9 +173,12 @@ pub fn execute(args: Args) -> Result<()> {
             include_bad,
             oob,
             oob_only,
+            oob_free,
             ignore_ecc,
+            soft_ecc,
             retries,
             bbt_file,
+            partitions,
         } => {
             let handler = VerifyHandler::new();
             let options = FlashOptions {
@@ -138,19 +186,35 @@ pub fn execute(args: Args) -> Result<()> {
                 length: None,
                 use_ecc: !disable_ecc,
                 ignore_ecc_errors: ignore_ecc,
+                soft_ecc,
                 bad_block_strategy: get_bad_block_strategy(skip_bad, include_bad),
-                oob_mode: get_oob_mode(oob, oob_only),
+                oob_mode: get_oob_mode(oob, oob_only, oob_free),
                 speed: Some(args.spi_speed),
                 verify: false,
+                write_mode: WriteMode::Direct,
                 retry_count: retries,
+                passes: 1,
                 bbt_file,
                 driver: Some(args.driver.clone()),
+                unlock: false,
+                mark_bad: false,
+                partitions: get_partitions(partitions)?,
             };
             handler.handle(input, options)
         }
-        Command::Protect { operation } => {
+        Command::Protect {
+            operation,
+            start,
+            length,
+        } => {
             let handler = ProtectHandler::new();
-            handler.handle_protect(&operation, Some(args.spi_speed), Some(&args.driver))
+            handler.handle_protect(
+                &operation,
+                start,
+                length,
+                Some(args.spi_speed),
+                Some(&args.driver),
+            )
         }
         Command::Status { value } => {
             let handler = ProtectHandler::new();
@@ -159,10 +223,94 @@ pub fn execute(args: Args) -> Result<()> {
         Command::Bbt { command } => {
             let handler = BbtHandler::new();
             match command {
-                args::BbtCommand::Scan { output } => {
-                    handler.handle_scan(Some(args.spi_speed), output, Some(&args.driver))
+                args::BbtCommand::Scan { output, file } => handler.handle_scan(
+                    Some(args.spi_speed),
+                    output,
+                    file.options(),
+                    Some(&args.driver),
+                ),
+                args::BbtCommand::Load { input, file } => {
+                    handler.handle_load(input, file.options())
+                }
+                args::BbtCommand::Diff { old, new, file } => {
+                    handler.handle_diff(old, new, file.options())
+                }
+                args::BbtCommand::Mark {
+                    block,
+                    file,
+                    format,
+                } => handler.handle_mark(
+                    block,
+                    file,
+                    format.options(),
+                    Some(args.spi_speed),
+                    Some(&args.driver),
+                ),
+                args::BbtCommand::Unmark {
+                    block,
+                    file,
+                    format,
+                    yes,
+                } => handler.handle_unmark(
+                    block,
+                    file,
+                    format.options(),
+                    yes,
+                    Some(args.spi_speed),
+                    Some(&args.driver),
+                ),
+                args::BbtCommand::ReadLinux { output, file } => handler.handle_read_linux(
+                    output,
+                    file.options(),
+                    Some(args.spi_speed),
+                    Some(&args.driver),
+                ),
+                args::BbtCommand::WriteLinux { input, file } => handler.handle_write_linux(
+                    input,
+                    file.options(),
+                    Some(args.spi_speed),
+                    Some(&args.driver),
+                ),
+            }
+        }
+        Command::Nand { command } => {
+            let handler = NandHandler::new();
+            let speed = Some(args.spi_speed);
+            let driver = Some(args.driver.as_str());
+            match command {
+                args::NandCommand::Health {
+                    output,
+                    threshold,
+                    stress,
+                    cycles,
+                    yes,
+                } => {
+                    let stress = stress.map(|blocks| (blocks, cycles));
+                    handler.handle_health(output, threshold, stress, yes, speed, driver)
+                }
+            }
+        }
+        Command::Otp { command } => {
+            let handler = OtpHandler::new();
+            let speed = Some(args.spi_speed);
+            let driver = Some(args.driver.as_str());
+            match command {
+                args::OtpCommand::Status => handler.handle_status(speed, driver),
+                args::OtpCommand::Read {
+                    region,
+                    offset,
+                    length,
+                    output,
+                } => handler.handle_read(region, offset, length, output, speed, driver),
+                args::OtpCommand::Program {
+                    region,
+                    offset,
+                    input,
+                } => handler.handle_program(region, offset, input, speed, driver),
+                args::OtpCommand::Erase { region } => handler.handle_erase(region, speed, driver),
+                args::OtpCommand::Lock { region, yes } => {
+                    handler.handle_lock(region, yes, speed, driver)
                 }
-                args::BbtCommand::Load { input } => handler.handle_load(input),
             }
         }
         Command::Diagnostic { interactive } => {
@@ -187,6 +335,7 @@ pub fn execute(args: Args) -> Result<()> {
             template,
             firmware,
             save_to,
+            report,
         } => {
             use crate::application::batch::{templates, BatchScript};
             use crate::infrastructure::chip_database::ChipRegistry;
@@ -254,7 +403,11 @@ pub fn execute(args: Args) -> Result<()> {
             }
 
             let registry = ChipRegistry::new();
-            batch_script.execute(prog.as_mut(), &registry)?;
+            let batch_report = batch_script.execute(prog.as_mut(), &registry)?;
+            if let Some(report_path) = report {
+                batch_report.to_json_file(&report_path)?;
+                println!("✓ Batch report saved to: {:?}", report_path);
+            }
 
             Ok(())
         }
@@ -298,10 +451,11 @@ mod tests {
 
     #[test]
     fn test_get_oob_mode() {
-        assert_eq!(get_oob_mode(false, false), OobMode::None);
-        assert_eq!(get_oob_mode(true, false), OobMode::Included);
-        assert_eq!(get_oob_mode(false, true), OobMode::Only);
+        assert_eq!(get_oob_mode(false, false, false), OobMode::None);
+        assert_eq!(get_oob_mode(true, false, false), OobMode::Included);
+        assert_eq!(get_oob_mode(false, true, false), OobMode::Only);
+        assert_eq!(get_oob_mode(false, false, true), OobMode::Free);
         // oob_only takes precedence
-        assert_eq!(get_oob_mode(true, true), OobMode::Only);
+        assert_eq!(get_oob_mode(true, true, false), OobMode::Only);
     }
 }
@@ -1,6 +1,7 @@
 use super::messages::{GuiMessage, WorkerMessage};
+use crate::domain::nand_health::{BlockCondition, BlockHealth, HealthReport, HealthVerdict};
 use crate::domain::serial_analysis::RollingQualityAnalyzer;
-use crate::domain::ChipSpec;
+use crate::domain::{ChipSpec, FlashType};
 use crate::infrastructure::programmer::traits::{Parity, SerialConfig, StopBits};
 use eframe::{egui, App, Frame};
 use serde::{Deserialize, Serialize};
@@ -11,6 +12,7 @@ enum Tab {
     Read,
     Write,
     Erase,
+    Health,
     Console,
 }
 
@@ -35,6 +37,8 @@ pub struct NanderApp {
     #[serde(skip)]
     chip_spec: Option<ChipSpec>,
     #[serde(skip)]
+    chip_unique_id: Option<String>,
+    #[serde(skip)]
     is_busy: bool,
     #[serde(skip)]
     progress: Option<f32>,
@@ -46,6 +50,10 @@ pub struct NanderApp {
     length: String,
     #[serde(skip)]
     preview_data: Vec<u8>,
+    /// Bit-flip threshold for the health survey; empty for the default
+    health_threshold: String,
+    #[serde(skip)]
+    health_report: Option<HealthReport>,
 
     // =========================================================================
     // Console/Serial State
@@ -112,6 +120,7 @@ impl Default for NanderApp {
             status_text: "Ready".to_string(),
             programmer_name: None,
             chip_spec: None,
+            chip_unique_id: None,
             is_busy: false,
             progress: None,
             logs: Vec::new(),
@@ -120,6 +129,8 @@ impl Default for NanderApp {
             start_address: "0x0".to_string(),
             length: "".to_string(),
             preview_data: Vec::new(),
+            health_threshold: String::new(),
+            health_report: None,
             // Console defaults
             serial_connected: false,
             serial_port_name: None,
@@ -186,6 +197,7 @@ impl NanderApp {
                 WorkerMessage::Disconnected => {
                     self.programmer_name = None;
                     self.chip_spec = None;
+                    self.chip_unique_id = None;
                     self.log("Programmer disconnected");
                     self.status_text = "Programmer Disconnected".to_string();
                     self.is_busy = false;
@@ -195,10 +207,15 @@ impl NanderApp {
                         "Chip detected: {} ({})",
                         spec.name, spec.manufacturer
                     ));
-                    self.chip_spec = Some(spec);
+                    self.chip_spec = Some(*spec);
+                    self.chip_unique_id = None;
                     self.is_busy = false; // Initial detect done
                     self.status_text = "Chip detected".to_string();
                 }
+                WorkerMessage::UniqueIdRead(id) => {
+                    self.log(&format!("Unique ID: {}", id));
+                    self.chip_unique_id = Some(id);
+                }
                 WorkerMessage::ChipDetectionFailed(err) => {
                     self.log(&format!("Chip detection failed: {}", err));
                     self.status_text = "Chip Detection Failed (see logs)".to_string();
@@ -227,6 +244,17 @@ impl NanderApp {
                     self.is_busy = false;
                     self.status_text = "Ready".to_string();
                 }
+                WorkerMessage::HealthReport(report) => {
+                    let summary = report.summary();
+                    self.log(&format!(
+                        "Health survey: {} good, {} worn, {} failing, {} bad blocks ({:?})",
+                        summary.good, summary.worn, summary.failing, summary.bad, summary.verdict
+                    ));
+                    self.health_report = Some(*report);
+                    self.progress = None;
+                    self.is_busy = false;
+                    self.status_text = "Ready".to_string();
+                }
                 WorkerMessage::OperationFailed(err) => {
                     self.log(&format!("Operation failed: {}", err));
                     self.progress = None;
@@ -400,6 +428,149 @@ impl NanderApp {
         }
     }
 
+    // =========================================================================
+    // Health Tab Rendering
+    // =========================================================================
+
+    fn render_health_tab(&mut self, ui: &mut egui::Ui, can_operate: bool) {
+        ui.heading("NAND Health");
+        let is_nand = self
+            .chip_spec
+            .as_ref()
+            .is_some_and(|spec| spec.flash_type == FlashType::Nand);
+
+        ui.horizontal(|ui| {
+            ui.label("Bit-flip threshold:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.health_threshold)
+                    .hint_text("default")
+                    .desired_width(60.0),
+            );
+            if ui
+                .add_enabled(can_operate && is_nand, egui::Button::new("Run Survey"))
+                .clicked()
+            {
+                self.is_busy = true;
+                self.status_text = "Surveying...".to_string();
+                let threshold = self.health_threshold.trim().parse().ok();
+                self.tx.send(GuiMessage::NandHealth { threshold }).ok();
+            }
+            if ui
+                .add_enabled(
+                    self.health_report.is_some(),
+                    egui::Button::new("Save Report..."),
+                )
+                .clicked()
+            {
+                self.save_health_report();
+            }
+        });
+        if !is_nand {
+            ui.label("Detect a NAND flash chip to survey its health.");
+        }
+
+        let Some(report) = &self.health_report else {
+            return;
+        };
+        ui.separator();
+
+        let summary = report.summary();
+        let (verdict, color) = match summary.verdict {
+            HealthVerdict::Healthy => ("Healthy", egui::Color32::GREEN),
+            HealthVerdict::Degraded => ("Degraded: blocks are wearing out", egui::Color32::YELLOW),
+            HealthVerdict::Replace => ("Replace: the flash is unreliable", egui::Color32::RED),
+        };
+        ui.label(egui::RichText::new(verdict).strong().color(color));
+        ui.label(format!(
+            "{} good, {} worn, {} failing, {} bad blocks (ECC strength {}, threshold {})",
+            summary.good,
+            summary.worn,
+            summary.failing,
+            summary.bad,
+            report.ecc_strength,
+            report.threshold
+        ));
+
+        ui.horizontal(|ui| {
+            for (label, color) in [
+                ("clean", heat_color(0.0)),
+                ("near threshold", heat_color(1.0)),
+                ("worn", egui::Color32::from_rgb(230, 110, 0)),
+                ("uncorrectable", egui::Color32::from_rgb(200, 0, 0)),
+                ("bad", egui::Color32::from_gray(70)),
+            ] {
+                let (rect, _) =
+                    ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                ui.painter().rect_filled(rect, 0.0, color);
+                ui.label(label);
+            }
+        });
+
+        // One cell per block, coloured by its worst page
+        const CELL: f32 = 10.0;
+        const GAP: f32 = 1.0;
+        let columns = ((ui.available_width() / (CELL + GAP)) as usize).max(1);
+        let rows = report.blocks.len().div_ceil(columns);
+        egui::ScrollArea::vertical()
+            .id_salt("health_map")
+            .max_height(400.0)
+            .show(ui, |ui| {
+                let size = egui::vec2(columns as f32 * (CELL + GAP), rows as f32 * (CELL + GAP));
+                let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+                let painter = ui.painter_at(rect);
+                for (i, block) in report.blocks.iter().enumerate() {
+                    let min = rect.min
+                        + egui::vec2(
+                            (i % columns) as f32 * (CELL + GAP),
+                            (i / columns) as f32 * (CELL + GAP),
+                        );
+                    let cell = egui::Rect::from_min_size(min, egui::vec2(CELL, CELL));
+                    painter.rect_filled(cell, 0.0, block_color(block, report.threshold));
+                }
+
+                let hovered = response.hover_pos().and_then(|pos| {
+                    let offset = pos - rect.min;
+                    let column = (offset.x / (CELL + GAP)) as usize;
+                    let row = (offset.y / (CELL + GAP)) as usize;
+                    (column < columns)
+                        .then(|| report.blocks.get(row * columns + column))
+                        .flatten()
+                });
+                if let Some(block) = hovered {
+                    let text = match block.condition {
+                        BlockCondition::Bad => format!("Block {}: bad", block.block),
+                        BlockCondition::Reserved => format!("Block {}: BBT", block.block),
+                        _ => format!(
+                            "Block {}: at most {} bit flips, {} uncorrectable pages",
+                            block.block,
+                            block.max_bit_flips(),
+                            block.uncorrectable_pages()
+                        ),
+                    };
+                    response.on_hover_text_at_pointer(text);
+                }
+            });
+    }
+
+    fn save_health_report(&mut self) {
+        let Some(report) = &self.health_report else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+        let result = report
+            .to_json()
+            .and_then(|json| std::fs::write(&path, json).map_err(crate::error::Error::Io));
+        match result {
+            Ok(()) => self.log(&format!("Health report saved to {}", path.display())),
+            Err(e) => self.log(&format!("Failed to save health report: {}", e)),
+        }
+    }
+
     // =========================================================================
     // Console Tab Rendering
     // =========================================================================
@@ -839,6 +1010,9 @@ impl App for NanderApp {
                             ui.label(format!("Name: {}", spec.name));
                             ui.label(format!("Size: {}", spec.capacity));
                             ui.label(format!("Type: {:?}", spec.flash_type));
+                            if let Some(id) = &self.chip_unique_id {
+                                ui.label(format!("Unique ID: {}", id));
+                            }
                         });
                     }
                 });
@@ -883,6 +1057,7 @@ impl App for NanderApp {
                 ui.selectable_value(&mut self.active_tab, Tab::Read, "Read");
                 ui.selectable_value(&mut self.active_tab, Tab::Write, "Write");
                 ui.selectable_value(&mut self.active_tab, Tab::Erase, "Erase");
+                ui.selectable_value(&mut self.active_tab, Tab::Health, "Health");
                 ui.separator();
                 ui.selectable_value(&mut self.active_tab, Tab::Console, "🔌 Console");
             });
@@ -1001,6 +1176,9 @@ impl App for NanderApp {
                             .ok();
                     }
                 }
+                Tab::Health => {
+                    self.render_health_tab(ui, can_operate);
+                }
                 Tab::Console => {
                     self.render_console_tab(ui);
                 }
@@ -1020,3 +1198,24 @@ impl App for NanderApp {
         eframe::set_value(storage, eframe::APP_KEY, self);
     }
 }
+
+/// Green for clean blocks through yellow at the worn threshold, by the
+/// share `level` of the threshold reached
+fn heat_color(level: f32) -> egui::Color32 {
+    let level = level.clamp(0.0, 1.0);
+    egui::Color32::from_rgb(
+        (40.0 + 180.0 * level) as u8,
+        (170.0 + 30.0 * level) as u8,
+        40,
+    )
+}
+
+fn block_color(block: &BlockHealth, threshold: u8) -> egui::Color32 {
+    match block.condition {
+        BlockCondition::Bad => egui::Color32::from_gray(70),
+        BlockCondition::Reserved => egui::Color32::from_rgb(60, 90, 160),
+        BlockCondition::Failing => egui::Color32::from_rgb(200, 0, 0),
+        BlockCondition::Worn => egui::Color32::from_rgb(230, 110, 0),
+        BlockCondition::Good => heat_color(block.max_bit_flips() as f32 / threshold.max(1) as f32),
+    }
+}
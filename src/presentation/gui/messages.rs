@@ -1,3 +1,4 @@
+use crate::domain::nand_health::HealthReport;
 use crate::domain::{ChipSpec, Progress};
 use crate::infrastructure::programmer::traits::SerialConfig;
 use std::path::PathBuf;
@@ -22,6 +23,9 @@ pub enum GuiMessage {
     },
     /// Request to erase flash
     EraseFlash { start: u32, length: Option<u32> },
+    /// Request a NAND health survey, optionally with a custom bit-flip
+    /// threshold
+    NandHealth { threshold: Option<u8> },
     /// Request to set SPI speed
     SetSpeed(u8),
     /// Request to select CS line
@@ -57,15 +61,19 @@ pub enum WorkerMessage {
     /// Programmer disconnected
     Disconnected,
     /// Chip detected
-    ChipDetected(ChipSpec),
+    ChipDetected(Box<ChipSpec>),
     /// Chip detection failed
     ChipDetectionFailed(String),
+    /// Factory unique ID of the detected chip (hex)
+    UniqueIdRead(String),
     /// Progress update
     Progress(Progress),
     /// Operation completed
     OperationComplete,
     /// Data read from flash (for preview)
     DataRead(Vec<u8>),
+    /// NAND health survey finished
+    HealthReport(Box<HealthReport>),
     /// Operation failed
     OperationFailed(String),
     /// Log message
@@ -1,9 +1,11 @@
-use crate::application::use_cases::detect_chip::DetectChipUseCase;
+use crate::application::use_cases::detect_chip::{format_unique_id, DetectChipUseCase};
 use crate::application::use_cases::erase_flash::{EraseFlashUseCase, EraseParams};
-use crate::application::use_cases::read_flash::{ReadFlashUseCase, ReadParams};
-use crate::application::use_cases::write_flash::{WriteFlashUseCase, WriteParams};
+use crate::application::use_cases::read_flash::{format_ecc_report, ReadFlashUseCase, ReadParams};
+use crate::application::use_cases::write_flash::{
+    format_relocations, WriteFlashUseCase, WriteParams,
+};
 use crate::domain::serial_analysis::{DataQualityMetrics, ProtocolType};
-use crate::domain::{BadBlockStrategy, FlashType, OobMode};
+use crate::domain::{BadBlockStrategy, FlashType, OobMode, WriteMode};
 use crate::infrastructure::chip_database::registry::ChipRegistry;
 use crate::infrastructure::flash_protocol::eeprom::{I2cEeprom, MicrowireEeprom, SpiEeprom};
 use crate::infrastructure::flash_protocol::nand::SpiNand;
@@ -72,7 +74,15 @@ pub fn run_worker(rx: Receiver<GuiMessage>, tx: Sender<WorkerMessage>) {
                         let use_case = DetectChipUseCase::new(registry.clone());
                         match use_case.identify_chip(p.as_mut()) {
                             Ok(spec) => {
-                                tx.send(WorkerMessage::ChipDetected(spec)).ok();
+                                let unique_id = use_case.read_unique_id(p.as_mut(), &spec);
+                                tx.send(WorkerMessage::ChipDetected(Box::new(spec))).ok();
+                                match unique_id {
+                                    Ok(id) => {
+                                        tx.send(WorkerMessage::UniqueIdRead(format_unique_id(&id)))
+                                            .ok();
+                                    }
+                                    Err(e) => log::debug!("Unique ID not available: {}", e),
+                                }
                             }
                             Err(e) => {
                                 tx.send(WorkerMessage::ChipDetectionFailed(e.to_string()))
@@ -109,10 +119,13 @@ pub fn run_worker(rx: Receiver<GuiMessage>, tx: Sender<WorkerMessage>) {
                             length: read_len,
                             use_ecc: true,
                             ignore_ecc_errors: false,
+                            soft_ecc: None,
                             oob_mode: OobMode::None,
                             bad_block_strategy: BadBlockStrategy::Skip,
                             bbt: None,
                             retry_count: 3,
+                            partitions: Vec::new(),
+                            passes: 1,
                         };
 
                         let tx_progress = tx.clone();
@@ -120,9 +133,18 @@ pub fn run_worker(rx: Receiver<GuiMessage>, tx: Sender<WorkerMessage>) {
                             FlashType::Nand => {
                                 let protocol = SpiNand::new(p.as_mut(), spec);
                                 let mut use_case = ReadFlashUseCase::new(protocol);
-                                use_case.execute(params, |prog| {
-                                    tx_progress.send(WorkerMessage::Progress(prog)).ok();
-                                })
+                                use_case
+                                    .execute_with_report(params, |prog| {
+                                        tx_progress.send(WorkerMessage::Progress(prog)).ok();
+                                    })
+                                    .map(|(data, report)| {
+                                        if !report.ecc.pages.is_empty() {
+                                            for line in format_ecc_report(&report.ecc) {
+                                                tx.send(WorkerMessage::Log(line)).ok();
+                                            }
+                                        }
+                                        data
+                                    })
                             }
                             FlashType::Nor => {
                                 let protocol = SpiNor::new(p.as_mut(), spec);
@@ -223,10 +245,13 @@ pub fn run_worker(rx: Receiver<GuiMessage>, tx: Sender<WorkerMessage>) {
                             use_ecc: true,
                             verify,
                             ignore_ecc_errors: false,
+                            soft_ecc: None,
                             oob_mode: OobMode::None,
                             bad_block_strategy: BadBlockStrategy::Skip,
                             bbt: None,
                             retry_count: 3,
+                            mode: WriteMode::Direct,
+                            partitions: Vec::new(),
                         };
 
                         let tx_progress = tx.clone();
@@ -276,7 +301,10 @@ pub fn run_worker(rx: Receiver<GuiMessage>, tx: Sender<WorkerMessage>) {
                         };
 
                         match result {
-                            Ok(_) => {
+                            Ok(report) => {
+                                for line in format_relocations(&report.relocated_blocks) {
+                                    tx.send(WorkerMessage::Log(line)).ok();
+                                }
                                 tx.send(WorkerMessage::OperationComplete).ok();
                             }
                             Err(e) => {
@@ -309,6 +337,8 @@ pub fn run_worker(rx: Receiver<GuiMessage>, tx: Sender<WorkerMessage>) {
                             length: erase_len,
                             bad_block_strategy: BadBlockStrategy::Skip,
                             bbt: None,
+                            mark_bad: false,
+                            partitions: Vec::new(),
                         };
 
                         let tx_progress = tx.clone();
@@ -367,6 +397,49 @@ pub fn run_worker(rx: Receiver<GuiMessage>, tx: Sender<WorkerMessage>) {
                             .ok();
                     }
                 }
+                GuiMessage::NandHealth { threshold } => {
+                    if let Some(ref mut p) = programmer {
+                        let detect_use_case = DetectChipUseCase::new(registry.clone());
+                        let spec = match detect_use_case.identify_chip(p.as_mut()) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                tx.send(WorkerMessage::OperationFailed(format!(
+                                    "Chip detection failed: {}",
+                                    e
+                                )))
+                                .ok();
+                                continue;
+                            }
+                        };
+                        if spec.flash_type != FlashType::Nand {
+                            tx.send(WorkerMessage::OperationFailed(
+                                "The health survey needs a NAND flash chip".to_string(),
+                            ))
+                            .ok();
+                            continue;
+                        }
+
+                        let tx_progress = tx.clone();
+                        let mut protocol = SpiNand::new(p.as_mut(), spec);
+                        let survey = protocol.read_flash_bbt().and_then(|flash_bbt| {
+                            let bbt = flash_bbt.map(|flash_bbt| flash_bbt.table);
+                            protocol.survey_health(threshold, &bbt, &|prog| {
+                                tx_progress.send(WorkerMessage::Progress(prog)).ok();
+                            })
+                        });
+                        match survey {
+                            Ok(report) => {
+                                tx.send(WorkerMessage::HealthReport(Box::new(report))).ok();
+                            }
+                            Err(e) => {
+                                tx.send(WorkerMessage::OperationFailed(e.to_string())).ok();
+                            }
+                        }
+                    } else {
+                        tx.send(WorkerMessage::OperationFailed("Not connected".to_string()))
+                            .ok();
+                    }
+                }
                 GuiMessage::Cancel => {
                     tx.send(WorkerMessage::Log(
                         "Cancellation ignored (not implemented)".to_string(),
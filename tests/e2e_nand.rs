@@ -1,11 +1,19 @@
 use nander_rs::application::use_cases::{
     EraseFlashUseCase, EraseParams, ReadFlashUseCase, ReadParams, WriteFlashUseCase, WriteParams,
 };
+use nander_rs::domain::bad_block::{BadBlockTable, BlockStatus};
+use nander_rs::domain::nand_health::{BlockCondition, HealthVerdict};
+use nander_rs::domain::partition::parse_mtdparts;
 use nander_rs::domain::{
-    BadBlockStrategy, Capacity, ChipCapabilities, ChipLayout, ChipSpec, FlashType, JedecId, OobMode,
+    BadBlockStrategy, BlockRelocation, Capacity, ChipCapabilities, ChipLayout, ChipSpec, EccStatus,
+    EccStatusMethod, FlashOperation, FlashType, JedecId, NandCacheRead, OobLayout, OobMode,
+    OobRegion, QuadEnableMethod, ReadRetry, ReadRetryRecovery, SoftEccConfig, WriteMode,
 };
+use nander_rs::error::Error;
+use nander_rs::infrastructure::chip_database::nand::winbond;
 use nander_rs::infrastructure::flash_protocol::nand::SpiNand;
 use nander_rs::infrastructure::programmer::simulator::SimulatedProgrammer;
+use nander_rs::infrastructure::programmer::BusWidth;
 
 #[test]
 fn test_e2e_nand_lifecycle() {
@@ -29,6 +37,8 @@ fn test_e2e_nand_lifecycle() {
             block_size,
             oob_size: Some(64),
             is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
         },
         capabilities: ChipCapabilities::default(),
         otp: None,
@@ -63,6 +73,8 @@ fn test_e2e_nand_lifecycle() {
         length: block_size,
         bad_block_strategy: BadBlockStrategy::Fail,
         bbt: None,
+        mark_bad: false,
+        partitions: Vec::new(),
     };
     erase_uc
         .execute(erase_params, |_| {})
@@ -78,10 +90,13 @@ fn test_e2e_nand_lifecycle() {
         use_ecc: true,
         verify: true, // Internal verify
         ignore_ecc_errors: false,
+        soft_ecc: None,
         oob_mode: OobMode::None,
         bad_block_strategy: BadBlockStrategy::Fail,
         bbt: None,
         retry_count: 0,
+        mode: WriteMode::Direct,
+        partitions: Vec::new(),
     };
     write_uc
         .execute(write_params, |_| {})
@@ -95,10 +110,13 @@ fn test_e2e_nand_lifecycle() {
         length: 512,
         use_ecc: true,
         ignore_ecc_errors: false,
+        soft_ecc: None,
         oob_mode: OobMode::None,
         bad_block_strategy: BadBlockStrategy::Fail,
         bbt: None,
         retry_count: 0,
+        partitions: Vec::new(),
+        passes: 1,
     };
     let read_data = read_uc.execute(read_params, |_| {}).expect("Read failed");
 
@@ -110,3 +128,943 @@ fn test_e2e_nand_lifecycle() {
     // and we only have `&mut flash`, getting the inner programmer is hard without casting.
     // But we verified via read_uc, which uses SPI commands, so that's good enough E2E.
 }
+
+fn simulated_nand() -> SpiNand<SimulatedProgrammer> {
+    let page_size = 2048;
+    let block_size = 128 * 1024;
+    let capacity_bytes = 128 * 1024 * 1024;
+
+    let programmer = SimulatedProgrammer::new(capacity_bytes, page_size, block_size);
+    let spec = ChipSpec {
+        name: "Simulated NAND".to_string(),
+        manufacturer: "Simulated".to_string(),
+        jedec_id: JedecId::new([0xEF, 0xAA, 0x21]),
+        flash_type: FlashType::Nand,
+        capacity: Capacity::bytes(capacity_bytes as u32),
+        layout: ChipLayout {
+            page_size,
+            block_size,
+            oob_size: Some(64),
+            is_dataflash: false,
+            die_count: 1,
+            oob_layout: None,
+        },
+        capabilities: ChipCapabilities::default(),
+        otp: None,
+    };
+    SpiNand::new(programmer, spec)
+}
+
+fn delta_params(data: &[u8]) -> WriteParams<'_> {
+    WriteParams {
+        address: 0,
+        data,
+        use_ecc: true,
+        verify: true,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        mode: WriteMode::Delta,
+        partitions: Vec::new(),
+    }
+}
+
+#[test]
+fn test_e2e_nand_delta_write() {
+    let mut flash = simulated_nand();
+
+    // First page changes, second page stays blank: nothing to erase
+    let mut image = vec![0xAB; 2048];
+    image.extend_from_slice(&[0xFF; 2048]);
+    let report = WriteFlashUseCase::new(&mut flash)
+        .execute(delta_params(&image), |_| {})
+        .expect("Delta write failed");
+    assert_eq!(report.units_total, 1);
+    assert_eq!(report.units_erased, 0);
+    assert_eq!(report.pages_programmed, 1);
+
+    // Same image again: the whole block is skipped
+    let report = WriteFlashUseCase::new(&mut flash)
+        .execute(delta_params(&image), |_| {})
+        .expect("Delta write failed");
+    assert_eq!(report.units_skipped, 1);
+    assert_eq!(report.pages_programmed, 0);
+
+    // Rewriting a programmed page forces an erase of its block
+    image[..2048].fill(0xCD);
+    let report = WriteFlashUseCase::new(&mut flash)
+        .execute(delta_params(&image), |_| {})
+        .expect("Delta write failed");
+    assert_eq!(report.units_erased, 1);
+    assert_eq!(report.pages_programmed, 1);
+
+    let read_params = ReadParams {
+        address: 0,
+        length: 4096,
+        use_ecc: true,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        partitions: Vec::new(),
+        passes: 1,
+    };
+    let read_data = ReadFlashUseCase::new(&mut flash)
+        .execute(read_params, |_| {})
+        .expect("Read failed");
+    assert_eq!(read_data, image);
+}
+
+#[test]
+fn test_e2e_nand_delta_write_keeps_oob() {
+    let block_size = 128 * 1024;
+    let mut programmer =
+        SimulatedProgrammer::new(2 * block_size, 2048, block_size as u32).with_oob(64);
+    let mut spec = simulated_nand().spec().clone();
+    spec.capacity = Capacity::bytes(2 * block_size as u32);
+
+    let mut memory = vec![0xFF; 2 * block_size];
+    memory[..2048].fill(0x11);
+    memory[2048..2 * 2048].fill(0x22);
+    memory[3 * 2048..4 * 2048].fill(0x44);
+    programmer.set_memory(&memory);
+    // A JFFS2-style cleanmarker and a tag on a page with data
+    programmer.set_oob(0, &[0xFF, 0xFF, 0x85, 0x19, 0x03, 0x20]);
+    programmer.set_oob(3, &[0xFF, 0xFF, 0xA5, 0x5A]);
+
+    // Changing programmed page 1 erases and rewrites the block
+    let image: Vec<u8> = [[0x11; 2048], [0x33; 2048]].concat();
+    let mut flash = SpiNand::new(&mut programmer, spec.clone());
+    let report = WriteFlashUseCase::new(&mut flash)
+        .execute(delta_params(&image), |_| {})
+        .expect("Delta write failed");
+    assert_eq!(report.units_erased, 1);
+    drop(flash);
+
+    assert_eq!(
+        programmer.get_oob(0)[..6],
+        [0xFF, 0xFF, 0x85, 0x19, 0x03, 0x20]
+    );
+    assert_eq!(programmer.get_oob(3)[..4], [0xFF, 0xFF, 0xA5, 0x5A]);
+    let memory = programmer.get_memory();
+    assert!(memory[2048..2 * 2048].iter().all(|&b| b == 0x33));
+    assert!(memory[3 * 2048..4 * 2048].iter().all(|&b| b == 0x44));
+
+    // A kept page that does not decode is not rewritten as if it were data
+    programmer.set_ecc_status(3, 0x30);
+    let image: Vec<u8> = [[0x11; 2048], [0x55; 2048]].concat();
+    let mut flash = SpiNand::new(&mut programmer, spec);
+    let err = WriteFlashUseCase::new(&mut flash)
+        .execute(delta_params(&image), |_| {})
+        .expect_err("Delta write over an uncorrectable page succeeded");
+    assert!(matches!(err, Error::EccError { address } if address == 3 * 2048));
+
+    let params = WriteParams {
+        ignore_ecc_errors: true,
+        verify: false,
+        ..delta_params(&image)
+    };
+    WriteFlashUseCase::new(&mut flash)
+        .execute(params, |_| {})
+        .expect("Delta write ignoring ECC errors failed");
+    drop(flash);
+    assert!(programmer.get_memory()[2048..2 * 2048]
+        .iter()
+        .all(|&b| b == 0x55));
+}
+
+#[test]
+fn test_e2e_nand_quad_io() {
+    let mut programmer = SimulatedProgrammer::new(128 * 1024 * 1024, 2048, 128 * 1024)
+        .with_bus_width(BusWidth::Quad);
+    let mut spec = simulated_nand().spec().clone();
+    spec.capabilities.supports_quad_spi = true;
+    spec.capabilities.quad_enable = QuadEnableMethod::ConfigBit0;
+
+    let mut flash = SpiNand::new(&mut programmer, spec);
+    assert_eq!(flash.data_width(), BusWidth::Quad);
+
+    let image: Vec<u8> = (0..4096).map(|i| i as u8).collect();
+    let write_params = WriteParams {
+        mode: WriteMode::Direct,
+        ..delta_params(&image)
+    };
+    WriteFlashUseCase::new(&mut flash)
+        .execute(write_params, |_| {})
+        .expect("Quad write failed");
+
+    let read_params = ReadParams {
+        address: 2048,
+        length: 2048,
+        use_ecc: true,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        partitions: Vec::new(),
+        passes: 1,
+    };
+    let read_data = ReadFlashUseCase::new(&mut flash)
+        .execute(read_params, |_| {})
+        .expect("Quad read failed");
+    assert_eq!(read_data, image[2048..]);
+
+    drop(flash);
+    assert!(programmer.multi_io_count() > 0);
+}
+
+#[test]
+fn test_e2e_nand_cache_read() {
+    let capacity = 128 * 1024 * 1024;
+    let image: Vec<u8> = (0..4 * 128 * 1024)
+        .map(|i: usize| (i / 2048 + i) as u8)
+        .collect();
+    // Starts mid-block and spans two block boundaries
+    let (start, length) = (60 * 2048, 140 * 2048 - 100);
+    let expected = &image[start..start + length];
+
+    for (mode, width) in [
+        (NandCacheRead::Sequential, BusWidth::Single),
+        (NandCacheRead::Sequential, BusWidth::Quad),
+        (NandCacheRead::Continuous, BusWidth::Single),
+        (NandCacheRead::Continuous, BusWidth::Quad),
+    ] {
+        let mut programmer =
+            SimulatedProgrammer::new(capacity, 2048, 128 * 1024).with_bus_width(width);
+        programmer.set_memory(&image);
+        let mut spec = simulated_nand().spec().clone();
+        spec.capabilities.cache_read = mode;
+        spec.capabilities.supports_quad_spi = true;
+
+        let mut flash = SpiNand::new(&mut programmer, spec);
+        let read_params = ReadParams {
+            address: start as u32,
+            length: length as u32,
+            use_ecc: true,
+            ignore_ecc_errors: false,
+            soft_ecc: None,
+            oob_mode: OobMode::None,
+            bad_block_strategy: BadBlockStrategy::Fail,
+            bbt: None,
+            retry_count: 0,
+            partitions: Vec::new(),
+            passes: 1,
+        };
+        let read_data = ReadFlashUseCase::new(&mut flash)
+            .execute(read_params, |_| {})
+            .expect("Cache read failed");
+        assert!(
+            read_data == expected,
+            "{:?} read mismatch on {:?}",
+            mode,
+            width
+        );
+
+        drop(flash);
+        assert_eq!(programmer.multi_io_count() > 0, width == BusWidth::Quad);
+    }
+}
+
+#[test]
+fn test_e2e_nand_multi_die() {
+    let die_size = 64 * 1024 * 1024;
+    let mut programmer = SimulatedProgrammer::new(2 * die_size, 2048, 128 * 1024).with_dies(2);
+    programmer.set_memory(&vec![0x00; 2 * die_size]);
+    let mut spec = simulated_nand().spec().clone();
+    spec.layout.die_count = 2;
+
+    let mut flash = SpiNand::new(&mut programmer, spec);
+
+    // The last block of die 0 and the first block of die 1
+    let start = die_size - 128 * 1024;
+    let erase_params = EraseParams {
+        address: start as u32,
+        length: 256 * 1024,
+        bad_block_strategy: BadBlockStrategy::Include,
+        bbt: None,
+        mark_bad: false,
+        partitions: Vec::new(),
+    };
+    EraseFlashUseCase::new(&mut flash)
+        .execute(erase_params, |_| {})
+        .expect("Erase failed");
+
+    let image: Vec<u8> = (0..256 * 1024).map(|i: usize| (i / 2048) as u8).collect();
+    let write_params = WriteParams {
+        address: start as u32,
+        mode: WriteMode::Direct,
+        ..delta_params(&image)
+    };
+    WriteFlashUseCase::new(&mut flash)
+        .execute(write_params, |_| {})
+        .expect("Write failed");
+
+    drop(flash);
+    let memory = programmer.get_memory();
+    assert_eq!(&memory[start..start + image.len()], &image[..]);
+    // Die 0 below the written range is untouched
+    assert!(memory[..start].iter().all(|&b| b == 0x00));
+}
+
+#[test]
+fn test_e2e_nand_soft_ecc() {
+    let mut programmer = SimulatedProgrammer::new(128 * 1024 * 1024, 2048, 128 * 1024).with_oob(64);
+    let spec = simulated_nand().spec().clone();
+    let mut flash = SpiNand::new(&mut programmer, spec);
+
+    let image: Vec<u8> = (0..2 * 2048)
+        .map(|i: usize| (i * 3 + i / 256) as u8)
+        .collect();
+    let write_params = WriteParams {
+        mode: WriteMode::Direct,
+        soft_ecc: Some(SoftEccConfig::bch(8)),
+        ..delta_params(&image)
+    };
+    WriteFlashUseCase::new(&mut flash)
+        .execute(write_params, |_| {})
+        .expect("Write with software ECC failed");
+
+    drop(flash);
+    // Four 13-byte BCH codes at the end of the OOB, the marker area untouched
+    let oob = programmer.get_oob(0);
+    assert!(oob[..12].iter().all(|&b| b == 0xFF));
+    assert!(oob[12..].iter().any(|&b| b != 0xFF));
+
+    // Flip bits in two steps of page 1
+    let mut memory = programmer.get_memory()[..2 * 2048].to_vec();
+    memory[2048 + 10] ^= 0x81;
+    memory[2048 + 1000] ^= 0x10;
+    programmer.set_memory(&memory);
+
+    let spec = simulated_nand().spec().clone();
+    let mut flash = SpiNand::new(&mut programmer, spec);
+    let read_params = ReadParams {
+        address: 0,
+        length: image.len() as u32,
+        use_ecc: true,
+        ignore_ecc_errors: false,
+        soft_ecc: Some(SoftEccConfig::bch(8)),
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        partitions: Vec::new(),
+        passes: 1,
+    };
+    let (data, report) = ReadFlashUseCase::new(&mut flash)
+        .execute_with_report(read_params, |_| {})
+        .expect("Read with software ECC failed");
+    assert_eq!(data, image);
+    assert_eq!(report.ecc.strength, 8);
+    assert_eq!(report.ecc.pages[0].status, EccStatus::NoError);
+    assert_eq!(
+        report.ecc.pages[1].status,
+        EccStatus::Corrected { bit_flips: 2 }
+    );
+}
+
+#[test]
+fn test_e2e_nand_soft_ecc_with_oob() {
+    let mut programmer = SimulatedProgrammer::new(128 * 1024 * 1024, 2048, 128 * 1024).with_oob(64);
+    let spec = simulated_nand().spec().clone();
+    let mut flash = SpiNand::new(&mut programmer, spec);
+
+    // One page plus OOB: a marker in the free bytes, ECC left for the host
+    let mut image: Vec<u8> = (0..2048).map(|i: usize| (i % 251) as u8).collect();
+    let mut oob = vec![0xFF; 64];
+    oob[2..6].copy_from_slice(b"JFFS");
+    image.extend_from_slice(&oob);
+
+    let write_params = WriteParams {
+        mode: WriteMode::Direct,
+        oob_mode: OobMode::Included,
+        soft_ecc: Some(SoftEccConfig::hamming()),
+        ..delta_params(&image)
+    };
+    WriteFlashUseCase::new(&mut flash)
+        .execute(write_params, |_| {})
+        .expect("Write with software ECC failed");
+
+    drop(flash);
+    let oob = programmer.get_oob(0);
+    assert_eq!(&oob[2..6], b"JFFS");
+    // Eight 3-byte Hamming codes from offset 40
+    assert_eq!(&oob[6..40], &[0xFF; 34][..]);
+    assert!(oob[40..].iter().any(|&b| b != 0xFF));
+}
+
+#[test]
+fn test_e2e_nand_oob_free_bytes() {
+    const LAYOUT: OobLayout = OobLayout {
+        bad_block_marker: OobRegion::new(0, 2),
+        free: &[OobRegion::new(2, 6), OobRegion::new(18, 6)],
+        ecc: &[OobRegion::new(8, 8), OobRegion::new(24, 8)],
+    };
+    let mut programmer = SimulatedProgrammer::new(128 * 1024 * 1024, 2048, 128 * 1024).with_oob(64);
+    let mut spec = simulated_nand().spec().clone();
+    spec.layout.oob_layout = Some(LAYOUT);
+    let mut flash = SpiNand::new(&mut programmer, spec.clone());
+
+    // Two pages worth of free bytes
+    let free: Vec<u8> = (0..24).collect();
+    let write_params = WriteParams {
+        mode: WriteMode::Direct,
+        oob_mode: OobMode::Free,
+        ..delta_params(&free)
+    };
+    WriteFlashUseCase::new(&mut flash)
+        .execute(write_params, |_| {})
+        .expect("Free OOB write failed");
+
+    let read_params = || ReadParams {
+        address: 0,
+        length: free.len() as u32,
+        use_ecc: true,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::Free,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        partitions: Vec::new(),
+        passes: 1,
+    };
+    let data = ReadFlashUseCase::new(&mut flash)
+        .execute(read_params(), |_| {})
+        .expect("Free OOB read failed");
+    assert_eq!(data, free);
+
+    drop(flash);
+    // Marker and ECC bytes stay erased, the data area is untouched
+    let oob = programmer.get_oob(1);
+    assert_eq!(&oob[..2], &[0xFF, 0xFF]);
+    assert_eq!(&oob[2..8], &[12, 13, 14, 15, 16, 17]);
+    assert_eq!(&oob[8..18], &[0xFF; 10]);
+    assert_eq!(&oob[18..24], &[18, 19, 20, 21, 22, 23]);
+    assert!(programmer.get_memory()[..4096].iter().all(|&b| b == 0xFF));
+
+    // Chips without a known layout refuse the mode
+    spec.layout.oob_layout = None;
+    let mut flash = SpiNand::new(&mut programmer, spec);
+    assert!(ReadFlashUseCase::new(&mut flash)
+        .execute(read_params(), |_| {})
+        .is_err());
+}
+
+#[test]
+fn test_e2e_nand_mark_bad_blocks() {
+    let mut programmer = SimulatedProgrammer::new(128 * 1024 * 1024, 2048, 128 * 1024).with_oob(64);
+    let spec = simulated_nand().spec().clone();
+    let mut flash = SpiNand::new(&mut programmer, spec.clone());
+
+    flash.mark_bad_block(3).expect("Marking block 3 failed");
+    drop(flash);
+    assert_eq!(&programmer.get_oob(3 * 64)[..2], &[0x00, 0x00]);
+
+    let mut flash = SpiNand::new(&mut programmer, spec.clone());
+    let bbt = flash.scan_bbt(&|_| {}).expect("BBT scan failed");
+    assert_eq!(bbt.get_status(3), BlockStatus::BadFactory);
+    assert_eq!(bbt.bad_block_count(), 1);
+
+    flash.unmark_bad_block(3).expect("Unmarking block 3 failed");
+    let bbt = flash.scan_bbt(&|_| {}).expect("BBT scan failed");
+    assert_eq!(bbt.bad_block_count(), 0);
+    assert!(flash.mark_bad_block(1024).is_err());
+    drop(flash);
+
+    // Block 1 fails to erase: it gets marked and block 2 is erased instead
+    programmer.fail_erase(1);
+    let mut flash = SpiNand::new(&mut programmer, spec.clone());
+    let erase_params = |strategy| EraseParams {
+        address: 0,
+        length: 2 * 128 * 1024,
+        bad_block_strategy: strategy,
+        bbt: None,
+        mark_bad: true,
+        partitions: Vec::new(),
+    };
+    let report = EraseFlashUseCase::new(&mut flash)
+        .execute_with_report(
+            EraseParams {
+                bbt: Some(BadBlockTable::new(1024)),
+                ..erase_params(BadBlockStrategy::Skip)
+            },
+            |_| {},
+        )
+        .expect("Erase with bad block marking failed");
+    // The caller gets the table back with the retired block in it
+    assert_eq!(report.retired_blocks, [1]);
+    assert_eq!(
+        report.bbt.expect("The BBT was dropped").get_status(1),
+        BlockStatus::BadRuntime
+    );
+    drop(flash);
+    // The raw marker write must not leave on-die ECC off
+    assert!(programmer.ecc_enabled());
+    let mut flash = SpiNand::new(&mut programmer, spec);
+    let bbt = flash.scan_bbt(&|_| {}).expect("BBT scan failed");
+    assert!(bbt.is_bad(1));
+
+    // Without Skip the failure is still reported
+    flash.unmark_bad_block(2).expect("Unmarking block 2 failed");
+    let result = EraseFlashUseCase::new(&mut flash).execute(
+        EraseParams {
+            address: 128 * 1024,
+            bad_block_strategy: BadBlockStrategy::Include,
+            ..erase_params(BadBlockStrategy::Include)
+        },
+        |_| {},
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_e2e_nand_write_relocates_failed_block() {
+    let block_size = 128 * 1024;
+    let mut programmer = SimulatedProgrammer::new(128 * 1024 * 1024, 2048, block_size).with_oob(64);
+    programmer.fail_program(1);
+    let spec = simulated_nand().spec().clone();
+    let mut flash = SpiNand::new(&mut programmer, spec);
+
+    // Three blocks of data over an erased range of three blocks
+    let image: Vec<u8> = (0..3 * block_size as usize)
+        .map(|i| (i / 2048 + i) as u8)
+        .collect();
+    let write_params = WriteParams {
+        mode: WriteMode::Direct,
+        bad_block_strategy: BadBlockStrategy::Skip,
+        ..delta_params(&image)
+    };
+    let report = WriteFlashUseCase::new(&mut flash)
+        .execute(write_params, |_| {})
+        .expect("Write with relocation failed");
+    assert_eq!(
+        report.relocated_blocks,
+        vec![BlockRelocation {
+            logical_block: 1,
+            failed_block: 1,
+            new_block: 2,
+        }]
+    );
+
+    // The failed block is marked and the data reads back around it
+    let bbt = flash.scan_bbt(&|_| {}).expect("BBT scan failed");
+    assert_eq!(bbt.get_status(1), BlockStatus::BadFactory);
+    let read_params = ReadParams {
+        address: 0,
+        length: image.len() as u32,
+        use_ecc: true,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Skip,
+        bbt: None,
+        retry_count: 0,
+        partitions: Vec::new(),
+        passes: 1,
+    };
+    let data = ReadFlashUseCase::new(&mut flash)
+        .execute(read_params, |_| {})
+        .expect("Read after relocation failed");
+    assert_eq!(data, image);
+
+    drop(flash);
+    let memory = programmer.get_memory();
+    let block = block_size as usize;
+    assert_eq!(&memory[3 * block..4 * block], &image[2 * block..]);
+}
+
+#[test]
+fn test_e2e_nand_linux_flash_bbt() {
+    let mut programmer = SimulatedProgrammer::new(128 * 1024 * 1024, 2048, 128 * 1024).with_oob(64);
+    let spec = simulated_nand().spec().clone();
+    let mut flash = SpiNand::new(&mut programmer, spec.clone());
+    assert!(flash.read_flash_bbt().expect("BBT search failed").is_none());
+
+    let mut bbt = flash.scan_bbt(&|_| {}).expect("BBT scan failed");
+    bbt.set_status(5, BlockStatus::BadRuntime);
+    bbt.set_status(1022, BlockStatus::BadFactory);
+
+    // The bad block 1022 is skipped when placing the copies
+    let written = flash.write_flash_bbt(&bbt).expect("Writing the BBT failed");
+    assert_eq!(written.main.map(|c| (c.block, c.version)), Some((1023, 1)));
+    assert_eq!(
+        written.mirror.map(|c| (c.block, c.version)),
+        Some((1021, 1))
+    );
+    drop(flash);
+
+    let main_oob = programmer.get_oob(1023 * 64);
+    assert_eq!(&main_oob[8..13], b"Bbt0\x01");
+    assert_eq!(&programmer.get_oob(1021 * 64)[8..12], b"1tbB");
+    let memory = programmer.get_memory();
+    let table = &memory[1023 * 128 * 1024..][..256];
+    assert_eq!(table[1], 0xFB); // block 5 worn
+    assert_eq!(table[255], 0x03); // blocks 1021-1023 reserved or bad, 1020 good
+
+    let mut flash = SpiNand::new(&mut programmer, spec);
+    let read = flash
+        .read_flash_bbt()
+        .expect("Reading the BBT failed")
+        .expect("No BBT found");
+    assert_eq!(read.table.get_status(5), BlockStatus::BadRuntime);
+    assert_eq!(read.table.get_status(1022), BlockStatus::BadFactory);
+    assert_eq!(read.table.get_status(1023), BlockStatus::Reserved);
+    assert_eq!(read.table.get_status(1021), BlockStatus::Reserved);
+    assert_eq!(read.table.bad_block_count(), 2);
+
+    // Rewriting keeps the blocks and bumps the version
+    let rewritten = flash.write_flash_bbt(&read.table).expect("Rewrite failed");
+    assert_eq!(
+        rewritten.main.map(|c| (c.block, c.version)),
+        Some((1023, 2))
+    );
+    assert_eq!(
+        rewritten.mirror.map(|c| (c.block, c.version)),
+        Some((1021, 2))
+    );
+}
+
+#[test]
+fn test_e2e_nand_flash_bbt_refuses_ecc_overlap() {
+    let mut programmer = SimulatedProgrammer::new(128 * 1024 * 1024, 2048, 128 * 1024).with_oob(64);
+    let w25n01gv = winbond::get_chips()
+        .into_iter()
+        .find(|chip| chip.name == "W25N01GV")
+        .unwrap();
+    let mut spec = simulated_nand().spec().clone();
+    // On-die ECC parity in OOB bytes 8..16, where the tags go
+    spec.layout.oob_layout = w25n01gv.layout.oob_layout;
+    let mut flash = SpiNand::new(&mut programmer, spec);
+
+    let bbt = flash.scan_bbt(&|_| {}).expect("BBT scan failed");
+    let result = flash.write_flash_bbt(&bbt);
+    assert!(
+        matches!(result, Err(Error::NotSupported(_))),
+        "{:?}",
+        result.map(|_| ())
+    );
+    drop(flash);
+
+    // No tag was written
+    assert!(programmer.get_oob(1023 * 64).iter().all(|&b| b == 0xFF));
+}
+
+#[test]
+fn test_e2e_nand_partition_bounded_skip() {
+    let block = 128 * 1024;
+    let mut programmer =
+        SimulatedProgrammer::new(128 * 1024 * 1024, 2048, block as u32).with_oob(64);
+    // Factory bad block at the start of "boot"
+    programmer.set_oob(0, &[0x00; 64]);
+    let spec = simulated_nand().spec().clone();
+    let mut flash = SpiNand::new(&mut programmer, spec.clone());
+    let partitions = parse_mtdparts("256k(boot),256k(kernel),-(rootfs)").unwrap();
+
+    // Each partition's image is padded with erased blocks
+    let mut image = vec![0xFF; 6 * block];
+    image[..block].fill(0xB0);
+    image[2 * block..3 * block].fill(0xC0);
+    image[4 * block..].fill(0xD0);
+    let write_params = |data| WriteParams {
+        mode: WriteMode::Direct,
+        bad_block_strategy: BadBlockStrategy::Skip,
+        partitions: partitions.clone(),
+        ..delta_params(data)
+    };
+    WriteFlashUseCase::new(&mut flash)
+        .execute(write_params(&image), |_| {})
+        .expect("Partition-bounded write failed");
+
+    // boot absorbs its bad block; kernel and rootfs do not move
+    let read_params = ReadParams {
+        address: 0,
+        length: image.len() as u32,
+        use_ecc: true,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Skip,
+        bbt: None,
+        retry_count: 0,
+        partitions: partitions.clone(),
+        passes: 1,
+    };
+    let data = ReadFlashUseCase::new(&mut flash)
+        .execute(read_params, |_| {})
+        .expect("Partition-bounded read failed");
+    assert_eq!(data, image);
+    drop(flash);
+    let memory = programmer.get_memory();
+    assert!(memory[block..2 * block].iter().all(|&b| b == 0xB0));
+    assert!(memory[2 * block..3 * block].iter().all(|&b| b == 0xC0));
+    assert!(memory[4 * block..6 * block].iter().all(|&b| b == 0xD0));
+
+    // Erasing skips the bad block without spilling into kernel
+    let mut flash = SpiNand::new(&mut programmer, spec.clone());
+    EraseFlashUseCase::new(&mut flash)
+        .execute(
+            EraseParams {
+                address: 0,
+                length: 6 * block as u32,
+                bad_block_strategy: BadBlockStrategy::Skip,
+                bbt: None,
+                mark_bad: false,
+                partitions: partitions.clone(),
+            },
+            |_| {},
+        )
+        .expect("Partition-bounded erase failed");
+    drop(flash);
+    assert!(programmer.get_memory()[block..6 * block]
+        .iter()
+        .all(|&b| b == 0xFF));
+
+    // Data that needs the spare block of a partition fails
+    programmer.set_oob(2 * 64, &[0x00; 64]);
+    let mut flash = SpiNand::new(&mut programmer, spec);
+    let mut crowded = image.clone();
+    crowded[3 * block..4 * block].fill(0xC1);
+    let err = WriteFlashUseCase::new(&mut flash)
+        .execute(write_params(&crowded), |_| {})
+        .expect_err("Write past the kernel partition succeeded");
+    assert!(err.to_string().contains("kernel"));
+}
+
+#[test]
+fn test_e2e_nand_health_survey_and_stress() {
+    // 8 blocks of 64 pages; GigaDevice status bits give exact flip counts
+    let block_size = 128 * 1024;
+    let mut programmer =
+        SimulatedProgrammer::new(8 * block_size, 2048, block_size as u32).with_oob(64);
+    let mut spec = simulated_nand().spec().clone();
+    spec.capacity = Capacity::bytes(8 * block_size as u32);
+    spec.capabilities.ecc_strength = 8;
+    spec.capabilities.ecc_status = EccStatusMethod::GigaDeviceEccse;
+
+    programmer.set_oob(64, &[0; 64]); // block 1 factory bad
+    programmer.set_ecc_status(2 * 64 + 5, 0x10); // 4 bit flips
+    programmer.set_ecc_status(3 * 64 + 7, 0x30); // 8 bit flips
+    programmer.set_ecc_status(5 * 64, 0x20); // uncorrectable
+    programmer.set_memory(&vec![0x5A; 8 * block_size]);
+    programmer.fail_read(4 * 64 + 10); // read error mid-chip
+    programmer.fail_erase(7);
+
+    // Block 4 holds the BBT, so the survey does not read it
+    let mut table = BadBlockTable::new(8);
+    table.set_status(4, BlockStatus::Reserved);
+    let bbt = Some(table);
+
+    let mut flash = SpiNand::new(&mut programmer, spec);
+    let mut report = flash
+        .survey_health(None, &bbt, &|_| {})
+        .expect("Survey failed");
+    assert_eq!(report.threshold, 6);
+    let conditions: Vec<_> = report.blocks.iter().map(|b| b.condition).collect();
+    assert_eq!(
+        conditions,
+        [
+            BlockCondition::Good,
+            BlockCondition::Bad,
+            BlockCondition::Good,
+            BlockCondition::Worn,
+            BlockCondition::Reserved,
+            BlockCondition::Failing,
+            BlockCondition::Good,
+            BlockCondition::Good,
+        ]
+    );
+    assert_eq!(report.blocks[2].bit_flips[5], Some(4));
+    assert!(report.blocks[4].bit_flips.is_empty());
+    assert_eq!(report.blocks[3].max_bit_flips(), 8);
+    assert_eq!(report.blocks[5].uncorrectable_pages(), 1);
+    assert_eq!(report.blocks[5].bit_flips[0], None);
+    assert_eq!(report.verdict(), HealthVerdict::Replace);
+
+    // A higher threshold only moves the worn block
+    let lenient = flash.survey_health(Some(9), &bbt, &|_| {}).unwrap();
+    assert_eq!(lenient.blocks[3].condition, BlockCondition::Good);
+    assert_eq!(lenient.blocks[5].condition, BlockCondition::Failing);
+
+    // A read error is not an ECC failure: without the BBT the survey stops
+    assert!(flash.survey_health(None, &None, &|_| {}).is_err());
+
+    // The stress test leaves the BBT block alone
+    assert!(flash
+        .stress_test(4..5, 1, &bbt, &|_| {})
+        .unwrap()
+        .is_empty());
+
+    // Block 6 survives and is left erased; block 7 cannot be erased
+    report.stress = flash
+        .stress_test(6..8, 2, &bbt, &|_| {})
+        .expect("Stress test failed");
+    assert_eq!(report.stress.len(), 2);
+    assert_eq!(report.stress[0].cycles, 2);
+    assert_eq!(report.stress[0].failure, None);
+    assert_eq!(report.stress[1].cycles, 0);
+    assert_eq!(report.stress[1].failure.as_deref(), Some("erase failed"));
+    assert!(flash.stress_test(6..9, 1, &bbt, &|_| {}).is_err());
+
+    let json = String::from_utf8(report.to_json().unwrap()).unwrap();
+    assert!(json.contains("\"verdict\": \"replace\""));
+    assert!(json.contains("\"stress_failures\": 1"));
+    drop(flash);
+
+    let memory = programmer.get_memory();
+    assert!(memory[6 * block_size..7 * block_size]
+        .iter()
+        .all(|&b| b == 0xFF));
+    assert!(memory[7 * block_size..].iter().all(|&b| b == 0x5A));
+    assert!(memory[4 * block_size..5 * block_size]
+        .iter()
+        .all(|&b| b == 0x5A));
+}
+
+#[test]
+fn test_e2e_nand_read_retry() {
+    let block_size = 128 * 1024;
+    let mut programmer = SimulatedProgrammer::new(2 * block_size, 2048, block_size as u32)
+        .with_oob(64)
+        .with_read_retry(0x70);
+    let mut spec = simulated_nand().spec().clone();
+    spec.capacity = Capacity::bytes(2 * block_size as u32);
+    spec.capabilities.read_retry = Some(ReadRetry {
+        feature: 0x70,
+        levels: 5,
+    });
+
+    let image: Vec<u8> = (0..4 * 2048).map(|i: usize| (i / 7) as u8).collect();
+    let mut memory = image.clone();
+    memory.resize(2 * block_size, 0xFF);
+    programmer.set_memory(&memory);
+    programmer.set_ecc_status(1, 0x30);
+    programmer.recover_at_level(1, 3);
+    programmer.set_ecc_status(2, 0x30); // lost for good
+
+    let read_params = |length: u32, ignore_ecc_errors| ReadParams {
+        address: 0,
+        length,
+        use_ecc: true,
+        ignore_ecc_errors,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        partitions: Vec::new(),
+        passes: 1,
+    };
+
+    let mut flash = SpiNand::new(&mut programmer, spec.clone());
+    let (data, report) = ReadFlashUseCase::new(&mut flash)
+        .execute_with_report(read_params(2 * 2048, false), |_| {})
+        .expect("Read retry did not recover page 1");
+    assert_eq!(data, image[..2 * 2048]);
+    assert_eq!(report.ecc.pages[1].status, EccStatus::NoError);
+    assert_eq!(
+        report.ecc.read_retries,
+        [ReadRetryRecovery { page: 1, level: 3 }]
+    );
+
+    let err = ReadFlashUseCase::new(&mut flash)
+        .execute(read_params(4 * 2048, false), |_| {})
+        .expect_err("Read of an unrecoverable page succeeded");
+    assert!(err.to_string().contains("ECC"));
+
+    let (data, report) = ReadFlashUseCase::new(&mut flash)
+        .execute_with_report(read_params(4 * 2048, true), |_| {})
+        .expect("Read ignoring ECC errors failed");
+    assert_eq!(data, image);
+    assert_eq!(report.ecc.uncorrectable(), [2]);
+    assert_eq!(report.ecc.read_retries.len(), 1);
+    drop(flash);
+
+    // The default read voltage is restored after every retry
+    assert_eq!(programmer.read_retry_level(), 0);
+}
+
+#[test]
+fn test_e2e_nand_consensus_read() {
+    let mut flash = simulated_nand();
+    let image: Vec<u8> = (0..3 * 2048).map(|i: usize| (i * 5) as u8).collect();
+    WriteFlashUseCase::new(&mut flash)
+        .execute(delta_params(&image), |_| {})
+        .expect("Write failed");
+
+    let read_params = ReadParams {
+        address: 0,
+        length: image.len() as u32,
+        use_ecc: true,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode: OobMode::None,
+        bad_block_strategy: BadBlockStrategy::Fail,
+        bbt: None,
+        retry_count: 0,
+        partitions: Vec::new(),
+        passes: 3,
+    };
+    let (data, report) = ReadFlashUseCase::new(&mut flash)
+        .execute_with_report(read_params, |_| {})
+        .expect("Consensus read failed");
+    assert_eq!(data, image);
+    assert!(report.disagreements.is_empty());
+    // The ECC report covers one pass, not all three
+    assert_eq!(report.ecc.pages.len(), 3);
+}
+
+#[test]
+fn test_e2e_nand_consensus_read_skips_bad_blocks() {
+    let block_size = 128 * 1024;
+    let mut flash = simulated_nand();
+    flash.mark_bad_block(1).expect("Marking block 1 failed");
+    let image: Vec<u8> = (0..2 * block_size + 2 * 2048)
+        .map(|i: usize| (i * 7 + i / 2048) as u8)
+        .collect();
+    let write_params = WriteParams {
+        bad_block_strategy: BadBlockStrategy::Skip,
+        ..delta_params(&image)
+    };
+    WriteFlashUseCase::new(&mut flash)
+        .execute(write_params, |_| {})
+        .expect("Write failed");
+
+    let read_params = |oob_mode, passes| ReadParams {
+        address: 0,
+        length: image.len() as u32,
+        use_ecc: true,
+        ignore_ecc_errors: false,
+        soft_ecc: None,
+        oob_mode,
+        bad_block_strategy: BadBlockStrategy::Skip,
+        bbt: None,
+        retry_count: 0,
+        partitions: Vec::new(),
+        passes,
+    };
+    // Each block is voted on at its physical address, past bad block 1
+    let (data, report) = ReadFlashUseCase::new(&mut flash)
+        .execute_with_report(read_params(OobMode::None, 3), |_| {})
+        .expect("Consensus read failed");
+    assert!(data == image);
+    assert!(report.disagreements.is_empty());
+    assert_eq!(report.ecc.pages.len(), 2 * 64 + 2);
+
+    // With OOB bytes the data matches a single pass
+    let single = ReadFlashUseCase::new(&mut flash)
+        .execute(read_params(OobMode::Included, 1), |_| {})
+        .unwrap();
+    let voted = ReadFlashUseCase::new(&mut flash)
+        .execute(read_params(OobMode::Included, 3), |_| {})
+        .unwrap();
+    assert_eq!(single.len(), image.len());
+    assert!(voted == single);
+}